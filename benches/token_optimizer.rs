@@ -0,0 +1,127 @@
+//! Measures `optimize_context` over commit contexts of varying shapes.
+//!
+//! Run with `cargo bench --bench token_optimizer`. Besides the timings, the token usage
+//! each fixture ends up with is printed, to see how weight changes affect truncation.
+
+use criterion::{BatchSize, BenchmarkId, Criterion, criterion_group, criterion_main};
+use gait::{
+    Config,
+    core::{
+        context::{ChangeType, CommitContext, RecentCommit, StagedFile},
+        token_optimizer::TokenOptimizer,
+    },
+};
+use std::hint::black_box;
+use tokio::runtime::Runtime;
+
+/// Token budgets each fixture is optimized for
+const BUDGETS: &[usize] = &[1_000, 8_000, 32_000];
+
+fn staged_file(i: usize, diff_lines: usize, content_lines: usize) -> StagedFile {
+    StagedFile {
+        path: format!("src/module_{i}.rs"),
+        change_type: if i % 4 == 0 {
+            ChangeType::Added
+        } else {
+            ChangeType::Modified
+        },
+        diff: format!("+    let value_{i} = compute({i}, \"input\");\n").repeat(diff_lines),
+        content_excluded: false,
+        content: (content_lines > 0)
+            .then(|| format!("fn helper_{i}() -> usize {{ {i} }}\n").repeat(content_lines)),
+    }
+}
+
+fn context(staged_files: Vec<StagedFile>) -> CommitContext {
+    let recent_commits = (0..5)
+        .map(|i| RecentCommit {
+            hash: format!("{i:07x}"),
+            message: format!("feat(module): add handler {i}\n\nWires the handler into the router."),
+            author: "Bench Author".to_string(),
+            timestamp: "2024-01-01 00:00:00".to_string(),
+        })
+        .collect();
+    CommitContext::new(
+        "main".to_string(),
+        recent_commits,
+        staged_files,
+        "Bench User".to_string(),
+        "bench@example.com".to_string(),
+        Vec::new(),
+    )
+}
+
+/// Named fixtures, from an everyday commit to the pathological shapes
+fn fixtures() -> Vec<(&'static str, CommitContext)> {
+    vec![
+        (
+            "typical",
+            context((0..8).map(|i| staged_file(i, 20, 40)).collect()),
+        ),
+        (
+            "many_small_files",
+            context((0..2_000).map(|i| staged_file(i, 2, 3)).collect()),
+        ),
+        (
+            "single_huge_file",
+            context(vec![staged_file(0, 20_000, 20_000)]),
+        ),
+    ]
+}
+
+fn total_tokens(optimizer: &TokenOptimizer, context: &CommitContext) -> usize {
+    let commits: usize = context
+        .recent_commits
+        .iter()
+        .map(|commit| optimizer.count_tokens(&commit.message))
+        .sum();
+    let files: usize = context
+        .staged_files
+        .iter()
+        .map(|file| {
+            optimizer.count_tokens(&file.diff)
+                + file
+                    .content
+                    .as_deref()
+                    .map_or(0, |content| optimizer.count_tokens(content))
+        })
+        .sum();
+    commits + files
+}
+
+fn bench_optimize_context(c: &mut Criterion) {
+    let runtime = Runtime::new().expect("Failed to start tokio runtime");
+    let mut group = c.benchmark_group("optimize_context");
+    group.sample_size(10);
+
+    for (name, fixture) in fixtures() {
+        for &budget in BUDGETS {
+            let optimizer = TokenOptimizer::new(budget, Config::default())
+                .expect("Failed to initialize token optimizer");
+
+            let mut optimized = fixture.clone();
+            let before = total_tokens(&optimizer, &optimized);
+            let _ = runtime.block_on(optimizer.optimize_context(&mut optimized));
+            println!(
+                "{name}/{budget}: {before} tokens -> {} tokens",
+                total_tokens(&optimizer, &optimized)
+            );
+
+            group.bench_with_input(BenchmarkId::new(name, budget), &fixture, |b, fixture| {
+                b.iter_batched(
+                    || fixture.clone(),
+                    |mut context| {
+                        let _ = runtime.block_on(optimizer.optimize_context(&mut context));
+                        black_box(context)
+                    },
+                    BatchSize::LargeInput,
+                );
+            });
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_optimize_context);
+criterion_main!(benches);
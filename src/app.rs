@@ -1,11 +1,20 @@
 use crate::common::CommonParams;
 use crate::core::llm::get_available_provider_names;
-use crate::features::changelog::{handle_changelog_command, handle_release_notes_command};
+use crate::features::cache::{self, ClearOptions};
+use crate::features::changelog::filter::{parse_time_bound, parse_until_bound};
+use crate::features::changelog::{
+    ChangelogOptions, CommitFilter, OutputFormat, ReleaseNotesOptions, handle_changelog_command,
+    handle_release_notes_command,
+};
 use crate::features::commit;
+use crate::features::commit::trailers::parse_co_author;
+use crate::features::doctor;
+use chrono::{DateTime, Utc};
 use clap::builder::{Styles, styling::AnsiColor};
 use clap::{Parser, Subcommand, crate_version};
 use colored::Colorize;
 use log::debug;
+use std::path::PathBuf;
 
 /// CLI structure defining the available commands and global arguments
 #[derive(Parser)]
@@ -50,7 +59,7 @@ pub struct Cli {
 
     /// Display the version
     #[arg(
-        short = 'v',
+        short = 'V',
         long = "version",
         global = true,
         help = "Display the version"
@@ -83,8 +92,14 @@ pub enum Gait {
         #[command(flatten)]
         common: CommonParams,
 
-        /// Automatically commit with the generated message
-        #[arg(short, long, help = "Automatically commit with the generated message")]
+        /// Automatically commit with the generated message, without the TUI or any prompt
+        #[arg(
+            short,
+            long,
+            visible_alias = "yes",
+            visible_short_alias = 'y',
+            help = "Commit with the generated message right away, without the TUI or any prompt"
+        )]
         auto_commit: bool,
 
         /// Print the generated message to stdout and exit
@@ -108,6 +123,67 @@ pub enum Gait {
             help = "Specific commit to amend (hash, branch, or reference). Defaults to HEAD when --amend is used"
         )]
         commit: Option<String>,
+
+        /// Sign the commit with the configured GPG/SSH key
+        #[arg(
+            short = 'S',
+            long,
+            help = "Sign the commit (uses git's gpg.format and user.signingkey)"
+        )]
+        sign: bool,
+
+        /// Credit a co-author with a `Co-authored-by:` trailer (repeatable)
+        #[arg(
+            long = "co-author",
+            value_name = "NAME <EMAIL>",
+            value_parser = parse_co_author,
+            help = "Add a 'Co-authored-by:' trailer for NAME <EMAIL> (repeatable)"
+        )]
+        co_authors: Vec<String>,
+
+        /// Stage modifications of tracked files before generating, like `git commit -a`
+        #[arg(
+            long,
+            help = "Stage modified and deleted tracked files first, like 'git commit -a' (skipped with --dry-run)"
+        )]
+        all: bool,
+
+        /// Generate the message from a unified diff read from stdin
+        #[arg(
+            long,
+            requires = "print",
+            conflicts_with_all = ["all", "amend", "auto_commit"],
+            help = "Generate the message from a unified diff read from stdin instead of the staged changes (requires --print)"
+        )]
+        stdin_diff: bool,
+
+        /// Leave the gitmoji out of the title
+        #[arg(long, help = "Leave the gitmoji out of the commit title")]
+        no_emoji: bool,
+
+        /// Generate this many distinct messages to choose from
+        #[arg(
+            long,
+            value_name = "N",
+            value_parser = clap::value_parser!(u8).range(1..),
+            help = "Generate N distinct messages to choose from (default: 1, or gait.candidates)"
+        )]
+        candidates: Option<u8>,
+
+        /// Forget the instructions saved from the TUI for this repository
+        #[arg(
+            long,
+            help = "Forget the custom instructions saved from the TUI for this repository"
+        )]
+        reset_instructions: bool,
+
+        /// Edit the generated message in the external editor and commit it, without the TUI
+        #[arg(
+            long,
+            conflicts_with_all = ["print", "auto_commit", "stdin_diff"],
+            help = "Edit the generated message in $GIT_EDITOR, core.editor, $VISUAL or $EDITOR and commit it, instead of opening the TUI"
+        )]
+        editor: bool,
     },
 
     /// Generate a pull request description
@@ -150,6 +226,39 @@ Supported commitish syntax: HEAD~2, HEAD^, @~3, main~1, origin/main^, etc."
             help = "Target branch, commit, or commitish for comparison. For single commit analysis, specify just this parameter with a commit hash or commitish (e.g., --to HEAD~2)"
         )]
         to: Option<String>,
+
+        /// Describe each commit in the range separately, then the range as a whole
+        #[arg(
+            long,
+            help = "Generate a description for each commit in the range, followed by an overall summary"
+        )]
+        per_commit: bool,
+
+        /// Write the generated PR description to this file
+        #[arg(
+            long,
+            value_name = "PATH",
+            help = "Write the generated PR description to PATH (parent directories are created)"
+        )]
+        output_file: Option<String>,
+    },
+
+    /// Explain an existing commit in plain language
+    #[command(
+        about = "Explain an existing commit in plain language",
+        long_about = "Explain what a commit changes, why it was likely made and what it risks, from its message and diff. Large commits are trimmed to fit the provider's token budget."
+    )]
+    Explain {
+        #[command(flatten)]
+        common: CommonParams,
+
+        /// Commit to explain (hash, branch, or reference)
+        #[arg(value_name = "COMMITISH", default_value = "HEAD")]
+        commit: String,
+
+        /// Print the explanation as JSON
+        #[arg(long, help = "Print the explanation as a JSON object")]
+        json: bool,
     },
 
     /// Generate a changelog
@@ -180,6 +289,43 @@ Supported commitish syntax: HEAD~2, HEAD^, @~3, main~1, origin/main^, etc."
         /// Explicit version name to use in the changelog instead of getting it from Git
         #[arg(long, help = "Explicit version name to use in the changelog")]
         version_name: Option<String>,
+
+        /// Group commits by Conventional Commit type instead of using the LLM
+        #[arg(
+            long,
+            help = "Group commits by Conventional Commit type (feat, fix, ...) instead of using the LLM"
+        )]
+        conventional: bool,
+
+        /// Output format: markdown (default) or json
+        #[arg(
+            long,
+            default_value = "markdown",
+            help = "Output format: markdown or json (json lists conventional commit entries by section)"
+        )]
+        format: OutputFormat,
+
+        /// Only include commits that touch files under this path
+        #[arg(
+            long,
+            help = "Only include commits touching this path (with --scope, a commit matching either is included)"
+        )]
+        path: Option<String>,
+
+        /// Only include commits with this Conventional Commit scope
+        #[arg(
+            long,
+            help = "Only include commits with this conventional commit scope (with --path, a commit matching either is included)"
+        )]
+        scope: Option<String>,
+
+        /// Write the generated changelog to this file
+        #[arg(
+            long,
+            value_name = "PATH",
+            help = "Write the generated changelog to PATH (parent directories are created)"
+        )]
+        output_file: Option<String>,
     },
 
     /// Generate release notes
@@ -192,16 +338,129 @@ Supported commitish syntax: HEAD~2, HEAD^, @~3, main~1, origin/main^, etc."
         common: CommonParams,
 
         /// Starting Git reference (commit hash, tag, or branch name)
-        #[arg(long, required = true)]
-        from: String,
+        #[arg(long, required_unless_present_any = ["since", "until"])]
+        from: Option<String>,
 
         /// Ending Git reference (commit hash, tag, or branch name). Defaults to HEAD if not specified.
         #[arg(long)]
         to: Option<String>,
 
+        /// Only include commits committed at or after this time
+        #[arg(
+            long,
+            value_name = "DATE",
+            value_parser = parse_time_bound,
+            help = "Only include commits since DATE (RFC 3339, YYYY-MM-DD or a duration like '2 weeks'); combines with --from"
+        )]
+        since: Option<DateTime<Utc>>,
+
+        /// Only include commits committed at or before this time
+        #[arg(
+            long,
+            value_name = "DATE",
+            value_parser = parse_until_bound,
+            help = "Only include commits until DATE (RFC 3339, YYYY-MM-DD for the whole day, or a duration like '3 days')"
+        )]
+        until: Option<DateTime<Utc>>,
+
+        /// List cherry-picked copies and cancelled-out reverts instead of collapsing them
+        #[arg(
+            long,
+            help = "Keep commits with duplicate subjects and revert pairs that cancel out in the range"
+        )]
+        keep_duplicates: bool,
+
         /// Explicit version name to use in the release notes instead of getting it from Git
         #[arg(long, help = "Explicit version name to use in the release notes")]
         version_name: Option<String>,
+
+        /// Output format: markdown (default) or json
+        #[arg(
+            long,
+            default_value = "markdown",
+            help = "Output format: markdown or json (json lists conventional commit entries by section)"
+        )]
+        format: OutputFormat,
+
+        /// Only include commits that touch files under this path
+        #[arg(
+            long,
+            help = "Only include commits touching this path (with --scope, a commit matching either is included)"
+        )]
+        path: Option<String>,
+
+        /// Only include commits with this Conventional Commit scope
+        #[arg(
+            long,
+            help = "Only include commits with this conventional commit scope (with --path, a commit matching either is included)"
+        )]
+        scope: Option<String>,
+
+        /// Write the generated release notes to this file
+        #[arg(
+            long,
+            value_name = "PATH",
+            help = "Write the generated release notes to PATH (parent directories are created)"
+        )]
+        output_file: Option<String>,
+    },
+
+    /// Check the configuration for common mistakes
+    #[command(
+        about = "Check the configuration for common mistakes",
+        long_about = "Validate the loaded configuration item by item: the default provider, each provider's model, API key and token limit, ticket and exclude patterns, and prompt rendering. Exits with an error if any hard check fails."
+    )]
+    Doctor {
+        #[command(flatten)]
+        common: CommonParams,
+    },
+
+    /// Manage on-disk caches
+    #[command(
+        about = "Manage on-disk caches",
+        long_about = "Manage the commit message, LLM response and git wire repository caches under the cache root (GITAI_CACHE_DIR, gait.cachedir, or the platform cache directory)."
+    )]
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommand,
+    },
+}
+
+/// Subcommands of `cache`
+#[derive(Subcommand, Debug)]
+pub enum CacheCommand {
+    /// Remove cached data and report the bytes freed
+    Clear {
+        /// Clear the commit message history cache
+        #[arg(long, help = "Clear the commit message history cache")]
+        commits: bool,
+
+        /// Clear the LLM response cache
+        #[arg(long, help = "Clear the LLM response cache")]
+        responses: bool,
+
+        /// Clear the git wire repository cache
+        #[arg(long, help = "Clear the git wire repository cache")]
+        repos: bool,
+
+        /// Clear every cache
+        #[arg(long, help = "Clear every cache")]
+        all: bool,
+
+        /// Only clear commit history recorded for this repository
+        #[arg(
+            long = "repo",
+            value_name = "PATH",
+            help = "With --commits or --all, only clear commit history recorded for the repository at PATH"
+        )]
+        repo_path: Option<PathBuf>,
+    },
+
+    /// Show the size and contents of each cache
+    Stats {
+        /// Print the statistics as JSON
+        #[arg(long, help = "Print the statistics as JSON")]
+        json: bool,
     },
 }
 
@@ -248,6 +507,22 @@ pub struct CmsgConfig {
     pub dry_run: bool,
     pub amend: bool,
     pub commit_ref: Option<String>,
+    /// Sign the commit with the configured GPG/SSH key
+    pub sign: bool,
+    /// `Name <email>` identities to credit with `Co-authored-by:` trailers
+    pub co_authors: Vec<String>,
+    /// Stage modifications of tracked files first, like `git commit -a`
+    pub stage_all: bool,
+    /// Generate from a unified diff on stdin instead of the staged changes
+    pub stdin_diff: bool,
+    /// Leave the gitmoji out of the title
+    pub no_emoji: bool,
+    /// Distinct messages to generate, overriding `gait.candidates`
+    pub candidates: Option<usize>,
+    /// Forget the instructions saved from the TUI for this repository first
+    pub reset_instructions: bool,
+    /// Edit the message in the external editor and commit it instead of opening the TUI
+    pub editor: bool,
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -260,8 +535,13 @@ pub async fn handle_message(
     context_ratio: Option<f32>,
 ) -> anyhow::Result<()> {
     debug!(
-        "Handling 'message' command with common: {common:?}, auto_commit: {}, print: {}, verify: {}, amend: {}, commit_ref: {:?}, complete: {complete}, prefix: {prefix:?}, context_ratio: {context_ratio:?}",
-        config.auto_commit, config.print_only, config.verify, config.amend, config.commit_ref,
+        "Handling 'message' command with common: {common:?}, auto_commit: {}, print: {}, verify: {}, amend: {}, commit_ref: {:?}, sign: {}, complete: {complete}, prefix: {prefix:?}, context_ratio: {context_ratio:?}",
+        config.auto_commit,
+        config.print_only,
+        config.verify,
+        config.amend,
+        config.commit_ref,
+        config.sign,
     );
 
     if complete {
@@ -280,6 +560,10 @@ pub async fn handle_message(
             config.amend,
             config.commit_ref,
             repository_url,
+            config.sign,
+            config.co_authors,
+            config.stage_all,
+            config.no_emoji,
         )
         .await
     } else {
@@ -293,6 +577,14 @@ pub async fn handle_message(
             config.amend,
             config.commit_ref,
             repository_url,
+            config.sign,
+            config.co_authors,
+            config.stage_all,
+            config.stdin_diff,
+            config.no_emoji,
+            config.candidates,
+            config.reset_instructions,
+            config.editor,
         )
         .await
     }
@@ -304,28 +596,26 @@ pub async fn handle_changelog(
     from: String,
     to: Option<String>,
     repository_url: Option<String>,
-    update: bool,
-    file: Option<String>,
-    version_name: Option<String>,
+    options: ChangelogOptions,
 ) -> anyhow::Result<()> {
     debug!(
-        "Handling 'changelog' command with common: {common:?}, from: {from}, to: {to:?}, update: {update}, file: {file:?}, version_name: {version_name:?}"
+        "Handling 'changelog' command with common: {common:?}, from: {from}, to: {to:?}, options: {options:?}"
     );
-    handle_changelog_command(common, from, to, repository_url, update, file, version_name).await
+    handle_changelog_command(common, from, to, repository_url, options).await
 }
 
 /// Handle the `ReleaseNotes` command
 pub async fn handle_release_notes(
     common: CommonParams,
-    from: String,
+    from: Option<String>,
     to: Option<String>,
     repository_url: Option<String>,
-    version_name: Option<String>,
+    options: ReleaseNotesOptions,
 ) -> anyhow::Result<()> {
     debug!(
-        "Handling 'release-notes' command with common: {common:?}, from: {from}, to: {to:?}, version_name: {version_name:?}"
+        "Handling 'release-notes' command with common: {common:?}, from: {from:?}, to: {to:?}, options: {options:?}"
     );
-    handle_release_notes_command(common, from, to, repository_url, version_name).await
+    handle_release_notes_command(common, from, to, repository_url, options).await
 }
 
 /// Handle the command based on parsed arguments
@@ -338,6 +628,14 @@ pub async fn handle_command(command: Gait, repository_url: Option<String>) -> an
             no_verify,
             amend,
             commit,
+            sign,
+            co_authors,
+            all,
+            stdin_diff,
+            no_emoji,
+            candidates,
+            reset_instructions,
+            editor,
         } => {
             handle_message(
                 common,
@@ -348,6 +646,14 @@ pub async fn handle_command(command: Gait, repository_url: Option<String>) -> an
                     dry_run: false,
                     amend,
                     commit_ref: commit,
+                    sign,
+                    co_authors,
+                    stage_all: all,
+                    stdin_diff,
+                    no_emoji,
+                    candidates: candidates.map(usize::from),
+                    reset_instructions,
+                    editor,
                 },
                 repository_url,
                 false,
@@ -363,32 +669,146 @@ pub async fn handle_command(command: Gait, repository_url: Option<String>) -> an
             update,
             file,
             version_name,
-        } => handle_changelog(common, from, to, repository_url, update, file, version_name).await,
+            conventional,
+            format,
+            path,
+            scope,
+            output_file,
+        } => {
+            handle_changelog(
+                common,
+                from,
+                to,
+                repository_url,
+                ChangelogOptions {
+                    update_file: update,
+                    changelog_path: file,
+                    version_name,
+                    conventional,
+                    format,
+                    filter: CommitFilter::new(path, scope),
+                    output_file,
+                },
+            )
+            .await
+        }
         Gait::ReleaseNotes {
             common,
             from,
             to,
+            since,
+            until,
+            keep_duplicates,
             version_name,
-        } => handle_release_notes(common, from, to, repository_url, version_name).await,
+            format,
+            path,
+            scope,
+            output_file,
+        } => {
+            handle_release_notes(
+                common,
+                from,
+                to,
+                repository_url,
+                ReleaseNotesOptions {
+                    version_name,
+                    format,
+                    filter: CommitFilter::new(path, scope)
+                        .with_dates(since, until)
+                        .with_collapsed_duplicates(!keep_duplicates),
+                    output_file,
+                },
+            )
+            .await
+        }
         Gait::Pr {
             common,
             print,
             from,
             to,
-        } => handle_pr_command(common, print, from, to, repository_url).await,
+            per_commit,
+            output_file,
+        } => {
+            handle_pr_command(
+                common,
+                print,
+                from,
+                to,
+                per_commit,
+                output_file,
+                repository_url,
+            )
+            .await
+        }
+        Gait::Explain {
+            common,
+            commit,
+            json,
+        } => handle_explain(common, commit, json, false, repository_url).await,
+        Gait::Doctor { common } => {
+            debug!("Handling 'doctor' command with common: {common:?}");
+            doctor::handle_doctor_command(common)
+        }
+        Gait::Cache { command } => handle_cache_command(command),
     }
 }
 
+/// Handle the `Cache` command
+pub fn handle_cache_command(command: CacheCommand) -> anyhow::Result<()> {
+    debug!("Handling 'cache' command: {command:?}");
+    match command {
+        CacheCommand::Clear {
+            commits,
+            responses,
+            repos,
+            all,
+            repo_path,
+        } => cache::handle_clear_command(&ClearOptions {
+            commits,
+            responses,
+            repos,
+            all,
+            repo_path,
+        }),
+        CacheCommand::Stats { json } => cache::handle_stats_command(json),
+    }
+}
+
+/// Handle the `Explain` command
+pub async fn handle_explain(
+    common: CommonParams,
+    commit: String,
+    json: bool,
+    dry_run: bool,
+    repository_url: Option<String>,
+) -> anyhow::Result<()> {
+    debug!(
+        "Handling 'explain' command with common: {common:?}, commit: {commit}, json: {json}, dry_run: {dry_run}"
+    );
+    commit::handle_explain_command(common, commit, json, dry_run, repository_url).await
+}
+
 /// Handle the `Pr` command
 pub async fn handle_pr_command(
     common: CommonParams,
     print: bool,
     from: Option<String>,
     to: Option<String>,
+    per_commit: bool,
+    output_file: Option<String>,
     repository_url: Option<String>,
 ) -> anyhow::Result<()> {
     debug!(
-        "Handling 'pr' command with common: {common:?}, print: {print}, from: {from:?}, to: {to:?}"
+        "Handling 'pr' command with common: {common:?}, print: {print}, from: {from:?}, to: {to:?}, per_commit: {per_commit}, output_file: {output_file:?}"
     );
-    commit::handle_pr_command(common, print, repository_url, from, to).await
+    commit::handle_pr_command(
+        common,
+        print,
+        repository_url,
+        from,
+        to,
+        per_commit,
+        output_file,
+    )
+    .await
 }
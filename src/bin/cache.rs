@@ -0,0 +1,34 @@
+use anyhow::Result;
+use clap::Parser;
+use gait::{
+    app::{self, CacheCommand},
+    common::LogParams,
+    completions, error,
+};
+
+#[derive(Parser)]
+#[command(
+    name = "git-cache",
+    about = "Manage the commit message, LLM response and git wire repository caches"
+)]
+struct CacheArgs {
+    #[command(flatten)]
+    log: LogParams,
+
+    #[command(subcommand)]
+    command: CacheCommand,
+}
+
+fn main() -> Result<()> {
+    if completions::print_completions_if_requested::<CacheArgs>("git-cache")? {
+        return Ok(());
+    }
+
+    let args: CacheArgs = error::parse_args();
+    args.log.init_logger();
+
+    match app::handle_cache_command(args.command) {
+        Ok(()) => Ok(()),
+        Err(e) => error::exit_with_error(&e),
+    }
+}
@@ -1,6 +1,11 @@
 use anyhow::Result;
 use clap::Parser;
-use gait::{app, common::CommonParams};
+use gait::{
+    app,
+    common::CommonParams,
+    completions, error,
+    features::changelog::{ChangelogOptions, CommitFilter, OutputFormat},
+};
 
 #[derive(Parser)]
 #[command(name = "git-flow-changelog", about = "Generate a changelog")]
@@ -27,13 +32,53 @@ struct ChangelogArgs {
     /// Explicit version name to use in the changelog instead of getting it from Git
     #[arg(long, help = "Explicit version name to use in the changelog")]
     version_name: Option<String>,
+
+    /// Group commits by Conventional Commit type instead of using the LLM
+    #[arg(
+        long,
+        help = "Group commits by Conventional Commit type (feat, fix, ...) instead of using the LLM"
+    )]
+    conventional: bool,
+
+    /// Output format: markdown (default) or json
+    #[arg(
+        long,
+        default_value = "markdown",
+        help = "Output format: markdown or json (json lists conventional commit entries)"
+    )]
+    format: OutputFormat,
+
+    /// Only include commits that touch files under this path
+    #[arg(
+        long,
+        help = "Only include commits touching this path (with --scope, a commit matching either is included)"
+    )]
+    path: Option<String>,
+
+    /// Only include commits with this Conventional Commit scope
+    #[arg(
+        long,
+        help = "Only include commits with this conventional commit scope (with --path, a commit matching either is included)"
+    )]
+    scope: Option<String>,
+
+    /// Write the generated changelog to this file
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Write the generated changelog to PATH (parent directories are created)"
+    )]
+    output_file: Option<String>,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    env_logger::init();
+    if completions::print_completions_if_requested::<ChangelogArgs>("git-changelog")? {
+        return Ok(());
+    }
 
-    let args = ChangelogArgs::parse();
+    let args: ChangelogArgs = error::parse_args();
+    args.common.init_logger();
 
     let repository_url = args.common.repository_url.clone();
 
@@ -42,16 +87,19 @@ async fn main() -> Result<()> {
         args.from,
         args.to,
         repository_url,
-        args.update,
-        args.file,
-        args.version_name,
+        ChangelogOptions {
+            update_file: args.update,
+            changelog_path: args.file,
+            version_name: args.version_name,
+            conventional: args.conventional,
+            format: args.format,
+            filter: CommitFilter::new(args.path, args.scope),
+            output_file: args.output_file,
+        },
     )
     .await
     {
         Ok(()) => Ok(()),
-        Err(e) => {
-            eprintln!("Error: {e}");
-            std::process::exit(1);
-        }
+        Err(e) => error::exit_with_error(&e),
     }
 }
@@ -0,0 +1,27 @@
+use anyhow::Result;
+use clap::Parser;
+use gait::{common::CommonParams, completions, error, features::doctor};
+
+#[derive(Parser)]
+#[command(
+    name = "git-doctor",
+    about = "Check the configuration for common mistakes"
+)]
+struct DoctorArgs {
+    #[command(flatten)]
+    common: CommonParams,
+}
+
+fn main() -> Result<()> {
+    if completions::print_completions_if_requested::<DoctorArgs>("git-doctor")? {
+        return Ok(());
+    }
+
+    let args: DoctorArgs = error::parse_args();
+    args.common.init_logger();
+
+    match doctor::handle_doctor_command(args.common) {
+        Ok(()) => Ok(()),
+        Err(e) => error::exit_with_error(&e),
+    }
+}
@@ -0,0 +1,53 @@
+use anyhow::Result;
+use clap::Parser;
+use gait::{app, common::CommonParams, completions, error};
+
+#[derive(Parser)]
+#[command(
+    name = "git-explain",
+    about = "Explain an existing commit in plain language using AI"
+)]
+struct ExplainArgs {
+    #[command(flatten)]
+    common: CommonParams,
+
+    /// Commit to explain (hash, branch, or reference)
+    #[arg(value_name = "COMMITISH", default_value = "HEAD")]
+    commit: String,
+
+    /// Print the explanation as JSON
+    #[arg(long, help = "Print the explanation as a JSON object")]
+    json: bool,
+
+    /// Dry run mode: do not make real HTTP requests, for UI testing
+    #[arg(
+        long,
+        help = "Dry run mode: do not make real HTTP requests, for UI testing"
+    )]
+    dry_run: bool,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    if completions::print_completions_if_requested::<ExplainArgs>("git-explain")? {
+        return Ok(());
+    }
+
+    let args: ExplainArgs = error::parse_args();
+    args.common.init_logger();
+
+    let repository_url = args.common.repository_url.clone();
+
+    match app::handle_explain(
+        args.common,
+        args.commit,
+        args.json,
+        args.dry_run,
+        repository_url,
+    )
+    .await
+    {
+        Ok(()) => Ok(()),
+        Err(e) => error::exit_with_error(&e),
+    }
+}
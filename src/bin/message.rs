@@ -3,6 +3,8 @@ use clap::Parser;
 use gait::{
     app::{self, CmsgConfig},
     common::CommonParams,
+    completions, error,
+    features::{commit::trailers::parse_co_author, presets::handle_list_presets_command},
 };
 
 #[derive(Parser)]
@@ -12,8 +14,14 @@ struct MessageArgs {
     #[command(flatten)]
     common: CommonParams,
 
-    /// Automatically commit with the generated message
-    #[arg(short, long, help = "Automatically commit with the generated message")]
+    /// Automatically commit with the generated message, without the TUI or any prompt
+    #[arg(
+        short,
+        long,
+        visible_alias = "yes",
+        visible_short_alias = 'y',
+        help = "Commit with the generated message right away, without the TUI or any prompt"
+    )]
     auto_commit: bool,
 
     /// Print the generated message to stdout and exit
@@ -45,6 +53,67 @@ struct MessageArgs {
     )]
     commit: Option<String>,
 
+    /// Sign the commit with the configured GPG/SSH key
+    #[arg(
+        short = 'S',
+        long,
+        help = "Sign the commit (uses git's gpg.format and user.signingkey)"
+    )]
+    sign: bool,
+
+    /// Credit a co-author with a `Co-authored-by:` trailer (repeatable)
+    #[arg(
+        long = "co-author",
+        value_name = "NAME <EMAIL>",
+        value_parser = parse_co_author,
+        help = "Add a 'Co-authored-by:' trailer for NAME <EMAIL> (repeatable)"
+    )]
+    co_authors: Vec<String>,
+
+    /// Stage modifications of tracked files before generating, like `git commit -a`
+    #[arg(
+        long,
+        help = "Stage modified and deleted tracked files first, like 'git commit -a' (skipped with --dry-run)"
+    )]
+    all: bool,
+
+    /// Generate the message from a unified diff read from stdin
+    #[arg(
+        long,
+        requires = "print",
+        conflicts_with_all = ["all", "amend", "auto_commit", "complete"],
+        help = "Generate the message from a unified diff read from stdin instead of the staged changes (requires --print)"
+    )]
+    stdin_diff: bool,
+
+    /// Leave the gitmoji out of the title
+    #[arg(long, help = "Leave the gitmoji out of the commit title")]
+    no_emoji: bool,
+
+    /// Generate this many distinct messages to choose from
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = clap::value_parser!(u8).range(1..),
+        help = "Generate N distinct messages to choose from (default: 1, or gait.candidates)"
+    )]
+    candidates: Option<u8>,
+
+    /// Forget the instructions saved from the TUI for this repository
+    #[arg(
+        long,
+        help = "Forget the custom instructions saved from the TUI for this repository"
+    )]
+    reset_instructions: bool,
+
+    /// Edit the generated message in the external editor and commit it, without the TUI
+    #[arg(
+        long,
+        conflicts_with_all = ["print", "auto_commit", "stdin_diff"],
+        help = "Edit the generated message in $GIT_EDITOR, core.editor, $VISUAL or $EDITOR and commit it, instead of opening the TUI"
+    )]
+    editor: bool,
+
     /// Complete a commit message instead of generating from scratch
     #[arg(
         long,
@@ -67,13 +136,26 @@ struct MessageArgs {
         requires = "complete"
     )]
     context_ratio: Option<f32>,
+
+    /// List the instruction presets available to --preset and exit
+    #[arg(
+        long,
+        help = "List the instruction presets available to --preset and exit"
+    )]
+    list_presets: bool,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    env_logger::init();
+    if completions::print_completions_if_requested::<MessageArgs>("git-message")? {
+        return Ok(());
+    }
 
-    let args = MessageArgs::parse();
+    let args: MessageArgs = error::parse_args();
+    args.common.init_logger();
+    if args.list_presets {
+        return handle_list_presets_command();
+    }
     let repository_url = args.common.repository_url.clone();
 
     match app::handle_message(
@@ -85,6 +167,14 @@ async fn main() -> Result<()> {
             dry_run: args.dry_run,
             amend: args.amend,
             commit_ref: args.commit,
+            sign: args.sign,
+            co_authors: args.co_authors,
+            stage_all: args.all,
+            stdin_diff: args.stdin_diff,
+            no_emoji: args.no_emoji,
+            candidates: args.candidates.map(usize::from),
+            reset_instructions: args.reset_instructions,
+            editor: args.editor,
         },
         repository_url,
         args.complete,
@@ -94,9 +184,6 @@ async fn main() -> Result<()> {
     .await
     {
         Ok(()) => Ok(()),
-        Err(e) => {
-            eprintln!("Error: {e}");
-            std::process::exit(1);
-        }
+        Err(e) => error::exit_with_error(&e),
     }
 }
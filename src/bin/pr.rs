@@ -1,6 +1,6 @@
 use anyhow::Result;
 use clap::Parser;
-use gait::{app, common::CommonParams};
+use gait::{app, common::CommonParams, completions, error};
 
 #[derive(Parser)]
 #[command(
@@ -32,22 +32,46 @@ struct PrArgs {
         help = "Target branch, commit, or commitish for comparison. For single commit analysis, specify just this parameter with a commit hash or commitish (e.g., --to HEAD~2)"
     )]
     to: Option<String>,
+
+    /// Describe each commit in the range separately, then the range as a whole
+    #[arg(
+        long,
+        help = "Generate a description for each commit in the range, followed by an overall summary"
+    )]
+    per_commit: bool,
+
+    /// Write the generated PR description to this file
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Write the generated PR description to PATH (parent directories are created)"
+    )]
+    output_file: Option<String>,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    env_logger::init();
+    if completions::print_completions_if_requested::<PrArgs>("git-pr")? {
+        return Ok(());
+    }
 
-    let args = PrArgs::parse();
+    let args: PrArgs = error::parse_args();
+    args.common.init_logger();
 
     let repository_url = args.common.repository_url.clone();
 
-    match app::handle_pr_command(args.common, args.print, args.from, args.to, repository_url).await
+    match app::handle_pr_command(
+        args.common,
+        args.print,
+        args.from,
+        args.to,
+        args.per_commit,
+        args.output_file,
+        repository_url,
+    )
+    .await
     {
         Ok(()) => Ok(()),
-        Err(e) => {
-            eprintln!("Error: {e}");
-            std::process::exit(1);
-        }
+        Err(e) => error::exit_with_error(&e),
     }
 }
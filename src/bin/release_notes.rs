@@ -1,6 +1,15 @@
 use anyhow::Result;
+use chrono::{DateTime, Utc};
 use clap::Parser;
-use gait::{app, common::CommonParams};
+use gait::{
+    app,
+    common::CommonParams,
+    completions, error,
+    features::changelog::{
+        CommitFilter, OutputFormat, ReleaseNotesOptions,
+        filter::{parse_time_bound, parse_until_bound},
+    },
+};
 
 #[derive(Parser)]
 #[command(name = "git-flow-release-notes", about = "Generate release notes")]
@@ -9,23 +18,81 @@ struct ReleaseNotesArgs {
     common: CommonParams,
 
     /// Starting Git reference (commit hash, tag, or branch name)
-    #[arg(long, required = true)]
-    from: String,
+    #[arg(long, required_unless_present_any = ["since", "until"])]
+    from: Option<String>,
 
     /// Ending Git reference (commit hash, tag, or branch name). Defaults to HEAD if not specified.
     #[arg(long)]
     to: Option<String>,
 
+    /// Only include commits committed at or after this time
+    #[arg(
+        long,
+        value_name = "DATE",
+        value_parser = parse_time_bound,
+        help = "Only include commits since DATE (RFC 3339, YYYY-MM-DD or a duration like '2 weeks'); combines with --from"
+    )]
+    since: Option<DateTime<Utc>>,
+
+    /// Only include commits committed at or before this time
+    #[arg(
+        long,
+        value_name = "DATE",
+        value_parser = parse_until_bound,
+        help = "Only include commits until DATE (RFC 3339, YYYY-MM-DD for the whole day, or a duration like '3 days')"
+    )]
+    until: Option<DateTime<Utc>>,
+
+    /// List cherry-picked copies and cancelled-out reverts instead of collapsing them
+    #[arg(
+        long,
+        help = "Keep commits with duplicate subjects and revert pairs that cancel out in the range"
+    )]
+    keep_duplicates: bool,
+
     /// Explicit version name to use in the release notes instead of getting it from Git
     #[arg(long, help = "Explicit version name to use in the release notes")]
     version_name: Option<String>,
+
+    /// Output format: markdown (default) or json
+    #[arg(
+        long,
+        default_value = "markdown",
+        help = "Output format: markdown or json (json lists conventional commit entries)"
+    )]
+    format: OutputFormat,
+
+    /// Only include commits that touch files under this path
+    #[arg(
+        long,
+        help = "Only include commits touching this path (with --scope, a commit matching either is included)"
+    )]
+    path: Option<String>,
+
+    /// Only include commits with this Conventional Commit scope
+    #[arg(
+        long,
+        help = "Only include commits with this conventional commit scope (with --path, a commit matching either is included)"
+    )]
+    scope: Option<String>,
+
+    /// Write the generated release notes to this file
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Write the generated release notes to PATH (parent directories are created)"
+    )]
+    output_file: Option<String>,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    env_logger::init();
+    if completions::print_completions_if_requested::<ReleaseNotesArgs>("git-release-notes")? {
+        return Ok(());
+    }
 
-    let args = ReleaseNotesArgs::parse();
+    let args: ReleaseNotesArgs = error::parse_args();
+    args.common.init_logger();
 
     let repository_url = args.common.repository_url.clone();
 
@@ -34,14 +101,18 @@ async fn main() -> Result<()> {
         args.from,
         args.to,
         repository_url,
-        args.version_name,
+        ReleaseNotesOptions {
+            version_name: args.version_name,
+            format: args.format,
+            filter: CommitFilter::new(args.path, args.scope)
+                .with_dates(args.since, args.until)
+                .with_collapsed_duplicates(!args.keep_duplicates),
+            output_file: args.output_file,
+        },
     )
     .await
     {
         Ok(()) => Ok(()),
-        Err(e) => {
-            eprintln!("Error: {e}");
-            std::process::exit(1);
-        }
+        Err(e) => error::exit_with_error(&e),
     }
 }
@@ -1,14 +1,20 @@
 use clap::{Parser, Subcommand};
 use colored::Colorize;
 use gait::{
-    init_logger,
+    completions,
+    error::{ErrorCategory, parse_args},
+    init_logger_with,
     remote::{
-        check,
-        common::{Parsed, Target, sequence},
+        check, checksum,
+        common::{Parsed, Target, command, sequence},
+        status,
+        summary::SyncSummary,
         sync,
     },
 };
+use std::num::NonZeroUsize;
 use std::process::exit;
+use std::time::Duration;
 
 pub use gait::{CachedRepository, RepositoryConfiguration, WireOperation};
 
@@ -28,17 +34,58 @@ struct Cli {
 
     /// Execute the command with single thread
     /// (slow, easy-to-read output, low resource consumption)
-    #[arg(global = true, short, long)]
+    #[arg(global = true, short, long, conflicts_with = "jobs")]
     singlethread: bool,
+
+    /// Process at most N entries at once (`--jobs 1` is the same as `--singlethread`).
+    /// Entries sharing a URL never run concurrently, whatever N is.
+    #[arg(global = true, short, long, value_name = "N")]
+    jobs: Option<NonZeroUsize>,
+
+    /// Kill any single git command (clone, fetch, ...) that runs longer than SECS seconds
+    #[arg(
+        global = true,
+        long,
+        value_name = "SECS",
+        env = "GITWIRE_TIMEOUT",
+        default_value_t = command::DEFAULT_GIT_TIMEOUT_SECS
+    )]
+    timeout: u64,
+
+    /// Only log warnings and errors (`RUST_LOG` still takes precedence)
+    #[arg(global = true, short, long)]
+    quiet: bool,
+
+    /// Also log debug details, such as how much of each entry's limits a sync used
+    #[arg(global = true, short, long, conflicts_with = "quiet")]
+    verbose: bool,
 }
 
 #[derive(Subcommand)]
 enum Command {
     /// Synchronizes code depending on a file '.gitwire' definition.
-    Sync,
+    Sync {
+        /// Run each entry's `post_sync` commands after its files are copied
+        #[arg(long)]
+        allow_hooks: bool,
+
+        /// Check each entry against its source afterwards, reusing the same clones
+        #[arg(long)]
+        check: bool,
+    },
 
     /// Checks if the synchronized code identical to the original.
-    Check,
+    Check {
+        /// Choose per differing file whether to take upstream, keep local or skip
+        #[arg(short, long)]
+        interactive: bool,
+    },
+
+    /// Records a checksum of each entry's upstream `src` in '.gitwire' for sync to verify.
+    Lock,
+
+    /// Shows whether each entry is behind its source or modified locally, without fetching trees.
+    Status,
 
     /// Directly synchronizes code depending on given arguments
     DirectSync {
@@ -67,21 +114,49 @@ enum Command {
 
 #[tokio::main]
 async fn main() {
-    init_logger();
+    match completions::print_completions_if_requested::<Cli>("git-wire") {
+        Ok(true) => return,
+        Ok(false) => {}
+        Err(e) => {
+            eprintln!("{}", e.to_string().red().bold());
+            exit(ErrorCategory::of(&e).exit_code());
+        }
+    }
+
+    let cli: Cli = parse_args();
+    init_logger_with(if cli.quiet {
+        log::LevelFilter::Warn
+    } else if cli.verbose {
+        log::LevelFilter::Debug
+    } else {
+        log::LevelFilter::Info
+    });
 
-    let cli = Cli::parse();
+    command::set_git_timeout(Duration::from_secs(cli.timeout));
 
     let target = cli.target.or(cli.name);
 
-    let mode = if cli.singlethread {
-        sequence::Mode::Single
-    } else {
-        sequence::Mode::Parallel
+    let mode = match (cli.singlethread, cli.jobs) {
+        (true, _) => sequence::Mode::Single,
+        (false, Some(jobs)) => sequence::Mode::Jobs(jobs),
+        (false, None) => sequence::Mode::Parallel,
     };
 
     let result = match cli.command {
-        Command::Sync => sync::sync_with_caching(&Target::Declared(target), mode).await,
-        Command::Check => check::check(Target::Declared(target), &mode),
+        Command::Sync { allow_hooks, check } => {
+            let target = Target::Declared(target);
+            let summary = if check {
+                sync::sync_then_check_with_caching(&target, mode, allow_hooks).await
+            } else {
+                sync::sync_with_caching(&target, mode, allow_hooks).await
+            };
+            summary.map(|summary| print_summary(&summary))
+        }
+        Command::Check { interactive } => {
+            check::check(Target::Declared(target), &mode, interactive)
+        }
+        Command::Lock => checksum::lock(&Target::Declared(target)),
+        Command::Status => status::status(&Target::Declared(target)),
         Command::DirectSync { url, rev, src, dst } => {
             sync::sync_with_caching(
                 // Also use caching for direct sync
@@ -89,26 +164,47 @@ async fn main() {
                     name: None,
                     dsc: None,
                     mtd: None,
+                    commit_hash: None,
+                    checksum: None,
+                    post_sync: Vec::new(),
+                    prune: false,
+                    follow_symlinks: false,
+                    token_env: None,
+                    max_files: None,
+                    max_total_bytes: None,
+                    ignore: Vec::new(),
                     url,
                     rev,
                     src,
                     dst,
                 }),
                 mode,
+                false,
             )
             .await
+            .map(|summary| print_summary(&summary))
         }
         Command::DirectCheck { url, rev, src, dst } => check::check(
             Target::Direct(Parsed {
                 name: None,
                 dsc: None,
                 mtd: None,
+                commit_hash: None,
+                checksum: None,
+                post_sync: Vec::new(),
+                prune: false,
+                follow_symlinks: false,
+                token_env: None,
+                max_files: None,
+                max_total_bytes: None,
+                ignore: Vec::new(),
                 url,
                 rev,
                 src,
                 dst,
             }),
             &mode,
+            false,
         ),
     };
 
@@ -118,12 +214,20 @@ async fn main() {
         Err(e) => eprintln!("{}", e.to_string().red().bold()),
     }
 
+    // Drift is the user's to fix, like any other failed check
     match result {
         Ok(true) => exit(0),
-        _ => exit(1),
+        Ok(false) => exit(ErrorCategory::User.exit_code()),
+        Err(e) => exit(e.get_type().category().exit_code()),
     }
 }
 
+/// Print the sync table, returning `false` if a checked entry drifted
+fn print_summary(summary: &SyncSummary) -> bool {
+    println!("\n{summary}");
+    summary.is_clean()
+}
+
 #[test]
 fn verify_cli() {
     use clap::CommandFactory;
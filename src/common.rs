@@ -1,8 +1,11 @@
-use crate::config::Config;
+use crate::config::{Config, ConfigSource, PROFILE_ENV_VAR};
 use crate::core::llm::get_available_provider_names;
-use anyhow::Result;
-use clap::Args;
+use anyhow::{Context, Result};
+use clap::{ArgAction, Args};
+use log::LevelFilter;
 use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
@@ -52,9 +55,29 @@ pub struct CommonParams {
     pub provider: Option<String>,
 
     /// Custom instructions for this operation
-    #[arg(short, long, help = "Custom instructions for this operation")]
+    #[arg(
+        short,
+        long,
+        help = "Custom instructions for this operation (`@path` reads them from a file)"
+    )]
     pub instructions: Option<String>,
 
+    /// File holding instructions for this operation, used before any `--instructions`
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Read instructions for this operation from a file, e.g. a shared COMMIT_GUIDELINES.md"
+    )]
+    pub instructions_file: Option<PathBuf>,
+
+    /// Named instruction preset used as the base for any other instructions
+    #[arg(
+        long,
+        value_name = "NAME",
+        help = "Instruction preset to start from (built-in, or from [presets.<name>] in the config file)"
+    )]
+    pub preset: Option<String>,
+
     /// Set the detail level
     #[arg(
         long,
@@ -70,6 +93,103 @@ pub struct CommonParams {
         help = "Repository URL to use instead of local repository"
     )]
     pub repository_url: Option<String>,
+
+    /// Ignore cached LLM responses and commit history for this run
+    #[arg(
+        long,
+        help = "Ignore cached LLM responses and learned commit history for this run (caches are still updated)"
+    )]
+    pub no_cache: bool,
+
+    /// Read untracked files into contexts that include unstaged changes
+    #[arg(
+        long,
+        help = "Read the content of untracked files along with unstaged changes, rather than only listing them (.gitignore is respected)"
+    )]
+    pub include_untracked: bool,
+
+    /// Named config profile to apply over the base configuration
+    #[arg(
+        long,
+        env = PROFILE_ENV_VAR,
+        help = "Config profile to use (from [profiles.<name>] in the config file)"
+    )]
+    pub profile: Option<String>,
+
+    /// Ignore the repository's `.gitai.toml`
+    #[arg(
+        long,
+        help = "Ignore the repository's .gitai.toml and use only your own config"
+    )]
+    pub no_project_config: bool,
+
+    /// Sampling temperature for this run
+    #[arg(
+        long,
+        help = "Sampling temperature for this run (0 for the most repeatable output)",
+        value_parser = temperature_parser
+    )]
+    pub temperature: Option<f32>,
+
+    /// Sampling seed for this run
+    #[arg(
+        long,
+        help = "Sampling seed for this run; combine with --temperature 0 for reproducible output"
+    )]
+    pub seed: Option<u64>,
+
+    #[command(flatten)]
+    pub log: LogParams,
+}
+
+/// `--verbose` and `--quiet`, shared by every binary that logs through `env_logger`
+#[derive(Args, Clone, Copy, Debug, Default)]
+pub struct LogParams {
+    /// Log more: `-v` info, `-vv` debug, `-vvv` trace
+    #[arg(
+        short,
+        long,
+        action = ArgAction::Count,
+        conflicts_with = "quiet",
+        help = "Log more (-v info, -vv debug, -vvv trace); RUST_LOG takes precedence when set"
+    )]
+    pub verbose: u8,
+
+    /// Only report errors, without spinners or progress output
+    #[arg(
+        short,
+        long,
+        help = "Only report errors, hiding spinners and progress output"
+    )]
+    pub quiet: bool,
+}
+
+impl LogParams {
+    /// Log level picked by `--verbose` and `--quiet`, errors only by default
+    pub const fn log_level(&self) -> LevelFilter {
+        if self.quiet {
+            return LevelFilter::Error;
+        }
+        match self.verbose {
+            0 => LevelFilter::Error,
+            1 => LevelFilter::Info,
+            2 => LevelFilter::Debug,
+            _ => LevelFilter::Trace,
+        }
+    }
+
+    /// Start logging at [`log_level`](Self::log_level), unless `RUST_LOG` says otherwise.
+    ///
+    /// `--quiet` also hides spinners and waiting messages.
+    pub fn init_logger(&self) {
+        env_logger::Builder::from_env(
+            env_logger::Env::default().default_filter_or(self.log_level().as_str()),
+        )
+        .init();
+        if self.quiet {
+            crate::ui::set_quiet_mode(true);
+        }
+    }
 }
 
 impl Default for CommonParams {
@@ -79,8 +199,17 @@ impl Default for CommonParams {
             debug_llm: false,
             provider: None,
             instructions: None,
+            instructions_file: None,
+            preset: None,
             detail_level: "standard".to_string(),
             repository_url: None,
+            no_cache: false,
+            include_untracked: false,
+            profile: None,
+            no_project_config: false,
+            temperature: None,
+            seed: None,
+            log: LogParams::default(),
         }
     }
 }
@@ -91,6 +220,22 @@ impl CommonParams {
         {
             config.debug_llm = self.debug_llm;
         }
+        if self.no_cache {
+            config.no_cache = true;
+        }
+        if self.include_untracked {
+            config.include_untracked = true;
+        }
+
+        // Repo-local settings override the global config; the profile and flags override both
+        if !self.no_project_config {
+            config.apply_repo_config(Path::new("."))?;
+        }
+
+        // The profile sits between the base config and the flags below
+        if let Some(profile) = &self.profile {
+            config.apply_profile(profile)?;
+        }
         let mut changes_made = false;
 
         if let Some(provider) = &self.provider {
@@ -118,17 +263,66 @@ impl CommonParams {
                 }
 
                 config.default_provider.clone_from(&provider_name);
+                config.record_source("default_provider", ConfigSource::Flag);
                 changes_made = true;
             }
         }
 
-        if let Some(instructions) = &self.instructions {
-            config.set_temp_instructions(Some(instructions.clone()));
+        // Like instructions, sampling overrides apply to this run only
+        if self.temperature.is_some() || self.seed.is_some() {
+            config.set_sampling_overrides(self.temperature, self.seed);
+        }
+
+        if let Some(instructions) = self.resolved_instructions(config)? {
+            config.set_temp_instructions(Some(instructions));
+            config.record_source("instructions", ConfigSource::Flag);
             // Note: temp instructions don't count as permanent changes
         }
 
         Ok(changes_made)
     }
+
+    /// Log level picked by `--verbose` and `--quiet`, errors only by default
+    pub const fn log_level(&self) -> LevelFilter {
+        self.log.log_level()
+    }
+
+    /// Start logging at [`log_level`](Self::log_level), unless `RUST_LOG` says otherwise
+    pub fn init_logger(&self) {
+        self.log.init_logger();
+    }
+
+    /// Instructions for this run: the `--preset` text, then the `--instructions-file` text,
+    /// then the inline `--instructions`, where `@path` stands for the contents of `path`
+    pub fn resolved_instructions(&self, config: &Config) -> Result<Option<String>> {
+        let preset = self
+            .preset
+            .as_deref()
+            .map(|name| config.preset_instructions(name))
+            .transpose()?;
+        let file = self
+            .instructions_file
+            .as_deref()
+            .map(read_instructions_file)
+            .transpose()?;
+        let inline = match self.instructions.as_deref() {
+            Some(inline) => match inline.strip_prefix('@') {
+                Some(path) => Some(read_instructions_file(Path::new(path))?),
+                None => Some(inline.to_string()),
+            },
+            None => None,
+        };
+
+        Ok([preset, file, inline]
+            .into_iter()
+            .flatten()
+            .reduce(|base, next| format!("{}\n\n{}", base.trim_end(), next)))
+    }
+}
+
+fn read_instructions_file(path: &Path) -> Result<String> {
+    fs::read_to_string(path)
+        .with_context(|| format!("Failed to read instructions file: {}", path.display()))
 }
 
 /// Validates that a provider name is available in the system
@@ -156,6 +350,16 @@ pub fn available_providers_parser(s: &str) -> Result<String, String> {
     }
 }
 
+/// Validates that a temperature is a number between 0 and 2
+pub fn temperature_parser(s: &str) -> Result<f32, String> {
+    match s.parse::<f32>() {
+        Ok(t) if (0.0..=2.0).contains(&t) => Ok(t),
+        _ => Err(format!(
+            "Invalid temperature '{s}': expected a number between 0 and 2"
+        )),
+    }
+}
+
 pub fn get_combined_instructions(config: &Config) -> String {
     let mut prompt = String::from("\n\n");
 
@@ -170,3 +374,181 @@ pub fn get_combined_instructions(config: &Config) -> String {
 
     prompt
 }
+
+/// Append a user's `<feature>_system_suffix` to the end of a built-in system prompt
+pub fn append_system_suffix(mut prompt: String, suffix: &str) -> String {
+    let suffix = suffix.trim();
+    if !suffix.is_empty() {
+        prompt.push_str("\n\n");
+        prompt.push_str(suffix);
+        prompt.push('\n');
+    }
+    prompt
+}
+
+/// Write a generated artifact to `path`, creating missing parent directories
+pub fn write_output_file(path: &str, contents: &str) -> Result<()> {
+    let path = Path::new(path);
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent).with_context(|| {
+            format!(
+                "Failed to create directory for output file: {}",
+                parent.display()
+            )
+        })?;
+    }
+
+    fs::write(path, contents)
+        .with_context(|| format!("Failed to write output file: {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::PresetConfig;
+
+    #[test]
+    fn test_write_output_file_creates_parent_dirs() {
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let path = dir.path().join("out/pr/body.md");
+        let path = path.to_string_lossy();
+
+        write_output_file(&path, "## Summary\n").expect("Failed to write output file");
+
+        assert_eq!(
+            fs::read_to_string(path.as_ref()).expect("Failed to read output file"),
+            "## Summary\n"
+        );
+    }
+
+    #[test]
+    fn test_instructions_file_comes_before_inline_instructions() {
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let path = dir.path().join("COMMIT_GUIDELINES.md");
+        fs::write(&path, "Use the imperative mood.\n\nReference the ticket.\n")
+            .expect("Failed to write instructions file");
+
+        let config = Config::default();
+        let params = CommonParams {
+            instructions_file: Some(path.clone()),
+            instructions: Some("Keep it short.".to_string()),
+            ..CommonParams::default()
+        };
+        assert_eq!(
+            params
+                .resolved_instructions(&config)
+                .expect("instructions load"),
+            Some("Use the imperative mood.\n\nReference the ticket.\n\nKeep it short.".to_string())
+        );
+
+        let shorthand = CommonParams {
+            instructions: Some(format!("@{}", path.display())),
+            ..CommonParams::default()
+        };
+        assert_eq!(
+            shorthand
+                .resolved_instructions(&config)
+                .expect("instructions load"),
+            Some("Use the imperative mood.\n\nReference the ticket.\n".to_string())
+        );
+
+        let missing = CommonParams {
+            instructions_file: Some(dir.path().join("missing.md")),
+            ..CommonParams::default()
+        };
+        let error = missing
+            .resolved_instructions(&config)
+            .expect_err("a missing file must fail");
+        assert!(format!("{error:#}").contains("missing.md"));
+    }
+
+    #[test]
+    fn test_user_preset_is_the_base_for_inline_instructions() {
+        let mut config = Config::default();
+        config.presets.insert(
+            "team".to_string(),
+            PresetConfig {
+                instructions: "Prefix the title with the ticket id.\n".to_string(),
+            },
+        );
+        let params = CommonParams {
+            preset: Some("team".to_string()),
+            instructions: Some("Mention the migration.".to_string()),
+            ..CommonParams::default()
+        };
+
+        assert_eq!(
+            params
+                .resolved_instructions(&config)
+                .expect("instructions load"),
+            Some("Prefix the title with the ticket id.\n\nMention the migration.".to_string())
+        );
+
+        let builtin = CommonParams {
+            preset: Some("concise".to_string()),
+            ..CommonParams::default()
+        };
+        assert!(
+            builtin
+                .resolved_instructions(&config)
+                .expect("built-in preset loads")
+                .is_some_and(|text| text.contains("minimal"))
+        );
+    }
+
+    #[test]
+    fn test_verbosity_flags_pick_the_log_level() {
+        #[derive(clap::Parser)]
+        struct Cli {
+            #[command(flatten)]
+            common: CommonParams,
+        }
+        let level = |args: &[&str]| {
+            <Cli as clap::Parser>::try_parse_from(
+                std::iter::once("git-message").chain(args.iter().copied()),
+            )
+            .expect("arguments parse")
+            .common
+            .log_level()
+        };
+
+        assert_eq!(level(&[]), LevelFilter::Error);
+        assert_eq!(level(&["-v"]), LevelFilter::Info);
+        assert_eq!(level(&["-vv"]), LevelFilter::Debug);
+        assert_eq!(level(&["-vvv"]), LevelFilter::Trace);
+        assert_eq!(level(&["-v", "-v", "-v", "-v"]), LevelFilter::Trace);
+        assert_eq!(level(&["--quiet"]), LevelFilter::Error);
+        assert!(
+            <Cli as clap::Parser>::try_parse_from(["git-message", "-q", "-v"]).is_err(),
+            "--quiet and --verbose conflict"
+        );
+    }
+
+    #[test]
+    fn test_unknown_preset_errors() {
+        let params = CommonParams {
+            preset: Some("nope".to_string()),
+            ..CommonParams::default()
+        };
+        let error = params
+            .resolved_instructions(&Config::default())
+            .expect_err("an unknown preset must fail");
+
+        let message = error.to_string();
+        assert!(message.contains("Unknown preset 'nope'"));
+        assert!(message.contains("conventional"));
+    }
+
+    #[test]
+    fn test_write_output_file_error_names_path() {
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        // A directory can't be overwritten with a file
+        let path = dir.path().to_string_lossy();
+
+        let error = write_output_file(&path, "x").expect_err("writing over a directory must fail");
+        assert!(format!("{error:#}").contains(path.as_ref()));
+    }
+}
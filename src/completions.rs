@@ -0,0 +1,97 @@
+use anyhow::{Result, anyhow};
+use clap::{Command, CommandFactory};
+use clap_complete::{Shell, generate};
+use std::io::Write;
+use std::str::FromStr;
+
+/// Hidden flag accepted by every binary to print a shell completion script
+pub const GENERATE_COMPLETIONS_FLAG: &str = "--generate-completions";
+
+/// Print a completion script for `P` and return `true` if `--generate-completions <shell>`
+/// was passed.
+///
+/// The flag is checked before clap parses the arguments, so it works even for binaries
+/// with required arguments. Install the output where your shell looks for completions:
+///
+/// ```text
+/// git-message --generate-completions bash > ~/.local/share/bash-completion/completions/git-message
+/// git-message --generate-completions zsh > ~/.zfunc/_git-message
+/// git-message --generate-completions fish > ~/.config/fish/completions/git-message.fish
+/// git-message --generate-completions powershell >> $PROFILE
+/// ```
+pub fn print_completions_if_requested<P: CommandFactory>(bin_name: &str) -> Result<bool> {
+    let Some(shell) = requested_shell(std::env::args().skip(1))? else {
+        return Ok(false);
+    };
+
+    write_completions(&mut P::command(), shell, bin_name, &mut std::io::stdout());
+    Ok(true)
+}
+
+/// Write the completion script for `cmd` to `out`
+pub fn write_completions(cmd: &mut Command, shell: Shell, bin_name: &str, out: &mut dyn Write) {
+    generate(shell, cmd, bin_name, out);
+}
+
+/// Find the shell requested with `--generate-completions <shell>` or `--generate-completions=<shell>`
+fn requested_shell(args: impl IntoIterator<Item = String>) -> Result<Option<Shell>> {
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let value = if arg == GENERATE_COMPLETIONS_FLAG {
+            args.next()
+                .ok_or_else(|| anyhow!("{GENERATE_COMPLETIONS_FLAG} requires a shell name"))?
+        } else if let Some(value) = arg
+            .strip_prefix(GENERATE_COMPLETIONS_FLAG)
+            .and_then(|rest| rest.strip_prefix('='))
+        {
+            value.to_string()
+        } else {
+            continue;
+        };
+
+        return Shell::from_str(&value.to_lowercase())
+            .map(Some)
+            .map_err(|_| {
+                anyhow!(
+                    "Unsupported shell '{value}': expected bash, zsh, fish, powershell or elvish"
+                )
+            });
+    }
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::Cli;
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn test_generates_completions_for_every_shell() {
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish, Shell::PowerShell] {
+            let mut output = Vec::new();
+            write_completions(&mut Cli::command(), shell, "git-message", &mut output);
+
+            assert!(!output.is_empty(), "empty completion script for {shell}");
+        }
+    }
+
+    #[test]
+    fn test_requested_shell_parsing() {
+        assert_eq!(
+            requested_shell(args(&["--generate-completions", "zsh"])).expect("valid shell"),
+            Some(Shell::Zsh)
+        );
+        assert_eq!(
+            requested_shell(args(&["--generate-completions=Fish"])).expect("valid shell"),
+            Some(Shell::Fish)
+        );
+        assert_eq!(requested_shell(args(&["--print"])).expect("no flag"), None);
+        assert!(requested_shell(args(&["--generate-completions", "tcsh"])).is_err());
+        assert!(requested_shell(args(&["--generate-completions"])).is_err());
+    }
+}
@@ -5,9 +5,175 @@ use crate::git::GitRepo;
 
 use anyhow::{Context, Result, anyhow};
 use git2::Config as GitConfig;
-use log::debug;
+use log::{debug, warn};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Default size above which a single file's diff is left out of the commit context
+pub const DEFAULT_MAX_FILE_DIFF_BYTES: usize = 256 * 1024;
+
+const fn default_max_file_diff_bytes() -> usize {
+    DEFAULT_MAX_FILE_DIFF_BYTES
+}
+
+/// Default size above which a changed file is listed but never read into the commit context
+pub const DEFAULT_MAX_FILE_BYTES: usize = 8 * 1024 * 1024;
+
+const fn default_max_file_bytes() -> usize {
+    DEFAULT_MAX_FILE_BYTES
+}
+
+/// Default number of recent commits read into the commit context
+pub const DEFAULT_RECENT_COMMITS_COUNT: usize = 10;
+
+const fn default_recent_commits_count() -> usize {
+    DEFAULT_RECENT_COMMITS_COUNT
+}
+
+/// Default longest commit title, in characters
+pub const DEFAULT_MAX_TITLE_LENGTH: usize = 72;
+
+const fn default_max_title_length() -> usize {
+    DEFAULT_MAX_TITLE_LENGTH
+}
+
+/// Default number of commit messages generated to choose from
+pub const DEFAULT_CANDIDATES: usize = 1;
+
+const fn default_candidates() -> usize {
+    DEFAULT_CANDIDATES
+}
+
+/// Default number of past author messages included in prompts
+pub const DEFAULT_ENHANCED_HISTORY_COUNT: usize = 10;
+
+const fn default_enhanced_history_count() -> usize {
+    DEFAULT_ENHANCED_HISTORY_COUNT
+}
+
+/// Default age in days at which a past author message counts half as much as a new one
+pub const DEFAULT_HISTORY_HALF_LIFE_DAYS: u64 = 90;
+
+const fn default_history_half_life_days() -> u64 {
+    DEFAULT_HISTORY_HALF_LIFE_DAYS
+}
+
+/// Emoji for each conventional commit type, following the gitmoji spec
+pub const DEFAULT_GITMOJI: &[(&str, &str)] = &[
+    ("feat", "✨"),
+    ("fix", "🐛"),
+    ("docs", "📝"),
+    ("style", "🎨"),
+    ("refactor", "♻️"),
+    ("perf", "⚡️"),
+    ("test", "✅"),
+    ("build", "📦️"),
+    ("ci", "👷"),
+    ("chore", "🔧"),
+    ("revert", "⏪️"),
+    ("security", "🔒️"),
+];
+
+/// [`DEFAULT_GITMOJI`] as a map
+pub fn default_gitmoji() -> BTreeMap<String, String> {
+    DEFAULT_GITMOJI
+        .iter()
+        .map(|(commit_type, emoji)| ((*commit_type).to_string(), (*emoji).to_string()))
+        .collect()
+}
+
+/// Instruction presets available without any configuration, selected with `--preset <name>`
+pub const BUILTIN_PRESETS: &[(&str, &str)] = &[
+    (
+        "concise",
+        "Keep the message minimal: a short imperative title and at most two sentences of body, without implementation details.",
+    ),
+    (
+        "conventional",
+        "Follow the Conventional Commits specification strictly: a lowercase type such as feat, fix, docs or refactor, an optional scope, and an imperative summary.",
+    ),
+    (
+        "detailed",
+        "Write a thorough body explaining what changed, why it was needed, and any side effects or follow-up work, with one bullet point per separate change.",
+    ),
+];
+
+const fn default_emoji() -> bool {
+    false
+}
+
+/// `additional_params` keys that are also read from `gait.<provider>-<key>`
+pub const SAMPLING_PARAMS: &[&str] = &["temperature", "seed"];
+
+/// Jira-style issue key pattern selected by setting `ticket_pattern` to `default`
+pub const DEFAULT_TICKET_PATTERN: &str = r"\b([A-Z][A-Z0-9]+-\d+)";
+
+/// Expand `${VAR}` and `$VAR` references in a config value from the process environment.
+///
+/// A `$` not followed by a variable name is kept as-is. Unset variables are an error
+/// rather than expanding to an empty string.
+pub fn expand_env_vars(value: &str) -> Result<String> {
+    expand_env_vars_with(value, |name| std::env::var(name).ok())
+}
+
+/// Like [`expand_env_vars`], resolving variables with `lookup`
+pub fn expand_env_vars_with(
+    value: &str,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<String> {
+    let mut expanded = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(start) = rest.find('$') {
+        expanded.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+
+        let (name, consumed) = if let Some(braced) = after.strip_prefix('{') {
+            let end = braced
+                .find('}')
+                .ok_or_else(|| anyhow!("Unterminated '${{' in config value"))?;
+            (&braced[..end], end + 2)
+        } else {
+            let end = after
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(after.len());
+            (&after[..end], end)
+        };
+
+        let is_name = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if is_name {
+            let resolved = lookup(name).ok_or_else(|| {
+                anyhow!("Environment variable '{name}' referenced in config is not set")
+            })?;
+            expanded.push_str(&resolved);
+            rest = &after[consumed..];
+        } else {
+            expanded.push('$');
+            rest = after;
+        }
+    }
+
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+/// Check that a sampling parameter from git config parses as its expected type
+fn validate_sampling_param(key: &str, value: &str) -> Result<()> {
+    let valid = match key {
+        "temperature" => value.parse::<f32>().is_ok_and(|t| (0.0..=2.0).contains(&t)),
+        "seed" => value.parse::<u64>().is_ok(),
+        _ => true,
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(anyhow!("'{value}' is not a valid {key}"))
+    }
+}
 
 /// Get a configuration value with layered priority: env var > local git config > global git config
 fn get_layered_value(
@@ -40,6 +206,24 @@ fn get_layered_value(
     None
 }
 
+/// Cache root from `gait.cachedir` in local or global git config, if set.
+///
+/// [`crate::core::cache::root_dir`] gives `GITAI_CACHE_DIR` precedence over this.
+pub fn configured_cache_dir() -> Option<String> {
+    let global_config = GitConfig::open_default().ok();
+    let local_config = git2::Repository::discover(".")
+        .ok()
+        .and_then(|repo| repo.config().ok());
+
+    get_layered_value(
+        "gait.cachedir",
+        None,
+        local_config.as_ref(),
+        global_config.as_ref(),
+    )
+    .filter(|value| !value.trim().is_empty())
+}
+
 /// Configuration structure
 #[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct Config {
@@ -47,9 +231,27 @@ pub struct Config {
     pub default_provider: String,
     /// Provider-specific configurations
     pub providers: HashMap<String, ProviderConfig>,
+    /// Providers to try, in order, when the default provider fails with a retriable error
+    #[serde(default)]
+    pub fallback_providers: Vec<String>,
     /// Instructions for commit messages
     #[serde(default)]
     pub instructions: String,
+    /// Text appended to the built-in commit message system prompt, after `instructions`
+    #[serde(default)]
+    pub commit_system_suffix: String,
+    /// Text appended to the built-in commit completion system prompt
+    #[serde(default)]
+    pub completion_system_suffix: String,
+    /// Text appended to the built-in pull request system prompt
+    #[serde(default)]
+    pub pr_system_suffix: String,
+    /// Text appended to the built-in changelog system prompt
+    #[serde(default)]
+    pub changelog_system_suffix: String,
+    /// Text appended to the built-in release notes system prompt
+    #[serde(default)]
+    pub release_notes_system_suffix: String,
     #[serde(skip)]
     pub temp_instructions: Option<String>,
     /// Enable LLM debugging (dumps prompts/responses to file) - debug builds only
@@ -58,6 +260,236 @@ pub struct Config {
     /// Flag indicating if this config is local
     #[serde(skip)]
     pub is_local: bool,
+    /// Reuse cached LLM responses for identical requests
+    #[serde(default)]
+    pub cache_responses: bool,
+    /// How long cached LLM responses stay valid, in seconds
+    #[serde(default)]
+    pub response_cache_ttl_secs: Option<u64>,
+    /// File diffs larger than this many bytes are replaced with a placeholder (0 disables)
+    #[serde(default = "default_max_file_diff_bytes")]
+    pub max_file_diff_bytes: usize,
+    /// Files with a version larger than this many bytes are listed but never read (0 disables)
+    #[serde(default = "default_max_file_bytes")]
+    pub max_file_bytes: usize,
+    /// How many recent commits are read into the commit context before relevance filtering
+    #[serde(default = "default_recent_commits_count")]
+    pub recent_commits_count: usize,
+    /// Longest generated commit title in characters, emoji included; asked of the model and
+    /// enforced on its answer (0 disables)
+    #[serde(default = "default_max_title_length")]
+    pub max_title_length: usize,
+    /// How many distinct commit messages are generated up front to choose from
+    #[serde(default = "default_candidates")]
+    pub candidates: usize,
+    /// How many of the author's past messages, most similar to the changes first, go into
+    /// the generation and completion prompts (0 leaves them out)
+    #[serde(default = "default_enhanced_history_count")]
+    pub enhanced_history_count: usize,
+    /// Age in days at which a cached author message counts half as much as a new one when
+    /// picking the history for the prompt, so old messages need to be more similar to the
+    /// changes to make it in (0 turns the decay off)
+    #[serde(default = "default_history_half_life_days")]
+    pub history_half_life_days: u64,
+    /// How the commit context is weighed when trimmed to the token budget: `default`, or
+    /// `tests-last` to trim test files before the code they test. Unset means `default`.
+    #[serde(default)]
+    pub context_scorer: Option<String>,
+    /// Gitignore-style globs for staged files to leave out of the commit context entirely.
+    ///
+    /// Applied before `max_file_diff_bytes`: excluded files are dropped from the prompt,
+    /// and the size guard only sees what remains. The files are still committed.
+    #[serde(default)]
+    pub exclude_paths: Vec<String>,
+    /// Regex extracting an issue key from the branch name; `default` selects
+    /// [`DEFAULT_TICKET_PATTERN`]. Ticket inference is off when unset.
+    #[serde(default)]
+    pub ticket_pattern: Option<String>,
+    /// Ignore cached data for this run (`--no-cache`): the LLM response cache and the
+    /// commit message history cache are not read, and no author history steers the
+    /// prompt. Both caches are still written, so later runs keep learning.
+    #[serde(skip)]
+    pub no_cache: bool,
+    /// Sign commits for this run (`--sign`), in addition to git's `commit.gpgsign`
+    #[serde(skip)]
+    pub sign_commits: bool,
+    /// `Name <email>` identities credited with `Co-authored-by:` trailers (`--co-author`)
+    #[serde(skip)]
+    pub co_authors: Vec<String>,
+    /// Prefix commit titles with the gitmoji of their commit type; off unless `gait.emoji` is set
+    #[serde(default = "default_emoji")]
+    pub emoji: bool,
+    /// Ask "Commit? [y/N]" before the TUI commits on Enter
+    #[serde(default)]
+    pub confirm_commit: bool,
+    /// Read untracked files into contexts that include unstaged changes, rather than
+    /// only listing them (`gait.includeuntracked`, `--include-untracked`)
+    #[serde(default)]
+    pub include_untracked: bool,
+    /// Emoji per conventional commit type: [`DEFAULT_GITMOJI`] overlaid with the
+    /// `[gitmoji]` table of the config file. An empty value drops the emoji for that type.
+    #[serde(default = "default_gitmoji")]
+    pub gitmoji: BTreeMap<String, String>,
+    /// TUI color overrides loaded from the config file
+    #[serde(default)]
+    pub theme: ThemeConfig,
+    /// Changelog section overrides loaded from the config file
+    #[serde(default)]
+    pub changelog: ChangelogConfig,
+    /// Named provider setups from the `[profiles.<name>]` tables of the config file
+    #[serde(default)]
+    pub profiles: HashMap<String, ProfileConfig>,
+    /// Instruction presets from the `[presets.<name>]` tables of the config file
+    #[serde(default)]
+    pub presets: BTreeMap<String, PresetConfig>,
+    /// Where overridden settings came from, keyed by setting name; unlisted ones are global
+    #[serde(skip)]
+    pub sources: BTreeMap<String, ConfigSource>,
+}
+
+/// Where the effective value of a setting came from, as reported by `git doctor`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigSource {
+    /// Built-in defaults, environment variables, git config or the global config file
+    Global,
+    /// The repository's `.gitai.toml` at this path
+    Repo(PathBuf),
+    /// The named `--profile`
+    Profile(String),
+    /// A command-line flag
+    Flag,
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Global => f.write_str("global config"),
+            Self::Repo(path) => write!(f, "{}", path.display()),
+            Self::Profile(name) => write!(f, "profile '{name}'"),
+            Self::Flag => f.write_str("command line"),
+        }
+    }
+}
+
+/// A named instruction set, from a `[presets.<name>]` table of the config file.
+///
+/// Selected with `--preset <name>`; a user preset replaces a built-in one of the same name.
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct PresetConfig {
+    pub instructions: String,
+}
+
+/// TUI color overrides, keyed by the `Theme` field they replace.
+///
+/// Values accept named colors (`"cyan"`, `"lightblue"`) and hex (`"#89b4fa"`).
+/// Unset fields keep the built-in default for the detected terminal.
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct ThemeConfig {
+    /// Title and highlight color
+    pub brand_primary: Option<String>,
+    pub text_default: Option<String>,
+    pub text_dimmed: Option<String>,
+    pub background_default: Option<String>,
+    pub background_elevated: Option<String>,
+    pub state_success: Option<String>,
+    pub state_error: Option<String>,
+    pub state_warning: Option<String>,
+    pub state_info: Option<String>,
+    pub accent: Option<String>,
+    /// Selected-item highlight and spinner color
+    pub accent_active: Option<String>,
+    pub border: Option<String>,
+    pub border_active: Option<String>,
+}
+
+/// Conventional-commit changelog sections, from the `[changelog]` table of the config file.
+///
+/// When `sections` is non-empty it replaces the built-in mapping entirely, and its order
+/// is the order sections appear in the changelog.
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct ChangelogConfig {
+    pub sections: Vec<ChangelogSectionConfig>,
+    /// Title for commits that don't match any section (default `Other`)
+    pub other_title: Option<String>,
+    /// Title for the breaking changes section (default `BREAKING CHANGES`)
+    pub breaking_title: Option<String>,
+}
+
+/// Maps a conventional commit type to a changelog section title
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct ChangelogSectionConfig {
+    #[serde(rename = "type")]
+    pub commit_type: String,
+    pub title: String,
+}
+
+/// A named set of overrides, from a `[profiles.<name>]` table of the config file.
+///
+/// Activated with `--profile <name>` or `GITAI_PROFILE`; set fields replace the base
+/// config and unset fields fall back to it.
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct ProfileConfig {
+    pub default_provider: Option<String>,
+    pub instructions: Option<String>,
+    /// Per-provider overrides, keyed by provider name
+    pub providers: HashMap<String, ProviderOverride>,
+}
+
+/// Provider settings a profile may override
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct ProviderOverride {
+    pub api_key: Option<String>,
+    pub model: Option<String>,
+    pub token_limit: Option<usize>,
+    pub base_url: Option<String>,
+    pub max_concurrent_requests: Option<usize>,
+    pub requests_per_minute: Option<u32>,
+}
+
+/// Environment variable selecting the active profile when `--profile` is not given
+pub const PROFILE_ENV_VAR: &str = "GITAI_PROFILE";
+
+/// Repo-local config files, looked up at the repository root in this order
+pub const REPO_CONFIG_FILES: &[&str] = &[".gitai.toml", ".gitai/config.toml"];
+
+/// Settings a repository can override in its `.gitai.toml`.
+///
+/// The file is usually committed, so it's trusted less than the user's own config: API
+/// keys and base URLs are never read from it, as they could send the user's key
+/// somewhere else.
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct RepoConfig {
+    pub default_provider: Option<String>,
+    pub instructions: Option<String>,
+    pub exclude_paths: Option<Vec<String>>,
+    pub ticket_pattern: Option<String>,
+    pub max_title_length: Option<usize>,
+    pub emoji: Option<bool>,
+    /// Per-provider overrides, keyed by provider name
+    pub providers: HashMap<String, ProviderOverride>,
+    /// Presets added to, or replacing, the user's
+    pub presets: BTreeMap<String, PresetConfig>,
+}
+
+/// Settings read from the TOML config file that have no git config equivalent
+#[derive(Deserialize, Debug, Default)]
+struct FileConfig {
+    #[serde(default)]
+    theme: ThemeConfig,
+    #[serde(default)]
+    changelog: ChangelogConfig,
+    #[serde(default)]
+    gitmoji: BTreeMap<String, String>,
+    #[serde(default)]
+    profiles: HashMap<String, ProfileConfig>,
+    #[serde(default)]
+    presets: BTreeMap<String, PresetConfig>,
 }
 
 /// Provider-specific configuration structure
@@ -72,6 +504,24 @@ pub struct ProviderConfig {
     pub additional_params: HashMap<String, String>,
     /// Token limit, if set by the user
     pub token_limit: Option<usize>,
+    /// Base URL of the provider's API, for self-hosted providers such as Ollama
+    #[serde(default)]
+    pub base_url: Option<String>,
+    /// Maximum number of requests in flight at once, if limited
+    #[serde(default)]
+    pub max_concurrent_requests: Option<usize>,
+    /// Maximum number of requests started per minute, if limited
+    #[serde(default)]
+    pub requests_per_minute: Option<u32>,
+    /// Azure OpenAI resource name, the `{resource}` in `{resource}.openai.azure.com`
+    #[serde(default)]
+    pub resource: Option<String>,
+    /// Azure OpenAI deployment to send requests to
+    #[serde(default)]
+    pub deployment: Option<String>,
+    /// Azure OpenAI REST API version, e.g. `2024-10-21`
+    #[serde(default)]
+    pub api_version: Option<String>,
 }
 
 impl Config {
@@ -99,21 +549,195 @@ impl Config {
         )
         .unwrap_or_default();
 
+        let [
+            commit_system_suffix,
+            completion_system_suffix,
+            pr_system_suffix,
+            changelog_system_suffix,
+            release_notes_system_suffix,
+        ] = ["commit", "completion", "pr", "changelog", "releasenotes"].map(|feature| {
+            get_layered_value(
+                &format!("gait.{feature}systemsuffix"),
+                None,
+                local_config.as_ref(),
+                global_config.as_ref(),
+            )
+            .unwrap_or_default()
+        });
+
+        let cache_responses = get_layered_value(
+            "gait.cacheresponses",
+            Some("GAIT_CACHE_RESPONSES"),
+            local_config.as_ref(),
+            global_config.as_ref(),
+        )
+        .is_some_and(|value| matches!(value.to_lowercase().as_str(), "true" | "1" | "yes"));
+
+        let response_cache_ttl_secs = get_layered_value(
+            "gait.responsecachettl",
+            None,
+            local_config.as_ref(),
+            global_config.as_ref(),
+        )
+        .and_then(|value| value.parse::<u64>().ok());
+
+        let max_file_diff_bytes = get_layered_value(
+            "gait.maxfilediffbytes",
+            Some("GAIT_MAX_FILE_DIFF_BYTES"),
+            local_config.as_ref(),
+            global_config.as_ref(),
+        )
+        .and_then(|value| value.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_MAX_FILE_DIFF_BYTES);
+
+        let max_file_bytes = get_layered_value(
+            "gait.maxfilebytes",
+            Some("GAIT_MAX_FILE_BYTES"),
+            local_config.as_ref(),
+            global_config.as_ref(),
+        )
+        .and_then(|value| value.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_MAX_FILE_BYTES);
+
+        let recent_commits_count = get_layered_value(
+            "gait.recentcommits",
+            Some("GAIT_RECENT_COMMITS"),
+            local_config.as_ref(),
+            global_config.as_ref(),
+        )
+        .and_then(|value| value.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_RECENT_COMMITS_COUNT);
+
+        let max_title_length = get_layered_value(
+            "gait.maxtitlelength",
+            Some("GAIT_MAX_TITLE_LENGTH"),
+            local_config.as_ref(),
+            global_config.as_ref(),
+        )
+        .and_then(|value| value.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_MAX_TITLE_LENGTH);
+
+        let candidates = get_layered_value(
+            "gait.candidates",
+            Some("GAIT_CANDIDATES"),
+            local_config.as_ref(),
+            global_config.as_ref(),
+        )
+        .and_then(|value| value.parse::<usize>().ok())
+        .filter(|&count| count > 0)
+        .unwrap_or(DEFAULT_CANDIDATES);
+
+        let enhanced_history_count = get_layered_value(
+            "gait.enhancedhistorycount",
+            Some("GAIT_ENHANCED_HISTORY_COUNT"),
+            local_config.as_ref(),
+            global_config.as_ref(),
+        )
+        .and_then(|value| value.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_ENHANCED_HISTORY_COUNT);
+
+        let history_half_life_days = get_layered_value(
+            "gait.historyhalflife",
+            Some("GAIT_HISTORY_HALF_LIFE"),
+            local_config.as_ref(),
+            global_config.as_ref(),
+        )
+        .and_then(|value| value.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_HISTORY_HALF_LIFE_DAYS);
+
+        let context_scorer = get_layered_value(
+            "gait.contextscorer",
+            Some("GAIT_CONTEXT_SCORER"),
+            local_config.as_ref(),
+            global_config.as_ref(),
+        )
+        .filter(|value| !value.trim().is_empty());
+
+        let fallback_providers = get_layered_value(
+            "gait.fallbackproviders",
+            Some("GAIT_FALLBACK_PROVIDERS"),
+            local_config.as_ref(),
+            global_config.as_ref(),
+        )
+        .map(|value| {
+            value
+                .split(',')
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(str::to_lowercase)
+                .collect()
+        })
+        .unwrap_or_default();
+
+        let exclude_paths = get_layered_value(
+            "gait.excludepaths",
+            Some("GAIT_EXCLUDE_PATHS"),
+            local_config.as_ref(),
+            global_config.as_ref(),
+        )
+        .map(|value| {
+            value
+                .split(',')
+                .map(str::trim)
+                .filter(|pattern| !pattern.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+
+        let emoji = get_layered_value(
+            "gait.emoji",
+            Some("GAIT_EMOJI"),
+            local_config.as_ref(),
+            global_config.as_ref(),
+        )
+        .is_some_and(|value| matches!(value.to_lowercase().as_str(), "true" | "1" | "yes"));
+
+        let confirm_commit = get_layered_value(
+            "gait.confirmcommit",
+            Some("GAIT_CONFIRM_COMMIT"),
+            local_config.as_ref(),
+            global_config.as_ref(),
+        )
+        .is_some_and(|value| matches!(value.to_lowercase().as_str(), "true" | "1" | "yes"));
+
+        let include_untracked = get_layered_value(
+            "gait.includeuntracked",
+            Some("GAIT_INCLUDE_UNTRACKED"),
+            local_config.as_ref(),
+            global_config.as_ref(),
+        )
+        .is_some_and(|value| matches!(value.to_lowercase().as_str(), "true" | "1" | "yes"));
+
+        let ticket_pattern = get_layered_value(
+            "gait.ticketpattern",
+            Some("GAIT_TICKET_PATTERN"),
+            local_config.as_ref(),
+            global_config.as_ref(),
+        )
+        .filter(|value| !value.trim().is_empty());
+
         let mut providers = HashMap::new();
         for provider in get_available_provider_names() {
             let api_key_env = match provider.as_str() {
                 "openai" => Some("OPENAI_API_KEY"),
                 "anthropic" => Some("ANTHROPIC_API_KEY"),
                 "google" => Some("GOOGLE_API_KEY"),
+                "azure" => Some("AZURE_OPENAI_API_KEY"),
                 _ => None,
             };
 
-            if let Some(api_key) = get_layered_value(
+            // Keyless providers (e.g. Ollama) are always available
+            if let Some(raw_api_key) = get_layered_value(
                 &format!("gait.{provider}-apikey"),
                 api_key_env,
                 local_config.as_ref(),
                 global_config.as_ref(),
-            ) {
+            )
+            .or_else(|| (!provider_requires_api_key(&provider)).then(String::new))
+            {
+                let api_key = expand_env_vars(&raw_api_key)
+                    .with_context(|| format!("Invalid gait.{provider}-apikey"))?;
                 let default_model = get_default_model_for_provider(&provider).to_string();
                 let model = get_layered_value(
                     &format!("gait.{provider}-model"),
@@ -132,7 +756,55 @@ impl Config {
                 .and_then(|s| s.parse::<i64>().ok())
                 .and_then(|v| usize::try_from(v).ok());
 
-                let additional_params = HashMap::new(); // TODO: handle additional params if needed
+                let base_url = get_layered_value(
+                    &format!("gait.{provider}-baseurl"),
+                    (provider == "ollama").then_some("OLLAMA_HOST"),
+                    local_config.as_ref(),
+                    global_config.as_ref(),
+                )
+                .map(|url| expand_env_vars(&url))
+                .transpose()
+                .with_context(|| format!("Invalid gait.{provider}-baseurl"))?;
+
+                let max_concurrent_requests = get_layered_value(
+                    &format!("gait.{provider}-maxconcurrent"),
+                    None,
+                    local_config.as_ref(),
+                    global_config.as_ref(),
+                )
+                .and_then(|s| s.parse::<usize>().ok());
+
+                let requests_per_minute = get_layered_value(
+                    &format!("gait.{provider}-rpm"),
+                    None,
+                    local_config.as_ref(),
+                    global_config.as_ref(),
+                )
+                .and_then(|s| s.parse::<u32>().ok());
+
+                let [resource, deployment, api_version] = ["resource", "deployment", "apiversion"]
+                    .map(|key| {
+                        get_layered_value(
+                            &format!("gait.{provider}-{key}"),
+                            None,
+                            local_config.as_ref(),
+                            global_config.as_ref(),
+                        )
+                    });
+
+                let mut additional_params = HashMap::new();
+                for key in SAMPLING_PARAMS {
+                    if let Some(value) = get_layered_value(
+                        &format!("gait.{provider}-{key}"),
+                        None,
+                        local_config.as_ref(),
+                        global_config.as_ref(),
+                    ) {
+                        validate_sampling_param(key, &value)
+                            .with_context(|| format!("Invalid gait.{provider}-{key}"))?;
+                        additional_params.insert((*key).to_string(), value);
+                    }
+                }
 
                 providers.insert(
                     #[allow(clippy::implicit_clone)]
@@ -142,25 +814,273 @@ impl Config {
                         model_name: model,
                         additional_params,
                         token_limit,
+                        base_url,
+                        max_concurrent_requests,
+                        requests_per_minute,
+                        resource,
+                        deployment,
+                        api_version,
                     },
                 );
             }
         }
 
+        let file_config = Self::load_file_config()?;
+        let mut gitmoji = default_gitmoji();
+        gitmoji.extend(file_config.gitmoji);
+
         let config = Self {
             default_provider,
             providers,
+            fallback_providers,
             instructions,
+            commit_system_suffix,
+            completion_system_suffix,
+            pr_system_suffix,
+            changelog_system_suffix,
+            release_notes_system_suffix,
             temp_instructions: None,
             is_local: false,
             #[cfg(debug_assertions)]
             debug_llm: false,
+            cache_responses,
+            response_cache_ttl_secs,
+            max_file_diff_bytes,
+            max_file_bytes,
+            recent_commits_count,
+            max_title_length,
+            candidates,
+            enhanced_history_count,
+            history_half_life_days,
+            context_scorer,
+            exclude_paths,
+            ticket_pattern,
+            no_cache: false,
+            sign_commits: false,
+            co_authors: Vec::new(),
+            emoji,
+            confirm_commit,
+            include_untracked,
+            gitmoji,
+            theme: file_config.theme,
+            changelog: file_config.changelog,
+            profiles: file_config.profiles,
+            presets: file_config.presets,
+            sources: BTreeMap::new(),
         };
 
         debug!("Configuration loaded: {config:?}");
         Ok(config)
     }
 
+    /// Path of the TOML config file (`$XDG_CONFIG_HOME/gait/config.toml`)
+    #[must_use]
+    pub fn config_file_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("gait").join("config.toml"))
+    }
+
+    /// Read the TOML config file, returning defaults when it does not exist
+    fn load_file_config() -> Result<FileConfig> {
+        let Some(path) = Self::config_file_path().filter(|p| p.exists()) else {
+            return Ok(FileConfig::default());
+        };
+
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+        toml::from_str(&content)
+            .with_context(|| format!("Failed to parse config file: {}", path.display()))
+    }
+
+    /// Path of the repo-local config file in `repo_root`, if there is one
+    pub fn repo_config_path(repo_root: &Path) -> Option<PathBuf> {
+        REPO_CONFIG_FILES
+            .iter()
+            .map(|name| repo_root.join(name))
+            .find(|path| path.is_file())
+    }
+
+    /// Deep-merge the repo-local config of the repository containing `dir` over this
+    /// config, returning the path of the file when there was one
+    pub fn apply_repo_config(&mut self, dir: &Path) -> Result<Option<PathBuf>> {
+        let Some(root) = git2::Repository::discover(dir)
+            .ok()
+            .and_then(|repo| repo.workdir().map(Path::to_path_buf))
+        else {
+            return Ok(None);
+        };
+        let Some(path) = Self::repo_config_path(&root) else {
+            return Ok(None);
+        };
+
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read repo config file: {}", path.display()))?;
+        let repo_config: RepoConfig = toml::from_str(&content)
+            .with_context(|| format!("Failed to parse repo config file: {}", path.display()))?;
+
+        debug!("Applying repo config {}", path.display());
+        self.merge_repo_config(repo_config, &path);
+        Ok(Some(path))
+    }
+
+    /// Merge `repo_config`, read from `path`, over this config
+    pub fn merge_repo_config(&mut self, repo_config: RepoConfig, path: &Path) {
+        let source = ConfigSource::Repo(path.to_path_buf());
+
+        if let Some(provider) = repo_config.default_provider {
+            self.default_provider = provider;
+            self.record_source("default_provider", source.clone());
+        }
+        if let Some(instructions) = repo_config.instructions {
+            self.instructions = instructions;
+            self.record_source("instructions", source.clone());
+        }
+        if let Some(exclude_paths) = repo_config.exclude_paths {
+            self.exclude_paths = exclude_paths;
+            self.record_source("exclude_paths", source.clone());
+        }
+        if let Some(ticket_pattern) = repo_config.ticket_pattern {
+            self.ticket_pattern = Some(ticket_pattern);
+            self.record_source("ticket_pattern", source.clone());
+        }
+        if let Some(max_title_length) = repo_config.max_title_length {
+            self.max_title_length = max_title_length;
+            self.record_source("max_title_length", source.clone());
+        }
+        if let Some(emoji) = repo_config.emoji {
+            self.emoji = emoji;
+            self.record_source("emoji", source.clone());
+        }
+
+        for (provider, overrides) in repo_config.providers {
+            if overrides.api_key.is_some() || overrides.base_url.is_some() {
+                warn!(
+                    "Ignoring the api_key and base_url of {provider} in {}; set them in your own config",
+                    path.display()
+                );
+            }
+
+            let entry = self
+                .providers
+                .entry(provider.clone())
+                .or_insert_with(|| ProviderConfig::default_for(&provider));
+            if let Some(model) = overrides.model {
+                entry.model_name = model;
+            }
+            if overrides.token_limit.is_some() {
+                entry.token_limit = overrides.token_limit;
+            }
+            if overrides.max_concurrent_requests.is_some() {
+                entry.max_concurrent_requests = overrides.max_concurrent_requests;
+            }
+            if overrides.requests_per_minute.is_some() {
+                entry.requests_per_minute = overrides.requests_per_minute;
+            }
+            self.record_source(format!("providers.{provider}"), source.clone());
+        }
+
+        for (name, preset) in repo_config.presets {
+            self.record_source(format!("presets.{name}"), source.clone());
+            self.presets.insert(name, preset);
+        }
+    }
+
+    /// Note that `key` now has its value from `source`
+    pub fn record_source(&mut self, key: impl Into<String>, source: ConfigSource) {
+        self.sources.insert(key.into(), source);
+    }
+
+    /// Where the effective value of `key` came from
+    pub fn source_of(&self, key: &str) -> ConfigSource {
+        self.sources
+            .get(key)
+            .cloned()
+            .unwrap_or(ConfigSource::Global)
+    }
+
+    /// Instructions of the preset called `name`, preferring a user preset over a built-in one
+    pub fn preset_instructions(&self, name: &str) -> Result<String> {
+        if let Some(preset) = self.presets.get(name) {
+            return Ok(preset.instructions.clone());
+        }
+        if let Some((_, instructions)) = BUILTIN_PRESETS.iter().find(|(n, _)| *n == name) {
+            return Ok((*instructions).to_string());
+        }
+
+        let mut available: Vec<&str> = BUILTIN_PRESETS
+            .iter()
+            .map(|(n, _)| *n)
+            .chain(self.presets.keys().map(String::as_str))
+            .collect();
+        available.sort_unstable();
+        available.dedup();
+        Err(anyhow!(
+            "Unknown preset '{name}'. Available presets: {}",
+            available.join(", ")
+        ))
+    }
+
+    /// Deep-merge the named profile over this config.
+    ///
+    /// Fields set in the profile win; everything else keeps its base value.
+    pub fn apply_profile(&mut self, name: &str) -> Result<()> {
+        let Some(profile) = self.profiles.get(name).cloned() else {
+            let mut available: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+            available.sort_unstable();
+            let available = if available.is_empty() {
+                "none defined".to_string()
+            } else {
+                available.join(", ")
+            };
+            return Err(anyhow!(
+                "Unknown profile '{name}'. Available profiles: {available}"
+            ));
+        };
+
+        debug!("Applying profile '{name}'");
+        let source = ConfigSource::Profile(name.to_string());
+        if let Some(provider) = profile.default_provider {
+            self.default_provider = provider;
+            self.record_source("default_provider", source.clone());
+        }
+        if let Some(instructions) = profile.instructions {
+            self.instructions = instructions;
+            self.record_source("instructions", source.clone());
+        }
+
+        for (provider, overrides) in profile.providers {
+            self.record_source(format!("providers.{provider}"), source.clone());
+            let entry = self
+                .providers
+                .entry(provider.clone())
+                .or_insert_with(|| ProviderConfig::default_for(&provider));
+
+            if let Some(api_key) = overrides.api_key {
+                entry.api_key = expand_env_vars(&api_key).with_context(|| {
+                    format!("Invalid api_key for {provider} in profile '{name}'")
+                })?;
+            }
+            if let Some(model) = overrides.model {
+                entry.model_name = model;
+            }
+            if overrides.token_limit.is_some() {
+                entry.token_limit = overrides.token_limit;
+            }
+            if let Some(base_url) = overrides.base_url {
+                entry.base_url = Some(expand_env_vars(&base_url).with_context(|| {
+                    format!("Invalid base_url for {provider} in profile '{name}'")
+                })?);
+            }
+            if overrides.max_concurrent_requests.is_some() {
+                entry.max_concurrent_requests = overrides.max_concurrent_requests;
+            }
+            if overrides.requests_per_minute.is_some() {
+                entry.requests_per_minute = overrides.requests_per_minute;
+            }
+        }
+
+        Ok(())
+    }
+
     /// Merge this config with project-specific config, with project config taking precedence
     /// But never allow API keys from project config
     pub fn merge_with_project_config(&mut self, project_config: Self) {
@@ -192,10 +1112,62 @@ impl Config {
             if proj_provider_config.token_limit.is_some() {
                 entry.token_limit = proj_provider_config.token_limit;
             }
+
+            if proj_provider_config.base_url.is_some() {
+                entry.base_url = proj_provider_config.base_url;
+            }
+
+            if proj_provider_config.max_concurrent_requests.is_some() {
+                entry.max_concurrent_requests = proj_provider_config.max_concurrent_requests;
+            }
+
+            if proj_provider_config.requests_per_minute.is_some() {
+                entry.requests_per_minute = proj_provider_config.requests_per_minute;
+            }
+
+            if proj_provider_config.resource.is_some() {
+                entry.resource = proj_provider_config.resource;
+            }
+
+            if proj_provider_config.deployment.is_some() {
+                entry.deployment = proj_provider_config.deployment;
+            }
+
+            if proj_provider_config.api_version.is_some() {
+                entry.api_version = proj_provider_config.api_version;
+            }
+        }
+
+        if !project_config.fallback_providers.is_empty() {
+            self.fallback_providers = project_config.fallback_providers;
         }
 
         // Always override instructions field if set in project config
         self.instructions.clone_from(&project_config.instructions);
+
+        for (suffix, project_suffix) in [
+            (
+                &mut self.commit_system_suffix,
+                project_config.commit_system_suffix,
+            ),
+            (
+                &mut self.completion_system_suffix,
+                project_config.completion_system_suffix,
+            ),
+            (&mut self.pr_system_suffix, project_config.pr_system_suffix),
+            (
+                &mut self.changelog_system_suffix,
+                project_config.changelog_system_suffix,
+            ),
+            (
+                &mut self.release_notes_system_suffix,
+                project_config.release_notes_system_suffix,
+            ),
+        ] {
+            if !project_suffix.is_empty() {
+                *suffix = project_suffix;
+            }
+        }
     }
 
     /// Save the configuration to git config
@@ -219,6 +1191,92 @@ impl Config {
         // Set instructions
         config.set_str(&format!("{prefix}.instructions"), &self.instructions)?;
 
+        for (feature, suffix) in [
+            ("commit", &self.commit_system_suffix),
+            ("completion", &self.completion_system_suffix),
+            ("pr", &self.pr_system_suffix),
+            ("changelog", &self.changelog_system_suffix),
+            ("releasenotes", &self.release_notes_system_suffix),
+        ] {
+            if !suffix.is_empty() {
+                config.set_str(&format!("{prefix}.{feature}systemsuffix"), suffix)?;
+            }
+        }
+
+        config.set_bool(&format!("{prefix}.cacheresponses"), self.cache_responses)?;
+        if self.confirm_commit {
+            config.set_bool(&format!("{prefix}.confirmcommit"), true)?;
+        }
+        if self.include_untracked {
+            config.set_bool(&format!("{prefix}.includeuntracked"), true)?;
+        }
+        if let Some(ttl) = self.response_cache_ttl_secs {
+            config.set_str(&format!("{prefix}.responsecachettl"), &ttl.to_string())?;
+        }
+        if self.max_file_diff_bytes != DEFAULT_MAX_FILE_DIFF_BYTES {
+            config.set_str(
+                &format!("{prefix}.maxfilediffbytes"),
+                &self.max_file_diff_bytes.to_string(),
+            )?;
+        }
+        if self.max_file_bytes != DEFAULT_MAX_FILE_BYTES {
+            config.set_str(
+                &format!("{prefix}.maxfilebytes"),
+                &self.max_file_bytes.to_string(),
+            )?;
+        }
+        if self.recent_commits_count != DEFAULT_RECENT_COMMITS_COUNT {
+            config.set_str(
+                &format!("{prefix}.recentcommits"),
+                &self.recent_commits_count.to_string(),
+            )?;
+        }
+        if self.max_title_length != DEFAULT_MAX_TITLE_LENGTH {
+            config.set_str(
+                &format!("{prefix}.maxtitlelength"),
+                &self.max_title_length.to_string(),
+            )?;
+        }
+        if self.candidates != DEFAULT_CANDIDATES {
+            config.set_str(
+                &format!("{prefix}.candidates"),
+                &self.candidates.to_string(),
+            )?;
+        }
+        if self.enhanced_history_count != DEFAULT_ENHANCED_HISTORY_COUNT {
+            config.set_str(
+                &format!("{prefix}.enhancedhistorycount"),
+                &self.enhanced_history_count.to_string(),
+            )?;
+        }
+        if self.history_half_life_days != DEFAULT_HISTORY_HALF_LIFE_DAYS {
+            config.set_str(
+                &format!("{prefix}.historyhalflife"),
+                &self.history_half_life_days.to_string(),
+            )?;
+        }
+        if let Some(scorer) = &self.context_scorer {
+            config.set_str(&format!("{prefix}.contextscorer"), scorer)?;
+        }
+
+        if !self.exclude_paths.is_empty() {
+            config.set_str(
+                &format!("{prefix}.excludepaths"),
+                &self.exclude_paths.join(","),
+            )?;
+        }
+
+        if let Some(pattern) = &self.ticket_pattern {
+            config.set_str(&format!("{prefix}.ticketpattern"), pattern)?;
+        }
+
+        if !self.fallback_providers.is_empty() {
+            config.set_str(
+                &format!("{prefix}.fallbackproviders"),
+                &self.fallback_providers.join(","),
+            )?;
+        }
+
         for (provider, provider_config) in &self.providers {
             // Set api key only if not empty
             if !provider_config.api_key.is_empty() {
@@ -241,8 +1299,37 @@ impl Config {
                 )?;
             }
 
+            if let Some(base_url) = &provider_config.base_url {
+                config.set_str(&format!("{prefix}.{provider}-baseurl"), base_url)?;
+            }
+
+            if let Some(max_concurrent) = provider_config.max_concurrent_requests {
+                config.set_str(
+                    &format!("{prefix}.{provider}-maxconcurrent"),
+                    &max_concurrent.to_string(),
+                )?;
+            }
+
+            if let Some(rpm) = provider_config.requests_per_minute {
+                config.set_str(&format!("{prefix}.{provider}-rpm"), &rpm.to_string())?;
+            }
+
+            for (key, value) in [
+                ("resource", &provider_config.resource),
+                ("deployment", &provider_config.deployment),
+                ("apiversion", &provider_config.api_version),
+            ] {
+                if let Some(value) = value {
+                    config.set_str(&format!("{prefix}.{provider}-{key}"), value)?;
+                }
+            }
+
             for (key, value) in &provider_config.additional_params {
-                config.set_str(&format!("{prefix}.{provider}-additional{key}"), value)?;
+                if SAMPLING_PARAMS.contains(&key.as_str()) {
+                    config.set_str(&format!("{prefix}.{provider}-{key}"), value)?;
+                } else {
+                    config.set_str(&format!("{prefix}.{provider}-additional{key}"), value)?;
+                }
             }
         }
 
@@ -347,6 +1434,37 @@ impl Config {
         Ok(())
     }
 
+    /// The effective ticket regex, resolving `default` to [`DEFAULT_TICKET_PATTERN`]
+    #[must_use]
+    pub fn ticket_regex(&self) -> Option<&str> {
+        self.ticket_pattern.as_deref().map(|pattern| {
+            if pattern.eq_ignore_ascii_case("default") {
+                DEFAULT_TICKET_PATTERN
+            } else {
+                pattern
+            }
+        })
+    }
+
+    /// Override the default provider's sampling temperature and seed for this run
+    pub fn set_sampling_overrides(&mut self, temperature: Option<f32>, seed: Option<u64>) {
+        let provider_config = self
+            .providers
+            .entry(self.default_provider.clone())
+            .or_insert_with(|| ProviderConfig::default_for(&self.default_provider));
+
+        if let Some(temperature) = temperature {
+            provider_config
+                .additional_params
+                .insert("temperature".to_string(), temperature.to_string());
+        }
+        if let Some(seed) = seed {
+            provider_config
+                .additional_params
+                .insert("seed".to_string(), seed.to_string());
+        }
+    }
+
     /// Get the configuration for a specific provider
     #[must_use]
     pub fn get_provider_config(&self, provider: &str) -> Option<&ProviderConfig> {
@@ -410,11 +1528,41 @@ impl Default for Config {
         Self {
             default_provider,
             providers,
+            fallback_providers: Vec::new(),
             instructions: String::new(),
+            commit_system_suffix: String::new(),
+            completion_system_suffix: String::new(),
+            pr_system_suffix: String::new(),
+            changelog_system_suffix: String::new(),
+            release_notes_system_suffix: String::new(),
             temp_instructions: None,
             is_local: false,
             #[cfg(debug_assertions)]
             debug_llm: false,
+            cache_responses: false,
+            response_cache_ttl_secs: None,
+            max_file_diff_bytes: DEFAULT_MAX_FILE_DIFF_BYTES,
+            max_file_bytes: DEFAULT_MAX_FILE_BYTES,
+            recent_commits_count: DEFAULT_RECENT_COMMITS_COUNT,
+            max_title_length: DEFAULT_MAX_TITLE_LENGTH,
+            candidates: DEFAULT_CANDIDATES,
+            enhanced_history_count: DEFAULT_ENHANCED_HISTORY_COUNT,
+            history_half_life_days: DEFAULT_HISTORY_HALF_LIFE_DAYS,
+            context_scorer: None,
+            exclude_paths: Vec::new(),
+            ticket_pattern: None,
+            no_cache: false,
+            sign_commits: false,
+            co_authors: Vec::new(),
+            emoji: default_emoji(),
+            confirm_commit: false,
+            include_untracked: false,
+            gitmoji: default_gitmoji(),
+            theme: ThemeConfig::default(),
+            changelog: ChangelogConfig::default(),
+            profiles: HashMap::new(),
+            presets: BTreeMap::new(),
+            sources: BTreeMap::new(),
         }
     }
 }
@@ -428,6 +1576,12 @@ impl ProviderConfig {
             model_name: get_default_model_for_provider(provider).to_string(),
             additional_params: HashMap::new(),
             token_limit: None, // Will use the default from get_default_token_limit_for_provider
+            base_url: None,
+            max_concurrent_requests: None,
+            requests_per_minute: None,
+            resource: None,
+            deployment: None,
+            api_version: None,
         }
     }
 
@@ -0,0 +1,85 @@
+use crate::config::{Config, ProviderConfig};
+
+use anyhow::{Result, anyhow};
+
+/// Name of the Azure OpenAI provider in config and `--provider`
+pub const AZURE_PROVIDER: &str = "azure";
+
+/// Where an Azure OpenAI deployment is served, from `gait.azure-resource`,
+/// `gait.azure-deployment` and `gait.azure-apiversion`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AzureDeployment {
+    pub resource: String,
+    pub deployment: String,
+    pub api_version: String,
+}
+
+impl AzureDeployment {
+    /// Read the deployment from a provider config, naming every missing field in the error
+    pub fn from_provider_config(provider_config: &ProviderConfig) -> Result<Self> {
+        let field = |value: &Option<String>| {
+            value
+                .as_deref()
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+        };
+        let resource = field(&provider_config.resource);
+        let deployment = field(&provider_config.deployment);
+        let api_version = field(&provider_config.api_version);
+
+        match (resource, deployment, api_version) {
+            (Some(resource), Some(deployment), Some(api_version)) => Ok(Self {
+                resource,
+                deployment,
+                api_version,
+            }),
+            (resource, deployment, api_version) => {
+                let missing: Vec<&str> = [
+                    (resource.is_none(), "gait.azure-resource"),
+                    (deployment.is_none(), "gait.azure-deployment"),
+                    (api_version.is_none(), "gait.azure-apiversion"),
+                ]
+                .into_iter()
+                .filter_map(|(is_missing, key)| is_missing.then_some(key))
+                .collect();
+                Err(anyhow!(
+                    "The azure provider needs {} to be set",
+                    missing.join(", ")
+                ))
+            }
+        }
+    }
+
+    /// Base URL of the Azure OpenAI resource
+    pub fn endpoint(&self) -> String {
+        format!("https://{}.openai.azure.com/", self.resource)
+    }
+}
+
+/// Fail early when `provider_name` is azure but its deployment settings are incomplete
+pub fn check_provider_settings(config: &Config, provider_name: &str) -> Result<()> {
+    if !provider_name.eq_ignore_ascii_case(AZURE_PROVIDER) {
+        return Ok(());
+    }
+    let provider_config = config
+        .get_provider_config(AZURE_PROVIDER)
+        .ok_or_else(|| anyhow!("The azure provider needs gait.azure-apikey to be set"))?;
+    AzureDeployment::from_provider_config(provider_config).map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_deployment_settings_are_named() {
+        let error = AzureDeployment::from_provider_config(&ProviderConfig {
+            resource: Some("contoso".to_string()),
+            ..Default::default()
+        })
+        .expect_err("deployment and api version are missing")
+        .to_string();
+        assert!(error.contains("gait.azure-deployment, gait.azure-apiversion"));
+    }
+}
@@ -0,0 +1,117 @@
+use anyhow::{Result, anyhow};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::env;
+use std::fmt::Write;
+use std::path::PathBuf;
+
+use crate::config;
+
+/// Environment variable overriding the cache root
+pub const CACHE_DIR_ENV_VAR: &str = "GITAI_CACHE_DIR";
+
+/// Subdirectory of the cache root holding commit message history
+pub const COMMITS_DIR: &str = "commit_cache";
+/// Subdirectory of the cache root holding cached LLM responses
+pub const RESPONSES_DIR: &str = "llm_responses";
+/// Subdirectory of the cache root holding repositories cloned by `git wire`
+pub const REPOS_DIR: &str = "wire_repos";
+/// Subdirectory of the cache root holding built commit contexts
+pub const CONTEXTS_DIR: &str = "context_cache";
+/// Subdirectory of the cache root holding instructions last typed in the TUI
+pub const INSTRUCTIONS_DIR: &str = "tui_instructions";
+
+/// Root directory every on-disk cache lives under.
+///
+/// Resolved from `GITAI_CACHE_DIR`, then `gait.cachedir`, then `gitsw` under the
+/// platform cache directory (`$XDG_CACHE_HOME` on Linux).
+pub fn root_dir() -> Result<PathBuf> {
+    root_dir_with(|name| env::var(name).ok(), config::configured_cache_dir())
+}
+
+/// [`root_dir`] with the environment lookup and configured directory supplied by the caller
+pub fn root_dir_with(
+    lookup: impl Fn(&str) -> Option<String>,
+    configured: Option<String>,
+) -> Result<PathBuf> {
+    if let Some(dir) = lookup(CACHE_DIR_ENV_VAR)
+        .filter(|dir| !dir.trim().is_empty())
+        .or(configured)
+    {
+        return Ok(PathBuf::from(dir));
+    }
+
+    dirs::cache_dir()
+        .map(|dir| dir.join("gitsw"))
+        .ok_or_else(|| anyhow!("Could not determine cache directory"))
+}
+
+/// Cache key for `parts`: the hex SHA-256 of their JSON encoding, so the same parts give
+/// the same key across runs, platforms and toolchains
+pub fn digest_key(parts: &impl Serialize) -> String {
+    let json = serde_json::to_vec(parts).expect("cache key parts serialize to JSON");
+    Sha256::digest(json)
+        .iter()
+        .fold(String::with_capacity(64), |mut hex, b| {
+            let _ = write!(hex, "{b:02x}");
+            hex
+        })
+}
+
+/// Directory of the commit message history cache
+pub fn commits_dir() -> Result<PathBuf> {
+    Ok(root_dir()?.join(COMMITS_DIR))
+}
+
+/// Directory of the LLM response cache
+pub fn responses_dir() -> Result<PathBuf> {
+    Ok(root_dir()?.join(RESPONSES_DIR))
+}
+
+/// Directory of the commit context cache
+pub fn contexts_dir() -> Result<PathBuf> {
+    Ok(root_dir()?.join(CONTEXTS_DIR))
+}
+
+/// Directory of the instructions saved by the TUI
+pub fn instructions_dir() -> Result<PathBuf> {
+    Ok(root_dir()?.join(INSTRUCTIONS_DIR))
+}
+
+/// Directory of the `git wire` repository cache
+pub fn repos_dir() -> Result<PathBuf> {
+    Ok(root_dir()?.join(REPOS_DIR))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_env_var_overrides_default() {
+        let dir = root_dir_with(
+            |name| (name == CACHE_DIR_ENV_VAR).then(|| "/mnt/cache".to_string()),
+            Some("/from/git/config".to_string()),
+        )
+        .expect("cache root resolves");
+        assert_eq!(dir, PathBuf::from("/mnt/cache"));
+
+        let configured = root_dir_with(|_| None, Some("/from/git/config".to_string()))
+            .expect("cache root resolves");
+        assert_eq!(configured, PathBuf::from("/from/git/config"));
+
+        if let Some(platform) = dirs::cache_dir() {
+            let default = root_dir_with(|_| None, None).expect("cache root resolves");
+            assert_eq!(default, platform.join("gitsw"));
+        }
+    }
+
+    #[test]
+    fn test_digest_key_is_stable() {
+        assert_eq!(
+            digest_key(&("openai", 1)),
+            "f74b3f60e34e0d02144bcbc7923c2103b5c844a88f0a30d2c496870f39c9a86d"
+        );
+        assert_ne!(digest_key(&("ab", "c")), digest_key(&("a", "bc")));
+    }
+}
@@ -1,3 +1,4 @@
+use super::cache;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -23,7 +24,11 @@ pub struct CommitMessageCache {
 impl CommitMessageCache {
     /// Create a new cache instance
     pub fn new() -> Result<Self> {
-        let cache_dir = Self::get_cache_dir()?;
+        Self::with_dir(Self::get_cache_dir()?)
+    }
+
+    /// Load the cache stored in a specific directory
+    pub fn with_dir(cache_dir: PathBuf) -> Result<Self> {
         fs::create_dir_all(&cache_dir)?;
 
         let cache_file = cache_dir.join("commit_messages.json");
@@ -39,11 +44,7 @@ impl CommitMessageCache {
 
     /// Get the cache directory path
     fn get_cache_dir() -> Result<PathBuf> {
-        let mut cache_dir = dirs::cache_dir()
-            .ok_or_else(|| anyhow::anyhow!("Could not determine cache directory"))?;
-        cache_dir.push("gitsw");
-        cache_dir.push("commit_cache");
-        Ok(cache_dir)
+        cache::commits_dir()
     }
 
     /// Get commit messages for a specific author and repository
@@ -103,7 +104,12 @@ impl CommitMessageCache {
     /// Get cache statistics
     pub fn get_stats(&self) -> CacheStats {
         let total_messages = self.cache.values().map(Vec::len).sum();
-        let total_authors = self.cache.len();
+        let authors: std::collections::HashSet<_> = self
+            .cache
+            .keys()
+            .map(|key| key.split(':').next().unwrap_or(""))
+            .collect();
+        let total_authors = authors.len();
         let repos: std::collections::HashSet<_> = self
             .cache
             .keys()
@@ -120,7 +126,7 @@ impl CommitMessageCache {
 }
 
 /// Statistics about the cache
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
 pub struct CacheStats {
     pub total_messages: usize,
     pub total_authors: usize,
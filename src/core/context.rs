@@ -1,4 +1,8 @@
-use serde::Serialize;
+use anyhow::{Context, Result};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use log::debug;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt;
 
@@ -6,9 +10,11 @@ use crate::Config;
 use crate::core::semantic_similarity::SemanticSimilarity;
 use crate::core::token_optimizer::TokenOptimizer;
 
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct CommitContext {
     pub branch: String,
+    /// Issue key inferred from the branch name via `ticket_pattern`, e.g. `PROJ-1234`
+    pub ticket_id: Option<String>,
     pub recent_commits: Vec<RecentCommit>,
     pub staged_files: Vec<StagedFile>,
     pub user_name: String,
@@ -16,7 +22,7 @@ pub struct CommitContext {
     pub author_history: Vec<String>,
 }
 
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct RecentCommit {
     pub hash: String,
     pub message: String,
@@ -24,7 +30,7 @@ pub struct RecentCommit {
     pub timestamp: String,
 }
 
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct StagedFile {
     pub path: String,
     pub change_type: ChangeType,
@@ -33,7 +39,7 @@ pub struct StagedFile {
     pub content_excluded: bool,
 }
 
-#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub enum ChangeType {
     Added,
     Modified,
@@ -135,6 +141,7 @@ impl CommitContext {
     ) -> Self {
         Self {
             branch,
+            ticket_id: None,
             recent_commits,
             staged_files,
             user_name,
@@ -143,6 +150,60 @@ impl CommitContext {
         }
     }
 
+    /// Infer `ticket_id` from the branch name using the `ticket_pattern` regex
+    pub fn infer_ticket_id(&mut self, pattern: &str) -> Result<()> {
+        self.ticket_id = extract_ticket_id(&self.branch, pattern)?;
+        Ok(())
+    }
+
+    /// Drop files matching any of the gitignore-style `patterns` from the context.
+    ///
+    /// This is intent-based rather than size-based, so it runs before
+    /// [`Self::omit_oversized_diffs`]; the files themselves are still committed.
+    pub fn exclude_paths(&mut self, patterns: &[String]) -> Result<()> {
+        if patterns.is_empty() {
+            return Ok(());
+        }
+
+        let matcher = build_exclude_matcher(patterns)?;
+
+        self.staged_files.retain(|file| {
+            let excluded = matcher
+                .matched_path_or_any_parents(&file.path, false)
+                .is_ignore();
+            if excluded {
+                debug!("Excluding {} from commit context", file.path);
+            }
+            !excluded
+        });
+
+        Ok(())
+    }
+
+    /// Replace diffs larger than `max_bytes` with a `[diff omitted: N KB]` placeholder.
+    ///
+    /// Runs before token optimization so huge generated files never reach the tokenizer;
+    /// the file path stays in the context. A `max_bytes` of 0 disables the guard.
+    pub fn omit_oversized_diffs(&mut self, max_bytes: usize) {
+        if max_bytes == 0 {
+            return;
+        }
+
+        for file in &mut self.staged_files {
+            if file.diff.len() > max_bytes {
+                debug!(
+                    "Omitting diff for {} ({} bytes > {} bytes)",
+                    file.path,
+                    file.diff.len(),
+                    max_bytes
+                );
+                file.diff = format!("[diff omitted: {} KB]", file.diff.len().div_ceil(1024));
+                file.content = None;
+                file.content_excluded = true;
+            }
+        }
+    }
+
     pub async fn optimize(&mut self, max_tokens: usize, config: &Config) {
         let optimizer = TokenOptimizer::new(max_tokens, config.clone()).expect(
             "Failed to initialize token optimizer. Ensure the tokenizer data is available.",
@@ -246,17 +307,32 @@ impl CommitContext {
         conventions
     }
 
-    /// Get enhanced author history with semantic filtering
+    /// Author history to include in a prompt.
+    ///
+    /// This is the enhanced history, or nothing when the cache is bypassed with
+    /// `--no-cache` so the suggestion isn't steered by past messages.
+    pub fn prompt_history(&self, max_history: usize, no_cache: bool) -> Vec<String> {
+        if no_cache {
+            Vec::new()
+        } else {
+            self.get_enhanced_history(max_history)
+        }
+    }
+
+    /// Get enhanced author history with semantic filtering.
+    ///
+    /// Up to half of `max_history` are the messages most similar to the changes; the
+    /// most recent ones fill the rest, so as many as `max_history` come back when the
+    /// author has that many.
     pub fn get_enhanced_history(&self, max_history: usize) -> Vec<String> {
-        let similar_history = self.get_similar_history(max_history / 2);
-        let mut enhanced_history = similar_history;
-
-        // Add some recent history for recency
-        let recent_count = (max_history / 2).min(self.author_history.len());
-        for i in 0..recent_count {
-            if let Some(msg) = self.author_history.get(i)
-                && !enhanced_history.contains(msg)
-            {
+        let mut enhanced_history = self.get_similar_history(max_history / 2);
+
+        // Add recent history for recency
+        for msg in &self.author_history {
+            if enhanced_history.len() >= max_history {
+                break;
+            }
+            if !enhanced_history.contains(msg) {
                 enhanced_history.push(msg.clone());
             }
         }
@@ -296,9 +372,60 @@ fn is_imperative_verb(word: &str) -> bool {
     imperative_verbs.contains(&word.to_lowercase().as_str())
 }
 
+/// Build a matcher for gitignore-style `exclude_paths` patterns, relative to the repo root
+pub fn build_exclude_matcher(patterns: &[String]) -> Result<Gitignore> {
+    let mut builder = GitignoreBuilder::new(".");
+    for pattern in patterns {
+        builder
+            .add_line(None, pattern)
+            .with_context(|| format!("Invalid exclude_paths pattern '{pattern}'"))?;
+    }
+    builder
+        .build()
+        .context("Failed to build exclude_paths matcher")
+}
+
+/// Extract an issue key from a branch name.
+///
+/// Uses the first capture group of `pattern` when it has one, otherwise the whole match.
+pub fn extract_ticket_id(branch: &str, pattern: &str) -> Result<Option<String>> {
+    let regex =
+        Regex::new(pattern).with_context(|| format!("Invalid ticket_pattern '{pattern}'"))?;
+
+    Ok(regex.captures(branch).and_then(|captures| {
+        captures
+            .get(1)
+            .or_else(|| captures.get(0))
+            .map(|m| m.as_str().to_string())
+    }))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::DEFAULT_TICKET_PATTERN;
+
+    #[test]
+    fn test_extract_ticket_id_from_branch() {
+        assert_eq!(
+            extract_ticket_id("feature/PROJ-1234-add-widget", DEFAULT_TICKET_PATTERN)
+                .expect("valid pattern"),
+            Some("PROJ-1234".to_string())
+        );
+        assert_eq!(
+            extract_ticket_id("fix/gh-42-crash", r"gh-(\d+)").expect("valid pattern"),
+            Some("42".to_string())
+        );
+    }
+
+    #[test]
+    fn test_extract_ticket_id_without_match() {
+        assert_eq!(
+            extract_ticket_id("feature/add-widget", DEFAULT_TICKET_PATTERN).expect("valid pattern"),
+            None
+        );
+        assert!(extract_ticket_id("main", "(unclosed").is_err());
+    }
 
     #[test]
     fn test_fixed_size_buffer() {
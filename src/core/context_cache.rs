@@ -0,0 +1,163 @@
+use super::cache;
+use super::context::CommitContext;
+use crate::config::Config;
+
+use anyhow::Result;
+use git2::Oid;
+use log::debug;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Age after which a cached context is removed
+pub const MAX_CONTEXT_AGE: Duration = Duration::from_secs(7 * 86_400);
+
+/// On-disk cache of built commit contexts, keyed by the staged tree they were built from.
+///
+/// Separate from the commit message cache. Staging anything or committing changes the
+/// key rather than invalidating an entry, so entries are only removed once they are
+/// older than [`MAX_CONTEXT_AGE`], or by `git-cache clear --all`.
+#[derive(Debug, Clone)]
+pub struct ContextCache {
+    cache_dir: PathBuf,
+}
+
+impl ContextCache {
+    /// Create a cache under the shared cache root
+    pub fn new() -> Result<Self> {
+        Ok(Self::with_dir(cache::contexts_dir()?))
+    }
+
+    /// Create a cache stored in a specific directory
+    pub const fn with_dir(cache_dir: PathBuf) -> Self {
+        Self { cache_dir }
+    }
+
+    /// Build the cache key for the context of `staged_tree` on top of `head`.
+    ///
+    /// The settings that shape the context are part of the key, so changing one of them
+    /// rebuilds it rather than reusing a context filtered the old way.
+    pub fn key(
+        repo_path: &Path,
+        staged_tree: Oid,
+        head: Option<Oid>,
+        branch: &str,
+        config: &Config,
+    ) -> String {
+        cache::digest_key(&(
+            repo_path.to_string_lossy(),
+            staged_tree.to_string(),
+            head.map(|oid| oid.to_string()),
+            branch,
+            config.recent_commits_count,
+            config.history_half_life_days,
+            config.max_file_diff_bytes,
+            config.max_file_bytes,
+            &config.exclude_paths,
+            &config.ticket_pattern,
+        ))
+    }
+
+    /// Get the cached context for `key`, if one was stored
+    pub fn get(&self, key: &str) -> Option<CommitContext> {
+        let content = fs::read_to_string(self.entry_path(key)).ok()?;
+        serde_json::from_str(&content)
+            .inspect_err(|e| debug!("Ignoring unreadable cached context {key}: {e}"))
+            .ok()
+    }
+
+    /// Store a built context in the cache, removing entries past [`MAX_CONTEXT_AGE`]
+    pub fn put(&self, key: &str, context: &CommitContext) -> Result<()> {
+        fs::create_dir_all(&self.cache_dir)?;
+        fs::write(self.entry_path(key), serde_json::to_string(context)?)?;
+        self.prune(MAX_CONTEXT_AGE)?;
+        Ok(())
+    }
+
+    /// Remove cached contexts last written more than `max_age` ago, returning how many
+    pub fn prune(&self, max_age: Duration) -> Result<usize> {
+        let now = SystemTime::now();
+        let mut removed = 0;
+        for entry in fs::read_dir(&self.cache_dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let age = entry
+                .metadata()?
+                .modified()
+                .ok()
+                .and_then(|modified| now.duration_since(modified).ok())
+                .unwrap_or_default();
+            if age > max_age {
+                debug!("Removing stale cached context {}", path.display());
+                fs::remove_file(&path)?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.cache_dir.join(format!("{key}.json"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_prune_removes_only_old_entries() {
+        let dir = TempDir::new().expect("Failed to create temp dir");
+        let cache = ContextCache::with_dir(dir.path().to_path_buf());
+        fs::write(dir.path().join("old.json"), "{}").expect("Failed to write entry");
+        fs::write(dir.path().join("notes.txt"), "keep").expect("Failed to write file");
+        std::thread::sleep(Duration::from_millis(20));
+
+        assert_eq!(cache.prune(MAX_CONTEXT_AGE).expect("prune succeeds"), 0);
+        assert_eq!(
+            cache
+                .prune(Duration::from_millis(10))
+                .expect("prune succeeds"),
+            1
+        );
+        assert!(!dir.path().join("old.json").exists());
+        assert!(dir.path().join("notes.txt").exists());
+    }
+
+    #[test]
+    fn test_key_changes_with_the_staged_tree() {
+        let config = Config::default();
+        let repo = Path::new("/repo");
+        let tree = Oid::hash_object(git2::ObjectType::Blob, b"tree").expect("valid oid");
+        let other_tree = Oid::hash_object(git2::ObjectType::Blob, b"other").expect("valid oid");
+        let key = ContextCache::key(repo, tree, None, "main", &config);
+
+        assert_eq!(key, ContextCache::key(repo, tree, None, "main", &config));
+        assert_ne!(
+            key,
+            ContextCache::key(repo, other_tree, None, "main", &config)
+        );
+        assert_ne!(
+            key,
+            ContextCache::key(repo, tree, Some(other_tree), "main", &config)
+        );
+        assert_ne!(key, ContextCache::key(repo, tree, None, "dev", &config));
+        assert_ne!(
+            key,
+            ContextCache::key(
+                repo,
+                tree,
+                None,
+                "main",
+                &Config {
+                    exclude_paths: vec!["vendor/**".to_string()],
+                    ..Config::default()
+                }
+            )
+        );
+    }
+}
@@ -1,16 +1,20 @@
 use crate::config::Config;
+use crate::core::azure::{AZURE_PROVIDER, AzureDeployment};
+use crate::core::rate_limiter;
+use crate::core::response_cache::{DEFAULT_RESPONSE_CACHE_TTL_SECS, ResponseCache};
+use crate::error::{Categorize, ErrorCategory};
 use anyhow::{Result, anyhow};
 #[cfg(debug_assertions)]
 use chrono::Utc;
+use futures::StreamExt;
 use llm::{
     LLMProvider,
     builder::{LLMBackend, LLMBuilder},
     chat::ChatMessage,
 };
-use log::debug;
-#[cfg(debug_assertions)]
-use log::warn;
+use log::{debug, warn};
 use schemars::JsonSchema;
+use serde::Serialize;
 use serde::de::DeserializeOwned;
 #[cfg(debug_assertions)]
 use serde_json::{json, to_string};
@@ -23,7 +27,8 @@ use std::io::Write;
 use std::path::Path;
 use std::str::FromStr;
 use std::time::Duration;
-use tokio_retry::Retry;
+use tokio::sync::mpsc;
+use tokio_retry::RetryIf;
 use tokio_retry::strategy::ExponentialBackoff;
 
 #[derive(Debug)]
@@ -57,6 +62,13 @@ static PROVIDER_DEFAULTS: std::sync::LazyLock<
             token_limit: 1_000_000,
         },
     );
+    m.insert(
+        "ollama",
+        ProviderDefault {
+            model: "llama3.2",
+            token_limit: 8_000,
+        },
+    );
     m.insert(
         "xai",
         ProviderDefault {
@@ -85,6 +97,13 @@ static PROVIDER_DEFAULTS: std::sync::LazyLock<
             token_limit: 8_192,
         },
     ); // assuming
+    m.insert(
+        "azure",
+        ProviderDefault {
+            model: "gpt-4.1",
+            token_limit: 16_000,
+        },
+    );
     m.insert(
         "openrouter",
         ProviderDefault {
@@ -95,13 +114,297 @@ static PROVIDER_DEFAULTS: std::sync::LazyLock<
     m
 });
 
+// Regex for extracting HTTP status codes from provider error messages
+static HTTP_STATUS_RE: std::sync::LazyLock<regex::Regex> = std::sync::LazyLock::new(|| {
+    regex::Regex::new(r"(?:status|http|error|code)\D{0,16}\b(\d{3})\b")
+        .expect("Failed to compile HTTP status regex pattern - this is a bug")
+});
+
+// Regexes for the token counts in provider "context length exceeded" errors, e.g.
+// "maximum context length is 8192 tokens ... resulted in 9000 tokens" (OpenAI),
+// "prompt is too long: 210000 tokens > 200000 maximum" (Anthropic) and
+// "input token count (1200000) exceeds the maximum number of tokens allowed (1048576)" (Google)
+static MAX_TOKENS_RE: std::sync::LazyLock<regex::Regex> = std::sync::LazyLock::new(|| {
+    regex::Regex::new(
+        r"(?:maximum context length is|maximum number of tokens allowed|context window of)\D{0,8}(\d+)|(\d+) maximum",
+    )
+    .expect("Failed to compile max tokens regex pattern - this is a bug")
+});
+static REQUESTED_TOKENS_RE: std::sync::LazyLock<regex::Regex> = std::sync::LazyLock::new(|| {
+    regex::Regex::new(r"(?:resulted in|you requested|input token count|too long:)\D{0,8}(\d+)")
+        .expect("Failed to compile requested tokens regex pattern - this is a bug")
+});
+
+/// Token counts reported by a provider that rejected a prompt as too long
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ContextLengthExceeded {
+    /// The model's context window, if the error states it
+    pub max_tokens: Option<usize>,
+    /// Size of the rejected request, if the error states it
+    pub requested_tokens: Option<usize>,
+}
+
+/// Recognise a provider error saying the prompt exceeds the model's context window
+pub fn context_length_exceeded(error: &anyhow::Error) -> Option<ContextLengthExceeded> {
+    let message = error.to_string().to_lowercase();
+    let is_context_error = [
+        "context length",
+        "context_length_exceeded",
+        "context window",
+        "prompt is too long",
+        "maximum number of tokens",
+    ]
+    .iter()
+    .any(|needle| message.contains(needle));
+    if !is_context_error {
+        return None;
+    }
+
+    let number = |re: &regex::Regex| {
+        re.captures(&message).and_then(|captures| {
+            captures
+                .iter()
+                .skip(1)
+                .flatten()
+                .find_map(|m| m.as_str().parse().ok())
+        })
+    };
+    Some(ContextLengthExceeded {
+        max_tokens: number(&MAX_TOKENS_RE),
+        requested_tokens: number(&REQUESTED_TOKENS_RE),
+    })
+}
+
+/// Budget for the retry after `token_limit` was rejected: 80% of the smaller of the
+/// previous budget and the context window the provider reported
+pub fn shrunk_token_limit(token_limit: usize, reported: ContextLengthExceeded) -> usize {
+    let base = reported
+        .max_tokens
+        .map_or(token_limit, |max_tokens| max_tokens.min(token_limit));
+    base - base / 5
+}
+
+/// Runs `request` with `token_limit`, retrying once with a [`shrunk_token_limit`] if the
+/// provider reports that the prompt exceeds its context window
+///
+/// `request` is expected to rebuild its prompt for the budget it is given. Only one
+/// shrink is attempted, so a second context length error is returned as-is.
+pub async fn with_context_shrink<T, F, Fut>(token_limit: usize, mut request: F) -> Result<T>
+where
+    F: FnMut(usize) -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    match request(token_limit).await {
+        Err(e) => {
+            let Some(reported) = context_length_exceeded(&e) else {
+                return Err(e);
+            };
+            let shrunk = shrunk_token_limit(token_limit, reported);
+            warn!(
+                "Prompt exceeded the provider's context window with a {token_limit} token budget, retrying with {shrunk}"
+            );
+            request(shrunk).await
+        }
+        result => result,
+    }
+}
+
+/// Sampling settings that control how repeatable a provider's output is
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SamplingParams {
+    pub temperature: Option<f32>,
+    pub seed: Option<u64>,
+}
+
+/// Providers whose chat API takes a `seed` in the request body
+const SEEDED_PROVIDERS: &[&str] = &["openai", "openrouter"];
+
+/// Returns whether a provider's API accepts a sampling seed
+pub fn provider_supports_seed(provider_name: &str) -> bool {
+    SEEDED_PROVIDERS
+        .iter()
+        .any(|provider| provider_name.eq_ignore_ascii_case(provider))
+}
+
+/// Returns the extra request body that sends `seed` to a provider, if it accepts one
+pub fn seed_request_body(provider_name: &str, seed: u64) -> Option<serde_json::Value> {
+    provider_supports_seed(provider_name).then(|| serde_json::json!({ "seed": seed }))
+}
+
+/// Reads the temperature and seed configured for a provider
+pub fn sampling_params(config: &Config, provider_name: &str) -> SamplingParams {
+    let Some(provider_config) = config.get_provider_config(provider_name) else {
+        return SamplingParams::default();
+    };
+    let param = |key: &str| provider_config.additional_params.get(key);
+
+    SamplingParams {
+        temperature: param("temperature").and_then(|t| t.parse().ok()),
+        seed: param("seed").and_then(|s| s.parse().ok()),
+    }
+}
+
 /// Generates a message using the given configuration
+///
+/// Tries `provider_name` first, then each of `config.fallback_providers` when the
+/// previous provider fails with a retriable error. When response caching is enabled,
+/// an identical earlier request is answered from the on-disk cache.
 pub async fn get_message<T>(
     config: &Config,
     provider_name: &str,
     system_prompt: &str,
     user_prompt: &str,
 ) -> Result<T>
+where
+    T: DeserializeOwned + Serialize + JsonSchema,
+{
+    get_message_streaming(config, provider_name, system_prompt, user_prompt, None).await
+}
+
+/// Generates a message like [`get_message`], sending the response text to `events` as it
+/// arrives
+///
+/// Providers without streaming support send the complete response as a single chunk, and
+/// a cached response sends nothing. When a request is retried or falls back to another
+/// provider, [`StreamEvent::Restart`] discards the text sent so far.
+pub async fn get_message_streaming<T>(
+    config: &Config,
+    provider_name: &str,
+    system_prompt: &str,
+    user_prompt: &str,
+    events: Option<&mpsc::Sender<StreamEvent>>,
+) -> Result<T>
+where
+    T: DeserializeOwned + Serialize + JsonSchema,
+{
+    let cache = response_cache_for(config, provider_name);
+    let seed = sampling_params(config, provider_name)
+        .seed
+        .filter(|_| provider_supports_seed(provider_name));
+    let cache_key = config
+        .get_provider_config(provider_name)
+        .map(|provider_config| {
+            // Seeded runs only replay responses generated with the same seed
+            let model = match seed {
+                Some(seed) => format!("{}#seed={seed}", provider_config.model_name),
+                None => provider_config.model_name.clone(),
+            };
+            ResponseCache::key(provider_name, &model, system_prompt, user_prompt)
+        });
+
+    with_response_cache(cache.as_ref(), cache_key.as_deref(), provider_name, || {
+        get_message_with_fallback(config, provider_name, system_prompt, user_prompt, events)
+    })
+    .await
+}
+
+/// Returns the response cached under `key`, or runs `request` and caches its response
+///
+/// `request` returns the provider that answered along with its response. The key and
+/// sampling settings describe `provider_name`, so an answer from a fallback provider is
+/// returned without being cached.
+pub async fn with_response_cache<T, F, Fut>(
+    cache: Option<&ResponseCache>,
+    key: Option<&str>,
+    provider_name: &str,
+    request: F,
+) -> Result<T>
+where
+    T: DeserializeOwned + Serialize,
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<(String, T)>>,
+{
+    if let (Some(cache), Some(key)) = (cache, key)
+        && let Some(cached) = cache.get::<T>(key)
+    {
+        debug!("Using cached response for provider: {provider_name}");
+        return Ok(cached);
+    }
+
+    let (answered_by, message) = request().await?;
+
+    if answered_by != provider_name {
+        debug!("Not caching the response from fallback provider: {answered_by}");
+    } else if let (Some(cache), Some(key)) = (cache, key)
+        && let Err(e) = cache.put(key, &message)
+    {
+        debug!("Failed to cache response: {e}");
+    }
+
+    Ok(message)
+}
+
+/// Returns the response cache to use for a request, if caching applies to it
+///
+/// Caching is skipped when disabled in the config, bypassed with `--no-cache`, when the
+/// provider samples with a temperature above zero and no seed, or when a seed is set but the
+/// provider cannot be sent one, so those runs still vary.
+fn response_cache_for(config: &Config, provider_name: &str) -> Option<ResponseCache> {
+    if !config.cache_responses || config.no_cache {
+        return None;
+    }
+
+    let sampling = sampling_params(config, provider_name);
+    let seeded = sampling.seed.is_some() && provider_supports_seed(provider_name);
+    let non_deterministic = if sampling.seed.is_some() {
+        !seeded
+    } else {
+        sampling.temperature.is_some_and(|t| t > 0.0)
+    };
+    if non_deterministic {
+        debug!("Skipping response cache for non-deterministic request");
+        return None;
+    }
+
+    let ttl = Duration::from_secs(
+        config
+            .response_cache_ttl_secs
+            .unwrap_or(DEFAULT_RESPONSE_CACHE_TTL_SECS),
+    );
+    ResponseCache::new(ttl)
+        .inspect_err(|e| debug!("Response cache unavailable: {e}"))
+        .ok()
+}
+
+/// Generates a message, trying each fallback provider in turn, and returns it with the
+/// name of the provider that answered
+async fn get_message_with_fallback<T>(
+    config: &Config,
+    provider_name: &str,
+    system_prompt: &str,
+    user_prompt: &str,
+    events: Option<&mpsc::Sender<StreamEvent>>,
+) -> Result<(String, T)>
+where
+    T: DeserializeOwned + JsonSchema,
+{
+    let mut providers = vec![provider_name.to_string()];
+    for fallback in &config.fallback_providers {
+        if !providers.contains(fallback) {
+            providers.push(fallback.clone());
+        }
+    }
+
+    with_provider_fallback(&providers, |name| {
+        let name = name.to_string();
+        async move {
+            let message =
+                get_message_from_provider(config, &name, system_prompt, user_prompt, events)
+                    .await?;
+            Ok((name, message))
+        }
+    })
+    .await
+}
+
+/// Generates a message using a single provider, without falling back
+async fn get_message_from_provider<T>(
+    config: &Config,
+    provider_name: &str,
+    system_prompt: &str,
+    user_prompt: &str,
+    events: Option<&mpsc::Sender<StreamEvent>>,
+) -> Result<T>
 where
     T: DeserializeOwned + JsonSchema,
 {
@@ -109,20 +412,162 @@ where
     debug!("System prompt: {system_prompt}");
     debug!("User prompt: {user_prompt}");
 
+    let provider = build_provider(config, provider_name, system_prompt)?;
+
+    // Wait for this provider's concurrency / rate limit budget
+    let limits = config.get_provider_config(provider_name);
+    let _permit = rate_limiter::limiter_for(
+        provider_name,
+        limits.and_then(|p| p.max_concurrent_requests),
+        limits.and_then(|p| p.requests_per_minute),
+    )
+    .acquire()
+    .await?;
+
+    // Generate the message
+    request_message(
+        provider.as_ref(),
+        user_prompt,
+        provider_name,
+        #[cfg(debug_assertions)]
+        config.debug_llm,
+        system_prompt,
+        events,
+    )
+    .await
+}
+
+/// Runs `request` against each provider in order until one succeeds
+///
+/// Only retriable errors (timeouts, rate limits, server errors) move on to the next
+/// provider; any other error is returned immediately. When every provider fails, the
+/// error lists each provider that was attempted.
+pub async fn with_provider_fallback<T, F, Fut>(providers: &[String], mut request: F) -> Result<T>
+where
+    F: FnMut(&str) -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut failures = Vec::new();
+    let mut last_error = None;
+    let mut retriable = false;
+
+    for (index, provider) in providers.iter().enumerate() {
+        match request(provider).await {
+            Ok(message) => return Ok(message),
+            Err(e) => {
+                failures.push(format!("{provider}: {e}"));
+                retriable = is_retriable_error(&e);
+                last_error = Some(e);
+
+                let Some(next) = providers.get(index + 1).filter(|_| retriable) else {
+                    break;
+                };
+                warn!("Provider '{provider}' failed, falling back to '{next}'");
+            }
+        }
+    }
+
+    match last_error {
+        // A single attempt keeps its original error
+        Some(e) if failures.len() == 1 => Err(e),
+        // Retrying makes sense only if the last provider failed for a passing reason
+        Some(_) => Err(anyhow!(
+            "All attempted providers failed: {}",
+            failures.join("; ")
+        ))
+        .categorize(if retriable {
+            ErrorCategory::Transient
+        } else {
+            ErrorCategory::User
+        }),
+        None => Err(anyhow!("No LLM provider configured")),
+    }
+}
+
+/// Returns whether an error is worth retrying against another provider
+///
+/// Timeouts, rate limiting (429) and server-side (5xx) failures are retriable.
+pub fn is_retriable_error(error: &anyhow::Error) -> bool {
+    let message = error.to_string().to_lowercase();
+
+    if [
+        "timed out",
+        "timeout",
+        "rate limit",
+        "too many requests",
+        "overloaded",
+    ]
+    .iter()
+    .any(|needle| message.contains(needle))
+    {
+        return true;
+    }
+
+    HTTP_STATUS_RE.captures_iter(&message).any(|cap| {
+        cap[1]
+            .parse::<u16>()
+            .is_ok_and(|status| status == 429 || (500..600).contains(&status))
+    })
+}
+
+/// Backend and endpoint settings a provider is built with
+#[derive(Debug, Clone)]
+pub struct ProviderEndpoint {
+    pub backend: LLMBackend,
+    pub base_url: Option<String>,
+    pub deployment_id: Option<String>,
+    pub api_version: Option<String>,
+}
+
+/// Resolves the backend and endpoint `provider_name` is sent to
+pub fn provider_endpoint(config: &Config, provider_name: &str) -> Result<ProviderEndpoint> {
     // Parse the provider type
-    let backend = if provider_name.to_lowercase() == "openrouter" {
-        LLMBackend::OpenRouter
-    } else {
-        LLMBackend::from_str(provider_name).map_err(|e| anyhow!("Invalid provider: {e}"))?
+    let backend = match provider_name.to_lowercase().as_str() {
+        "openrouter" => LLMBackend::OpenRouter,
+        AZURE_PROVIDER => LLMBackend::AzureOpenAI,
+        _ => LLMBackend::from_str(provider_name).map_err(|e| anyhow!("Invalid provider: {e}"))?,
     };
 
+    let provider_config = config
+        .get_provider_config(provider_name)
+        .ok_or_else(|| anyhow!("Provider '{provider_name}' not found in configuration"))?;
+
+    // Azure serves the model from a named deployment on the resource's own endpoint
+    if provider_name.eq_ignore_ascii_case(AZURE_PROVIDER) {
+        let deployment = AzureDeployment::from_provider_config(provider_config)?;
+        return Ok(ProviderEndpoint {
+            backend,
+            base_url: Some(deployment.endpoint()),
+            deployment_id: Some(deployment.deployment),
+            api_version: Some(deployment.api_version),
+        });
+    }
+
+    // Point self-hosted providers (e.g. Ollama at http://localhost:11434) at their API
+    Ok(ProviderEndpoint {
+        backend,
+        base_url: provider_config.base_url.clone(),
+        deployment_id: None,
+        api_version: None,
+    })
+}
+
+/// Builds an LLM provider from the configuration for the given provider name
+fn build_provider(
+    config: &Config,
+    provider_name: &str,
+    system_prompt: &str,
+) -> Result<Box<dyn LLMProvider + Send + Sync>> {
+    let endpoint = provider_endpoint(config, provider_name)?;
+    let backend = endpoint.backend.clone();
+
     // Get provider configuration
     let provider_config = config
         .get_provider_config(provider_name)
         .ok_or_else(|| anyhow!("Provider '{provider_name}' not found in configuration"))?;
 
     // Build the provider
-    let mut builder = LLMBuilder::new().backend(backend.clone());
+    let mut builder = LLMBuilder::new().backend(endpoint.backend);
 
     // Set model
     if !provider_config.model_name.is_empty() {
@@ -132,6 +577,16 @@ where
     // Set system prompt
     builder = builder.system(system_prompt.to_string());
 
+    if let Some(base_url) = endpoint.base_url {
+        builder = builder.base_url(base_url);
+    }
+    if let Some(deployment_id) = endpoint.deployment_id {
+        builder = builder.deployment_id(deployment_id);
+    }
+    if let Some(api_version) = endpoint.api_version {
+        builder = builder.api_version(api_version);
+    }
+
     // Set API key if needed
     if requires_api_key(&backend) && !provider_config.api_key.is_empty() {
         builder = builder.api_key(provider_config.api_key.clone());
@@ -158,7 +613,8 @@ where
     } else {
         let default_max = get_default_token_limit_for_provider(provider_name)
             .try_into()
-            .map_err(|e| anyhow!("Token limit too large for u32: {e}"))?;
+            .map_err(|e| anyhow!("Token limit too large for u32: {e}"))
+            .categorize(ErrorCategory::Internal)?;
         builder = builder.max_tokens(default_max);
     }
 
@@ -169,30 +625,108 @@ where
         builder = builder.top_p(tp_val);
     }
 
+    // Send the seed where the provider's API takes one
+    if let Some(seed) = sampling_params(config, provider_name).seed {
+        match seed_request_body(provider_name, seed) {
+            Some(body) => builder = builder.extra_body(body),
+            None => warn!(
+                "Provider '{provider_name}' does not accept a seed; ignoring it and not caching the response"
+            ),
+        }
+    }
+
     // Build the provider
-    let provider = builder
+    builder
         .build()
-        .map_err(|e| anyhow!("Failed to build provider: {e}"))?;
+        .map_err(|e| anyhow!("Failed to build provider: {e}"))
+}
 
-    // Generate the message
-    get_message_with_provider(
-        provider,
+/// Partial output of a request made with [`get_message_streaming`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StreamEvent {
+    /// Text appended to the response
+    Chunk(String),
+    /// The request is retried, so the text received so far is discarded
+    Restart,
+}
+
+/// Longest a single request to a provider may take, streamed or not
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Returns whether the provider can stream responses over SSE
+pub fn provider_supports_streaming(provider_name: &str) -> bool {
+    matches!(
+        provider_name.to_lowercase().as_str(),
+        "openai" | "anthropic" | "openrouter"
+    )
+}
+
+/// Sends `messages` to `provider` and returns the response text
+///
+/// With `events`, a provider that supports streaming is streamed and each chunk is sent
+/// on as it arrives.
+async fn chat_text(
+    provider: &(dyn LLMProvider + Send + Sync),
+    messages: &[ChatMessage],
+    provider_type: &str,
+    events: Option<&mpsc::Sender<StreamEvent>>,
+) -> Result<String> {
+    let Some(events) = events.filter(|_| provider_supports_streaming(provider_type)) else {
+        let response = provider
+            .chat(messages)
+            .await
+            .map_err(|e| anyhow!("Provider error: {e}"))?;
+        let text = response.text().unwrap_or_default();
+        if let Some(events) = events {
+            let _ = events.send(StreamEvent::Chunk(text.clone())).await;
+        }
+        return Ok(text);
+    };
+
+    let mut stream = provider
+        .chat_stream(messages)
+        .await
+        .map_err(|e| anyhow!("Provider error: {e}"))?;
+    let mut text = String::new();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| anyhow!("Provider error: {e}"))?;
+        text.push_str(&chunk);
+        let _ = events.send(StreamEvent::Chunk(chunk)).await;
+    }
+    Ok(text)
+}
+
+/// Generates a message using the given provider (mainly for testing purposes)
+pub async fn get_message_with_provider<T>(
+    provider: Box<dyn LLMProvider + Send + Sync>,
+    user_prompt: &str,
+    provider_type: &str,
+    #[cfg(debug_assertions)] debug_llm: bool,
+    system_prompt: &str,
+) -> Result<T>
+where
+    T: DeserializeOwned + JsonSchema,
+{
+    request_message(
+        provider.as_ref(),
         user_prompt,
-        provider_name,
+        provider_type,
         #[cfg(debug_assertions)]
-        config.debug_llm,
+        debug_llm,
         system_prompt,
+        None,
     )
     .await
 }
 
-/// Generates a message using the given provider (mainly for testing purposes)
-pub async fn get_message_with_provider<T>(
-    provider: Box<dyn LLMProvider + Send + Sync>,
+/// Requests a message from `provider`, retrying once unless the prompt is too long
+async fn request_message<T>(
+    provider: &(dyn LLMProvider + Send + Sync),
     user_prompt: &str,
     provider_type: &str,
     #[cfg(debug_assertions)] debug_llm: bool,
     #[allow(clippy::used_underscore_binding)] _system_prompt: &str,
+    events: Option<&mpsc::Sender<StreamEvent>>,
 ) -> Result<T>
 where
     T: DeserializeOwned + JsonSchema,
@@ -201,9 +735,15 @@ where
 
     let retry_strategy = ExponentialBackoff::from_millis(10).factor(2).take(2); // 2 attempts total: initial + 1 retry
 
-    let result = Retry::spawn(retry_strategy, || async {
+    // A prompt over the context window fails the same way again; callers shrink it instead
+    let result = RetryIf::spawn(retry_strategy, || async {
         debug!("Attempting to generate message");
 
+        // Text streamed by an earlier attempt or provider is not part of this response
+        if let Some(events) = events {
+            let _ = events.send(StreamEvent::Restart).await;
+        }
+
         // Enhanced prompt that requests specifically formatted JSON output
         let enhanced_prompt = if std::any::type_name::<T>() == std::any::type_name::<String>() {
             user_prompt.to_string()
@@ -219,9 +759,8 @@ where
             messages.push(ChatMessage::assistant().content("Here is the JSON:\n{").build());
         }
 
-        match tokio::time::timeout(Duration::from_secs(30), provider.chat(&messages)).await {
-            Ok(Ok(response)) => {
-                let response_text = response.text().unwrap_or_default();
+        match tokio::time::timeout(REQUEST_TIMEOUT, chat_text(provider, &messages, provider_type, events)).await {
+            Ok(Ok(response_text)) => {
 
                 // Debug logging if enabled
                 #[cfg(debug_assertions)]
@@ -270,15 +809,15 @@ where
                 }
             }
             Ok(Err(e)) => {
-                debug!("Provider error: {e}");
-                Err(anyhow!("Provider error: {e}"))
+                debug!("{e}");
+                Err(e)
             }
             Err(_) => {
                 debug!("Provider timed out");
                 Err(anyhow!("Provider timed out"))
             }
         }
-    })
+    }, |e: &anyhow::Error| context_length_exceeded(e).is_none())
     .await;
 
     match result {
@@ -336,6 +875,7 @@ pub fn get_available_provider_names() -> Vec<String> {
         "deepseek".to_string(),
         "phind".to_string(),
         "openrouter".to_string(),
+        "azure".to_string(),
     ]
 }
 
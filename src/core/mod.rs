@@ -1,6 +1,12 @@
+pub mod azure;
+pub mod cache;
 pub mod commit_cache;
 pub mod context;
+pub mod context_cache;
 pub mod llm;
 pub mod messages;
+pub mod rate_limiter;
+pub mod response_cache;
+pub mod saved_instructions;
 pub mod semantic_similarity;
 pub mod token_optimizer;
@@ -0,0 +1,131 @@
+use anyhow::Result;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock};
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::Instant;
+
+/// Process-wide limiters, one per provider so providers don't share a budget
+static LIMITERS: LazyLock<Mutex<HashMap<String, Arc<RateLimiter>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Limits concurrent requests and, optionally, requests per minute
+#[derive(Debug)]
+pub struct RateLimiter {
+    semaphore: Arc<Semaphore>,
+    /// Minimum spacing between request starts, derived from the requests-per-minute limit
+    interval: Option<Duration>,
+    next_slot: Mutex<Option<Instant>>,
+}
+
+impl RateLimiter {
+    /// Create a limiter allowing `max_concurrent` in-flight requests and at most
+    /// `requests_per_minute` request starts per minute, spread evenly
+    pub fn new(max_concurrent: usize, requests_per_minute: Option<u32>) -> Self {
+        Self {
+            semaphore: Arc::new(Semaphore::new(max_concurrent.max(1))),
+            interval: requests_per_minute
+                .filter(|rpm| *rpm > 0)
+                .map(|rpm| Duration::from_secs(60) / rpm),
+            next_slot: Mutex::new(None),
+        }
+    }
+
+    /// Wait until a request may start; the request holds the returned permit until it finishes
+    pub async fn acquire(&self) -> Result<OwnedSemaphorePermit> {
+        let permit = Arc::clone(&self.semaphore).acquire_owned().await?;
+
+        if let Some(interval) = self.interval {
+            let slot = {
+                let mut next_slot = self.next_slot.lock();
+                let now = Instant::now();
+                let slot = next_slot.map_or(now, |next| next.max(now));
+                *next_slot = Some(slot + interval);
+                slot
+            };
+            tokio::time::sleep_until(slot).await;
+        }
+
+        Ok(permit)
+    }
+}
+
+/// Get the shared limiter for a provider, creating it with the given limits on first use
+pub fn limiter_for(
+    provider_name: &str,
+    max_concurrent: Option<usize>,
+    requests_per_minute: Option<u32>,
+) -> Arc<RateLimiter> {
+    let mut limiters = LIMITERS.lock();
+    Arc::clone(
+        limiters
+            .entry(provider_name.to_lowercase())
+            .or_insert_with(|| {
+                Arc::new(RateLimiter::new(
+                    max_concurrent.unwrap_or(Semaphore::MAX_PERMITS),
+                    requests_per_minute,
+                ))
+            }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_concurrency_of_one_serializes_calls() {
+        let limiter = Arc::new(RateLimiter::new(1, None));
+        let active = Arc::new(AtomicUsize::new(0));
+        let max_active = Arc::new(AtomicUsize::new(0));
+
+        let call = |limiter: Arc<RateLimiter>,
+                    active: Arc<AtomicUsize>,
+                    max_active: Arc<AtomicUsize>| async move {
+            let _permit = limiter.acquire().await.expect("Failed to acquire permit");
+            let now_active = active.fetch_add(1, Ordering::SeqCst) + 1;
+            max_active.fetch_max(now_active, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            active.fetch_sub(1, Ordering::SeqCst);
+        };
+
+        let first = tokio::spawn(call(
+            Arc::clone(&limiter),
+            Arc::clone(&active),
+            Arc::clone(&max_active),
+        ));
+        let second = tokio::spawn(call(
+            Arc::clone(&limiter),
+            Arc::clone(&active),
+            Arc::clone(&max_active),
+        ));
+        first.await.expect("first call panicked");
+        second.await.expect("second call panicked");
+
+        assert_eq!(max_active.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_requests_per_minute_spaces_requests() {
+        // 1200 requests per minute is one request every 50ms
+        let limiter = RateLimiter::new(10, Some(1200));
+        let start = Instant::now();
+
+        drop(limiter.acquire().await.expect("Failed to acquire permit"));
+        drop(limiter.acquire().await.expect("Failed to acquire permit"));
+
+        assert!(start.elapsed() >= Duration::from_millis(50));
+    }
+
+    #[test]
+    fn test_limiters_are_shared_per_provider() {
+        let a = limiter_for("test-provider-a", Some(1), None);
+        let again = limiter_for("test-provider-a", Some(5), None);
+        let b = limiter_for("test-provider-b", Some(1), None);
+
+        assert!(Arc::ptr_eq(&a, &again));
+        assert!(!Arc::ptr_eq(&a, &b));
+    }
+}
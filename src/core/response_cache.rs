@@ -0,0 +1,139 @@
+use super::cache;
+use anyhow::Result;
+use log::debug;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Default time-to-live for cached responses (one day)
+pub const DEFAULT_RESPONSE_CACHE_TTL_SECS: u64 = 86_400;
+
+/// A cached LLM response with the time it was stored
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedResponse {
+    /// Seconds since the Unix epoch when the response was cached
+    created_at: u64,
+    response: serde_json::Value,
+}
+
+/// On-disk cache for LLM responses keyed by a hash of the request
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    cache_dir: PathBuf,
+    ttl: Duration,
+}
+
+impl ResponseCache {
+    /// Create a cache under the shared cache root
+    pub fn new(ttl: Duration) -> Result<Self> {
+        Ok(Self::with_dir(cache::responses_dir()?, ttl))
+    }
+
+    /// Create a cache stored in a specific directory
+    pub const fn with_dir(cache_dir: PathBuf, ttl: Duration) -> Self {
+        Self { cache_dir, ttl }
+    }
+
+    /// Build the cache key for a request
+    pub fn key(provider: &str, model: &str, system_prompt: &str, user_prompt: &str) -> String {
+        cache::digest_key(&(provider, model, system_prompt, user_prompt))
+    }
+
+    /// Get a cached response, if one exists and has not expired
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let content = fs::read_to_string(self.entry_path(key)).ok()?;
+        let entry: CachedResponse = serde_json::from_str(&content).ok()?;
+
+        if now_secs().saturating_sub(entry.created_at) >= self.ttl.as_secs() {
+            debug!("Cached response {key} has expired");
+            return None;
+        }
+
+        serde_json::from_value(entry.response).ok()
+    }
+
+    /// Store a response in the cache
+    pub fn put<T: Serialize>(&self, key: &str, response: &T) -> Result<()> {
+        fs::create_dir_all(&self.cache_dir)?;
+
+        let entry = CachedResponse {
+            created_at: now_secs(),
+            response: serde_json::to_value(response)?,
+        };
+        fs::write(self.entry_path(key), serde_json::to_string(&entry)?)?;
+        Ok(())
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.cache_dir.join(format!("{key}.json"))
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_put_and_get_roundtrip() {
+        let dir = TempDir::new().expect("Failed to create temp dir");
+        let cache = ResponseCache::with_dir(dir.path().to_path_buf(), Duration::from_secs(60));
+        let key = ResponseCache::key("openai", "gpt-4.1", "system", "user");
+
+        cache
+            .put(&key, &"feat: add cache".to_string())
+            .expect("Failed to write cache entry");
+
+        assert_eq!(
+            cache.get::<String>(&key),
+            Some("feat: add cache".to_string())
+        );
+    }
+
+    #[test]
+    fn test_expired_entries_are_ignored() {
+        let dir = TempDir::new().expect("Failed to create temp dir");
+        let cache = ResponseCache::with_dir(dir.path().to_path_buf(), Duration::ZERO);
+        let key = ResponseCache::key("openai", "gpt-4.1", "system", "user");
+
+        cache
+            .put(&key, &"feat: add cache".to_string())
+            .expect("Failed to write cache entry");
+
+        assert_eq!(cache.get::<String>(&key), None);
+    }
+
+    #[test]
+    fn test_key_depends_on_every_input() {
+        let key = ResponseCache::key("openai", "gpt-4.1", "system", "user");
+
+        assert_ne!(
+            key,
+            ResponseCache::key("anthropic", "gpt-4.1", "system", "user")
+        );
+        assert_ne!(
+            key,
+            ResponseCache::key("openai", "gpt-4o", "system", "user")
+        );
+        assert_ne!(
+            key,
+            ResponseCache::key("openai", "gpt-4.1", "other", "user")
+        );
+        assert_ne!(
+            key,
+            ResponseCache::key("openai", "gpt-4.1", "system", "other")
+        );
+        assert_ne!(
+            key,
+            ResponseCache::key("openai", "gpt-4.1", "systemuser", "")
+        );
+    }
+}
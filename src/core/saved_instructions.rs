@@ -0,0 +1,56 @@
+use super::cache;
+
+use anyhow::Result;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Custom instructions last typed in the TUI, kept per repository so the next session
+/// starts from them.
+///
+/// Separate from the configured `instructions`, which stay untouched.
+#[derive(Debug, Clone)]
+pub struct SavedInstructions {
+    dir: PathBuf,
+}
+
+impl SavedInstructions {
+    /// Store instructions under the shared cache root
+    pub fn new() -> Result<Self> {
+        Ok(Self::with_dir(cache::instructions_dir()?))
+    }
+
+    /// Store instructions in a specific directory
+    pub const fn with_dir(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Instructions saved for `repo_path`, if any
+    pub fn load(&self, repo_path: &Path) -> Option<String> {
+        fs::read_to_string(self.entry_path(repo_path)).ok()
+    }
+
+    /// Save `instructions` for `repo_path`; saving nothing but whitespace clears them
+    pub fn save(&self, repo_path: &Path, instructions: &str) -> Result<()> {
+        if instructions.trim().is_empty() {
+            return self.clear(repo_path);
+        }
+        fs::create_dir_all(&self.dir)?;
+        fs::write(self.entry_path(repo_path), instructions)?;
+        Ok(())
+    }
+
+    /// Forget the instructions saved for `repo_path`
+    pub fn clear(&self, repo_path: &Path) -> Result<()> {
+        match fs::remove_file(self.entry_path(repo_path)) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    fn entry_path(&self, repo_path: &Path) -> PathBuf {
+        let repo_path = fs::canonicalize(repo_path).unwrap_or_else(|_| repo_path.to_path_buf());
+        let key = cache::digest_key(&repo_path.to_string_lossy());
+        self.dir.join(format!("{key}.txt"))
+    }
+}
@@ -1,5 +1,7 @@
 use std::collections::HashMap;
 
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
 /// Simple semantic similarity calculator for commit messages
 pub struct SemanticSimilarity {
     // For now, we'll use enhanced keyword matching
@@ -36,9 +38,36 @@ impl SemanticSimilarity {
         similarities
     }
 
-    /// Calculate similarity between change keywords and a single commit message
-    #[allow(clippy::cast_precision_loss)]
-    #[allow(clippy::as_conversions)]
+    /// Rank `messages`, given with their unix timestamps, by similarity to the changes
+    /// weighted by [`recency_weight`] as of `now`; most relevant first, newer first on ties.
+    pub fn rank_with_recency(
+        &self,
+        change_keywords: &[String],
+        messages: &[(String, i64)],
+        half_life_days: u64,
+        now: i64,
+    ) -> Vec<(usize, f32)> {
+        let mut ranked: Vec<(usize, f32)> = messages
+            .iter()
+            .enumerate()
+            .map(|(idx, (message, timestamp))| {
+                let similarity = self.calculate_message_similarity(change_keywords, message);
+                let weight = recency_weight(now.saturating_sub(*timestamp), half_life_days);
+                (idx, similarity * weight)
+            })
+            .collect();
+
+        ranked.sort_by(|a, b| {
+            b.1.partial_cmp(&a.1)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| messages[b.0].1.cmp(&messages[a.0].1))
+        });
+        ranked
+    }
+
+    /// Calculate similarity between change keywords and a single commit message: the
+    /// weight of the matched keywords over the weight of them all, so a matched file name
+    /// counts for more than a matched common word
     #[allow(clippy::unused_self)]
     fn calculate_message_similarity(&self, keywords: &[String], message: &str) -> f32 {
         if keywords.is_empty() {
@@ -46,7 +75,7 @@ impl SemanticSimilarity {
         }
 
         let message_lower = message.to_lowercase();
-        let mut matches = 0;
+        let mut matched_weight = 0.0;
         let mut total_weight = 0.0;
 
         for keyword in keywords {
@@ -54,14 +83,14 @@ impl SemanticSimilarity {
             total_weight += weight;
 
             if message_lower.contains(keyword) {
-                matches += 1;
+                matched_weight += weight;
             }
         }
 
         if total_weight == 0.0 {
             0.0
         } else {
-            (matches as f32) / (keywords.len() as f32)
+            matched_weight / total_weight
         }
     }
 
@@ -179,6 +208,21 @@ impl SemanticSimilarity {
     }
 }
 
+/// Weight of a message `age_secs` old when relevance halves every `half_life_days`.
+///
+/// A half-life of 0 turns the decay off; messages dated in the future count as new.
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::as_conversions
+)]
+pub fn recency_weight(age_secs: i64, half_life_days: u64) -> f32 {
+    if half_life_days == 0 || age_secs <= 0 {
+        return 1.0;
+    }
+    0.5_f64.powf(age_secs as f64 / (half_life_days as f64 * SECONDS_PER_DAY)) as f32
+}
+
 /// Split camelCase into individual words
 fn split_camel_case(s: &str) -> Vec<String> {
     let mut words = Vec::new();
@@ -243,6 +287,36 @@ mod tests {
         assert_eq!(split_camel_case("simple"), vec!["simple"]);
     }
 
+    #[test]
+    fn test_recent_messages_outrank_older_ones_of_equal_similarity() {
+        let similarity = SemanticSimilarity::new();
+        let keywords = vec!["parser".to_string()];
+        let now = 1_700_000_000;
+        let day = 86_400;
+        let messages = vec![
+            (
+                "fix(parser): handle empty input".to_string(),
+                now - 180 * day,
+            ),
+            ("feat(parser): support comments".to_string(), now - 7 * day),
+            ("docs: update readme".to_string(), now),
+        ];
+
+        // With a week-long half-life the week-old message keeps half its weight, the
+        // six-month-old one almost none
+        let ranked = similarity.rank_with_recency(&keywords, &messages, 7, now);
+        let order: Vec<usize> = ranked.iter().map(|(idx, _)| *idx).collect();
+        assert_eq!(order, vec![1, 0, 2]);
+        assert!((ranked[0].1 / ranked[1].1) > 1_000.0);
+
+        // Without decay equal similarity falls back to the newer message first
+        let ranked = similarity.rank_with_recency(&keywords, &messages, 0, now);
+        assert_eq!((ranked[0].0, ranked[1].0), (1, 0));
+
+        assert!((recency_weight(30 * day, 30) - 0.5).abs() < 1e-6);
+        assert!((recency_weight(-day, 30) - 1.0).abs() < f32::EPSILON);
+    }
+
     #[test]
     fn test_semantic_similarity() {
         let similarity = SemanticSimilarity::new();
@@ -252,4 +326,17 @@ mod tests {
         let score = similarity.calculate_message_similarity(&keywords, &message);
         assert!(score > 0.0);
     }
+
+    #[test]
+    fn test_file_name_matches_weigh_more_than_word_matches() {
+        let similarity = SemanticSimilarity::new();
+        let keywords = vec!["config.rs".to_string(), "update".to_string()];
+
+        // One match each: the file name carries twice the weight of the plain word
+        let file_match = similarity.calculate_message_similarity(&keywords, "fix: parse config.rs");
+        let word_match = similarity.calculate_message_similarity(&keywords, "fix: update parsing");
+        assert!(file_match > word_match);
+        assert!((file_match - 2.0 / 3.0).abs() < f32::EPSILON);
+        assert!((word_match - 1.0 / 3.0).abs() < f32::EPSILON);
+    }
 }
@@ -1,12 +1,23 @@
-use crate::{config::Config, core::context::CommitContext};
-use log::debug;
+use crate::{
+    config::Config,
+    core::context::{ChangeType, CommitContext},
+};
+use log::{debug, warn};
+use rayon::prelude::*;
 use tiktoken_rs::cl100k_base;
 
+/// Below this many texts, counting on one thread beats spreading the work over rayon
+const PARALLEL_COUNT_THRESHOLD: usize = 16;
+
+/// Share of its default importance a test file keeps under [`TestsLastScorer`]
+const TEST_FILE_WEIGHT: f32 = 0.1;
+
 pub struct TokenOptimizer {
     encoder: tiktoken_rs::CoreBPE,
     max_tokens: usize,
     #[allow(dead_code)]
     config: Config,
+    scorer: Box<dyn ImportanceScorer>,
 }
 
 #[derive(Debug)]
@@ -28,39 +39,165 @@ impl std::fmt::Display for TokenError {
 
 impl std::error::Error for TokenError {}
 
+/// A piece of the commit context competing for the token budget
 #[derive(Debug)]
-struct ContextItem {
-    item_type: ContextItemType,
-    token_count: usize,
-    importance: f32,
+pub struct ContextItem {
+    pub item_type: ContextItemType,
+    pub token_count: usize,
 }
 
+/// What a [`ContextItem`] is, indexing into the `CommitContext` it came from
 #[derive(Debug)]
-enum ContextItemType {
+pub enum ContextItemType {
     Diff { file_index: usize },
     Commit { commit_index: usize },
     Content { file_index: usize },
 }
 
+/// Weighs context items against each other when the context is trimmed to the budget.
+///
+/// Each item gets a share of the budget proportional to its score; items scored 0 are
+/// the first to go. Negative scores count as 0.
+pub trait ImportanceScorer: Send + Sync {
+    fn score(&self, item: &ContextItem, context: &CommitContext) -> f32;
+}
+
+/// The built-in weighting: diffs first, then recent commits, then file contents, each
+/// scaled by its size
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultScorer;
+
+impl DefaultScorer {
+    // Define base importance multipliers for different context types
+    // Staged changes (diffs) are most important, then recent commits, then file contents
+    const DIFF_BASE_MULTIPLIER: f32 = 3.0; // Highest priority - current changes
+    const COMMIT_BASE_MULTIPLIER: f32 = 2.0; // Medium priority - recent history
+    const CONTENT_BASE_MULTIPLIER: f32 = 1.0; // Lower priority - supporting context
+}
+
+impl ImportanceScorer for DefaultScorer {
+    #[allow(clippy::cast_precision_loss, clippy::as_conversions)]
+    fn score(&self, item: &ContextItem, context: &CommitContext) -> f32 {
+        let token_count = item.token_count;
+        match item.item_type {
+            ContextItemType::Diff { file_index } => {
+                // Importance = base_multiplier * token_count * change_type_factor
+                let change_type_factor = match context
+                    .staged_files
+                    .get(file_index)
+                    .map(|file| &file.change_type)
+                {
+                    Some(ChangeType::Added) => 1.2,           // New files are important
+                    Some(ChangeType::Modified) | None => 1.0, // Standard modifications
+                    Some(ChangeType::Deleted) => 0.8,         // Deletions less important
+                };
+                Self::DIFF_BASE_MULTIPLIER * token_count as f32 * change_type_factor
+            }
+            ContextItemType::Commit { commit_index } => {
+                // Importance = base_multiplier * token_count * recency_factor * length_factor
+                let recency_factor = 1.0 / (commit_index + 1) as f32; // Earlier commits more important
+                let length_factor = if token_count > 50 { 1.2 } else { 1.0 }; // Longer messages may be more informative
+                Self::COMMIT_BASE_MULTIPLIER * token_count as f32 * recency_factor * length_factor
+            }
+            ContextItemType::Content { .. } => {
+                // Importance = base_multiplier * token_count * relevance_factor * size_factor
+                let relevance_factor = 1.0; // All staged files are equally relevant
+                let size_factor = if token_count > 100 { 0.8 } else { 1.0 }; // Very large files get slightly lower priority
+                Self::CONTENT_BASE_MULTIPLIER * token_count as f32 * relevance_factor * size_factor
+            }
+        }
+    }
+}
+
+/// [`DefaultScorer`] with test files down-weighted, so their diffs and contents are
+/// trimmed before the code under test
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TestsLastScorer;
+
+impl ImportanceScorer for TestsLastScorer {
+    fn score(&self, item: &ContextItem, context: &CommitContext) -> f32 {
+        let score = DefaultScorer.score(item, context);
+        let file = match item.item_type {
+            ContextItemType::Diff { file_index } | ContextItemType::Content { file_index } => {
+                context.staged_files.get(file_index)
+            }
+            ContextItemType::Commit { .. } => None,
+        };
+        if file.is_some_and(|file| is_test_path(&file.path)) {
+            score * TEST_FILE_WEIGHT
+        } else {
+            score
+        }
+    }
+}
+
+/// Whether `path` looks like a test file in the common layouts: `tests/` or `test/`
+/// directories, `_test`/`_spec` suffixes, `test_` prefixes and `.test.`/`.spec.` infixes
+fn is_test_path(path: &str) -> bool {
+    let path = path.to_lowercase();
+    let mut components = path.split('/');
+    let file_name = components.next_back().unwrap_or_default();
+    let stem = file_name.split('.').next().unwrap_or_default();
+    components.any(|dir| matches!(dir, "test" | "tests" | "__tests__"))
+        || stem.starts_with("test_")
+        || stem.ends_with("_test")
+        || stem.ends_with("_spec")
+        || file_name.contains(".test.")
+        || file_name.contains(".spec.")
+}
+
+/// The built-in scorer selected by `name` in the `context_scorer` setting
+pub fn scorer_by_name(name: &str) -> Option<Box<dyn ImportanceScorer>> {
+    match name.trim().to_lowercase().as_str() {
+        "default" => Some(Box::new(DefaultScorer)),
+        "tests-last" => Some(Box::new(TestsLastScorer)),
+        _ => None,
+    }
+}
+
+/// A context item with the budget share it competes for
+struct ScoredItem {
+    item: ContextItem,
+    importance: f32,
+}
+
 impl TokenOptimizer {
     pub fn new(max_tokens: usize, config: Config) -> Result<Self, TokenError> {
         let encoder = cl100k_base().map_err(|e| TokenError::EncoderInit(e.to_string()))?;
+        let scorer = config.context_scorer.as_deref().map_or_else(
+            || Box::new(DefaultScorer) as Box<dyn ImportanceScorer>,
+            |name| {
+                scorer_by_name(name).unwrap_or_else(|| {
+                    warn!("Unknown context scorer '{name}', using the default");
+                    Box::new(DefaultScorer)
+                })
+            },
+        );
 
         Ok(Self {
             encoder,
             max_tokens,
             config,
+            scorer,
         })
     }
 
+    /// Weigh context items with `scorer` instead of the configured one
+    #[must_use]
+    pub fn with_scorer(mut self, scorer: impl ImportanceScorer + 'static) -> Self {
+        self.scorer = Box::new(scorer);
+        self
+    }
+
     /// Create a token optimizer for counting only (no config needed)
     pub fn for_counting() -> Result<Self, TokenError> {
         let encoder = cl100k_base().map_err(|e| TokenError::EncoderInit(e.to_string()))?;
 
         Ok(Self {
             encoder,
-            max_tokens: 0,             // Not used for counting
-            config: Config::default(), // Not used for counting
+            max_tokens: 0,                   // Not used for counting
+            config: Config::default(),       // Not used for counting
+            scorer: Box::new(DefaultScorer), // Not used for counting
         })
     }
 
@@ -71,77 +208,63 @@ impl TokenOptimizer {
         Ok(())
     }
 
-    // Define base importance multipliers for different context types
-    // Staged changes (diffs) are most important, then recent commits, then file contents
-    const DIFF_BASE_MULTIPLIER: f32 = 3.0; // Highest priority - current changes
-    const COMMIT_BASE_MULTIPLIER: f32 = 2.0; // Medium priority - recent history
-    const CONTENT_BASE_MULTIPLIER: f32 = 1.0; // Lower priority - supporting context
-
-    fn calculate_context_items(&self, context: &CommitContext) -> Vec<ContextItem> {
-        let mut context_items = Vec::new();
-
-        // Add diffs with importance scores (staged changes are most important)
-        for (i, file) in context.staged_files.iter().enumerate() {
-            let token_count = self.count_tokens(&file.diff);
-            // Importance = base_multiplier * token_count * change_type_factor
-            let change_type_factor = match file.change_type {
-                crate::core::context::ChangeType::Added => 1.2, // New files are important
-                crate::core::context::ChangeType::Modified => 1.0, // Standard modifications
-                crate::core::context::ChangeType::Deleted => 0.8, // Deletions less important
-            };
-            #[allow(clippy::cast_precision_loss, clippy::as_conversions)]
-            let importance = Self::DIFF_BASE_MULTIPLIER * token_count as f32 * change_type_factor;
-            context_items.push(ContextItem {
-                item_type: ContextItemType::Diff { file_index: i },
+    fn calculate_context_items(&self, context: &CommitContext) -> Vec<ScoredItem> {
+        // Encoding dominates for large commits, so count everything up front, in parallel
+        let diffs: Vec<&str> = context
+            .staged_files
+            .iter()
+            .map(|file| file.diff.as_str())
+            .collect();
+        let messages: Vec<&str> = context
+            .recent_commits
+            .iter()
+            .map(|commit| commit.message.as_str())
+            .collect();
+        let (content_indices, contents): (Vec<usize>, Vec<&str>) = context
+            .staged_files
+            .iter()
+            .enumerate()
+            .filter_map(|(i, file)| file.content.as_deref().map(|content| (i, content)))
+            .unzip();
+
+        let diff_items =
+            self.count_tokens_batch(&diffs)
+                .into_iter()
+                .enumerate()
+                .map(|(i, token_count)| ContextItem {
+                    item_type: ContextItemType::Diff { file_index: i },
+                    token_count,
+                });
+        let commit_items = self
+            .count_tokens_batch(&messages)
+            .into_iter()
+            .enumerate()
+            .map(|(i, token_count)| ContextItem {
+                item_type: ContextItemType::Commit { commit_index: i },
                 token_count,
-                importance,
             });
-        }
-
-        // Add commits with importance scores (recent commits are important for context)
-        for (i, commit) in context.recent_commits.iter().enumerate() {
-            let token_count = self.count_tokens(&commit.message);
-            // Importance = base_multiplier * token_count * recency_factor * length_factor
-            #[allow(clippy::cast_precision_loss, clippy::as_conversions)]
-            let recency_factor = 1.0 / (i + 1) as f32; // Earlier commits more important
-            let length_factor = if token_count > 50 { 1.2 } else { 1.0 }; // Longer messages may be more informative
-            #[allow(clippy::cast_precision_loss, clippy::as_conversions)]
-            let importance =
-                Self::COMMIT_BASE_MULTIPLIER * token_count as f32 * recency_factor * length_factor;
-            context_items.push(ContextItem {
-                item_type: ContextItemType::Commit { commit_index: i },
+        let content_items = content_indices
+            .into_iter()
+            .zip(self.count_tokens_batch(&contents))
+            .map(|(i, token_count)| ContextItem {
+                item_type: ContextItemType::Content { file_index: i },
                 token_count,
-                importance,
             });
-        }
-
-        // Add file contents with importance scores (supporting context, lowest priority)
-        for (i, file) in context.staged_files.iter().enumerate() {
-            if let Some(content) = &file.content {
-                let token_count = self.count_tokens(content);
-                // Importance = base_multiplier * token_count * relevance_factor * size_factor
-                let relevance_factor = 1.0; // All staged files are equally relevant
-                let size_factor = if token_count > 100 { 0.8 } else { 1.0 }; // Very large files get slightly lower priority
-                #[allow(clippy::cast_precision_loss, clippy::as_conversions)]
-                let importance = Self::CONTENT_BASE_MULTIPLIER
-                    * token_count as f32
-                    * relevance_factor
-                    * size_factor;
-                context_items.push(ContextItem {
-                    item_type: ContextItemType::Content { file_index: i },
-                    token_count,
-                    importance,
-                });
-            }
-        }
 
-        context_items
+        diff_items
+            .chain(commit_items)
+            .chain(content_items)
+            .map(|item| {
+                let importance = self.scorer.score(&item, context).max(0.0);
+                ScoredItem { item, importance }
+            })
+            .collect()
     }
 
     fn allocate_tokens_proportionally(
         &self,
         context: &mut CommitContext,
-        mut context_items: Vec<ContextItem>,
+        mut context_items: Vec<ScoredItem>,
     ) {
         // Sort by importance (highest first)
         context_items.sort_by(|a, b| {
@@ -154,11 +277,9 @@ impl TokenOptimizer {
         let total_importance: f32 = context_items.iter().map(|item| item.importance).sum();
         let mut remaining_tokens = self.max_tokens;
 
-        for item in &context_items {
-            if remaining_tokens == 0 {
-                break;
-            }
-
+        // Items reached after the budget runs out get nothing, rather than keeping their
+        // full text
+        for ScoredItem { item, importance } in &context_items {
             #[allow(
                 clippy::cast_possible_truncation,
                 clippy::cast_sign_loss,
@@ -166,7 +287,7 @@ impl TokenOptimizer {
                 clippy::as_conversions
             )]
             let allocated_tokens = if total_importance > 0.0 {
-                ((item.importance / total_importance) * self.max_tokens as f32) as usize
+                ((importance / total_importance) * self.max_tokens as f32) as usize
             } else {
                 0
             }
@@ -185,7 +306,7 @@ impl TokenOptimizer {
                                 allocated = allocated_tokens
                             );
                             let _ = self
-                                .truncate_string(&file.diff, allocated_tokens)
+                                .fit_to_budget(&file.diff, allocated_tokens)
                                 .map(|truncated| file.diff = truncated);
                         }
                     }
@@ -197,12 +318,18 @@ impl TokenOptimizer {
                                 allocated = allocated_tokens
                             );
                             let _ = self
-                                .truncate_string(&commit.message, allocated_tokens)
+                                .fit_to_budget(&commit.message, allocated_tokens)
                                 .map(|truncated| commit.message = truncated);
                         }
                     }
                     ContextItemType::Content { file_index } => {
-                        if let Some(file) = context.staged_files.get_mut(*file_index)
+                        if allocated_tokens == 0
+                            && let Some(file) = context.staged_files.get_mut(*file_index)
+                        {
+                            // Content with no room left is dropped, as it is optional
+                            file.content = None;
+                            file.content_excluded = true;
+                        } else if let Some(file) = context.staged_files.get_mut(*file_index)
                             && let Some(content) = &mut file.content
                         {
                             debug!(
@@ -222,33 +349,22 @@ impl TokenOptimizer {
             remaining_tokens = remaining_tokens.saturating_sub(allocated_tokens);
         }
 
-        // Clear any remaining items that didn't get tokens
-        if remaining_tokens == 0 {
-            // Clear remaining low-importance items
-            #[allow(clippy::match_same_arms)]
-            for item in context_items
-                .iter()
-                .skip_while(|item| match &item.item_type {
-                    ContextItemType::Diff { .. } => true,
-                    ContextItemType::Commit { .. } => true,
-                    ContextItemType::Content { .. } => false,
-                })
-            {
-                if let ContextItemType::Content { file_index } = &item.item_type
-                    && let Some(file) = context.staged_files.get_mut(*file_index)
-                {
-                    file.content = None;
-                    file.content_excluded = true;
-                }
-            }
-        }
-
         debug!(
             "Optimized context with importance weighting, final token usage: {}",
             self.max_tokens - remaining_tokens
         );
     }
 
+    /// `s` cut down to `max_tokens`, or emptied when there are none, since even the
+    /// ellipsis `truncate_string` leaves would go over budget
+    fn fit_to_budget(&self, s: &str, max_tokens: usize) -> Result<String, TokenError> {
+        if max_tokens == 0 {
+            Ok(String::new())
+        } else {
+            self.truncate_string(s, max_tokens)
+        }
+    }
+
     pub fn truncate_string(&self, s: &str, max_tokens: usize) -> Result<String, TokenError> {
         let tokens = self.encoder.encode_ordinary(s);
 
@@ -357,6 +473,21 @@ impl TokenOptimizer {
         self.encoder.encode_ordinary(s).len()
     }
 
+    /// Token counts of `texts`, in order.
+    ///
+    /// Batches of [`PARALLEL_COUNT_THRESHOLD`] texts or more are encoded concurrently;
+    /// the encoder is shared, as it is `Sync`.
+    pub fn count_tokens_batch(&self, texts: &[&str]) -> Vec<usize> {
+        if texts.len() < PARALLEL_COUNT_THRESHOLD {
+            texts.iter().map(|text| self.count_tokens(text)).collect()
+        } else {
+            texts
+                .par_iter()
+                .map(|text| self.count_tokens(text))
+                .collect()
+        }
+    }
+
     /// Summarize text using LLM
     #[allow(dead_code)]
     async fn summarize_text(&self, text: &str, max_tokens: usize) -> Result<String, TokenError> {
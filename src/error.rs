@@ -0,0 +1,162 @@
+use crate::core::llm::is_retriable_error;
+
+use clap::Parser;
+use std::fmt;
+use std::panic;
+use std::process::exit;
+
+/// Kind of failure a command ended with, which decides the process exit code.
+///
+/// Scripts can retry on [`Transient`](Self::Transient), fix their input on
+/// [`User`](Self::User), and report [`Internal`](Self::Internal) as a bug.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCategory {
+    /// Bad arguments, configuration or repository state, e.g. nothing staged
+    User,
+    /// Worth retrying as is, e.g. a provider timeout, rate limit or 5xx
+    Transient,
+    /// A bug in gait itself
+    Internal,
+}
+
+impl ErrorCategory {
+    /// Process exit code for this category
+    pub const fn exit_code(self) -> i32 {
+        match self {
+            Self::User => 1,
+            Self::Transient => 2,
+            Self::Internal => 3,
+        }
+    }
+
+    /// Category of `error`: the outermost explicit tag, or else transient for
+    /// retriable provider failures and user for everything else
+    pub fn of(error: &anyhow::Error) -> Self {
+        if let Some(tagged) = error
+            .chain()
+            .find_map(|cause| cause.downcast_ref::<CategorizedError>())
+        {
+            return tagged.category;
+        }
+        if is_retriable_error(error) {
+            Self::Transient
+        } else {
+            Self::User
+        }
+    }
+}
+
+impl fmt::Display for ErrorCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::User => "user error",
+            Self::Transient => "transient error",
+            Self::Internal => "internal error",
+        })
+    }
+}
+
+/// An error tagged with its [`ErrorCategory`]; displays exactly like the error it wraps
+#[derive(Debug)]
+pub struct CategorizedError {
+    category: ErrorCategory,
+    error: anyhow::Error,
+}
+
+impl fmt::Display for CategorizedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.error, f)
+    }
+}
+
+impl std::error::Error for CategorizedError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.error.source()
+    }
+}
+
+/// Tag the error of a `Result` with the [`ErrorCategory`] it should exit with
+pub trait Categorize<T> {
+    fn categorize(self, category: ErrorCategory) -> anyhow::Result<T>;
+}
+
+impl<T, E> Categorize<T> for Result<T, E>
+where
+    E: Into<anyhow::Error>,
+{
+    fn categorize(self, category: ErrorCategory) -> anyhow::Result<T> {
+        self.map_err(|error| {
+            anyhow::Error::new(CategorizedError {
+                category,
+                error: error.into(),
+            })
+        })
+    }
+}
+
+/// Parse the arguments, exiting with the user error code on invalid ones.
+///
+/// clap would exit with 2 on its own, which scripts would read as transient. This also
+/// sets up [`exit_on_panic`], as every command starts here.
+pub fn parse_args<T: Parser>() -> T {
+    exit_on_panic();
+    T::try_parse().unwrap_or_else(|error| {
+        if error.use_stderr() {
+            let _ = error.print();
+            exit(ErrorCategory::User.exit_code());
+        }
+        error.exit()
+    })
+}
+
+/// Make a panic exit with the [`Internal`](ErrorCategory::Internal) code rather than
+/// Rust's 101, after printing the usual panic message
+pub fn exit_on_panic() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        default_hook(info);
+        exit(ErrorCategory::Internal.exit_code());
+    }));
+}
+
+/// Report `error` on stderr and exit with the code of its category
+pub fn exit_with_error(error: &anyhow::Error) -> ! {
+    eprintln!("Error: {error}");
+    exit(ErrorCategory::of(error).exit_code())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{Context, anyhow};
+
+    #[test]
+    fn test_categories_map_to_distinct_exit_codes() {
+        assert_eq!(ErrorCategory::User.exit_code(), 1);
+        assert_eq!(ErrorCategory::Transient.exit_code(), 2);
+        assert_eq!(ErrorCategory::Internal.exit_code(), 3);
+    }
+
+    #[test]
+    fn test_category_of_error() {
+        let internal: anyhow::Result<()> =
+            Err(anyhow!("index out of sync")).categorize(ErrorCategory::Internal);
+        let internal = internal
+            .context("Failed to write the cache")
+            .expect_err("tagged error");
+        assert_eq!(ErrorCategory::of(&internal), ErrorCategory::Internal);
+        assert_eq!(
+            format!("{internal:#}"),
+            "Failed to write the cache: index out of sync"
+        );
+
+        assert_eq!(
+            ErrorCategory::of(&anyhow!("Failed to generate message: Provider timed out")),
+            ErrorCategory::Transient
+        );
+        assert_eq!(
+            ErrorCategory::of(&anyhow!("Nothing staged")),
+            ErrorCategory::User
+        );
+    }
+}
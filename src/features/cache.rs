@@ -0,0 +1,405 @@
+use crate::core::cache::{
+    self, COMMITS_DIR, CONTEXTS_DIR, INSTRUCTIONS_DIR, REPOS_DIR, RESPONSES_DIR,
+};
+use crate::core::commit_cache::{CacheStats, CommitMessageCache};
+use crate::error::{Categorize, ErrorCategory};
+
+use anyhow::{Context, Result, anyhow};
+use colored::Colorize;
+use serde::Serialize;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Which caches `cache clear` removes
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct ClearOptions {
+    pub commits: bool,
+    pub responses: bool,
+    pub repos: bool,
+    pub all: bool,
+    /// Only drop commit history recorded for this repository
+    pub repo_path: Option<PathBuf>,
+}
+
+/// Bytes freed from each cache by `cache clear`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ClearedBytes {
+    pub commits: u64,
+    pub responses: u64,
+    pub repos: u64,
+    /// Built commit contexts, only cleared with `--all`
+    pub contexts: u64,
+    /// Instructions saved by the TUI, only cleared with `--all`
+    pub instructions: u64,
+}
+
+impl ClearedBytes {
+    pub const fn total(&self) -> u64 {
+        self.commits + self.responses + self.repos + self.contexts + self.instructions
+    }
+}
+
+/// Handle `cache clear` against the configured cache root
+pub fn handle_clear_command(options: &ClearOptions) -> Result<()> {
+    let root = cache::root_dir()?;
+    let cleared = clear_caches(&root, options)?;
+
+    println!("{}", "Cleared caches:".bold());
+    for (name, selected, bytes) in [
+        ("commits", options.commits || options.all, cleared.commits),
+        (
+            "responses",
+            options.responses || options.all,
+            cleared.responses,
+        ),
+        ("repos", options.repos || options.all, cleared.repos),
+        ("contexts", options.all, cleared.contexts),
+        ("instructions", options.all, cleared.instructions),
+    ] {
+        if selected {
+            println!("  - {name}: {bytes} bytes");
+        }
+    }
+    println!("Freed {} bytes from {}", cleared.total(), root.display());
+    Ok(())
+}
+
+/// Remove the selected caches under `root`.
+///
+/// Only the managed subdirectories of `root` are touched; a subdirectory that is a
+/// symlink is unlinked without following it.
+pub fn clear_caches(root: &Path, options: &ClearOptions) -> Result<ClearedBytes> {
+    let commits = options.commits || options.all;
+    let responses = options.responses || options.all;
+    let repos = options.repos || options.all;
+    if !(commits || responses || repos) {
+        return Err(anyhow!(
+            "Nothing to clear: pass --commits, --responses, --repos or --all"
+        ));
+    }
+
+    let mut cleared = ClearedBytes::default();
+    if commits {
+        cleared.commits = match &options.repo_path {
+            Some(repo_path) => clear_repo_commits(&root.join(COMMITS_DIR), repo_path)?,
+            None => remove_managed_dir(root, COMMITS_DIR)?,
+        };
+    }
+    if responses {
+        cleared.responses = remove_managed_dir(root, RESPONSES_DIR)?;
+    }
+    if repos {
+        cleared.repos = remove_managed_dir(root, REPOS_DIR)?;
+    }
+    if options.all {
+        cleared.contexts = remove_managed_dir(root, CONTEXTS_DIR)?;
+        cleared.instructions = remove_managed_dir(root, INSTRUCTIONS_DIR)?;
+    }
+    Ok(cleared)
+}
+
+/// Drop one repository's entries from the commit message cache
+fn clear_repo_commits(dir: &Path, repo_path: &Path) -> Result<u64> {
+    if !dir.exists() {
+        return Ok(0);
+    }
+
+    let repo_path = fs::canonicalize(repo_path).unwrap_or_else(|_| repo_path.to_path_buf());
+    let before = dir_size(dir)?;
+    let mut commit_cache = CommitMessageCache::with_dir(dir.to_path_buf())?;
+    commit_cache.clear_repo_cache(&repo_path.to_string_lossy());
+    commit_cache.save()?;
+    Ok(before.saturating_sub(dir_size(dir)?))
+}
+
+/// Remove `root/name`, returning the bytes it held
+fn remove_managed_dir(root: &Path, name: &str) -> Result<u64> {
+    let path = root.join(name);
+    let metadata = match fs::symlink_metadata(&path) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+
+    if metadata.is_dir() {
+        let size = dir_size(&path)?;
+        fs::remove_dir_all(&path)
+            .with_context(|| format!("Failed to remove {}", path.display()))?;
+        Ok(size)
+    } else {
+        fs::remove_file(&path).with_context(|| format!("Failed to remove {}", path.display()))?;
+        Ok(if metadata.is_file() {
+            metadata.len()
+        } else {
+            0
+        })
+    }
+}
+
+/// Size and contents of every cache, as printed by `cache stats`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct CacheReport {
+    pub root: PathBuf,
+    pub commits: CacheStats,
+    pub commits_bytes: u64,
+    pub responses_bytes: u64,
+    pub contexts_bytes: u64,
+    pub instructions_bytes: u64,
+    /// Present once `git wire` has cached a repository
+    pub repos: Option<RepoCacheStats>,
+}
+
+/// Contents of the `git wire` repository cache
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct RepoCacheStats {
+    pub repositories: usize,
+    pub bytes: u64,
+}
+
+/// Handle `cache stats` against the configured cache root
+pub fn handle_stats_command(json: bool) -> Result<()> {
+    let report = cache_report(&cache::root_dir()?)?;
+    if json {
+        let json = serde_json::to_string_pretty(&report).categorize(ErrorCategory::Internal)?;
+        println!("{json}");
+    } else {
+        println!("{report}");
+    }
+    Ok(())
+}
+
+/// Collect statistics for the caches under `root` without creating anything
+pub fn cache_report(root: &Path) -> Result<CacheReport> {
+    let commits_dir = root.join(COMMITS_DIR);
+    let commits = if commits_dir.is_dir() {
+        CommitMessageCache::with_dir(commits_dir.clone())?.get_stats()
+    } else {
+        CacheStats::default()
+    };
+
+    let repos_dir = root.join(REPOS_DIR);
+    let repos = if repos_dir.is_dir() {
+        let mut repositories = 0;
+        for entry in fs::read_dir(&repos_dir)? {
+            if entry?.file_type()?.is_dir() {
+                repositories += 1;
+            }
+        }
+        Some(RepoCacheStats {
+            repositories,
+            bytes: dir_size(&repos_dir)?,
+        })
+    } else {
+        None
+    };
+
+    Ok(CacheReport {
+        root: root.to_path_buf(),
+        commits,
+        commits_bytes: size_if_exists(&commits_dir)?,
+        responses_bytes: size_if_exists(&root.join(RESPONSES_DIR))?,
+        contexts_bytes: size_if_exists(&root.join(CONTEXTS_DIR))?,
+        instructions_bytes: size_if_exists(&root.join(INSTRUCTIONS_DIR))?,
+        repos,
+    })
+}
+
+impl fmt::Display for CacheReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} {}", "Cache root:".bold(), self.root.display())?;
+        writeln!(
+            f,
+            "{} {} ({} messages, {} authors, {} repos)",
+            "Commit history:".bold(),
+            format_size(self.commits_bytes),
+            self.commits.total_messages,
+            self.commits.total_authors,
+            self.commits.total_repos
+        )?;
+        write!(
+            f,
+            "{} {}",
+            "LLM responses:".bold(),
+            format_size(self.responses_bytes)
+        )?;
+        write!(
+            f,
+            "\n{} {}",
+            "Commit contexts:".bold(),
+            format_size(self.contexts_bytes)
+        )?;
+        write!(
+            f,
+            "\n{} {}",
+            "Saved instructions:".bold(),
+            format_size(self.instructions_bytes)
+        )?;
+        if let Some(repos) = &self.repos {
+            write!(
+                f,
+                "\n{} {} ({} repositories)",
+                "Wire repositories:".bold(),
+                format_size(repos.bytes),
+                repos.repositories
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn size_if_exists(path: &Path) -> io::Result<u64> {
+    if path.is_dir() { dir_size(path) } else { Ok(0) }
+}
+
+/// Format a byte count with a binary unit, e.g. `1.5 MiB`
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+
+    #[allow(clippy::cast_precision_loss, clippy::as_conversions)]
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{size:.1} {}", UNITS[unit])
+}
+
+/// Total size of the regular files under `path`, without following symlinks
+pub fn dir_size(path: &Path) -> io::Result<u64> {
+    let mut total = 0;
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            total += dir_size(&entry.path())?;
+        } else if file_type.is_file() {
+            total += entry.metadata()?.len();
+        }
+    }
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::commit_cache::CachedCommitMessage;
+    use tempfile::TempDir;
+
+    fn write_file(path: &Path, contents: &str) {
+        fs::create_dir_all(path.parent().expect("file has a parent"))
+            .expect("Failed to create directory");
+        fs::write(path, contents).expect("Failed to write file");
+    }
+
+    #[test]
+    fn test_clear_all_removes_every_cache() {
+        let outer = TempDir::new().expect("Failed to create temp dir");
+        let root = outer.path().join("cache");
+        write_file(&root.join(COMMITS_DIR).join("commit_messages.json"), "{}");
+        write_file(&root.join(RESPONSES_DIR).join("abc.json"), "response");
+        write_file(&root.join(REPOS_DIR).join("key").join("README"), "readme");
+        write_file(&root.join(CONTEXTS_DIR).join("def.json"), "{}");
+        write_file(&root.join(INSTRUCTIONS_DIR).join("ghi.txt"), "terse");
+        let unmanaged = outer.path().join("keep.txt");
+        write_file(&unmanaged, "not ours");
+
+        let cleared = clear_caches(
+            &root,
+            &ClearOptions {
+                all: true,
+                ..ClearOptions::default()
+            },
+        )
+        .expect("clear succeeds");
+
+        assert_eq!(
+            cleared,
+            ClearedBytes {
+                commits: 2,
+                responses: 8,
+                repos: 6,
+                contexts: 2,
+                instructions: 5,
+            }
+        );
+        assert!(!root.join(COMMITS_DIR).exists());
+        assert!(!root.join(RESPONSES_DIR).exists());
+        assert!(!root.join(REPOS_DIR).exists());
+        assert!(!root.join(CONTEXTS_DIR).exists());
+        assert!(!root.join(INSTRUCTIONS_DIR).exists());
+        assert!(unmanaged.exists());
+
+        assert!(clear_caches(&root, &ClearOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_stats_match_seeded_cache() {
+        let dir = TempDir::new().expect("Failed to create temp dir");
+        let root = dir.path();
+        assert_eq!(
+            cache_report(root).expect("report builds"),
+            CacheReport {
+                root: root.to_path_buf(),
+                ..CacheReport::default()
+            }
+        );
+
+        let message = |hash: &str| CachedCommitMessage {
+            message: "fix: handle empty diff".to_string(),
+            timestamp: "2024-01-01T00:00:00Z".to_string(),
+            hash: hash.to_string(),
+        };
+        let mut commit_cache =
+            CommitMessageCache::with_dir(root.join(COMMITS_DIR)).expect("cache loads");
+        commit_cache.add_commit_messages("a@example.com", "/repo/one", vec![message("1")]);
+        commit_cache.add_commit_messages(
+            "b@example.com",
+            "/repo/one",
+            vec![message("2"), message("3")],
+        );
+        commit_cache.add_commit_messages("a@example.com", "/repo/two", vec![message("4")]);
+        commit_cache.save().expect("cache saves");
+        write_file(
+            &root.join(RESPONSES_DIR).join("abc.json"),
+            &"x".repeat(2048),
+        );
+        write_file(&root.join(REPOS_DIR).join("first").join("README"), "readme");
+        write_file(&root.join(REPOS_DIR).join("second").join("lib.rs"), "fn");
+        write_file(&root.join(INSTRUCTIONS_DIR).join("abc.txt"), "terse");
+
+        let report = cache_report(root).expect("report builds");
+        assert_eq!(
+            report.commits,
+            CacheStats {
+                total_messages: 4,
+                total_authors: 2,
+                total_repos: 2,
+            }
+        );
+        assert_eq!(report.responses_bytes, 2048);
+        assert_eq!(report.instructions_bytes, 5);
+        assert_eq!(
+            report.repos,
+            Some(RepoCacheStats {
+                repositories: 2,
+                bytes: 8,
+            })
+        );
+
+        let printed = report.to_string();
+        assert!(printed.contains("(4 messages, 2 authors, 2 repos)"));
+        assert!(printed.contains("2.0 KiB"));
+        assert!(printed.contains("8 B (2 repositories)"));
+
+        let json = serde_json::to_value(&report).expect("report serializes");
+        assert_eq!(json["commits"]["total_messages"], 4);
+        assert_eq!(json["repos"]["repositories"], 2);
+    }
+}
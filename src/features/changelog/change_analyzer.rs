@@ -1,3 +1,5 @@
+use super::dedupe::collapse_duplicates;
+use super::filter::CommitFilter;
 use super::models::{ChangeMetrics, ChangelogType};
 use crate::core::context::{ChangeType, RecentCommit};
 use crate::git::GitRepo;
@@ -47,12 +49,23 @@ pub struct FileChange {
 /// Analyzer for processing Git commits and generating detailed change information
 pub struct ChangeAnalyzer {
     git_repo: Arc<GitRepo>,
+    filter: CommitFilter,
 }
 
 impl ChangeAnalyzer {
     /// Create a new `ChangeAnalyzer` instance
     pub fn new(git_repo: Arc<GitRepo>) -> Result<Self> {
-        Ok(Self { git_repo })
+        Ok(Self {
+            git_repo,
+            filter: CommitFilter::default(),
+        })
+    }
+
+    /// Only analyze commits matching the given path/scope filter
+    #[must_use]
+    pub fn with_filter(mut self, filter: CommitFilter) -> Self {
+        self.filter = filter;
+        self
     }
 
     /// Analyze commits between two Git references, streaming results via channel
@@ -63,12 +76,22 @@ impl ChangeAnalyzer {
         tx: mpsc::Sender<Result<AnalyzedChange>>,
     ) -> Result<()> {
         let git_repo = self.git_repo.clone();
+        let filter = self.filter.clone();
         let from = from.to_string();
         let to = to.to_string();
         let _ = tokio::task::spawn_blocking(move || {
             git_repo.get_commits_between_stream(&from, &to, |commit| {
+                if !filter.matches_time(commit.timestamp.parse().unwrap_or_default()) {
+                    return Ok(());
+                }
                 let analyzed = Self::analyze_commit_inner(&git_repo, commit)?;
-                let _ = tx.blocking_send(Ok(analyzed));
+                let changed_paths = analyzed
+                    .file_changes
+                    .iter()
+                    .flat_map(|f| [f.old_path.as_str(), f.new_path.as_str()]);
+                if filter.matches(&analyzed.commit_message, changed_paths) {
+                    let _ = tx.blocking_send(Ok(analyzed));
+                }
                 Ok(())
             })
         })
@@ -127,7 +150,12 @@ impl ChangeAnalyzer {
             }
             Ok(analyzed_changes)
         };
-        let ((), analyzed_changes) = tokio::try_join!(analyze_task, collect_task)?;
+        let ((), mut analyzed_changes) = tokio::try_join!(analyze_task, collect_task)?;
+        if self.filter.collapse_duplicates {
+            analyzed_changes = collapse_duplicates(analyzed_changes, |change| {
+                (change.commit_hash.as_str(), change.commit_message.as_str())
+            });
+        }
         let total_metrics = self.calculate_total_metrics(&analyzed_changes);
         Ok((analyzed_changes, total_metrics))
     }
@@ -1,8 +1,14 @@
 use super::common::generate_changes_content;
+use super::conventional::{
+    self, ChangelogDocument, ConventionalCommit, GroupedChangelog, SectionMapping,
+};
+use super::dedupe::collapse_duplicates;
+use super::filter::CommitFilter;
 use super::models::{BreakingChange, ChangeEntry, ChangeMetrics, ChangelogResponse, ChangelogType};
 use super::prompt;
 use crate::common::DetailLevel;
 use crate::config::Config;
+use crate::core::context::RecentCommit;
 use crate::git::GitRepo;
 use anyhow::{Context, Result};
 use chrono;
@@ -28,6 +34,7 @@ impl ChangelogGenerator {
     /// * `to` - Ending point for the changelog (e.g., a commit hash, tag, or "HEAD")
     /// * `config` - Configuration object containing LLM settings
     /// * `detail_level` - Level of detail for the changelog (Minimal, Standard, or Detailed)
+    /// * `filter` - Path/scope filter; commits matching neither are left out
     ///
     /// # Returns
     ///
@@ -38,6 +45,7 @@ impl ChangelogGenerator {
         to: &str,
         config: &Config,
         detail_level: DetailLevel,
+        filter: &CommitFilter,
     ) -> Result<String> {
         let changelog: ChangelogResponse = generate_changes_content::<ChangelogResponse>(
             git_repo,
@@ -45,6 +53,7 @@ impl ChangelogGenerator {
             to,
             config,
             detail_level,
+            filter,
             prompt::create_changelog_system_prompt,
             prompt::create_changelog_user_prompt,
         )
@@ -53,6 +62,117 @@ impl ChangelogGenerator {
         Ok(format_changelog_response(&changelog))
     }
 
+    /// Generates a changelog by grouping commits on their Conventional Commit type.
+    ///
+    /// Unlike [`Self::generate`], this does not call the LLM: sections come directly from
+    /// commit prefixes, using the mapping from the `[changelog]` table of the config file.
+    ///
+    /// # Arguments
+    ///
+    /// * `git_repo` - `GitRepo` instance
+    /// * `from` - Starting point for the changelog (e.g., a commit hash or tag)
+    /// * `to` - Ending point for the changelog (e.g., a commit hash, tag, or "HEAD")
+    /// * `config` - Configuration object containing the section mapping
+    /// * `filter` - Path/scope filter; commits matching neither are left out
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the generated changelog as a String, or an error
+    pub fn generate_conventional(
+        git_repo: &GitRepo,
+        from: &str,
+        to: &str,
+        config: &Config,
+        filter: &CommitFilter,
+    ) -> Result<String> {
+        let document = Self::generate_document(git_repo, from, to, config, None, filter)?;
+        Ok(conventional::format_changelog_document(&document))
+    }
+
+    /// Generates a structured changelog document for the specified range of commits.
+    ///
+    /// [`Self::generate_conventional`] renders this same document as Markdown, so the JSON
+    /// and Markdown outputs always contain the same entries.
+    ///
+    /// # Arguments
+    ///
+    /// * `git_repo` - `GitRepo` instance
+    /// * `from` - Starting point for the changelog (e.g., a commit hash or tag)
+    /// * `to` - Ending point for the changelog (e.g., a commit hash, tag, or "HEAD")
+    /// * `config` - Configuration object containing the section mapping
+    /// * `version_name` - Optional version name; defaults to "Unreleased"
+    /// * `filter` - Path/scope filter; commits matching neither are left out
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the changelog document, or an error
+    pub fn generate_document(
+        git_repo: &GitRepo,
+        from: &str,
+        to: &str,
+        config: &Config,
+        version_name: Option<&str>,
+        filter: &CommitFilter,
+    ) -> Result<ChangelogDocument> {
+        let grouped = Self::group_conventional(git_repo, from, to, config, filter)?;
+        let date = git_repo.get_commit_date(to).unwrap_or_else(|e| {
+            debug!("Failed to get commit date for {to}: {e}");
+            chrono::Local::now().format("%Y-%m-%d").to_string()
+        });
+
+        Ok(ChangelogDocument {
+            version: version_name.unwrap_or("Unreleased").to_string(),
+            date,
+            grouped,
+        })
+    }
+
+    /// Parses and groups the commits in a range by Conventional Commit type
+    fn group_conventional(
+        git_repo: &GitRepo,
+        from: &str,
+        to: &str,
+        config: &Config,
+        filter: &CommitFilter,
+    ) -> Result<GroupedChangelog> {
+        let commits: Vec<RecentCommit> = git_repo
+            .get_commits_between_with_callback(from, to, |commit| {
+                // Only look up changed files when the date and scope alone don't decide it
+                let included = filter.matches_time(commit.timestamp.parse().unwrap_or_default())
+                    && (filter.is_empty()
+                        || filter.matches_scope(&commit.message)
+                        || (filter.has_path()
+                            && filter.matches_paths(
+                                git_repo
+                                    .get_file_paths_for_commit(&commit.hash)?
+                                    .iter()
+                                    .map(String::as_str),
+                            )));
+                Ok(included.then(|| commit.clone()))
+            })
+            .context("Failed to collect commits for changelog")?
+            .into_iter()
+            .flatten()
+            .collect();
+        let commits = if filter.collapse_duplicates {
+            collapse_duplicates(commits, |commit| {
+                (commit.hash.as_str(), commit.message.as_str())
+            })
+        } else {
+            commits
+        };
+        let commits: Vec<ConventionalCommit> = commits
+            .iter()
+            .map(|commit| ConventionalCommit::parse(&commit.hash, &commit.message))
+            .collect();
+        debug!("Grouping {} commits by conventional type", commits.len());
+
+        Ok(conventional::group_commits(
+            &commits,
+            &SectionMapping::from_config(config),
+        ))
+    }
+
     /// Updates a changelog file with new content
     ///
     /// This function reads the existing changelog file (if it exists), preserves the header,
@@ -1,15 +1,72 @@
 use super::change_log::ChangelogGenerator;
+use super::filter::CommitFilter;
 use super::releasenotes::ReleaseNotesGenerator;
-use crate::common::{CommonParams, DetailLevel};
+use crate::common::{CommonParams, DetailLevel, write_output_file};
 use crate::config::Config;
+use crate::error::{Categorize, ErrorCategory};
 use crate::git::GitRepo;
 use crate::ui;
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use colored::Colorize;
 use std::env;
 use std::str::FromStr;
 use std::sync::Arc;
 
+/// Output format for changelogs and release notes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Rendered Markdown (the default)
+    #[default]
+    Markdown,
+    /// Structured JSON document with one entry per commit
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "markdown" | "md" => Ok(Self::Markdown),
+            "json" => Ok(Self::Json),
+            _ => Err(anyhow::anyhow!("Invalid output format: {s}")),
+        }
+    }
+}
+
+/// Options controlling how the changelog command generates and writes its output
+#[derive(Debug, Clone, Default)]
+pub struct ChangelogOptions {
+    /// Whether to update the changelog file
+    pub update_file: bool,
+    /// Path to the changelog file (defaults to `CHANGELOG.md`)
+    pub changelog_path: Option<String>,
+    /// Version name to use instead of extracting it from Git refs
+    pub version_name: Option<String>,
+    /// Group commits by Conventional Commit type instead of asking the LLM
+    pub conventional: bool,
+    /// Output format; JSON always uses Conventional Commit grouping
+    pub format: OutputFormat,
+    /// Path/scope filter; a commit matching either is included
+    pub filter: CommitFilter,
+    /// Also write the rendered changelog to this file
+    pub output_file: Option<String>,
+}
+
+/// Options controlling how the release notes command generates its output
+#[derive(Debug, Clone, Default)]
+pub struct ReleaseNotesOptions {
+    /// Version name to use instead of extracting it from Git refs
+    pub version_name: Option<String>,
+    /// Output format; JSON always uses Conventional Commit grouping
+    pub format: OutputFormat,
+    /// Path/scope filter; a commit matching either is included, within the date window.
+    /// Duplicate subjects and revert pairs are collapsed when `collapse_duplicates` is set
+    pub filter: CommitFilter,
+    /// Also write the rendered release notes to this file
+    pub output_file: Option<String>,
+}
+
 /// Handles the changelog generation command.
 ///
 /// This function orchestrates the process of generating a changelog based on the provided
@@ -22,9 +79,7 @@ use std::sync::Arc;
 /// * `from` - The starting point (commit or tag) for the changelog.
 /// * `to` - The ending point for the changelog. Defaults to "HEAD" if not provided.
 /// * `repository_url` - Optional URL of the remote repository to use.
-/// * `update_file` - Whether to update the changelog file.
-/// * `changelog_path` - Optional path to the changelog file.
-/// * `version_name` - Optional version name to use instead of extracting from Git refs.
+/// * `options` - Output options such as file updating and conventional grouping.
 ///
 /// # Returns
 ///
@@ -34,10 +89,12 @@ pub async fn handle_changelog_command(
     from: String,
     to: Option<String>,
     repository_url: Option<String>,
-    update_file: bool,
-    changelog_path: Option<String>,
-    version_name: Option<String>,
+    options: ChangelogOptions,
 ) -> Result<()> {
+    if options.format == OutputFormat::Json && options.update_file {
+        bail!("--update cannot be combined with --format json");
+    }
+
     // Load and apply configuration
     let mut config = Config::load()?;
     common.apply_to_config(&mut config)?;
@@ -74,12 +131,31 @@ pub async fn handle_changelog_command(
     // Set the default 'to' reference if not provided
     let to = to.unwrap_or_else(|| "HEAD".to_string());
 
-    // Parse the detail level for the changelog
-    let detail_level = DetailLevel::from_str(&common.detail_level)?;
+    if options.format == OutputFormat::Json {
+        let json = generate_json_document(
+            &git_repo,
+            &from,
+            &to,
+            &config,
+            options.version_name.as_deref(),
+            &options.filter,
+        )?;
+        spinner.tick();
+        println!("{json}");
+        if let Some(path) = &options.output_file {
+            write_output_file(path, &json)?;
+        }
+        return Ok(());
+    }
 
-    // Generate the changelog
-    let changelog =
-        ChangelogGenerator::generate(git_repo, &from, &to, &config, detail_level).await?;
+    // Generate the changelog, either from commit prefixes or with the LLM
+    let changelog = if options.conventional {
+        ChangelogGenerator::generate_conventional(&git_repo, &from, &to, &config, &options.filter)?
+    } else {
+        let detail_level = DetailLevel::from_str(&common.detail_level)?;
+        ChangelogGenerator::generate(git_repo, &from, &to, &config, detail_level, &options.filter)
+            .await?
+    };
 
     // Clear the spinner and display the result
     spinner.tick();
@@ -87,9 +163,16 @@ pub async fn handle_changelog_command(
     // Output the changelog with decorative borders
     ui::print_bordered_content(&changelog);
 
+    if let Some(path) = &options.output_file {
+        write_output_file(path, &changelog)?;
+        ui::print_success(&format!("Changelog written to {path}"));
+    }
+
     // Update the changelog file if requested
-    if update_file {
-        let path = changelog_path.unwrap_or_else(|| "CHANGELOG.md".to_string());
+    if options.update_file {
+        let path = options
+            .changelog_path
+            .unwrap_or_else(|| "CHANGELOG.md".to_string());
         let mut update_spinner =
             ui::create_tui_spinner(&format!("Updating changelog file at {path}..."));
 
@@ -98,7 +181,7 @@ pub async fn handle_changelog_command(
             &path,
             &git_repo_for_update,
             &to,
-            version_name,
+            options.version_name,
         ) {
             Ok(()) => {
                 update_spinner.tick();
@@ -126,21 +209,28 @@ pub async fn handle_changelog_command(
 /// # Arguments
 ///
 /// * `common` - Common parameters for the command, including configuration overrides.
-/// * `from` - The starting point (commit or tag) for the release notes.
+/// * `from` - The starting point (commit or tag) for the release notes. When omitted, the
+///   whole history is considered and `options.filter` must carry a `since`/`until` window.
 /// * `to` - The ending point for the release notes. Defaults to "HEAD" if not provided.
 /// * `repository_url` - Optional URL of the remote repository to use.
-/// * `version_name` - Optional version name to use instead of extracting from Git refs.
+/// * `options` - Output options such as the version name and output format.
 ///
 /// # Returns
 ///
 /// Returns a Result indicating success or containing an error if the operation failed.
 pub async fn handle_release_notes_command(
     common: CommonParams,
-    from: String,
+    from: Option<String>,
     to: Option<String>,
     repository_url: Option<String>,
-    version_name: Option<String>,
+    options: ReleaseNotesOptions,
 ) -> Result<()> {
+    if from.is_none() && !options.filter.has_dates() {
+        bail!("--from is required unless --since or --until is given");
+    }
+    // An empty starting reference walks the whole history of `to`
+    let from = from.unwrap_or_default();
+
     // Load and apply configuration
     let mut config = Config::load()?;
     common.apply_to_config(&mut config)?;
@@ -174,13 +264,37 @@ pub async fn handle_release_notes_command(
     // Set the default 'to' reference if not provided
     let to = to.unwrap_or_else(|| "HEAD".to_string());
 
+    if options.format == OutputFormat::Json {
+        let json = generate_json_document(
+            &git_repo,
+            &from,
+            &to,
+            &config,
+            options.version_name.as_deref(),
+            &options.filter,
+        )?;
+        spinner.tick();
+        println!("{json}");
+        if let Some(path) = &options.output_file {
+            write_output_file(path, &json)?;
+        }
+        return Ok(());
+    }
+
     // Parse the detail level for the release notes
     let detail_level = DetailLevel::from_str(&common.detail_level)?;
 
     // Generate the release notes
-    let release_notes =
-        ReleaseNotesGenerator::generate(git_repo, &from, &to, &config, detail_level, version_name)
-            .await?;
+    let release_notes = ReleaseNotesGenerator::generate(
+        git_repo,
+        &from,
+        &to,
+        &config,
+        detail_level,
+        options.version_name,
+        &options.filter,
+    )
+    .await?;
 
     // Clear the spinner and display the result
     spinner.tick();
@@ -188,5 +302,26 @@ pub async fn handle_release_notes_command(
     // Output the release notes with decorative borders
     ui::print_bordered_content(&release_notes);
 
+    if let Some(path) = &options.output_file {
+        write_output_file(path, &release_notes)?;
+        ui::print_success(&format!("Release notes written to {path}"));
+    }
+
     Ok(())
 }
+
+/// Builds the Conventional Commit document for a range and serializes it as pretty JSON
+fn generate_json_document(
+    git_repo: &GitRepo,
+    from: &str,
+    to: &str,
+    config: &Config,
+    version_name: Option<&str>,
+    filter: &CommitFilter,
+) -> Result<String> {
+    let document =
+        ChangelogGenerator::generate_document(git_repo, from, to, config, version_name, filter)?;
+    serde_json::to_string_pretty(&document)
+        .context("Failed to serialize changelog document")
+        .categorize(ErrorCategory::Internal)
+}
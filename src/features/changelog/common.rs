@@ -1,3 +1,4 @@
+use super::filter::CommitFilter;
 use super::models::ChangeMetrics;
 use super::readme_reader::get_readme_summary;
 use crate::common::DetailLevel;
@@ -16,12 +17,14 @@ use std::sync::Arc;
 type UserPromptFn =
     fn(&[AnalyzedChange], &ChangeMetrics, DetailLevel, &str, &str, Option<&str>) -> String;
 
+#[allow(clippy::too_many_arguments)]
 pub async fn generate_changes_content<T>(
     git_repo: Arc<GitRepo>,
     from: &str,
     to: &str,
     config: &Config,
     detail_level: DetailLevel,
+    filter: &CommitFilter,
     create_system_prompt: fn(&Config) -> String,
     create_user_prompt: UserPromptFn,
 ) -> Result<T>
@@ -29,7 +32,7 @@ where
     T: DeserializeOwned + Serialize + Debug + JsonSchema,
 {
     // Create ChangeAnalyzer with Arc<GitRepo>
-    let analyzer = ChangeAnalyzer::new(git_repo.clone())?;
+    let analyzer = ChangeAnalyzer::new(git_repo.clone())?.with_filter(filter.clone());
 
     // Get analyzed changes
     let (analyzed_changes, total_metrics) = analyzer.analyze_changes(from, to).await?;
@@ -0,0 +1,404 @@
+use crate::config::{ChangelogConfig, Config};
+use regex::Regex;
+use serde::Serialize;
+use std::fmt::Write as FmtWrite;
+use std::sync::LazyLock;
+
+// Regex for the Conventional Commits header: `type(scope)!: subject`
+static HEADER_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(?P<type>[a-zA-Z]+)(?:\((?P<scope>[^)]*)\))?(?P<breaking>!)?:\s*(?P<subject>.+)$")
+        .expect("Failed to compile conventional commit regex pattern - this is a bug")
+});
+
+// Regex for a leading gitmoji, as an emoji or a `:shortcode:`, ahead of the header
+static EMOJI_PREFIX_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^(?::[a-z0-9_+-]+:|[\p{Extended_Pictographic}\p{Emoji_Modifier}\x{FE0F}\x{200D}])+\s*",
+    )
+    .expect("Failed to compile emoji prefix regex pattern - this is a bug")
+});
+
+// Regex for `BREAKING CHANGE:` / `BREAKING-CHANGE:` footers
+static BREAKING_FOOTER_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?m)^BREAKING[ -]CHANGE:\s*(?P<note>.+)$")
+        .expect("Failed to compile breaking change footer regex pattern - this is a bug")
+});
+
+/// Default mapping from commit type to section title, in output order
+const DEFAULT_SECTIONS: &[(&str, &str)] = &[
+    ("feat", "Features"),
+    ("fix", "Bug Fixes"),
+    ("perf", "Performance"),
+    ("refactor", "Refactoring"),
+    ("docs", "Documentation"),
+    ("test", "Tests"),
+    ("build", "Build System"),
+    ("ci", "Continuous Integration"),
+    ("style", "Styles"),
+    ("chore", "Chores"),
+    ("revert", "Reverts"),
+];
+
+/// A commit parsed according to the Conventional Commits specification
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConventionalCommit {
+    pub commit_hash: String,
+    /// Commit type (e.g. `feat`), or `None` if the message doesn't follow the convention
+    #[serde(rename = "type")]
+    pub commit_type: Option<String>,
+    pub scope: Option<String>,
+    pub subject: String,
+    /// Whether the commit is marked breaking with `!` or a `BREAKING CHANGE:` footer
+    pub breaking: bool,
+    /// Description from the `BREAKING CHANGE:` footer, if present
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub breaking_note: Option<String>,
+}
+
+impl ConventionalCommit {
+    /// Parse a commit message
+    pub fn parse(commit_hash: &str, message: &str) -> Self {
+        let header = message.lines().next().unwrap_or_default().trim();
+        let breaking_note = BREAKING_FOOTER_RE
+            .captures(message)
+            .map(|cap| cap["note"].trim().to_string());
+
+        match HEADER_RE.captures(&EMOJI_PREFIX_RE.replace(header, "")) {
+            Some(cap) => Self {
+                commit_hash: commit_hash.to_string(),
+                commit_type: Some(cap["type"].to_lowercase()),
+                scope: cap
+                    .name("scope")
+                    .map(|m| m.as_str().trim().to_string())
+                    .filter(|s| !s.is_empty()),
+                subject: cap["subject"].trim().to_string(),
+                breaking: cap.name("breaking").is_some() || breaking_note.is_some(),
+                breaking_note,
+            },
+            None => Self {
+                commit_hash: commit_hash.to_string(),
+                commit_type: None,
+                scope: None,
+                subject: header.to_string(),
+                breaking: breaking_note.is_some(),
+                breaking_note,
+            },
+        }
+    }
+
+    /// Short form of the commit hash
+    pub fn short_hash(&self) -> &str {
+        &self.commit_hash[..self.commit_hash.len().min(7)]
+    }
+}
+
+/// Mapping from commit types to changelog sections
+#[derive(Debug, Clone)]
+pub struct SectionMapping {
+    /// `(commit type, section title)` pairs in output order
+    sections: Vec<(String, String)>,
+    other_title: String,
+    breaking_title: String,
+}
+
+impl Default for SectionMapping {
+    fn default() -> Self {
+        Self {
+            sections: DEFAULT_SECTIONS
+                .iter()
+                .map(|(ty, title)| ((*ty).to_string(), (*title).to_string()))
+                .collect(),
+            other_title: "Other".to_string(),
+            breaking_title: "BREAKING CHANGES".to_string(),
+        }
+    }
+}
+
+impl SectionMapping {
+    /// Build the mapping from the `[changelog]` table of the config, keeping defaults
+    /// for anything not overridden
+    pub fn from_config(config: &Config) -> Self {
+        Self::from_changelog_config(&config.changelog)
+    }
+
+    fn from_changelog_config(changelog: &ChangelogConfig) -> Self {
+        let mut mapping = Self::default();
+
+        if !changelog.sections.is_empty() {
+            mapping.sections = changelog
+                .sections
+                .iter()
+                .map(|section| (section.commit_type.to_lowercase(), section.title.clone()))
+                .collect();
+        }
+        if let Some(title) = &changelog.other_title {
+            mapping.other_title.clone_from(title);
+        }
+        if let Some(title) = &changelog.breaking_title {
+            mapping.breaking_title.clone_from(title);
+        }
+
+        mapping
+    }
+
+    /// Title of the section a commit type belongs to
+    fn title_for(&self, commit_type: Option<&str>) -> &str {
+        commit_type
+            .and_then(|ty| self.sections.iter().find(|(t, _)| t == ty))
+            .map_or(self.other_title.as_str(), |(_, title)| title.as_str())
+    }
+
+    /// All section titles in output order, ending with the catch-all section
+    fn ordered_titles(&self) -> Vec<&str> {
+        let mut titles: Vec<&str> = Vec::new();
+        for (_, title) in &self.sections {
+            if !titles.contains(&title.as_str()) {
+                titles.push(title);
+            }
+        }
+        if !titles.contains(&self.other_title.as_str()) {
+            titles.push(&self.other_title);
+        }
+        titles
+    }
+}
+
+/// A titled group of commits in the changelog
+#[derive(Debug, Clone, Serialize)]
+pub struct ChangelogSection {
+    pub title: String,
+    #[serde(rename = "entries")]
+    pub commits: Vec<ConventionalCommit>,
+}
+
+/// Commits grouped into changelog sections
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupedChangelog {
+    pub breaking_title: String,
+    pub breaking_changes: Vec<ConventionalCommit>,
+    /// Non-empty sections in a stable order
+    pub sections: Vec<ChangelogSection>,
+}
+
+/// Group commits into sections by their Conventional Commit type
+///
+/// Commits keep their relative order within a section. Breaking commits appear both in
+/// their type's section and in the breaking changes list.
+pub fn group_commits(commits: &[ConventionalCommit], mapping: &SectionMapping) -> GroupedChangelog {
+    let sections = mapping
+        .ordered_titles()
+        .into_iter()
+        .map(|title| ChangelogSection {
+            title: title.to_string(),
+            commits: commits
+                .iter()
+                .filter(|c| mapping.title_for(c.commit_type.as_deref()) == title)
+                .cloned()
+                .collect(),
+        })
+        .filter(|section| !section.commits.is_empty())
+        .collect();
+
+    GroupedChangelog {
+        breaking_title: mapping.breaking_title.clone(),
+        breaking_changes: commits.iter().filter(|c| c.breaking).cloned().collect(),
+        sections,
+    }
+}
+
+/// A version's grouped changelog, rendered as Markdown or serialized as is for
+/// `--format json`
+#[derive(Debug, Clone, Serialize)]
+pub struct ChangelogDocument {
+    pub version: String,
+    pub date: String,
+    #[serde(flatten)]
+    pub grouped: GroupedChangelog,
+}
+
+impl ChangelogDocument {
+    /// Every entry, in the same order as the sections
+    pub fn entries(&self) -> impl Iterator<Item = &ConventionalCommit> {
+        self.grouped
+            .sections
+            .iter()
+            .flat_map(|section| section.commits.iter())
+    }
+}
+
+/// Render a changelog document as a Markdown version section
+///
+/// The date is left empty, matching the LLM changelog output, so it can be filled in
+/// when the changelog file is updated.
+pub fn format_changelog_document(document: &ChangelogDocument) -> String {
+    let grouped = &document.grouped;
+    let mut formatted = format!("## [{}] - \n\n", document.version);
+
+    if !grouped.breaking_changes.is_empty() {
+        writeln!(formatted, "### {}\n", grouped.breaking_title)
+            .expect("writing to string should never fail");
+        for commit in &grouped.breaking_changes {
+            let note = commit.breaking_note.as_deref().unwrap_or(&commit.subject);
+            writeln!(
+                formatted,
+                "- {}{note} ({})",
+                scope_prefix(commit),
+                commit.short_hash()
+            )
+            .expect("writing to string should never fail");
+        }
+        formatted.push('\n');
+    }
+
+    for section in &grouped.sections {
+        writeln!(formatted, "### {}\n", section.title)
+            .expect("writing to string should never fail");
+        for commit in &section.commits {
+            writeln!(
+                formatted,
+                "- {}{} ({})",
+                scope_prefix(commit),
+                commit.subject,
+                commit.short_hash()
+            )
+            .expect("writing to string should never fail");
+        }
+        formatted.push('\n');
+    }
+
+    formatted
+}
+
+fn scope_prefix(commit: &ConventionalCommit) -> String {
+    commit
+        .scope
+        .as_ref()
+        .map(|scope| format!("**{scope}:** "))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ChangelogSectionConfig;
+
+    fn parse_all(messages: &[&str]) -> Vec<ConventionalCommit> {
+        messages
+            .iter()
+            .enumerate()
+            .map(|(i, message)| ConventionalCommit::parse(&format!("{i:07}"), message))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_conventional_header() {
+        let commit = ConventionalCommit::parse("abc1234", "feat(parser)!: support arrays");
+        assert_eq!(commit.commit_type.as_deref(), Some("feat"));
+        assert_eq!(commit.scope.as_deref(), Some("parser"));
+        assert_eq!(commit.subject, "support arrays");
+        assert!(commit.breaking);
+    }
+
+    #[test]
+    fn test_parse_header_after_leading_emoji() {
+        for message in [
+            "🐛 fix(cli): handle empty input",
+            ":bug: fix(cli): handle empty input",
+        ] {
+            let commit = ConventionalCommit::parse("abc1234", message);
+            assert_eq!(commit.commit_type.as_deref(), Some("fix"), "{message}");
+            assert_eq!(commit.scope.as_deref(), Some("cli"));
+            assert_eq!(commit.subject, "handle empty input");
+        }
+
+        let commit = ConventionalCommit::parse("abc1234", "♻️ refactor: split parser");
+        assert_eq!(commit.commit_type.as_deref(), Some("refactor"));
+    }
+
+    #[test]
+    fn test_parse_breaking_footer() {
+        let commit = ConventionalCommit::parse(
+            "abc1234",
+            "fix: drop legacy flag\n\nBREAKING CHANGE: the --legacy flag is gone",
+        );
+        assert!(commit.breaking);
+        assert_eq!(
+            commit.breaking_note.as_deref(),
+            Some("the --legacy flag is gone")
+        );
+    }
+
+    #[test]
+    fn test_group_mixed_commits() {
+        let commits = parse_all(&[
+            "fix(cli): handle empty input",
+            "feat: add export command",
+            "Update README",
+            "perf: cache parsed config",
+            "feat(api)!: rename endpoints",
+            "chore: bump deps",
+        ]);
+
+        let grouped = group_commits(&commits, &SectionMapping::default());
+        let titles: Vec<&str> = grouped.sections.iter().map(|s| s.title.as_str()).collect();
+
+        assert_eq!(
+            titles,
+            ["Features", "Bug Fixes", "Performance", "Chores", "Other"]
+        );
+        assert_eq!(grouped.sections[0].commits.len(), 2);
+        assert_eq!(grouped.sections[4].commits[0].subject, "Update README");
+        assert_eq!(grouped.breaking_changes.len(), 1);
+        assert_eq!(grouped.breaking_changes[0].scope.as_deref(), Some("api"));
+    }
+
+    #[test]
+    fn test_document_serializes_entries() {
+        let commits = parse_all(&["fix: two", "feat(api)!: one"]);
+        let document = ChangelogDocument {
+            version: "1.2.0".to_string(),
+            date: "2024-05-01".to_string(),
+            grouped: group_commits(&commits, &SectionMapping::default()),
+        };
+
+        let json = serde_json::to_value(&document).expect("Failed to serialize document");
+
+        assert_eq!(json["version"], "1.2.0");
+        assert_eq!(json["date"], "2024-05-01");
+        let features = &json["sections"][0];
+        assert_eq!(features["title"], "Features");
+        assert_eq!(features["entries"][0]["type"], "feat");
+        assert_eq!(features["entries"][0]["scope"], "api");
+        assert_eq!(features["entries"][0]["subject"], "one");
+        assert_eq!(features["entries"][0]["breaking"], true);
+        assert_eq!(json["sections"][1]["entries"][0]["type"], "fix");
+        assert_eq!(json["sections"][1]["entries"][0]["commit_hash"], "0000000");
+        assert_eq!(json["breaking_changes"][0]["subject"], "one");
+        assert!(format_changelog_document(&document).starts_with("## [1.2.0] - "));
+    }
+
+    #[test]
+    fn test_section_mapping_overrides() {
+        let changelog = ChangelogConfig {
+            sections: vec![
+                ChangelogSectionConfig {
+                    commit_type: "fix".to_string(),
+                    title: "Fixed".to_string(),
+                },
+                ChangelogSectionConfig {
+                    commit_type: "feat".to_string(),
+                    title: "Added".to_string(),
+                },
+            ],
+            other_title: Some("Misc".to_string()),
+            breaking_title: None,
+        };
+        let mapping = SectionMapping::from_changelog_config(&changelog);
+        let commits = parse_all(&["feat: one", "fix: two", "docs: three"]);
+
+        let grouped = group_commits(&commits, &mapping);
+        let titles: Vec<&str> = grouped.sections.iter().map(|s| s.title.as_str()).collect();
+
+        assert_eq!(titles, ["Fixed", "Added", "Misc"]);
+    }
+}
@@ -0,0 +1,156 @@
+use std::collections::HashSet;
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+/// Subject of a `git revert` commit: `Revert "original subject"`
+static REVERT_SUBJECT_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"^Revert "(.+)"$"#).expect("valid revert subject regex"));
+
+/// Body line `git revert` adds: `This reverts commit <hash>.`
+static REVERT_HASH_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?m)^This reverts commit ([0-9a-fA-F]{7,40})").expect("valid revert hash regex")
+});
+
+/// First line of a commit message with runs of whitespace collapsed to one space
+pub fn normalize_subject(message: &str) -> String {
+    message
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// What a revert commit undoes, if the message looks like `git revert` output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RevertTarget {
+    /// Hash from `This reverts commit <hash>.`, lowercased
+    pub hash: Option<String>,
+    /// Normalized subject from `Revert "<subject>"`
+    pub subject: Option<String>,
+}
+
+impl RevertTarget {
+    /// Parse a commit message, returning `None` for commits that aren't reverts
+    pub fn parse(message: &str) -> Option<Self> {
+        let subject = REVERT_SUBJECT_RE
+            .captures(&normalize_subject(message))
+            .map(|cap| cap[1].to_string());
+        let hash = REVERT_HASH_RE
+            .captures(message)
+            .map(|cap| cap[1].to_lowercase());
+        (subject.is_some() || hash.is_some()).then_some(Self { hash, subject })
+    }
+
+    /// Whether this revert undoes the commit with the given hash and message
+    fn targets(&self, hash: &str, message: &str) -> bool {
+        match &self.hash {
+            Some(target) => hash.to_lowercase().starts_with(target.as_str()),
+            None => self
+                .subject
+                .as_ref()
+                .is_some_and(|subject| *subject == normalize_subject(message)),
+        }
+    }
+}
+
+/// Collapse cherry-picked duplicates and cancel out reverts within a list of commits.
+///
+/// A revert whose target is also in the list is dropped together with that target, so
+/// reverting a revert leaves the original commit in place. A revert of a commit outside the
+/// list is kept. Of the remaining commits, only the first with each normalized subject is
+/// kept. `key` returns a commit's hash and full message; order is otherwise preserved.
+pub fn collapse_duplicates<T, F>(commits: Vec<T>, key: F) -> Vec<T>
+where
+    F: Fn(&T) -> (&str, &str),
+{
+    let mut removed = vec![false; commits.len()];
+    for (index, commit) in commits.iter().enumerate() {
+        if removed[index] {
+            continue;
+        }
+        let Some(target) = RevertTarget::parse(key(commit).1) else {
+            continue;
+        };
+        let cancelled = (0..commits.len()).find(|&other| {
+            let (hash, message) = key(&commits[other]);
+            other != index && !removed[other] && target.targets(hash, message)
+        });
+        if let Some(other) = cancelled {
+            removed[index] = true;
+            removed[other] = true;
+        }
+    }
+
+    let mut seen = HashSet::new();
+    commits
+        .into_iter()
+        .zip(removed)
+        .filter(|(commit, removed)| !removed && seen.insert(normalize_subject(key(commit).1)))
+        .map(|(commit, _)| commit)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subjects(commits: &[(&str, &str)]) -> Vec<String> {
+        collapse_duplicates(commits.to_vec(), |&(hash, message)| (hash, message))
+            .into_iter()
+            .map(|(_, message)| normalize_subject(message))
+            .collect()
+    }
+
+    #[test]
+    fn test_cherry_picked_duplicate_is_collapsed() {
+        let commits = [
+            (
+                "bbbbbbb",
+                "fix:  handle empty diff\n\n(cherry picked from commit aaaaaaa)",
+            ),
+            ("ccccccc", "feat: add --since"),
+            ("aaaaaaa", "fix: handle empty diff"),
+        ];
+
+        assert_eq!(
+            subjects(&commits),
+            ["fix: handle empty diff", "feat: add --since"]
+        );
+    }
+
+    #[test]
+    fn test_revert_pair_cancels_out() {
+        let commits = [
+            (
+                "ddddddd",
+                "Revert \"feat: add cache\"\n\nThis reverts commit aaaaaaa1234.",
+            ),
+            ("ccccccc", "fix: typo"),
+            ("aaaaaaa1234", "feat: add cache"),
+            (
+                "eeeeeee",
+                "Revert \"feat: older feature\"\n\nThis reverts commit 0123456.",
+            ),
+        ];
+
+        // The last revert's target is outside the range, so it stays
+        assert_eq!(
+            subjects(&commits),
+            ["fix: typo", "Revert \"feat: older feature\""]
+        );
+    }
+
+    #[test]
+    fn test_reverting_a_revert_keeps_the_original() {
+        let commits = [
+            ("ccccccc", "Revert \"Revert \"feat: add cache\"\""),
+            ("bbbbbbb", "Revert \"feat: add cache\""),
+            ("aaaaaaa", "feat: add cache"),
+        ];
+
+        assert_eq!(subjects(&commits), ["feat: add cache"]);
+    }
+}
@@ -0,0 +1,263 @@
+use std::sync::LazyLock;
+
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, Utc};
+use regex::Regex;
+
+use super::conventional::ConventionalCommit;
+
+/// A relative time such as `3 days`, `2 weeks ago` or `12h`
+static RELATIVE_TIME_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(?i)(\d+)\s*([a-z]+?)s?(?:\s+ago)?$").expect("valid relative time regex")
+});
+
+/// Restricts changelogs and release notes to commits touching a path or using a scope.
+///
+/// When both a path and a scope are set, a commit is included if it matches **either**
+/// of them. An empty filter includes every commit. The `since`/`until` window applies on
+/// top of that: a commit outside it is left out whatever its path or scope. With
+/// `collapse_duplicates`, cherry-picked copies and reverts that cancel out are dropped from
+/// the range as well (see [`collapse_duplicates`](super::dedupe::collapse_duplicates)).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommitFilter {
+    /// Directory or file prefix, e.g. `packages/foo`; a trailing `/**` is ignored
+    pub path: Option<String>,
+    /// Conventional Commit scope, e.g. `foo` to match `feat(foo): ...`
+    pub scope: Option<String>,
+    /// Leave out commits committed before this time
+    pub since: Option<DateTime<Utc>>,
+    /// Leave out commits committed after this time
+    pub until: Option<DateTime<Utc>>,
+    /// Collapse commits with the same subject and drop revert pairs
+    pub collapse_duplicates: bool,
+}
+
+impl CommitFilter {
+    /// Create a filter from optional path and scope values
+    pub fn new(path: Option<String>, scope: Option<String>) -> Self {
+        Self {
+            path,
+            scope,
+            ..Self::default()
+        }
+    }
+
+    /// Only include commits committed inside the given window
+    #[must_use]
+    pub const fn with_dates(
+        mut self,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    ) -> Self {
+        self.since = since;
+        self.until = until;
+        self
+    }
+
+    /// Collapse duplicate subjects and revert pairs in the range
+    #[must_use]
+    pub const fn with_collapsed_duplicates(mut self, collapse: bool) -> Self {
+        self.collapse_duplicates = collapse;
+        self
+    }
+
+    /// Whether the path/scope part of the filter includes every commit
+    pub const fn is_empty(&self) -> bool {
+        self.path.is_none() && self.scope.is_none()
+    }
+
+    /// Whether the filter restricts commits by date
+    pub const fn has_dates(&self) -> bool {
+        self.since.is_some() || self.until.is_some()
+    }
+
+    /// Whether a commit time, in seconds since the Unix epoch, falls inside the date window
+    pub fn matches_time(&self, seconds: i64) -> bool {
+        let Some(time) = DateTime::from_timestamp(seconds, 0) else {
+            return !self.has_dates();
+        };
+        self.since.is_none_or(|since| time >= since) && self.until.is_none_or(|until| time <= until)
+    }
+
+    /// Whether the filter restricts by changed paths
+    pub const fn has_path(&self) -> bool {
+        self.path.is_some()
+    }
+
+    /// Whether the commit message's Conventional Commit scope matches the scope filter
+    pub fn matches_scope(&self, message: &str) -> bool {
+        let Some(scope) = &self.scope else {
+            return false;
+        };
+        ConventionalCommit::parse("", message)
+            .scope
+            .is_some_and(|s| s.eq_ignore_ascii_case(scope))
+    }
+
+    /// Whether any of the changed paths falls under the path filter
+    pub fn matches_paths<'a>(&self, paths: impl IntoIterator<Item = &'a str>) -> bool {
+        let Some(filter_path) = &self.path else {
+            return false;
+        };
+        let prefix = normalize_path(filter_path);
+        if prefix.is_empty() {
+            return true;
+        }
+
+        paths.into_iter().any(|path| {
+            path == prefix
+                || path
+                    .strip_prefix(prefix)
+                    .is_some_and(|rest| rest.starts_with('/'))
+        })
+    }
+
+    /// Whether a commit should be included, given its message and changed paths
+    pub fn matches<'a>(&self, message: &str, paths: impl IntoIterator<Item = &'a str>) -> bool {
+        self.is_empty() || self.matches_scope(message) || self.matches_paths(paths)
+    }
+}
+
+/// Parse a `--since`/`--until` value relative to the current time
+pub fn parse_time_bound(value: &str) -> Result<DateTime<Utc>> {
+    parse_time_bound_at(value, Utc::now())
+}
+
+/// Parse a `--until` value relative to the current time
+pub fn parse_until_bound(value: &str) -> Result<DateTime<Utc>> {
+    parse_until_bound_at(value, Utc::now())
+}
+
+/// Like [`parse_time_bound_at`], except that a `YYYY-MM-DD` date stands for the end of that
+/// day (UTC), so `--until` includes the commits made on it
+pub fn parse_until_bound_at(value: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>> {
+    if let Some(end_of_day) = NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(23, 59, 59))
+    {
+        return Ok(end_of_day.and_utc());
+    }
+    parse_time_bound_at(value, now)
+}
+
+/// Parse an RFC 3339 timestamp, a `YYYY-MM-DD` date (midnight UTC) or a duration before
+/// `now` such as `3 days`, `2 weeks ago` or `12h`
+pub fn parse_time_bound_at(value: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.with_timezone(&Utc));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(date.and_time(NaiveTime::MIN).and_utc());
+    }
+
+    let invalid = || {
+        anyhow!(
+            "Invalid time '{value}': expected an RFC 3339 timestamp, a YYYY-MM-DD date or a duration like '2 weeks'"
+        )
+    };
+    let captures = RELATIVE_TIME_RE.captures(value).ok_or_else(invalid)?;
+    let amount: i64 = captures[1].parse().map_err(|_| invalid())?;
+    let unit = match captures[2].to_lowercase().as_str() {
+        "s" | "sec" | "second" => Duration::try_seconds(amount),
+        "m" | "min" | "minute" => Duration::try_minutes(amount),
+        "h" | "hour" => Duration::try_hours(amount),
+        "d" | "day" => Duration::try_days(amount),
+        "w" | "week" => Duration::try_weeks(amount),
+        "month" => Duration::try_days(amount.saturating_mul(30)),
+        "y" | "year" => Duration::try_days(amount.saturating_mul(365)),
+        _ => None,
+    };
+    unit.and_then(|duration| now.checked_sub_signed(duration))
+        .ok_or_else(invalid)
+}
+
+/// Strip `./`, trailing slashes and a trailing `/**` glob from a path filter
+fn normalize_path(path: &str) -> &str {
+    let path = path.trim().trim_start_matches("./");
+    path.strip_suffix("/**")
+        .unwrap_or(path)
+        .trim_end_matches('/')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_matching_respects_directory_boundaries() {
+        let filter = CommitFilter::new(Some("packages/foo/**".to_string()), None);
+
+        assert!(filter.matches_paths(["packages/foo/src/lib.rs"]));
+        assert!(!filter.matches_paths(["packages/foobar/src/lib.rs"]));
+        assert!(!filter.matches_paths(["packages/bar/src/lib.rs"]));
+    }
+
+    #[test]
+    fn test_path_or_scope_either_matches() {
+        let filter = CommitFilter::new(Some("packages/foo".to_string()), Some("foo".to_string()));
+
+        assert!(filter.matches("feat(foo): add api", ["docs/README.md"]));
+        assert!(filter.matches("fix: typo", ["packages/foo/lib.rs"]));
+        assert!(!filter.matches("feat(bar): add api", ["packages/bar/lib.rs"]));
+    }
+
+    #[test]
+    fn test_parse_time_bound_accepts_dates_and_durations() {
+        let now = DateTime::parse_from_rfc3339("2024-03-15T12:00:00Z")
+            .expect("valid timestamp")
+            .with_timezone(&Utc);
+        let at = |value: &str| {
+            parse_time_bound_at(value, now)
+                .expect("valid time bound")
+                .to_rfc3339()
+        };
+
+        assert_eq!(at("2024-01-01T08:30:00+02:00"), "2024-01-01T06:30:00+00:00");
+        assert_eq!(at("2024-02-01"), "2024-02-01T00:00:00+00:00");
+        assert_eq!(at("2 weeks"), "2024-03-01T12:00:00+00:00");
+        assert_eq!(at("3 days ago"), "2024-03-12T12:00:00+00:00");
+        assert_eq!(at("1 day"), "2024-03-14T12:00:00+00:00");
+        assert_eq!(at("12h"), "2024-03-15T00:00:00+00:00");
+        assert!(parse_time_bound_at("last tuesday", now).is_err());
+        assert!(parse_time_bound_at("3 fortnights", now).is_err());
+    }
+
+    #[test]
+    fn test_until_date_covers_the_whole_day() {
+        let now = Utc::now();
+        assert_eq!(
+            parse_until_bound_at("2024-01-31", now)
+                .expect("valid date")
+                .to_rfc3339(),
+            "2024-01-31T23:59:59+00:00"
+        );
+        assert_eq!(
+            parse_until_bound_at("2024-01-31T08:00:00Z", now).expect("valid timestamp"),
+            parse_time_bound_at("2024-01-31T08:00:00Z", now).expect("valid timestamp")
+        );
+        assert_eq!(
+            parse_until_bound_at("2 weeks", now).expect("valid duration"),
+            parse_time_bound_at("2 weeks", now).expect("valid duration")
+        );
+    }
+
+    #[test]
+    fn test_date_window_is_inclusive() {
+        let time = |value: &str| parse_time_bound_at(value, Utc::now()).expect("valid time");
+        let until = parse_until_bound_at("2024-01-31", Utc::now()).expect("valid time");
+        let filter = CommitFilter::default().with_dates(Some(time("2024-01-01")), Some(until));
+
+        assert!(filter.matches_time(time("2024-01-01").timestamp()));
+        assert!(filter.matches_time(time("2024-01-31").timestamp()));
+        assert!(filter.matches_time(time("2024-01-31T18:30:00Z").timestamp()));
+        assert!(!filter.matches_time(time("2023-12-31").timestamp()));
+        assert!(!filter.matches_time(time("2024-02-01").timestamp()));
+        assert!(CommitFilter::default().matches_time(0));
+    }
+
+    #[test]
+    fn test_empty_filter_matches_everything() {
+        assert!(CommitFilter::default().matches("chore: bump", ["Cargo.lock"]));
+    }
+}
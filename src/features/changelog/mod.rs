@@ -12,11 +12,21 @@ mod releasenotes;
 #[allow(clippy::uninlined_format_args)]
 pub mod change_analyzer;
 #[allow(clippy::uninlined_format_args)]
+pub mod conventional;
+#[allow(clippy::uninlined_format_args)]
+pub mod dedupe;
+#[allow(clippy::uninlined_format_args)]
+pub mod filter;
+#[allow(clippy::uninlined_format_args)]
 pub mod models;
 #[allow(clippy::uninlined_format_args)]
 pub mod prompt;
 
-pub use cli::{handle_changelog_command, handle_release_notes_command};
+pub use cli::{
+    ChangelogOptions, OutputFormat, ReleaseNotesOptions, handle_changelog_command,
+    handle_release_notes_command,
+};
 
 pub use change_log::ChangelogGenerator;
+pub use filter::CommitFilter;
 pub use releasenotes::ReleaseNotesGenerator;
@@ -2,7 +2,7 @@ use super::{
     change_analyzer::AnalyzedChange,
     models::{ChangeMetrics, ChangelogResponse, ReleaseNotesResponse},
 };
-use crate::common::{DetailLevel, get_combined_instructions};
+use crate::common::{DetailLevel, append_system_suffix, get_combined_instructions};
 use crate::config::Config;
 use log::debug;
 use std::fmt::Write;
@@ -112,7 +112,7 @@ pub fn create_changelog_system_prompt(config: &Config) -> String {
         Adjust the level of detail based on the specified detail level (Minimal, Standard, or Detailed)."
     );
 
-    prompt
+    append_system_suffix(prompt, &config.changelog_system_suffix)
 }
 
 pub fn create_release_notes_system_prompt(config: &Config) -> String {
@@ -216,7 +216,7 @@ pub fn create_release_notes_system_prompt(config: &Config) -> String {
     prompt.push_str(&release_notes_schema_str);
     prompt.push_str(get_combined_instructions(config).as_str());
 
-    prompt
+    append_system_suffix(prompt, &config.release_notes_system_suffix)
 }
 
 /// Common helper function to format metrics summary
@@ -1,4 +1,5 @@
 use super::common::generate_changes_content;
+use super::filter::CommitFilter;
 use super::models::{
     BreakingChange, ChangeMetrics, Highlight, ReleaseNotesResponse, Section, SectionItem,
 };
@@ -25,6 +26,7 @@ impl ReleaseNotesGenerator {
     /// * `config` - Configuration object containing LLM settings
     /// * `detail_level` - Level of detail for the release notes (Minimal, Standard, or Detailed)
     /// * `version_name` - Optional explicit version name to use instead of detecting from Git
+    /// * `filter` - Path/scope filter; commits matching neither are left out
     ///
     /// # Returns
     ///
@@ -36,6 +38,7 @@ impl ReleaseNotesGenerator {
         config: &Config,
         detail_level: DetailLevel,
         version_name: Option<String>,
+        filter: &CommitFilter,
     ) -> Result<String> {
         let release_notes: ReleaseNotesResponse = generate_changes_content::<ReleaseNotesResponse>(
             git_repo,
@@ -43,6 +46,7 @@ impl ReleaseNotesGenerator {
             to,
             config,
             detail_level,
+            filter,
             prompt::create_release_notes_system_prompt,
             prompt::create_release_notes_user_prompt,
         )
@@ -0,0 +1,138 @@
+use super::types::{GeneratedMessage, format_commit_message};
+
+use anyhow::Result;
+use log::debug;
+use std::fmt::Write;
+
+/// Angles the candidates after the first are asked to take, so they differ from each other
+const CANDIDATE_ANGLES: &[&str] = &[
+    "Describe the change by its user-visible effect rather than by the code that changed.",
+    "Describe the change by the main code change itself, naming the component it touches.",
+    "Be as brief as the change allows: a short title and a one or two sentence body.",
+    "Explain why the change was needed before what it does.",
+];
+
+/// Instructions for candidate `index` of `count`.
+///
+/// The first candidate gets `instructions` unchanged; each later one is also asked to take
+/// a different angle, so the candidates aren't near-copies of each other.
+pub fn candidate_instructions(instructions: &str, index: usize, count: usize) -> String {
+    if index == 0 || count <= 1 {
+        return instructions.to_string();
+    }
+
+    let angle = CANDIDATE_ANGLES[(index - 1) % CANDIDATE_ANGLES.len()];
+    let nudge = format!(
+        "This is candidate {} of {count} for the same changes. {angle}",
+        index + 1
+    );
+    if instructions.trim().is_empty() {
+        nudge
+    } else {
+        format!("{}\n\n{nudge}", instructions.trim_end())
+    }
+}
+
+/// The distinct messages among `results`, in order, or the first error if none succeeded
+pub fn distinct_candidates(
+    results: impl IntoIterator<Item = Result<GeneratedMessage>>,
+) -> Result<Vec<GeneratedMessage>> {
+    let mut candidates: Vec<GeneratedMessage> = Vec::new();
+    let mut first_error = None;
+    for result in results {
+        match result {
+            Ok(message) => {
+                let formatted = format_commit_message(&message);
+                if !candidates
+                    .iter()
+                    .any(|c| format_commit_message(c) == formatted)
+                {
+                    candidates.push(message);
+                }
+            }
+            Err(e) => {
+                debug!("Candidate request failed: {e}");
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+
+    match first_error {
+        Some(e) if candidates.is_empty() => Err(e),
+        _ => Ok(candidates),
+    }
+}
+
+/// Format candidates for `--print`: a lone message as is, several under numbered rules
+pub fn format_candidates(candidates: &[GeneratedMessage]) -> String {
+    if let [message] = candidates {
+        return format_commit_message(message);
+    }
+
+    let mut output = String::new();
+    for (index, message) in candidates.iter().enumerate() {
+        if index > 0 {
+            output.push('\n');
+        }
+        let _ = writeln!(
+            output,
+            "--- Candidate {} of {} ---",
+            index + 1,
+            candidates.len()
+        );
+        output.push_str(&format_commit_message(message));
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    fn message(title: &str) -> GeneratedMessage {
+        GeneratedMessage {
+            emoji: None,
+            title: title.to_string(),
+            message: "Body.".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_candidates_are_distinct() {
+        let count = 3;
+        let instructions: Vec<String> = (0..count)
+            .map(|index| candidate_instructions("Use the imperative mood", index, count))
+            .collect();
+        assert_eq!(instructions[0], "Use the imperative mood");
+        assert!(instructions[1].starts_with("Use the imperative mood\n\n"));
+        assert_ne!(instructions[1], instructions[2]);
+
+        let candidates = distinct_candidates([
+            Ok(message("feat: add candidates")),
+            Ok(message("feat: add candidates")),
+            Err(anyhow!("rate limited")),
+            Ok(message("feat(tui): generate several messages up front")),
+            Ok(message("feat: let users pick from several messages")),
+        ])
+        .expect("some candidates succeeded");
+        assert_eq!(candidates.len(), count);
+        for (i, candidate) in candidates.iter().enumerate() {
+            assert!(
+                candidates[i + 1..]
+                    .iter()
+                    .all(|c| c.title != candidate.title)
+            );
+        }
+
+        let printed = format_candidates(&candidates);
+        assert!(printed.starts_with("--- Candidate 1 of 3 ---\nfeat: add candidates\n"));
+        assert!(printed.contains("\n--- Candidate 3 of 3 ---\n"));
+        assert_eq!(
+            format_candidates(&candidates[..1]),
+            format_commit_message(&candidates[0])
+        );
+
+        assert!(distinct_candidates([Err(anyhow!("rate limited"))]).is_err());
+    }
+}
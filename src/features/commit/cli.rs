@@ -1,24 +1,54 @@
-use super::completion::CompletionService;
-use super::format_commit_result;
+use super::candidates::format_candidates;
+use super::completion::{CompletionRequest, CompletionService};
+use super::editor::edit_message;
+use super::generator::MessageGenerator;
 use super::service::CommitService;
-use super::types::{format_commit_message, format_pull_request};
-use crate::common::CommonParams;
+use super::types::{
+    format_commit_explanation, format_commit_message, format_per_commit_pull_request,
+    format_pull_request, parse_commit_message,
+};
+use super::{commit_generated_message, context_from_diff, ensure_staged_changes};
+use crate::common::{CommonParams, write_output_file};
 use crate::config::Config;
 use crate::core::messages;
+use crate::core::saved_instructions::SavedInstructions;
+use crate::error::{Categorize, ErrorCategory};
 use crate::features::commit::types;
 use crate::git::GitRepo;
-use crate::tui::run_tui_commit;
+use crate::tui::{AmendTarget, run_tui_commit};
 use crate::ui::{self, SpinnerState};
 
 use anyhow::{Context, Result};
 use std::{
-    io::{self, Write},
+    io::{self, IsTerminal, Read, Write},
     sync::Arc,
     time::Duration,
 };
 use tokio::time;
 
+/// Whether stdin and stdout are both a terminal, as the TUI needs
+fn is_interactive_terminal() -> bool {
+    io::stdin().is_terminal() && io::stdout().is_terminal()
+}
+
+/// Why the TUI can't run, so the message is printed instead, or `None` if it can
+fn tui_unavailable_reason(remote: bool, terminal: bool) -> Option<&'static str> {
+    if remote {
+        Some("Interactive commit not available for remote repositories. Using print mode instead.")
+    } else if !terminal {
+        Some(
+            "Not running in a terminal, so the message is printed instead of opening the TUI.\n\
+             Use --print to output only the message, or --yes to commit it directly.",
+        )
+    } else {
+        None
+    }
+}
+
 /// Run an async operation with a CLI spinner display
+///
+/// Without a terminal on stdout the operation just runs, so no spinner frames end up in
+/// piped output.
 async fn run_with_spinner<F, Fut, T>(
     mut spinner: SpinnerState,
     operation: F,
@@ -27,6 +57,10 @@ where
     F: FnOnce() -> Fut,
     Fut: std::future::Future<Output = Result<T, anyhow::Error>>,
 {
+    if !io::stdout().is_terminal() {
+        return operation().await;
+    }
+
     let (tx, mut rx) = tokio::sync::mpsc::channel(1);
 
     // Spawn spinner animation task
@@ -52,7 +86,7 @@ where
 
     // Stop the spinner
     let _ = tx.send(()).await;
-    spinner_handle.await?;
+    spinner_handle.await.categorize(ErrorCategory::Internal)?;
 
     result
 }
@@ -69,9 +103,33 @@ pub async fn handle_message_command(
     amend: bool,
     commit_ref: Option<String>,
     repository_url: Option<String>,
+    sign: bool,
+    co_authors: Vec<String>,
+    stage_all: bool,
+    stdin_diff: bool,
+    no_emoji: bool,
+    candidates: Option<usize>,
+    reset_instructions: bool,
+    editor: bool,
 ) -> Result<()> {
     let mut config = Config::load()?;
     common.apply_to_config(&mut config)?;
+    config.sign_commits = sign;
+    config.co_authors = co_authors;
+    if no_emoji {
+        config.emoji = false;
+    }
+    if let Some(candidates) = candidates {
+        config.candidates = candidates;
+    }
+
+    // A diff piped in on stdin is described as is, without opening a repository
+    if stdin_diff {
+        let instructions = common
+            .resolved_instructions(&config)?
+            .unwrap_or_else(|| config.instructions.clone());
+        return print_message_for_stdin_diff(&config, &instructions, dry_run).await;
+    }
 
     // Create the service using the common function
     let service =
@@ -93,24 +151,23 @@ pub async fn handle_message_command(
         return Err(anyhow::anyhow!("--commit requires --amend"));
     }
 
-    if amend && commit_ref.is_some() && commit_ref.as_deref() != Some("HEAD") {
-        ui::print_error(
-            "Currently, only amending HEAD is supported. For amending other commits, use git directly.",
-        );
-        ui::print_info("Example: git commit --amend");
-        return Err(anyhow::anyhow!(
-            "Only HEAD amendments are currently supported"
-        ));
+    // Amending an older commit rewords it, so its own diff drives the message
+    let reword_ref = commit_ref.as_deref().filter(|r| amend && *r != "HEAD");
+
+    if reset_instructions {
+        SavedInstructions::new()?.clear(service.repo_path())?;
+        ui::print_info("Cleared the instructions saved from the TUI for this repository.");
+    }
+
+    // A dry run leaves the index alone, as it makes no commit to stage for
+    if stage_all && !dry_run {
+        service.stage_tracked_changes().await?;
     }
 
     let git_info = service.get_git_info().await?;
 
-    if git_info.staged_files.is_empty() && !dry_run && !amend {
-        ui::print_warning(
-            "No staged changes. Please stage your changes before generating a commit message.",
-        );
-        ui::print_info("You can stage changes using 'git add <file>' or 'git add .'");
-        return Ok(());
+    if !dry_run && !amend {
+        ensure_staged_changes(&git_info)?;
     }
 
     // Run pre-commit hook before we do anything else
@@ -119,32 +176,60 @@ pub async fn handle_message_command(
         return Err(e);
     }
 
-    let effective_instructions = common
-        .instructions
-        .unwrap_or_else(|| config.instructions.clone());
+    // Interactive runs pick up the instructions last typed in the TUI, unless given here
+    let interactive = !print && !auto_commit && !editor;
+    let effective_instructions = match common.resolved_instructions(&config)? {
+        Some(instructions) => instructions,
+        None => interactive
+            .then(|| saved_tui_instructions(&service))
+            .flatten()
+            .unwrap_or_else(|| config.instructions.clone()),
+    };
 
     // Create spinner for message generation
     let random_message = messages::get_waiting_message();
     let spinner = ui::create_tui_spinner(&random_message.text);
 
-    // Generate an initial message with spinner display
-    let initial_message = if dry_run {
-        types::GeneratedMessage {
+    // Auto-commit and the editor take a single message, so there's nothing to choose between
+    let candidate_count = if auto_commit || editor {
+        1
+    } else {
+        config.candidates
+    };
+
+    // Generate the initial messages with spinner display
+    let initial_messages = if dry_run {
+        vec![types::GeneratedMessage {
+            emoji: None,
             title: "Fix bug in UI rendering".to_string(),
             message: "Updated the layout to properly handle dynamic constraints and improve user experience.".to_string(),
-        }
+        }]
     } else {
-        run_with_spinner(spinner, || {
-            service.generate_message(&effective_instructions)
+        run_with_spinner(spinner, || async {
+            let context = match reword_ref {
+                Some(reword_ref) => Some(service.get_git_info_for_commit(reword_ref).await?),
+                None => None,
+            };
+            service
+                .generate_candidates(&effective_instructions, context, candidate_count)
+                .await
         })
         .await?
     };
 
     if print {
-        println!("{}", format_commit_message(&initial_message));
+        println!("{}", format_candidates(&initial_messages));
         return Ok(());
     }
 
+    let Some(initial_message) = initial_messages.first().cloned() else {
+        return Err(anyhow::anyhow!("No commit message was generated"));
+    };
+
+    if editor {
+        return commit_from_editor(&service, &initial_message, dry_run, amend, commit_ref);
+    }
+
     if auto_commit {
         // Only allow auto-commit for local repositories
         if service.is_remote_repository() {
@@ -157,21 +242,15 @@ pub async fn handle_message_command(
         }
 
         if dry_run {
-            ui::print_info("Dry run mode: would amend commit with message:");
+            let action = if amend { "amend the commit" } else { "commit" };
+            ui::print_info(&format!("Dry run mode: would {action} with message:"));
             println!("{}", format_commit_message(&initial_message));
             return Ok(());
         }
 
-        match service.perform_commit(
-            &format_commit_message(&initial_message),
-            amend,
-            commit_ref.as_deref(),
-        ) {
-            Ok(result) => {
-                let output =
-                    format_commit_result(&result, &format_commit_message(&initial_message));
-                println!("{output}");
-            }
+        // Straight to the commit: no TUI and no prompts
+        match commit_generated_message(&service, &initial_message, amend, commit_ref.as_deref()) {
+            Ok(output) => println!("{output}"),
             Err(e) => {
                 eprintln!("Failed to commit: {e}");
                 return Err(e);
@@ -180,33 +259,132 @@ pub async fn handle_message_command(
         return Ok(());
     }
 
-    // Only allow interactive commit for local repositories
-    if service.is_remote_repository() {
-        ui::print_warning(
-            "Interactive commit not available for remote repositories. Using print mode instead.",
-        );
-        println!("{}", format_commit_message(&initial_message));
+    // The TUI needs a local repository and a terminal; print the message otherwise
+    if let Some(reason) =
+        tui_unavailable_reason(service.is_remote_repository(), is_interactive_terminal())
+    {
+        if !ui::is_quiet_mode() {
+            eprintln!("{reason}");
+        }
+        println!("{}", format_candidates(&initial_messages));
         return Ok(());
     }
 
+    // Amending starts from the commit's current message rather than only generated ones
+    let amend_target = if amend {
+        let commit_ish = commit_ref.as_deref().unwrap_or("HEAD");
+        Some(AmendTarget {
+            original: service.get_commit_message(commit_ish)?,
+            commit_ref,
+        })
+    } else {
+        None
+    };
+
     run_tui_commit(
-        vec![initial_message],
+        initial_messages,
         effective_instructions,
         service,
         completion_service,
+        amend_target,
     )
     .await?;
 
     Ok(())
 }
 
+/// Edit `message` in the external editor and commit the result (`--editor`).
+///
+/// An editor that fails or leaves the message empty cancels without committing.
+fn commit_from_editor(
+    service: &CommitService,
+    message: &types::GeneratedMessage,
+    dry_run: bool,
+    amend: bool,
+    commit_ref: Option<String>,
+) -> Result<()> {
+    if service.is_remote_repository() {
+        ui::print_error("Cannot commit to a remote repository. Use --print instead.");
+        return Err(anyhow::anyhow!(
+            "--editor is not supported for remote repositories"
+        ));
+    }
+
+    let formatted = format_commit_message(message);
+    let edited = edit_message(
+        &service.editor(),
+        &formatted,
+        &service.comment_prefix(&formatted),
+    )?;
+    let Some(edited) = edited else {
+        ui::print_info("Commit cancelled: the editor failed or the message was left empty.");
+        return Ok(());
+    };
+    let message = parse_commit_message(&edited);
+
+    if dry_run {
+        let action = if amend { "amend the commit" } else { "commit" };
+        ui::print_info(&format!("Dry run mode: would {action} with message:"));
+        println!("{}", format_commit_message(&message));
+        return Ok(());
+    }
+
+    let output = commit_generated_message(service, &message, amend, commit_ref.as_deref())?;
+    println!("{output}");
+    Ok(())
+}
+
+/// Instructions last typed in the TUI for the service's repository
+fn saved_tui_instructions(service: &CommitService) -> Option<String> {
+    SavedInstructions::new().ok()?.load(service.repo_path())
+}
+
+/// Print a message generated from a unified diff read from stdin (`--stdin-diff`).
+///
+/// The repository is never read: no staged files, recent commits or author history.
+async fn print_message_for_stdin_diff(
+    config: &Config,
+    instructions: &str,
+    dry_run: bool,
+) -> Result<()> {
+    let mut diff = String::new();
+    io::stdin()
+        .read_to_string(&mut diff)
+        .context("Failed to read a diff from stdin")?;
+
+    let context = context_from_diff(&diff, config)?;
+    let generator = MessageGenerator::new(config, &config.default_provider);
+    generator
+        .check_environment()
+        .context("Environment check failed")?;
+
+    let messages = if dry_run {
+        vec![types::GeneratedMessage {
+            emoji: None,
+            title: "Fix bug in UI rendering".to_string(),
+            message: "Updated the layout to properly handle dynamic constraints and improve user experience.".to_string(),
+        }]
+    } else {
+        let spinner = ui::create_tui_spinner(&messages::get_waiting_message().text);
+        run_with_spinner(spinner, || {
+            generator.generate_candidates(instructions, &context, config.candidates)
+        })
+        .await?
+    };
+
+    println!("{}", format_candidates(&messages));
+    Ok(())
+}
+
 /// Handles the PR description generation command
 pub async fn handle_pr_command(
     common: CommonParams,
-    _print: bool,
+    print: bool,
     repository_url: Option<String>,
     from: Option<String>,
     to: Option<String>,
+    per_commit: bool,
+    output_file: Option<String>,
 ) -> Result<()> {
     let mut config = Config::load()?;
     common.apply_to_config(&mut config)?;
@@ -214,15 +392,74 @@ pub async fn handle_pr_command(
     // Setup the service
     let service = setup_pr_service(&common, repository_url, &config)?;
 
-    // Generate the PR description
-    let pr_description = generate_pr_based_on_parameters(service, common, config, from, to).await?;
+    let rendered = if per_commit {
+        let review = generate_per_commit_pr(service, common, config, from, to).await?;
+        format_per_commit_pull_request(&review)
+    } else {
+        let pr_description =
+            generate_pr_based_on_parameters(service, common, config, from, to).await?;
+        format_pull_request(&pr_description)
+    };
+
+    // With --output-file alone the description goes to the file; --print keeps stdout too
+    if let Some(path) = &output_file {
+        write_output_file(path, &rendered)?;
+        if !print {
+            ui::print_success(&format!("PR description written to {path}"));
+            return Ok(());
+        }
+    }
 
     // Print the PR description to stdout
-    println!("{}", format_pull_request(&pr_description));
+    println!("{rendered}");
 
     Ok(())
 }
 
+/// Handles the `explain` command: print a plain-language explanation of `commitish`
+pub async fn handle_explain_command(
+    common: CommonParams,
+    commitish: String,
+    json: bool,
+    dry_run: bool,
+    repository_url: Option<String>,
+) -> Result<()> {
+    let mut config = Config::load()?;
+    common.apply_to_config(&mut config)?;
+
+    let service = create_commit_service(&common, repository_url, &config, false)?;
+    let effective_instructions = common
+        .resolved_instructions(&config)?
+        .unwrap_or_else(|| config.instructions.clone());
+
+    let explanation = if dry_run {
+        service.dry_run_explanation(&commitish).await?
+    } else if json {
+        // Keep stdout clean for the JSON document
+        service
+            .explain_commit(&effective_instructions, &commitish)
+            .await?
+    } else {
+        let random_message = messages::get_waiting_message();
+        let spinner = ui::create_tui_spinner(
+            format!("{} - Explaining {commitish}", random_message.text).as_str(),
+        );
+        run_with_spinner(spinner, || {
+            service.explain_commit(&effective_instructions, &commitish)
+        })
+        .await?
+    };
+
+    if json {
+        let json =
+            serde_json::to_string_pretty(&explanation).categorize(ErrorCategory::Internal)?;
+        println!("{json}");
+    } else {
+        println!("{}", format_commit_explanation(&explanation));
+    }
+    Ok(())
+}
+
 /// Handles the commit message completion command
 #[allow(clippy::too_many_arguments)]
 #[allow(clippy::fn_params_excessive_bools)]
@@ -236,9 +473,18 @@ pub async fn handle_completion_command(
     amend: bool,
     commit_ref: Option<String>,
     repository_url: Option<String>,
+    sign: bool,
+    co_authors: Vec<String>,
+    stage_all: bool,
+    no_emoji: bool,
 ) -> Result<()> {
     let mut config = Config::load()?;
     common.apply_to_config(&mut config)?;
+    config.sign_commits = sign;
+    config.co_authors = co_authors;
+    if no_emoji {
+        config.emoji = false;
+    }
 
     // Default context ratio to 0.5 (50%) if not specified
     let context_ratio = context_ratio.unwrap_or(0.5);
@@ -278,24 +524,15 @@ pub async fn handle_completion_command(
         return Err(anyhow::anyhow!("--commit requires --amend"));
     }
 
-    if amend && commit_ref.is_some() && commit_ref.as_deref() != Some("HEAD") {
-        ui::print_error(
-            "Currently, only amending HEAD is supported. For amending other commits, use git directly.",
-        );
-        ui::print_info("Example: git commit --amend");
-        return Err(anyhow::anyhow!(
-            "Only HEAD amendments are currently supported"
-        ));
+    // A dry run leaves the index alone, as it makes no commit to stage for
+    if stage_all && !dry_run {
+        service.stage_tracked_changes().await?;
     }
 
     let git_info = service.get_git_info().await?;
 
-    if git_info.staged_files.is_empty() && !dry_run && !amend {
-        ui::print_warning(
-            "No staged changes. Please stage your changes before completing a commit message.",
-        );
-        ui::print_info("You can stage changes using 'git add <file>' or 'git add .'");
-        return Ok(());
+    if !dry_run && !amend {
+        ensure_staged_changes(&git_info)?;
     }
 
     // Run pre-commit hook before we do anything else
@@ -305,7 +542,7 @@ pub async fn handle_completion_command(
     }
 
     let _effective_instructions = common
-        .instructions
+        .resolved_instructions(&config)?
         .unwrap_or_else(|| config.instructions.clone());
 
     // Create spinner for completion generation
@@ -318,11 +555,16 @@ pub async fn handle_completion_command(
     // Generate completion with spinner display
     let completed_message = if dry_run {
         types::GeneratedMessage {
+            emoji: None,
             title: format!("{}: Complete the implementation", prefix),
             message: "Add comprehensive error handling and improve code documentation.".to_string(),
         }
     } else {
-        run_with_spinner(spinner, || service.complete_message(&prefix, context_ratio)).await?
+        let request = CompletionRequest::title(&prefix);
+        run_with_spinner(spinner, || {
+            service.complete_message(&request, context_ratio)
+        })
+        .await?
     };
 
     if print {
@@ -375,7 +617,7 @@ async fn generate_pr_based_on_parameters(
     to: Option<String>,
 ) -> Result<super::types::GeneratedPullRequest> {
     let effective_instructions = common
-        .instructions
+        .resolved_instructions(&config)?
         .unwrap_or_else(|| config.instructions.clone());
 
     // Create spinner for PR generation
@@ -420,6 +662,47 @@ async fn generate_pr_based_on_parameters(
     Ok(pr_description)
 }
 
+/// Generates one PR description per commit in the range, plus an overall summary
+async fn generate_per_commit_pr(
+    service: Arc<CommitService>,
+    common: CommonParams,
+    config: Config,
+    from: Option<String>,
+    to: Option<String>,
+) -> Result<super::types::PerCommitPullRequest> {
+    let effective_instructions = common
+        .resolved_instructions(&config)?
+        .unwrap_or_else(|| config.instructions.clone());
+    let (from_ref, to_ref) = per_commit_range(from, to);
+
+    let random_message = messages::get_waiting_message();
+    let spinner = ui::create_tui_spinner(
+        format!(
+            "{} - Reviewing each commit in {from_ref}..{to_ref}",
+            random_message.text
+        )
+        .as_str(),
+    );
+
+    run_with_spinner(spinner, || async {
+        service
+            .generate_pr_per_commit(&effective_instructions, &from_ref, &to_ref)
+            .await
+    })
+    .await
+}
+
+/// Resolve `--from`/`--to` into a `from..to` range for per-commit descriptions
+///
+/// A missing `--from` defaults to `main` and a missing `--to` to `HEAD`, matching the
+/// combined-diff mode.
+fn per_commit_range(from: Option<String>, to: Option<String>) -> (String, String) {
+    (
+        from.unwrap_or_else(|| "main".to_string()),
+        to.unwrap_or_else(|| "HEAD".to_string()),
+    )
+}
+
 /// Handle case where both --from and --to parameters are provided
 async fn handle_from_and_to_parameters(
     service: Arc<CommitService>,
@@ -679,3 +962,18 @@ fn create_completion_service(
 
     Ok(service)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tui_is_skipped_without_a_terminal() {
+        assert_eq!(tui_unavailable_reason(false, true), None);
+
+        let reason = tui_unavailable_reason(false, false).expect("no terminal, no TUI");
+        assert!(reason.contains("--print"));
+        assert!(reason.contains("--yes"));
+        assert!(tui_unavailable_reason(true, true).is_some());
+    }
+}
@@ -0,0 +1,49 @@
+/// Comment prefix git uses when `core.commentChar` isn't set
+pub const DEFAULT_COMMENT_PREFIX: &str = "#";
+
+/// Characters `core.commentChar=auto` picks from, in git's order of preference
+const AUTO_COMMENT_CHARS: &[char] = &['#', ';', '@', '!', '$', '%', '^', '&', '|', ':'];
+
+/// Comment prefix for editing `message`, from the `core.commentChar` setting.
+///
+/// `auto` picks the first of git's candidates that no line of `message` starts with, so
+/// the message's own lines are never mistaken for comments. Unset or empty means `#`.
+pub fn resolve_comment_prefix(setting: Option<&str>, message: &str) -> String {
+    match setting.map(str::trim) {
+        Some("auto") => AUTO_COMMENT_CHARS
+            .iter()
+            .find(|&&c| !message.lines().any(|line| line.starts_with(c)))
+            .map_or_else(|| DEFAULT_COMMENT_PREFIX.to_string(), char::to_string),
+        Some(prefix) if !prefix.is_empty() => prefix.to_string(),
+        _ => DEFAULT_COMMENT_PREFIX.to_string(),
+    }
+}
+
+/// Drop the lines of an edited message that start with `comment_prefix`, as git does
+/// before committing an edited message
+pub fn strip_comment_lines(message: &str, comment_prefix: &str) -> String {
+    message
+        .lines()
+        .filter(|line| !line.starts_with(comment_prefix))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_comment_prefix_follows_the_setting() {
+        let message = "fix: handle #42\n\n# not a heading";
+        assert_eq!(resolve_comment_prefix(None, message), "#");
+        assert_eq!(resolve_comment_prefix(Some(";"), message), ";");
+        assert_eq!(resolve_comment_prefix(Some("auto"), message), ";");
+        assert_eq!(resolve_comment_prefix(Some("auto"), "fix: typo"), "#");
+
+        assert_eq!(
+            strip_comment_lines("fix: typo\n# scratch\n\nBody.\n; kept", "#"),
+            "fix: typo\n\nBody.\n; kept"
+        );
+    }
+}
@@ -1,20 +1,133 @@
 #![allow(clippy::cast_possible_truncation)]
 #![allow(clippy::as_conversions)]
 
-use super::prompt::{create_completion_system_prompt, create_completion_user_prompt};
+use super::gitmoji::apply_gitmoji;
+use super::prompt::{
+    clamp_context_ratio, create_completion_system_prompt, create_completion_user_prompt,
+};
+use super::title::enforce_title_length;
+use super::trailers::append_co_author_trailers;
 use super::types::GeneratedMessage;
 use crate::config::Config;
+use crate::core::azure;
 use crate::core::context::CommitContext;
 use crate::core::llm;
 use crate::core::token_optimizer::TokenOptimizer;
 use crate::git::{CommitResult, GitRepo};
 
 use anyhow::Result;
+use futures::future::join_all;
 use log::debug;
 use std::path::Path;
 use std::sync::Arc;
 use tokio::sync::{RwLock, mpsc};
 
+/// Part of a commit message a completion fills in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionRegion {
+    /// The first line
+    Title,
+    /// Everything after the title and the blank line separating them
+    Body,
+}
+
+/// A completion asked for mid-edit: the message so far and where the cursor is in it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompletionRequest {
+    /// The message being edited, title on the first line
+    pub text: String,
+    /// Zero-based line and character column of the cursor
+    pub cursor: (usize, usize),
+}
+
+impl CompletionRequest {
+    pub fn new(text: impl Into<String>, cursor: (usize, usize)) -> Self {
+        Self {
+            text: text.into(),
+            cursor,
+        }
+    }
+
+    /// Complete `prefix` as the title of an otherwise empty message
+    pub fn title(prefix: &str) -> Self {
+        Self::new(prefix, (0, prefix.chars().count()))
+    }
+
+    /// Region the cursor is in
+    pub const fn region(&self) -> CompletionRegion {
+        if self.cursor.0 == 0 {
+            CompletionRegion::Title
+        } else {
+            CompletionRegion::Body
+        }
+    }
+
+    /// The message's current title
+    pub fn title_line(&self) -> &str {
+        self.text.lines().next().unwrap_or_default()
+    }
+
+    /// Text of the cursor's region up to the cursor, which the completion continues
+    pub fn prefix(&self) -> String {
+        let (before, _) = self.split_region();
+        match self.region() {
+            CompletionRegion::Title => before,
+            CompletionRegion::Body => before.trim_start().to_string(),
+        }
+    }
+
+    /// The part of a generated message that belongs in the cursor's region
+    pub fn suggestion(&self, message: &GeneratedMessage) -> String {
+        match self.region() {
+            CompletionRegion::Title => message.title.clone(),
+            CompletionRegion::Body => message.message.trim().to_string(),
+        }
+    }
+
+    /// The message with `suggestion` in place of the region's text before the cursor.
+    ///
+    /// Text after the cursor and the other region are kept as they are.
+    pub fn splice(&self, suggestion: &str) -> String {
+        let (_, after) = self.split_region();
+        match self.region() {
+            CompletionRegion::Title => {
+                let title = suggestion.lines().next().unwrap_or_default().trim_end();
+                let rest: Vec<&str> = self.text.lines().skip(1).collect();
+                if rest.is_empty() {
+                    format!("{title}{after}")
+                } else {
+                    format!("{title}{after}\n{}", rest.join("\n"))
+                }
+            }
+            CompletionRegion::Body => {
+                format!("{}\n\n{}{after}", self.title_line(), suggestion.trim())
+            }
+        }
+    }
+
+    /// Text of the cursor's region before and after the cursor
+    fn split_region(&self) -> (String, String) {
+        let lines: Vec<&str> = self.text.lines().collect();
+        let line = |row: usize| lines.get(row).copied().unwrap_or_default();
+        let (first, end) = match self.region() {
+            CompletionRegion::Title => (0, 1),
+            CompletionRegion::Body => (1, lines.len()),
+        };
+        let (row, col) = self.cursor;
+        let current = line(row);
+        let at = current
+            .char_indices()
+            .nth(col)
+            .map_or(current.len(), |(index, _)| index);
+
+        let mut before: Vec<&str> = (first..row).map(line).collect();
+        before.push(&current[..at]);
+        let mut after = vec![&current[at..]];
+        after.extend((row + 1..end).map(line));
+        (before.join("\n"), after.join("\n"))
+    }
+}
+
 /// Service for handling Git commit message completion with AI assistance
 pub struct CompletionService {
     config: Config,
@@ -61,7 +174,16 @@ impl CompletionService {
 
     /// Check the environment for necessary prerequisites
     pub fn check_environment(&self) -> Result<()> {
-        self.config.check_environment()
+        self.config.check_environment()?;
+        azure::check_provider_settings(&self.config, &self.provider_name)
+    }
+
+    /// Stage modifications and deletions of tracked files (`--all`), like `git commit -a`
+    pub async fn stage_tracked_changes(&self) -> Result<()> {
+        self.repo.stage_tracked_changes()?;
+        // Drop any context read before staging
+        *self.cached_context.write().await = None;
+        Ok(())
     }
 
     /// Get Git information for the current repository
@@ -84,33 +206,78 @@ impl CompletionService {
 
     /// Generate a commit message completion using AI
     ///
+    /// Convenience wrapper around [`Self::complete_messages`] that returns a single result.
+    ///
     /// # Arguments
     ///
-    /// * `prefix` - The prefix text to complete
-    /// * `context_ratio` - The ratio of the original message to use as context (0.0 to 1.0)
+    /// * `request` - The message so far and the cursor position to complete at
+    /// * `context_ratio` - Share of the available history to include, clamped to 0.0..=1.0
     ///
     /// # Returns
     ///
     /// A Result containing the generated completion or an error
     pub async fn complete_message(
         &self,
-        prefix: &str,
+        request: &CompletionRequest,
         context_ratio: f32,
     ) -> anyhow::Result<GeneratedMessage> {
+        self.complete_messages(request, context_ratio, 1)
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("No completion was generated"))
+    }
+
+    /// Generate several distinct commit message completions using AI
+    ///
+    /// The prompt is built once and `n` requests are sent to the provider concurrently.
+    /// Only the region holding the cursor is completed: a title completion continues the
+    /// title, a body completion keeps the title and continues the body. Failed requests are
+    /// skipped and duplicate suggestions for the region are dropped, so fewer than `n`
+    /// completions may be returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The message so far and the cursor position to complete at
+    /// * `context_ratio` - Share of the available history to include, clamped to 0.0..=1.0.
+    ///   `0.0` sends no recent commits or author history; `1.0` sends all of it
+    /// * `n` - The number of completions to request
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the generated completions, or the first error if every request failed
+    pub async fn complete_messages(
+        &self,
+        request: &CompletionRequest,
+        context_ratio: f32,
+        n: usize,
+    ) -> anyhow::Result<Vec<GeneratedMessage>> {
+        let context_ratio = clamp_context_ratio(context_ratio);
         let mut config_clone = self.config.clone();
+        let prefix = &request.prefix();
 
         // Set instructions to include completion context
+        let target = match request.region() {
+            CompletionRegion::Title => {
+                format!("Complete the commit message starting with the prefix: '{prefix}'.")
+            }
+            CompletionRegion::Body => format!(
+                "Keep the commit title exactly '{}' and complete the body starting with: '{prefix}'.",
+                request.title_line()
+            ),
+        };
         let completion_instructions = format!(
-            "Complete the commit message starting with the prefix: '{}'. Use {}% of the original message as context.",
-            prefix,
+            "{target} Use {}% of the available commit history as context.",
             (context_ratio * 100.0) as i32
         );
         config_clone.instructions = completion_instructions;
 
         let mut context = self.get_git_info().await?;
 
-        // Enhance context with semantically similar history
-        context.author_history = context.get_enhanced_history(10);
+        // Enhance context with semantically similar history; the user prompt keeps
+        // `context_ratio` of it
+        context.author_history =
+            context.prompt_history(self.config.enhanced_history_count, self.config.no_cache);
 
         // Create system prompt for completion
         let system_prompt = create_completion_system_prompt(&config_clone)?;
@@ -122,15 +289,41 @@ impl CompletionService {
             })
             .await;
 
-        let generated_message = llm::get_message::<GeneratedMessage>(
-            &config_clone,
-            &self.provider_name,
-            &system_prompt,
-            &final_user_prompt,
-        )
-        .await?;
+        let requests = (0..n.max(1)).map(|_| {
+            llm::get_message::<GeneratedMessage>(
+                &config_clone,
+                &self.provider_name,
+                &system_prompt,
+                &final_user_prompt,
+            )
+        });
+
+        let mut completions: Vec<GeneratedMessage> = Vec::new();
+        let mut first_error = None;
+        for result in join_all(requests).await {
+            match result {
+                Ok(mut message) => {
+                    apply_gitmoji(&mut message, &self.config);
+                    enforce_title_length(&mut message, &self.config);
+                    let suggestion = request.suggestion(&message);
+                    if !completions
+                        .iter()
+                        .any(|c| request.suggestion(c) == suggestion)
+                    {
+                        completions.push(message);
+                    }
+                }
+                Err(e) => {
+                    debug!("Completion request failed: {}", e);
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
 
-        Ok(generated_message)
+        match first_error {
+            Some(e) if completions.is_empty() => Err(e),
+            _ => Ok(completions),
+        }
     }
 
     /// Private helper method to handle common token optimization logic
@@ -166,6 +359,7 @@ impl CompletionService {
                     "anthropic" => 100_000,
                     "groq" | "openrouter" => 32_000,
                     "google" => 1_000_000,
+                    "ollama" => llm::get_default_token_limit_for_provider("ollama"),
                     _ => 8_000,
                 }
             });
@@ -241,6 +435,8 @@ impl CompletionService {
             return Err(anyhow::anyhow!("Cannot commit to a remote repository"));
         }
 
+        let message = &append_co_author_trailers(message, &self.config.co_authors);
+
         debug!(
             "Performing commit with message: {}, amend: {}, commit_ref: {:?}",
             message, amend, commit_ref
@@ -249,11 +445,13 @@ impl CompletionService {
         if !self.verify {
             debug!("Skipping pre-commit hook (verify=false)");
             if amend {
-                return self
-                    .repo
-                    .amend_commit(message, commit_ref.unwrap_or("HEAD"));
+                return self.repo.amend_commit(
+                    message,
+                    commit_ref.unwrap_or("HEAD"),
+                    self.config.sign_commits,
+                );
             }
-            return self.repo.commit(message);
+            return self.repo.commit(message, self.config.sign_commits);
         }
 
         // Execute pre-commit hook
@@ -266,10 +464,13 @@ impl CompletionService {
 
         // Perform the commit
         let commit_result = if amend {
-            self.repo
-                .amend_commit(message, commit_ref.unwrap_or("HEAD"))
+            self.repo.amend_commit(
+                message,
+                commit_ref.unwrap_or("HEAD"),
+                self.config.sign_commits,
+            )
         } else {
-            self.repo.commit(message)
+            self.repo.commit(message, self.config.sign_commits)
         };
 
         match commit_result {
@@ -0,0 +1,115 @@
+use super::comments::strip_comment_lines;
+
+use anyhow::{Context, Result};
+use log::debug;
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// Editor used when neither git nor the environment names one, as in git
+const FALLBACK_EDITOR: &str = "vi";
+
+/// Editor to compose messages in, chosen as git chooses it: `$GIT_EDITOR`, then
+/// `core.editor`, then `$VISUAL`, then `$EDITOR`
+pub fn editor_command(core_editor: Option<&str>) -> String {
+    let from_env = |name: &str| env::var(name).ok();
+    [
+        from_env("GIT_EDITOR"),
+        core_editor.map(str::to_string),
+        from_env("VISUAL"),
+        from_env("EDITOR"),
+    ]
+    .into_iter()
+    .flatten()
+    .find(|editor| !editor.trim().is_empty())
+    .unwrap_or_else(|| FALLBACK_EDITOR.to_string())
+}
+
+/// Let the user edit `message` in `editor`, returning what they saved with the comment
+/// lines stripped.
+///
+/// Returns `None` when the editor exits nonzero or the message is left empty, which
+/// cancels the edit as it cancels `git commit`.
+pub fn edit_message(editor: &str, message: &str, comment_prefix: &str) -> Result<Option<String>> {
+    let file = tempfile::Builder::new()
+        .prefix("COMMIT_EDITMSG-")
+        .tempfile()
+        .context("Failed to create a file for the editor")?;
+    fs::write(
+        file.path(),
+        format!(
+            "{}\n\n{comment_prefix} Edit the commit message above. Lines starting with '{comment_prefix}' are\n{comment_prefix} ignored, and an empty message cancels.\n",
+            message.trim_end()
+        ),
+    )?;
+
+    let status = editor_process(editor, file.path())
+        .status()
+        .with_context(|| format!("Failed to start the editor '{editor}'"))?;
+    if !status.success() {
+        debug!("Editor '{editor}' exited with {status}, discarding the edit");
+        return Ok(None);
+    }
+
+    let edited = fs::read_to_string(file.path()).context("Failed to read the edited message")?;
+    let edited = strip_comment_lines(&edited, comment_prefix);
+    let edited = edited.trim();
+    Ok((!edited.is_empty()).then(|| edited.to_string()))
+}
+
+/// Command running `editor` on `path` through the shell, as git does, so editors set up
+/// with arguments (`code --wait`) work
+fn editor_process(editor: &str, path: &Path) -> Command {
+    let mut command = Command::new("sh");
+    command
+        .arg("-c")
+        .arg(format!("{editor} \"$@\""))
+        .arg(editor)
+        .arg(path);
+    command
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(unix)]
+    fn test_edited_message_is_reloaded_without_comments() {
+        let dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+
+        // Checks it was handed the message, then rewrites it with a comment line
+        let editor = dir.path().join("editor.sh");
+        fs::write(
+            &editor,
+            "grep -q '^fix: original title$' \"$1\" || exit 1\nprintf 'fix: edited title\\n# a note to self\\n\\nEdited body.\\n' > \"$1\"\n",
+        )
+        .expect("Failed to write fake editor");
+        let edited = edit_message(
+            &format!("sh {}", editor.display()),
+            "fix: original title\n\nOriginal body.",
+            "#",
+        )
+        .expect("Failed to run fake editor");
+        assert_eq!(edited.as_deref(), Some("fix: edited title\n\nEdited body."));
+
+        // A failing editor, or one that leaves only comments, cancels
+        assert_eq!(
+            edit_message("false", "fix: original title", "#").expect("Editor ran"),
+            None
+        );
+        let emptying = dir.path().join("empty.sh");
+        fs::write(&emptying, "printf '; only a comment\\n' > \"$1\"\n")
+            .expect("Failed to write fake editor");
+        assert_eq!(
+            edit_message(
+                &format!("sh {}", emptying.display()),
+                "fix: original title",
+                ";"
+            )
+            .expect("Editor ran"),
+            None
+        );
+    }
+}
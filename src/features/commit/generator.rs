@@ -0,0 +1,284 @@
+use super::candidates::{candidate_instructions, distinct_candidates};
+use super::gitmoji::apply_gitmoji;
+use super::prompt::{create_system_prompt, create_user_prompt};
+use super::title::enforce_title_length;
+use super::types::GeneratedMessage;
+use crate::config::Config;
+use crate::core::azure;
+use crate::core::context::CommitContext;
+use crate::core::llm::{self, StreamEvent};
+use crate::core::token_optimizer::TokenOptimizer;
+
+use anyhow::Result;
+use futures::future::join_all;
+use log::debug;
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use tokio::sync::mpsc;
+
+/// Turns a prepared context into LLM requests, without reading a repository
+///
+/// [`CommitService`](super::CommitService) generates through one of these for its
+/// repository's changes; a diff piped in on stdin is described with one directly.
+#[derive(Debug, Clone, Copy)]
+pub struct MessageGenerator<'a> {
+    config: &'a Config,
+    provider_name: &'a str,
+}
+
+impl<'a> MessageGenerator<'a> {
+    pub const fn new(config: &'a Config, provider_name: &'a str) -> Self {
+        Self {
+            config,
+            provider_name,
+        }
+    }
+
+    /// Check the environment for necessary prerequisites
+    pub fn check_environment(&self) -> Result<()> {
+        self.config.check_environment()?;
+        azure::check_provider_settings(self.config, self.provider_name)
+    }
+
+    /// Token budget for a prompt to the provider, from config or a per-provider default
+    pub fn token_limit(&self, config: &Config) -> usize {
+        config
+            .providers
+            .get(self.provider_name)
+            .and_then(|p| p.token_limit)
+            .unwrap_or({
+                match self.provider_name.as_str() {
+                    "openai" => 16_000,
+                    azure::AZURE_PROVIDER => {
+                        llm::get_default_token_limit_for_provider(azure::AZURE_PROVIDER)
+                    }
+                    "anthropic" => 100_000,
+                    "groq" | "openrouter" => 32_000,
+                    "google" => 1_000_000,
+                    "ollama" => llm::get_default_token_limit_for_provider("ollama"),
+                    _ => 8_000,
+                }
+            })
+    }
+
+    /// Optimize the context for the provider's token budget and request a response.
+    ///
+    /// If the provider still rejects the prompt as too long, the context is optimized
+    /// again for a smaller budget and the request is retried once.
+    pub async fn request_within_budget<T, F>(
+        &self,
+        config: &Config,
+        system_prompt: &str,
+        context: CommitContext,
+        create_user_prompt_fn: F,
+    ) -> Result<T>
+    where
+        T: DeserializeOwned + Serialize + JsonSchema,
+        F: Fn(&CommitContext) -> String,
+    {
+        self.request_within_budget_streaming(
+            config,
+            system_prompt,
+            context,
+            create_user_prompt_fn,
+            None,
+        )
+        .await
+    }
+
+    /// Like [`Self::request_within_budget`], sending the response text to `events` as it
+    /// arrives
+    pub async fn request_within_budget_streaming<T, F>(
+        &self,
+        config: &Config,
+        system_prompt: &str,
+        context: CommitContext,
+        create_user_prompt_fn: F,
+        events: Option<&mpsc::Sender<StreamEvent>>,
+    ) -> Result<T>
+    where
+        T: DeserializeOwned + Serialize + JsonSchema,
+        F: Fn(&CommitContext) -> String,
+    {
+        let create_user_prompt_fn = &create_user_prompt_fn;
+        llm::with_context_shrink(self.token_limit(config), |token_limit| {
+            let context = context.clone();
+            async move {
+                let (_, final_user_prompt) = self
+                    .optimize_prompt(
+                        config,
+                        system_prompt,
+                        context,
+                        token_limit,
+                        create_user_prompt_fn,
+                    )
+                    .await;
+                llm::get_message_streaming::<T>(
+                    config,
+                    self.provider_name,
+                    system_prompt,
+                    &final_user_prompt,
+                    events,
+                )
+                .await
+            }
+        })
+        .await
+    }
+
+    /// Private helper method to handle common token optimization logic
+    ///
+    /// # Arguments
+    ///
+    /// * `config_clone` - Configuration with preset and instructions
+    /// * `system_prompt` - The system prompt to use
+    /// * `context` - The commit context
+    /// * `token_limit` - Token budget for the system and user prompts together
+    /// * `create_user_prompt_fn` - A function that creates a user prompt from a context
+    ///
+    /// # Returns
+    ///
+    /// A tuple containing the optimized context and final user prompt
+    pub async fn optimize_prompt<F>(
+        &self,
+        config_clone: &Config,
+        system_prompt: &str,
+        mut context: CommitContext,
+        token_limit: usize,
+        create_user_prompt_fn: F,
+    ) -> (CommitContext, String)
+    where
+        F: Fn(&CommitContext) -> String,
+    {
+        // Create a token optimizer to count tokens
+        let optimizer = TokenOptimizer::for_counting().expect("Failed to create TokenOptimizer");
+        let system_tokens = optimizer.count_tokens(system_prompt);
+
+        debug!("Token limit: {}", token_limit);
+        debug!("System prompt tokens: {}", system_tokens);
+
+        // Reserve tokens for system prompt and some buffer for formatting
+        // 1000 token buffer provides headroom for model responses and formatting
+        let context_token_limit = token_limit.saturating_sub(system_tokens + 1000);
+        debug!("Available tokens for context: {}", context_token_limit);
+
+        // Count tokens before optimization
+        let user_prompt_before = create_user_prompt_fn(&context);
+        let total_tokens_before = system_tokens + optimizer.count_tokens(&user_prompt_before);
+        debug!("Total tokens before optimization: {}", total_tokens_before);
+
+        // Optimize the context with remaining token budget
+        context.optimize(context_token_limit, config_clone).await;
+
+        let user_prompt = create_user_prompt_fn(&context);
+        let user_tokens = optimizer.count_tokens(&user_prompt);
+        let total_tokens = system_tokens + user_tokens;
+
+        debug!("User prompt tokens after optimization: {}", user_tokens);
+        debug!("Total tokens after optimization: {}", total_tokens);
+
+        // If we're still over the limit, truncate the user prompt directly
+        // 100 token safety buffer ensures we stay under the limit
+        let final_user_prompt = if total_tokens > token_limit {
+            debug!(
+                "Total tokens {} still exceeds limit {}, truncating user prompt",
+                total_tokens, token_limit
+            );
+            let max_user_tokens = token_limit.saturating_sub(system_tokens + 100);
+            optimizer
+                .truncate_string(&user_prompt, max_user_tokens)
+                .expect("Failed to truncate user prompt")
+        } else {
+            user_prompt
+        };
+
+        let final_tokens = system_tokens + optimizer.count_tokens(&final_user_prompt);
+        debug!(
+            "Final total tokens after potential truncation: {}",
+            final_tokens
+        );
+
+        (context, final_user_prompt)
+    }
+
+    /// `context` as it goes into a generation prompt: the author history is cut down to
+    /// the `enhanced_history_count` entries most relevant to the changes
+    pub fn prompt_context(&self, mut context: CommitContext) -> CommitContext {
+        context.author_history = context.get_enhanced_history(self.config.enhanced_history_count);
+        context
+    }
+
+    /// Generate a commit message for `context`
+    ///
+    /// # Arguments
+    ///
+    /// * `instructions` - Custom instructions for the AI
+    /// * `context` - The context to use for generation
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the generated message or an error
+    pub async fn generate_message(
+        &self,
+        instructions: &str,
+        context: CommitContext,
+    ) -> Result<GeneratedMessage> {
+        self.generate_message_streaming(instructions, context, None)
+            .await
+    }
+
+    /// Like [`Self::generate_message`], sending the response text to `events` as it
+    /// arrives
+    pub async fn generate_message_streaming(
+        &self,
+        instructions: &str,
+        context: CommitContext,
+        events: Option<&mpsc::Sender<StreamEvent>>,
+    ) -> Result<GeneratedMessage> {
+        let mut config_clone = self.config.clone();
+
+        config_clone.instructions = instructions.to_string();
+        let context = self.prompt_context(context);
+
+        // Create system prompt
+        let system_prompt = create_system_prompt(&config_clone)?;
+
+        // Use the shared optimization logic with provided context
+        let mut generated_message: GeneratedMessage = self
+            .request_within_budget_streaming(
+                &config_clone,
+                &system_prompt,
+                context,
+                create_user_prompt,
+                events,
+            )
+            .await?;
+        apply_gitmoji(&mut generated_message, self.config);
+        enforce_title_length(&mut generated_message, self.config);
+
+        Ok(generated_message)
+    }
+
+    /// Generate up to `count` distinct commit messages for `context`, concurrently.
+    ///
+    /// Every candidate after the first is asked to take a different angle; identical
+    /// answers are dropped, so fewer than `count` may come back.
+    pub async fn generate_candidates(
+        &self,
+        instructions: &str,
+        context: &CommitContext,
+        count: usize,
+    ) -> Result<Vec<GeneratedMessage>> {
+        let count = count.max(1);
+
+        let requests = (0..count).map(|index| {
+            let context = context.clone();
+            async move {
+                let instructions = candidate_instructions(instructions, index, count);
+                self.generate_message(&instructions, context).await
+            }
+        });
+        distinct_candidates(join_all(requests).await)
+    }
+}
@@ -0,0 +1,76 @@
+use super::types::GeneratedMessage;
+use crate::config::Config;
+
+use regex::Regex;
+use std::sync::LazyLock;
+
+// Regex for a conventional commit title prefix such as `fix:`, `feat(tui):` or `refactor!:`
+static COMMIT_TYPE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^([A-Za-z]+)(?:\([^)]*\))?!?:")
+        .expect("Failed to compile commit type regex pattern - this is a bug")
+});
+
+/// Conventional commit type of a title, lowercased (`Fix(ui): ...` gives `fix`)
+pub fn commit_type(title: &str) -> Option<String> {
+    COMMIT_TYPE_RE
+        .captures(title.trim_start())
+        .map(|captures| captures[1].to_lowercase())
+}
+
+/// Replace the message's emoji with the one `config.gitmoji` maps its commit type to.
+///
+/// The model's own choice is never kept: without a recognised type, a mapping, or with
+/// emoji turned off (`--no-emoji`, `gait.emoji = false`), the emoji is removed.
+pub fn apply_gitmoji(message: &mut GeneratedMessage, config: &Config) {
+    message.emoji = if config.emoji {
+        commit_type(&message.title)
+            .and_then(|commit_type| config.gitmoji.get(&commit_type))
+            .filter(|emoji| !emoji.trim().is_empty())
+            .cloned()
+    } else {
+        None
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(title: &str) -> GeneratedMessage {
+        GeneratedMessage {
+            emoji: Some("🚀".to_string()),
+            title: title.to_string(),
+            message: "Body".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_commit_type_parses_conventional_prefixes() {
+        assert_eq!(commit_type("fix: crash").as_deref(), Some("fix"));
+        assert_eq!(commit_type("Feat(tui)!: redo").as_deref(), Some("feat"));
+        assert_eq!(commit_type("Update readme"), None);
+    }
+
+    #[test]
+    fn test_fix_gets_configured_bug_emoji() {
+        let mut config = Config {
+            emoji: true,
+            ..Config::default()
+        };
+        let mut fix = message("fix(cli): handle empty diff");
+        apply_gitmoji(&mut fix, &config);
+        assert_eq!(fix.emoji.as_deref(), Some("🐛"));
+
+        config.gitmoji.insert("fix".to_string(), "🚑️".to_string());
+        apply_gitmoji(&mut fix, &config);
+        assert_eq!(fix.emoji.as_deref(), Some("🚑️"));
+
+        let mut untyped = message("Handle empty diff");
+        apply_gitmoji(&mut untyped, &config);
+        assert_eq!(untyped.emoji, None);
+
+        config.emoji = false;
+        apply_gitmoji(&mut fix, &config);
+        assert_eq!(fix.emoji, None);
+    }
+}
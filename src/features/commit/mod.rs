@@ -1,7 +1,13 @@
+pub mod candidates;
 #[allow(clippy::uninlined_format_args)]
 mod cli;
+pub mod comments;
 #[allow(clippy::uninlined_format_args)]
 pub mod completion;
+pub mod editor;
+#[allow(clippy::uninlined_format_args)]
+pub mod generator;
+pub mod gitmoji;
 mod relevance;
 pub mod types;
 
@@ -9,17 +15,81 @@ pub mod types;
 pub mod prompt;
 #[allow(clippy::uninlined_format_args)]
 pub mod service;
+pub mod title;
+pub mod trailers;
 
-pub use cli::{handle_completion_command, handle_message_command, handle_pr_command};
+pub use cli::{
+    handle_completion_command, handle_explain_command, handle_message_command, handle_pr_command,
+};
+pub use generator::MessageGenerator;
 use git2::FileMode;
 pub use service::CommitService;
 pub use types::{
-    GeneratedMessage, GeneratedPullRequest, format_commit_message, format_pull_request,
+    CommitExplanation, CommitPullRequest, GeneratedMessage, GeneratedPullRequest,
+    PerCommitPullRequest, format_commit_explanation, format_commit_message,
+    format_per_commit_pull_request, format_pull_request,
 };
 
-use crate::git::CommitResult;
+use crate::config::Config;
+use crate::core::context::CommitContext;
+use crate::error::{Categorize, ErrorCategory};
+use crate::git::{CommitResult, parse_unified_diff};
+use anyhow::{Result, anyhow};
 use std::fmt::Write;
 
+/// Error shown when a message is requested with nothing staged
+pub const NOTHING_STAGED_MESSAGE: &str = "Nothing staged — stage changes or use --all";
+
+/// Fail before any LLM call when there are no staged changes to describe
+pub fn ensure_staged_changes(context: &CommitContext) -> Result<()> {
+    if context.staged_files.is_empty() {
+        Err(anyhow!(NOTHING_STAGED_MESSAGE)).categorize(ErrorCategory::User)
+    } else {
+        Ok(())
+    }
+}
+
+/// Build a commit context from a unified diff, applying the configured path filters.
+///
+/// Used by `--stdin-diff`; the branch, recent commits and author history are left empty.
+pub fn context_from_diff(diff: &str, config: &Config) -> Result<CommitContext> {
+    let staged_files = parse_unified_diff(diff);
+    if staged_files.is_empty() {
+        return Err(anyhow!("No file changes found in the diff"));
+    }
+
+    let mut context = CommitContext::new(
+        "(stdin diff)".to_string(),
+        Vec::new(),
+        staged_files,
+        String::new(),
+        String::new(),
+        Vec::new(),
+    );
+    context.exclude_paths(&config.exclude_paths)?;
+    context.omit_oversized_diffs(config.max_file_diff_bytes);
+    Ok(context)
+}
+
+/// Commit `message` without any prompt, as `--auto-commit`/`--yes` do, running the hooks
+/// unless the service skips verification.
+///
+/// Returns what to print: the `[branch hash] title` summary, then the full message.
+pub fn commit_generated_message(
+    service: &CommitService,
+    message: &GeneratedMessage,
+    amend: bool,
+    commit_ref: Option<&str>,
+) -> Result<String> {
+    let formatted = format_commit_message(message);
+    let result = service.perform_commit(&formatted, amend, commit_ref)?;
+    Ok(format!(
+        "{}\n{}",
+        format_commit_result(&result, &formatted),
+        formatted.trim_end()
+    ))
+}
+
 pub fn format_commit_result(result: &CommitResult, message: &str) -> String {
     let mut output = format!(
         "[{} {}] {}\n",
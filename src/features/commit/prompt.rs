@@ -1,18 +1,19 @@
 use super::relevance::RelevanceScorer;
 use super::types::GeneratedMessage;
-use crate::common::get_combined_instructions;
+use crate::common::{append_system_suffix, get_combined_instructions};
 use crate::config::Config;
 use crate::core::context::{ChangeType, CommitContext, RecentCommit, StagedFile};
+use crate::error::{Categorize, ErrorCategory};
 
 use log::debug;
 use std::collections::HashMap;
 
 pub fn create_system_prompt(config: &Config) -> anyhow::Result<String> {
-    let commit_schema = schemars::schema_for!(GeneratedMessage);
-    let commit_schema_str = serde_json::to_string_pretty(&commit_schema)?;
+    let commit_schema_str = serde_json::to_string_pretty(&GeneratedMessage::json_schema())
+        .categorize(ErrorCategory::Internal)?;
 
     let combined_instructions = get_combined_instructions(config);
-    Ok(format!(
+    let prompt = format!(
         "# ROLE: Git Commit Message Generator\n\
          \n\
          You are an expert Git Commit Message Generator specializing in creating high-quality, \
@@ -31,6 +32,7 @@ pub fn create_system_prompt(config: &Config) -> anyhow::Result<String> {
          \n\
          ## Output Requirements\n\
          \n\
+         {}\
          **Format:** Your final output MUST STRICTLY conform to the following JSON schema:\n\
          \n\
          ```json\n\
@@ -39,8 +41,23 @@ pub fn create_system_prompt(config: &Config) -> anyhow::Result<String> {
          \n\
          **Important:** Output ONLY the JSON object. No explanatory text, preambles, \
          or additional content.\n",
-        combined_instructions, commit_schema_str
-    ))
+        combined_instructions,
+        title_length_rule(config),
+        commit_schema_str
+    );
+
+    Ok(append_system_suffix(prompt, &config.commit_system_suffix))
+}
+
+/// Output requirement keeping the title within `max_title_length`, empty when unlimited
+fn title_length_rule(config: &Config) -> String {
+    if config.max_title_length == 0 {
+        return String::new();
+    }
+    format!(
+        "**Title Length:** The title MUST be at most {} characters; longer titles are cut.\n\n",
+        config.max_title_length
+    )
 }
 
 pub fn create_user_prompt(context: &CommitContext) -> String {
@@ -51,6 +68,7 @@ pub fn create_user_prompt(context: &CommitContext) -> String {
     let recent_commits = format_recent_commits(&context.recent_commits);
     let staged_changes = format_staged_files(&context.staged_files, &relevance_scores);
     let author_history = format_enhanced_author_history(&context.author_history, context);
+    let ticket = format_ticket(context);
 
     debug!(
         "Generated commit prompt for {} files ({} added, {} modified, {} deleted)",
@@ -81,6 +99,7 @@ pub fn create_user_prompt(context: &CommitContext) -> String {
          \n\
          **Branch:** {}\n\
          \n\
+         {}\
          **Recent Commits:**\n\
          {}\n\
          \n\
@@ -100,10 +119,19 @@ pub fn create_user_prompt(context: &CommitContext) -> String {
          3. Follow conventional commit standards when appropriate\n\
          4. Make the message concise yet descriptive\n\
          5. Focus on the intent and impact of the changes\n",
-        context.branch, recent_commits, staged_changes, detailed_changes, author_history
+        context.branch, ticket, recent_commits, staged_changes, detailed_changes, author_history
     )
 }
 
+/// Ticket line asking for a `Refs:` trailer, or nothing when no ticket was inferred
+fn format_ticket(context: &CommitContext) -> String {
+    context
+        .ticket_id
+        .as_ref()
+        .map(|id| format!("**Ticket:** {id} (end the message with a `Refs: {id}` trailer)\n\n"))
+        .unwrap_or_default()
+}
+
 fn format_recent_commits(commits: &[RecentCommit]) -> String {
     commits
         .iter()
@@ -290,7 +318,8 @@ fn _format_conventions(conventions: &std::collections::HashMap<String, usize>) -
 /// Creates a system prompt for PR description generation
 pub fn create_pr_system_prompt(config: &Config) -> anyhow::Result<String> {
     let pr_schema = schemars::schema_for!(super::types::GeneratedPullRequest);
-    let pr_schema_str = serde_json::to_string_pretty(&pr_schema)?;
+    let pr_schema_str =
+        serde_json::to_string_pretty(&pr_schema).categorize(ErrorCategory::Internal)?;
 
     let mut prompt = String::from(
         "# ROLE: Pull Request Description Specialist\n\
@@ -377,16 +406,74 @@ pub fn create_pr_system_prompt(config: &Config) -> anyhow::Result<String> {
 
     prompt.push_str(&pr_schema_str);
 
+    Ok(append_system_suffix(prompt, &config.pr_system_suffix))
+}
+
+/// Creates a system prompt for explaining an existing commit
+pub fn create_explain_system_prompt(config: &Config) -> anyhow::Result<String> {
+    let explanation_schema = schemars::schema_for!(super::types::CommitExplanation);
+    let explanation_schema_str =
+        serde_json::to_string_pretty(&explanation_schema).categorize(ErrorCategory::Internal)?;
+
+    let prompt = format!(
+        "# ROLE: Commit Explainer\n\
+         \n\
+         You explain existing commits to developers who are new to the codebase. \
+         ANALYZE the commit message and diff, then describe the commit in plain English.\n\
+         \n\
+         ## Sections\n\
+         \n\
+         1. **What changed:** The behavior and structure the commit changes, not a file-by-file \
+         list of edits\n\
+         2. **Likely intent:** Why the change was probably made; say so when the intent is a guess\n\
+         3. **Risk:** What could break, what deserves a closer look, or that the change is low risk\n\
+         \n\
+         ## Guidelines\n\
+         \n\
+         - Explain domain terms and abbreviations the first time they appear\n\
+         - Stay with what the diff shows; do not invent tickets, tests or motivations\n\
+         - Keep each section to a short paragraph or a few bullet points\n\
+         {}\n\
+         ## Output Requirements\n\
+         \n\
+         **Format:** Your final output MUST STRICTLY conform to the following JSON schema:\n\
+         \n\
+         ```json\n\
+         {}\n\
+         ```\n\
+         \n\
+         **Important:** Output ONLY the JSON object. No explanatory text, preambles, \
+         or additional content.\n",
+        get_combined_instructions(config),
+        explanation_schema_str
+    );
+
     Ok(prompt)
 }
 
+/// Creates a user prompt for explaining the commit in `context`
+pub fn create_explain_user_prompt(context: &CommitContext) -> String {
+    let scorer = RelevanceScorer::new();
+    let relevance_scores = scorer.score(context);
+
+    format!(
+        "Explain the following commit:\n\n\
+         Commit:\n{}\n\n\
+         File changes summary:\n{}\n\n\
+         Detailed changes:\n{}",
+        format_recent_commits(&context.recent_commits),
+        format_staged_files(&context.staged_files, &relevance_scores),
+        format_detailed_changes(&context.staged_files, &relevance_scores)
+    )
+}
+
 /// Creates a system prompt for commit message completion
 pub fn create_completion_system_prompt(config: &Config) -> anyhow::Result<String> {
-    let completion_schema = schemars::schema_for!(super::types::GeneratedMessage);
-    let completion_schema_str = serde_json::to_string_pretty(&completion_schema)?;
+    let completion_schema_str = serde_json::to_string_pretty(&GeneratedMessage::json_schema())
+        .categorize(ErrorCategory::Internal)?;
 
     let combined_instructions = get_combined_instructions(config);
-    Ok(format!(
+    let prompt = format!(
         "# ROLE: Git Commit Message Completion Specialist\n\
          \n\
          You are an expert Git Commit Message Completion Specialist specializing in completing \
@@ -413,6 +500,7 @@ pub fn create_completion_system_prompt(config: &Config) -> anyhow::Result<String
          \n\
          ## Output Requirements\n\
          \n\
+         {}\
          **Format:** Your final output MUST STRICTLY conform to the following JSON schema:\n\
          \n\
          ```json\n\
@@ -421,23 +509,74 @@ pub fn create_completion_system_prompt(config: &Config) -> anyhow::Result<String
          \n\
          **Important:** Output ONLY the JSON object. No explanatory text, preambles, \
          or additional content.\n",
-        combined_instructions, completion_schema_str
+        combined_instructions,
+        title_length_rule(config),
+        completion_schema_str
+    );
+
+    Ok(append_system_suffix(
+        prompt,
+        &config.completion_system_suffix,
     ))
 }
 
+/// Clamp a completion context ratio to `[0.0, 1.0]`, treating NaN as `0.0`
+pub fn clamp_context_ratio(context_ratio: f32) -> f32 {
+    if context_ratio.is_nan() {
+        0.0
+    } else {
+        context_ratio.clamp(0.0, 1.0)
+    }
+}
+
+/// How many of `total` history items a context ratio keeps.
+///
+/// Rounds up, so any ratio above zero keeps at least one item when there are any.
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::as_conversions
+)]
+pub fn context_item_count(total: usize, context_ratio: f32) -> usize {
+    let kept = (total as f32 * clamp_context_ratio(context_ratio)).ceil() as usize;
+    kept.min(total)
+}
+
 /// Creates a user prompt for commit message completion
+///
+/// `context_ratio` (clamped to `[0.0, 1.0]`) sets how much history backs the prefix:
+/// `0.0` sends only the prefix and the staged changes, `1.0` includes every recent
+/// commit and author history entry in `context`, and values in between keep that
+/// fraction of each, most relevant first.
 pub fn create_completion_user_prompt(
     context: &CommitContext,
     prefix: &str,
     context_ratio: f32,
 ) -> String {
+    let context_ratio = clamp_context_ratio(context_ratio);
     let scorer = RelevanceScorer::new();
     let relevance_scores = scorer.score(context);
     let detailed_changes = format_detailed_changes(&context.staged_files, &relevance_scores);
 
-    let recent_commits = format_recent_commits(&context.recent_commits);
+    let recent =
+        &context.recent_commits[..context_item_count(context.recent_commits.len(), context_ratio)];
+    let history =
+        &context.author_history[..context_item_count(context.author_history.len(), context_ratio)];
+    let omitted = || "Omitted at a 0% context ratio.".to_string();
+
+    let recent_commits = if recent.is_empty() && !context.recent_commits.is_empty() {
+        omitted()
+    } else {
+        format_recent_commits(recent)
+    };
     let staged_changes = format_staged_files(&context.staged_files, &relevance_scores);
-    let author_history = format_enhanced_author_history(&context.author_history, context);
+    let author_history = if history.is_empty() && !context.author_history.is_empty() {
+        omitted()
+    } else {
+        format_enhanced_author_history(history, context)
+    };
+    let ticket = format_ticket(context);
 
     // Detect conventions from history (already included in enhanced author history)
 
@@ -477,6 +616,7 @@ pub fn create_completion_user_prompt(
          \n\
          **Branch:** {}\n\
          \n\
+         {}\
          **Recent Commits:**\n\
          {}\n\
          \n\
@@ -499,6 +639,7 @@ pub fn create_completion_user_prompt(
         prefix,
         context_ratio * 100.0,
         context.branch,
+        ticket,
         recent_commits,
         staged_changes,
         detailed_changes,
@@ -1,9 +1,11 @@
-use super::prompt::{create_system_prompt, create_user_prompt};
-use super::types::GeneratedMessage;
+use super::comments::resolve_comment_prefix;
+use super::editor::editor_command;
+use super::trailers::append_co_author_trailers;
+use super::types::{CommitExplanation, GeneratedMessage, parse_commit_message};
 use crate::config::Config;
 use crate::core::context::CommitContext;
+use crate::core::context_cache::ContextCache;
 use crate::core::llm;
-use crate::core::token_optimizer::TokenOptimizer;
 use crate::git::{CommitResult, GitRepo};
 
 use anyhow::Result;
@@ -19,6 +21,7 @@ pub struct CommitService {
     provider_name: String,
     verify: bool,
     cached_context: Arc<RwLock<Option<CommitContext>>>,
+    context_cache: Option<ContextCache>,
 }
 
 impl CommitService {
@@ -42,23 +45,78 @@ impl CommitService {
         verify: bool,
         git_repo: GitRepo,
     ) -> Result<Self> {
+        // Contexts of throwaway clones are never built twice
+        let context_cache = if git_repo.is_remote() {
+            None
+        } else {
+            ContextCache::new()
+                .inspect_err(|e| debug!("Context cache unavailable: {e}"))
+                .ok()
+        };
+
         Ok(Self {
             config,
             repo: Arc::new(git_repo),
             provider_name: provider_name.to_string(),
             verify,
             cached_context: Arc::new(RwLock::new(None)),
+            context_cache,
         })
     }
 
+    /// Keep built contexts in `cache` instead of the one under the cache root
+    #[must_use]
+    pub fn with_context_cache(mut self, cache: ContextCache) -> Self {
+        self.context_cache = Some(cache);
+        self
+    }
+
+    /// Get the configuration used by this service
+    pub const fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Path of the repository the service works on
+    pub fn repo_path(&self) -> &Path {
+        self.repo.repo_path()
+    }
+
+    /// Prefix of the comment lines stripped from `message` once edited (`core.commentChar`)
+    pub fn comment_prefix(&self, message: &str) -> String {
+        resolve_comment_prefix(self.repo.comment_char_setting().as_deref(), message)
+    }
+
+    /// Editor to compose messages in, honoring `core.editor` as git does
+    pub fn editor(&self) -> String {
+        editor_command(self.repo.editor_setting().as_deref())
+    }
+
     /// Check if the repository is remote
     pub fn is_remote_repository(&self) -> bool {
         self.repo.is_remote()
     }
 
+    /// The active provider and model, e.g. `anthropic/claude-3-5-sonnet`
+    pub fn provider_label(&self) -> String {
+        match self.config.get_provider_config(&self.provider_name) {
+            Some(provider) if !provider.model_name.is_empty() => {
+                format!("{}/{}", self.provider_name, provider.model_name)
+            }
+            _ => self.provider_name.clone(),
+        }
+    }
+
     /// Check the environment for necessary prerequisites
     pub fn check_environment(&self) -> Result<()> {
-        self.config.check_environment()
+        self.generator().check_environment()
+    }
+
+    /// Stage modifications and deletions of tracked files (`--all`), like `git commit -a`
+    pub async fn stage_tracked_changes(&self) -> Result<()> {
+        self.repo.stage_tracked_changes()?;
+        // Drop any context read before staging
+        *self.cached_context.write().await = None;
+        Ok(())
     }
 
     /// Get Git information for the current repository
@@ -70,7 +128,7 @@ impl CommitService {
             }
         }
 
-        let context = self.repo.get_git_info(&self.config).await?;
+        let context = self.build_git_info().await?;
 
         {
             let mut cached_context = self.cached_context.write().await;
@@ -79,7 +137,39 @@ impl CommitService {
         Ok(context)
     }
 
+    /// Build the context, reusing the one on disk if the staged tree hasn't changed since
+    async fn build_git_info(&self) -> Result<CommitContext> {
+        let cache_key = self.context_cache.as_ref().and_then(|_| {
+            self.repo
+                .context_cache_key(&self.config)
+                .inspect_err(|e| debug!("Skipping context cache: {e}"))
+                .ok()
+        });
+
+        // --no-cache rebuilds the context but still refreshes the cached one
+        if let (Some(cache), Some(key)) = (&self.context_cache, &cache_key)
+            && !self.config.no_cache
+            && let Some(context) = cache.get(key)
+        {
+            debug!("Using cached context for staged tree: {key}");
+            return Ok(context);
+        }
+
+        let context = self.repo.get_git_info(&self.config).await?;
+
+        if let (Some(cache), Some(key)) = (&self.context_cache, &cache_key)
+            && let Err(e) = cache.put(key, &context)
+        {
+            debug!("Failed to cache context: {e}");
+        }
+
+        Ok(context)
+    }
+
     /// Get Git information including unstaged changes
+    ///
+    /// With `include_untracked` set in the config, new files that haven't been added yet
+    /// come as diffs adding the whole file.
     pub async fn get_git_info_with_unstaged(
         &self,
         include_unstaged: bool,
@@ -113,6 +203,12 @@ impl CommitService {
         Ok(context)
     }
 
+    /// The current message of the commit an amend would rewrite
+    pub fn get_commit_message(&self, commit_ish: &str) -> Result<GeneratedMessage> {
+        let message = self.repo.get_commit_message(commit_ish)?;
+        Ok(parse_commit_message(&message))
+    }
+
     /// Get Git information for a specific commit
     #[allow(clippy::unused_async)]
     pub async fn get_git_info_for_commit(&self, commit_id: &str) -> Result<CommitContext> {
@@ -124,92 +220,15 @@ impl CommitService {
         Ok(context)
     }
 
-    /// Private helper method to handle common token optimization logic
-    ///
-    /// # Arguments
-    ///
-    /// * `config_clone` - Configuration with preset and instructions
-    /// * `system_prompt` - The system prompt to use
-    /// * `context` - The commit context
-    /// * `create_user_prompt_fn` - A function that creates a user prompt from a context
-    ///
-    /// # Returns
-    ///
-    /// A tuple containing the optimized context and final user prompt
-    async fn optimize_prompt<F>(
-        &self,
-        config_clone: &Config,
-        system_prompt: &str,
-        mut context: CommitContext,
-        create_user_prompt_fn: F,
-    ) -> (CommitContext, String)
-    where
-        F: Fn(&CommitContext) -> String,
-    {
-        // Get the token limit for the provider from config or default value
-        let token_limit = config_clone
-            .providers
-            .get(&self.provider_name)
-            .and_then(|p| p.token_limit)
-            .unwrap_or({
-                match self.provider_name.as_str() {
-                    "openai" => 16_000,
-                    "anthropic" => 100_000,
-                    "groq" | "openrouter" => 32_000,
-                    "google" => 1_000_000,
-                    _ => 8_000,
-                }
-            });
-
-        // Create a token optimizer to count tokens
-        let optimizer = TokenOptimizer::for_counting().expect("Failed to create TokenOptimizer");
-        let system_tokens = optimizer.count_tokens(system_prompt);
-
-        debug!("Token limit: {}", token_limit);
-        debug!("System prompt tokens: {}", system_tokens);
-
-        // Reserve tokens for system prompt and some buffer for formatting
-        // 1000 token buffer provides headroom for model responses and formatting
-        let context_token_limit = token_limit.saturating_sub(system_tokens + 1000);
-        debug!("Available tokens for context: {}", context_token_limit);
-
-        // Count tokens before optimization
-        let user_prompt_before = create_user_prompt_fn(&context);
-        let total_tokens_before = system_tokens + optimizer.count_tokens(&user_prompt_before);
-        debug!("Total tokens before optimization: {}", total_tokens_before);
-
-        // Optimize the context with remaining token budget
-        context.optimize(context_token_limit, config_clone).await;
-
-        let user_prompt = create_user_prompt_fn(&context);
-        let user_tokens = optimizer.count_tokens(&user_prompt);
-        let total_tokens = system_tokens + user_tokens;
-
-        debug!("User prompt tokens after optimization: {}", user_tokens);
-        debug!("Total tokens after optimization: {}", total_tokens);
-
-        // If we're still over the limit, truncate the user prompt directly
-        // 100 token safety buffer ensures we stay under the limit
-        let final_user_prompt = if total_tokens > token_limit {
-            debug!(
-                "Total tokens {} still exceeds limit {}, truncating user prompt",
-                total_tokens, token_limit
-            );
-            let max_user_tokens = token_limit.saturating_sub(system_tokens + 100);
-            optimizer
-                .truncate_string(&user_prompt, max_user_tokens)
-                .expect("Failed to truncate user prompt")
-        } else {
-            user_prompt
-        };
-
-        let final_tokens = system_tokens + optimizer.count_tokens(&final_user_prompt);
-        debug!(
-            "Final total tokens after potential truncation: {}",
-            final_tokens
-        );
+    /// Generator for requests to this service's provider
+    pub fn generator(&self) -> MessageGenerator<'_> {
+        MessageGenerator::new(&self.config, &self.provider_name)
+    }
 
-        (context, final_user_prompt)
+    /// `context` as it goes into a generation prompt: the author history is cut down to
+    /// the `enhanced_history_count` entries most relevant to the changes
+    pub fn prompt_context(&self, context: CommitContext) -> CommitContext {
+        self.generator().prompt_context(context)
     }
 
     /// Generate a commit message using AI
@@ -223,29 +242,10 @@ impl CommitService {
     ///
     /// A Result containing the generated commit message or an error
     pub async fn generate_message(&self, instructions: &str) -> anyhow::Result<GeneratedMessage> {
-        let mut config_clone = self.config.clone();
-
-        config_clone.instructions = instructions.to_string();
-
         let context = self.get_git_info().await?;
-
-        // Create system prompt
-        let system_prompt = create_system_prompt(&config_clone)?;
-
-        // Use the shared optimization logic
-        let (_, final_user_prompt) = self
-            .optimize_prompt(&config_clone, &system_prompt, context, create_user_prompt)
-            .await;
-
-        let generated_message = llm::get_message::<GeneratedMessage>(
-            &config_clone,
-            &self.provider_name,
-            &system_prompt,
-            &final_user_prompt,
-        )
-        .await?;
-
-        Ok(generated_message)
+        self.generator()
+            .generate_message(instructions, context)
+            .await
     }
 
     /// Generate a commit message using AI with custom context
@@ -263,27 +263,55 @@ impl CommitService {
         instructions: &str,
         context: CommitContext,
     ) -> anyhow::Result<GeneratedMessage> {
-        let mut config_clone = self.config.clone();
+        self.generator()
+            .generate_message(instructions, context)
+            .await
+    }
 
-        config_clone.instructions = instructions.to_string();
+    /// Generate up to `count` distinct commit messages for the same changes, concurrently.
+    ///
+    /// Every candidate after the first is asked to take a different angle; identical
+    /// answers are dropped, so fewer than `count` may come back. Uses `context` when given,
+    /// the staged changes otherwise.
+    pub async fn generate_candidates(
+        &self,
+        instructions: &str,
+        context: Option<CommitContext>,
+        count: usize,
+    ) -> anyhow::Result<Vec<GeneratedMessage>> {
+        let context = match context {
+            Some(context) => context,
+            None => self.get_git_info().await?,
+        };
+        self.generator()
+            .generate_candidates(instructions, &context, count)
+            .await
+    }
 
-        // Create system prompt
-        let system_prompt = create_system_prompt(&config_clone)?;
-
-        // Use the shared optimization logic with provided context
-        let (_, final_user_prompt) = self
-            .optimize_prompt(&config_clone, &system_prompt, context, create_user_prompt)
-            .await;
-
-        let generated_message = llm::get_message::<GeneratedMessage>(
-            &config_clone,
-            &self.provider_name,
-            &system_prompt,
-            &final_user_prompt,
-        )
-        .await?;
-
-        Ok(generated_message)
+    /// Generate a commit message, sending the response text to `events` as it arrives
+    ///
+    /// # Arguments
+    ///
+    /// * `instructions` - Custom instructions for the AI
+    /// * `context` - Optional context to use instead of the repository's staged changes
+    /// * `events` - Receives the response chunks, and a restart when a request is retried
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the generated message or an error
+    pub async fn generate_message_streaming(
+        &self,
+        instructions: &str,
+        context: Option<CommitContext>,
+        events: &mpsc::Sender<llm::StreamEvent>,
+    ) -> anyhow::Result<GeneratedMessage> {
+        let context = match context {
+            Some(context) => context,
+            None => self.get_git_info().await?,
+        };
+        self.generator()
+            .generate_message_streaming(instructions, context, Some(events))
+            .await
     }
 
     /// Generate a PR description for a commit range
@@ -320,21 +348,118 @@ impl CommitService {
         let system_prompt = super::prompt::create_pr_system_prompt(&config_clone)?;
 
         // Use the shared optimization logic
-        let (_, final_user_prompt) = self
-            .optimize_prompt(&config_clone, &system_prompt, context, |ctx| {
+        self.generator()
+            .request_within_budget(&config_clone, &system_prompt, context, |ctx| {
                 super::prompt::create_pr_user_prompt(ctx, &commit_messages)
             })
-            .await;
+            .await
+    }
 
-        let generated_pr = llm::get_message::<super::types::GeneratedPullRequest>(
-            &config_clone,
-            &self.provider_name,
-            &system_prompt,
-            &final_user_prompt,
-        )
-        .await?;
+    /// Generate a PR description for each commit in a range, plus one for the whole range
+    ///
+    /// Each commit is described from its own diff against its first parent, so the token
+    /// optimizer budgets every commit separately and long ranges don't overflow the context.
+    ///
+    /// # Arguments
+    ///
+    /// * `instructions` - Custom instructions for the AI
+    /// * `from` - The starting Git reference (exclusive)
+    /// * `to` - The ending Git reference (inclusive)
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the per-commit descriptions and the overall summary or an error
+    pub async fn generate_pr_per_commit(
+        &self,
+        instructions: &str,
+        from: &str,
+        to: &str,
+    ) -> anyhow::Result<super::types::PerCommitPullRequest> {
+        let commit_ids = self.repo.get_commit_ids_in_range(from, to)?;
+        if commit_ids.is_empty() {
+            return Err(anyhow::anyhow!("No commits found in range {from}..{to}"));
+        }
+        debug!(
+            "Generating per-commit PR descriptions for {} commits",
+            commit_ids.len()
+        );
+
+        let mut commits = Vec::with_capacity(commit_ids.len());
+        for commit_id in commit_ids {
+            let description = self
+                .generate_pr_for_commit_range(instructions, &format!("{commit_id}^"), &commit_id)
+                .await?;
+            commits.push(super::types::CommitPullRequest {
+                commit: commit_id,
+                description,
+            });
+        }
+
+        let overall = self
+            .generate_pr_for_commit_range(instructions, from, to)
+            .await?;
 
-        Ok(generated_pr)
+        Ok(super::types::PerCommitPullRequest { commits, overall })
+    }
+
+    /// Explain an existing commit from its message and diff
+    ///
+    /// # Arguments
+    ///
+    /// * `instructions` - Custom instructions for the AI
+    /// * `commitish` - The commit to explain (hash, branch, or reference)
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the explanation or an error
+    pub async fn explain_commit(
+        &self,
+        instructions: &str,
+        commitish: &str,
+    ) -> anyhow::Result<CommitExplanation> {
+        let mut config_clone = self.config.clone();
+        config_clone.instructions = instructions.to_string();
+
+        let context = self.get_git_info_for_commit(commitish).await?;
+        let system_prompt = super::prompt::create_explain_system_prompt(&config_clone)?;
+
+        // Big commits go through the same token optimizer as messages and PRs
+        self.generator()
+            .request_within_budget(
+                &config_clone,
+                &system_prompt,
+                context,
+                super::prompt::create_explain_user_prompt,
+            )
+            .await
+    }
+
+    /// Placeholder explanation of a commit for `--dry-run`, built from its real context
+    /// without calling the provider
+    pub async fn dry_run_explanation(&self, commitish: &str) -> Result<CommitExplanation> {
+        let context = self.get_git_info_for_commit(commitish).await?;
+        let subject = context
+            .recent_commits
+            .first()
+            .and_then(|commit| commit.message.lines().next())
+            .unwrap_or(commitish)
+            .to_string();
+        let files: Vec<&str> = context
+            .staged_files
+            .iter()
+            .map(|file| file.path.as_str())
+            .collect();
+        let files = if files.is_empty() {
+            "no files".to_string()
+        } else {
+            files.join(", ")
+        };
+
+        Ok(CommitExplanation {
+            what_changed: format!("\"{subject}\" touches {files}."),
+            likely_intent: "Dry run mode: no provider was asked for the intent.".to_string(),
+            risk: "Dry run mode: no provider was asked for the risk.".to_string(),
+        })
     }
 
     /// Generate a PR description for branch comparison
@@ -371,21 +496,11 @@ impl CommitService {
         let system_prompt = super::prompt::create_pr_system_prompt(&config_clone)?;
 
         // Use the shared optimization logic
-        let (_, final_user_prompt) = self
-            .optimize_prompt(&config_clone, &system_prompt, context, |ctx| {
+        self.generator()
+            .request_within_budget(&config_clone, &system_prompt, context, |ctx| {
                 super::prompt::create_pr_user_prompt(ctx, &commit_messages)
             })
-            .await;
-
-        let generated_pr = llm::get_message::<super::types::GeneratedPullRequest>(
-            &config_clone,
-            &self.provider_name,
-            &system_prompt,
-            &final_user_prompt,
-        )
-        .await?;
-
-        Ok(generated_pr)
+            .await
     }
 
     /// Performs a commit with the given message.
@@ -408,6 +523,8 @@ impl CommitService {
             return Err(anyhow::anyhow!("Cannot commit to a remote repository"));
         }
 
+        let message = &append_co_author_trailers(message, &self.config.co_authors);
+
         debug!(
             "Performing commit with message: {}, amend: {}, commit_ref: {:?}",
             message, amend, commit_ref
@@ -416,11 +533,13 @@ impl CommitService {
         if !self.verify {
             debug!("Skipping pre-commit hook (verify=false)");
             if amend {
-                return self
-                    .repo
-                    .amend_commit(message, commit_ref.unwrap_or("HEAD"));
+                return self.repo.amend_commit(
+                    message,
+                    commit_ref.unwrap_or("HEAD"),
+                    self.config.sign_commits,
+                );
             }
-            return self.repo.commit(message);
+            return self.repo.commit(message, self.config.sign_commits);
         }
 
         // Execute pre-commit hook
@@ -433,10 +552,13 @@ impl CommitService {
 
         // Perform the commit
         let commit_result = if amend {
-            self.repo
-                .amend_commit(message, commit_ref.unwrap_or("HEAD"))
+            self.repo.amend_commit(
+                message,
+                commit_ref.unwrap_or("HEAD"),
+                self.config.sign_commits,
+            )
         } else {
-            self.repo.commit(message)
+            self.repo.commit(message, self.config.sign_commits)
         };
 
         match commit_result {
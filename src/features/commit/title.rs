@@ -0,0 +1,96 @@
+use super::types::GeneratedMessage;
+use crate::config::Config;
+
+use log::warn;
+
+/// Separators left dangling at the end of a title cut short
+const TRAILING_SEPARATORS: &[char] = &[',', ';', ':', '-', '(', '/'];
+
+/// Shorten `title` to at most `max` characters, cutting between words.
+///
+/// Returns `None` when the title already fits. A single word longer than `max` is cut
+/// mid-word, as there's no boundary to cut at.
+pub fn shorten_title(title: &str, max: usize) -> Option<String> {
+    if title.chars().count() <= max {
+        return None;
+    }
+
+    let mut shortened = String::new();
+    for word in title.split_whitespace() {
+        let separator = usize::from(!shortened.is_empty());
+        if shortened.chars().count() + separator + word.chars().count() > max {
+            break;
+        }
+        if separator == 1 {
+            shortened.push(' ');
+        }
+        shortened.push_str(word);
+    }
+
+    let shortened = shortened.trim_end_matches(|c: char| TRAILING_SEPARATORS.contains(&c));
+    if shortened.is_empty() {
+        return Some(title.chars().take(max).collect());
+    }
+    Some(shortened.trim_end().to_string())
+}
+
+/// Keep the subject line, emoji included, within `config.max_title_length` (0 means no
+/// limit), shortening the title and warning when the model went over.
+pub fn enforce_title_length(message: &mut GeneratedMessage, config: &Config) {
+    if config.max_title_length == 0 {
+        return;
+    }
+    let emoji_width = message
+        .emoji
+        .as_deref()
+        .map(str::trim)
+        .filter(|emoji| !emoji.is_empty())
+        .map_or(0, |emoji| emoji.chars().count() + 1);
+    let max = config.max_title_length.saturating_sub(emoji_width).max(1);
+
+    if let Some(shortened) = shorten_title(&message.title, max) {
+        warn!(
+            "Generated title is {} characters, over the {} character limit; shortened to \"{shortened}\"",
+            message.title.chars().count() + emoji_width,
+            config.max_title_length
+        );
+        message.title = shortened;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_long_title_is_cut_at_a_word_boundary() {
+        let title = "feat(config): add a configurable maximum length for generated commit titles";
+        let shortened = shorten_title(title, 50).expect("title is over the limit");
+
+        assert_eq!(shortened, "feat(config): add a configurable maximum length");
+        assert!(shortened.chars().count() <= 50);
+        assert!(title.starts_with(&shortened));
+        assert_eq!(shorten_title("fix: typo", 50), None);
+    }
+
+    #[test]
+    fn test_limit_counts_the_emoji() {
+        let mut config = Config {
+            max_title_length: 20,
+            ..Config::default()
+        };
+        let mut message = GeneratedMessage {
+            emoji: Some("✨".to_string()),
+            title: "feat: add title budget support".to_string(),
+            message: String::new(),
+        };
+
+        enforce_title_length(&mut message, &config);
+        assert_eq!(message.title, "feat: add title");
+
+        config.max_title_length = 0;
+        message.title = "feat: add title budget support".to_string();
+        enforce_title_length(&mut message, &config);
+        assert_eq!(message.title, "feat: add title budget support");
+    }
+}
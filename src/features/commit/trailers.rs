@@ -0,0 +1,115 @@
+use anyhow::{Result, anyhow};
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// Trailer key used for pairing credits
+const CO_AUTHOR_KEY: &str = "Co-authored-by";
+
+// Regex for a `Name <email>` identity
+static IDENTITY_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^[^<>\n]*[^<>\s]\s+<[^<>\s@]+@[^<>\s@]+>$")
+        .expect("Failed to compile co-author identity regex pattern - this is a bug")
+});
+
+// Regex for a git trailer line such as `Signed-off-by: Name <email>`
+static TRAILER_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^[A-Za-z][A-Za-z0-9-]*:\s+\S")
+        .expect("Failed to compile trailer regex pattern - this is a bug")
+});
+
+/// Validate a `--co-author` value of the form `Name <email>`
+pub fn parse_co_author(value: &str) -> Result<String> {
+    let value = value.trim();
+    if IDENTITY_RE.is_match(value) {
+        Ok(value.to_string())
+    } else {
+        Err(anyhow!(
+            "Invalid co-author '{value}': expected the form 'Name <email@example.com>'"
+        ))
+    }
+}
+
+/// Append `Co-authored-by:` trailers to a commit message.
+///
+/// Co-authors already credited in the message are skipped, so re-applying the trailers
+/// (e.g. when amending) never duplicates them. A blank line separates the body from the
+/// trailer block unless the message already ends with one.
+pub fn append_co_author_trailers(message: &str, co_authors: &[String]) -> String {
+    let mut credited: Vec<String> = message
+        .lines()
+        .filter_map(|line| line.strip_prefix(CO_AUTHOR_KEY)?.strip_prefix(':'))
+        .map(|identity| identity.trim().to_lowercase())
+        .collect();
+
+    let mut new_trailers: Vec<String> = Vec::new();
+    for co_author in co_authors {
+        let identity = co_author.trim();
+        let key = identity.to_lowercase();
+        if !credited.contains(&key) {
+            credited.push(key);
+            new_trailers.push(format!("{CO_AUTHOR_KEY}: {identity}"));
+        }
+    }
+
+    if new_trailers.is_empty() {
+        return message.to_string();
+    }
+
+    let body = message.trim_end();
+    let ends_with_trailers = body
+        .rsplit_once("\n\n")
+        .is_some_and(|(_, last)| last.lines().all(|line| TRAILER_RE.is_match(line)));
+    let separator = if body.is_empty() {
+        ""
+    } else if ends_with_trailers {
+        "\n"
+    } else {
+        "\n\n"
+    };
+
+    format!("{body}{separator}{}\n", new_trailers.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_co_author_validates_format() {
+        assert_eq!(
+            parse_co_author(" Jane Doe <jane@example.com> ").expect("valid co-author"),
+            "Jane Doe <jane@example.com>"
+        );
+        assert!(parse_co_author("Jane Doe").is_err());
+        assert!(parse_co_author("<jane@example.com>").is_err());
+        assert!(parse_co_author("Jane Doe <jane>").is_err());
+    }
+
+    #[test]
+    fn test_trailers_follow_blank_line() {
+        let message = append_co_author_trailers(
+            "feat: add widget\n\nAdds the widget.\n",
+            &["Jane Doe <jane@example.com>".to_string()],
+        );
+
+        assert_eq!(
+            message,
+            "feat: add widget\n\nAdds the widget.\n\nCo-authored-by: Jane Doe <jane@example.com>\n"
+        );
+    }
+
+    #[test]
+    fn test_trailers_are_not_duplicated() {
+        let co_authors = vec![
+            "Jane Doe <jane@example.com>".to_string(),
+            "John Roe <john@example.com>".to_string(),
+        ];
+        let once = append_co_author_trailers("fix: bug\n\nBody.", &co_authors[..1]);
+        let twice = append_co_author_trailers(&once, &co_authors);
+
+        assert_eq!(twice.matches("Jane Doe").count(), 1);
+        assert!(twice.ends_with(
+            "\n\nCo-authored-by: Jane Doe <jane@example.com>\nCo-authored-by: John Roe <john@example.com>\n"
+        ));
+    }
+}
@@ -3,15 +3,36 @@ use serde::{Deserialize, Serialize};
 use std::fmt::Write as _;
 use textwrap;
 
+/// Version of the [`GeneratedMessage`] JSON schema, bumped whenever a field is added,
+/// removed or changes type
+pub const GENERATED_MESSAGE_SCHEMA_VERSION: u32 = 1;
+
 /// Model for commit message generation results
 #[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 pub struct GeneratedMessage {
+    /// Optional gitmoji placed before the title; leave it out, it is picked from the commit type
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub emoji: Option<String>,
     /// Commit message title/subject line
     pub title: String,
     /// Detailed commit message body
     pub message: String,
 }
 
+impl GeneratedMessage {
+    /// JSON Schema of a generated message, as given to the LLM and published to clients.
+    ///
+    /// The schema version is carried in the `x-schema-version` keyword.
+    pub fn json_schema() -> serde_json::Value {
+        let mut schema = schemars::schema_for!(Self);
+        schema.insert(
+            "x-schema-version".to_string(),
+            GENERATED_MESSAGE_SCHEMA_VERSION.into(),
+        );
+        schema.to_value()
+    }
+}
+
 /// Model for pull request description generation results
 #[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 pub struct GeneratedPullRequest {
@@ -35,10 +56,43 @@ pub struct GeneratedPullRequest {
     pub notes: Option<String>,
 }
 
+/// Plain-language explanation of an existing commit, for `git-explain`
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct CommitExplanation {
+    /// What the commit changes, in terms a newcomer to the codebase can follow
+    pub what_changed: String,
+    /// Why the change was most likely made, inferred from the diff and message
+    pub likely_intent: String,
+    /// What could break or needs care, or that the change is low risk
+    pub risk: String,
+}
+
+/// Pull request feedback for a single commit in a range
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CommitPullRequest {
+    /// Full hash of the commit
+    pub commit: String,
+    /// Description generated from this commit's diff alone
+    pub description: GeneratedPullRequest,
+}
+
+/// Per-commit descriptions for a range plus an aggregate over the whole range
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PerCommitPullRequest {
+    /// One entry per commit, oldest first
+    pub commits: Vec<CommitPullRequest>,
+    /// Description of the combined changes
+    pub overall: GeneratedPullRequest,
+}
+
 /// Formats a commit message from a `GeneratedMessage`
 pub fn format_commit_message(response: &GeneratedMessage) -> String {
     let mut message = String::new();
 
+    if let Some(emoji) = response.emoji.as_deref().filter(|e| !e.trim().is_empty()) {
+        message.push_str(emoji.trim());
+        message.push(' ');
+    }
     message.push_str(&response.title);
     message.push_str("\n\n");
 
@@ -50,6 +104,19 @@ pub fn format_commit_message(response: &GeneratedMessage) -> String {
     message
 }
 
+/// Splits an existing commit message into its subject line and body.
+///
+/// Any emoji stays part of the title, so formatting the result gives the subject back as is.
+pub fn parse_commit_message(message: &str) -> GeneratedMessage {
+    let message = message.trim();
+    let (title, body) = message.split_once('\n').unwrap_or((message, ""));
+    GeneratedMessage {
+        emoji: None,
+        title: title.trim_end().to_string(),
+        message: body.trim().to_string(),
+    }
+}
+
 /// Formats a pull request description from a `GeneratedPullRequest`
 pub fn format_pull_request(response: &GeneratedPullRequest) -> String {
     let mut message = String::new();
@@ -97,3 +164,30 @@ pub fn format_pull_request(response: &GeneratedPullRequest) -> String {
 
     message
 }
+
+/// Formats a commit explanation as Markdown sections
+pub fn format_commit_explanation(response: &CommitExplanation) -> String {
+    format!(
+        "## What changed\n{}\n\n## Likely intent\n{}\n\n## Risk\n{}\n",
+        response.what_changed, response.likely_intent, response.risk
+    )
+}
+
+/// Formats a per-commit pull request description: one section per commit, then the overall summary
+pub fn format_per_commit_pull_request(response: &PerCommitPullRequest) -> String {
+    let mut message = String::new();
+
+    for entry in &response.commits {
+        let short_hash = entry.commit.get(..7).unwrap_or(&entry.commit);
+        let _ = writeln!(&mut message, "# {short_hash}: {}", entry.description.title);
+        message.push('\n');
+        message.push_str(&format_pull_request(&entry.description));
+        message.push('\n');
+    }
+
+    let _ = writeln!(&mut message, "# Overall: {}", response.overall.title);
+    message.push('\n');
+    message.push_str(&format_pull_request(&response.overall));
+
+    message
+}
@@ -0,0 +1,351 @@
+use crate::common::CommonParams;
+use crate::config::{Config, ProviderConfig};
+use crate::core::azure::{AZURE_PROVIDER, AzureDeployment};
+use crate::core::context::{build_exclude_matcher, extract_ticket_id};
+use crate::core::llm::{get_available_provider_names, provider_requires_api_key};
+use crate::features::commit::prompt::create_system_prompt;
+use crate::ui;
+
+use anyhow::{Result, anyhow};
+use colored::Colorize;
+use std::fmt;
+
+/// Outcome of a single configuration check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Pass,
+    /// Suspicious but usable; does not fail the run
+    Warn,
+    /// Will break commands at runtime
+    Fail,
+}
+
+/// One line of the doctor checklist
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckItem {
+    pub name: String,
+    pub status: CheckStatus,
+    /// What was found, and for failures how to fix it
+    pub detail: String,
+}
+
+impl CheckItem {
+    fn new(name: impl Into<String>, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            status,
+            detail: detail.into(),
+        }
+    }
+}
+
+impl fmt::Display for CheckItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let marker = match self.status {
+            CheckStatus::Pass => "✓".green().bold(),
+            CheckStatus::Warn => "!".yellow().bold(),
+            CheckStatus::Fail => "✗".red().bold(),
+        };
+        write!(f, "{marker} {}: {}", self.name.bold(), self.detail)
+    }
+}
+
+/// Result of validating a loaded `Config`
+#[derive(Debug, Clone, Default)]
+pub struct DoctorReport {
+    pub items: Vec<CheckItem>,
+}
+
+impl DoctorReport {
+    fn push(&mut self, name: impl Into<String>, status: CheckStatus, detail: impl Into<String>) {
+        self.items.push(CheckItem::new(name, status, detail));
+    }
+
+    /// Number of hard failures
+    pub fn failure_count(&self) -> usize {
+        self.items
+            .iter()
+            .filter(|item| item.status == CheckStatus::Fail)
+            .count()
+    }
+}
+
+/// Expected key prefixes for providers whose keys have a recognizable shape
+const API_KEY_PREFIXES: &[(&str, &str)] = &[
+    ("anthropic", "sk-ant-"),
+    ("openrouter", "sk-or-"),
+    ("openai", "sk-"),
+    ("deepseek", "sk-"),
+    ("groq", "gsk_"),
+    ("xai", "xai-"),
+    ("google", "AIza"),
+];
+
+/// Validate the configuration item by item
+pub fn diagnose(config: &Config) -> DoctorReport {
+    let mut report = DoctorReport::default();
+    let available = get_available_provider_names();
+
+    match config.check_environment() {
+        Ok(()) => report.push("Git repository", CheckStatus::Pass, "inside a work tree"),
+        Err(e) => report.push("Git repository", CheckStatus::Warn, e.to_string()),
+    }
+
+    let default_provider = config.default_provider.as_str();
+    if !available.iter().any(|p| p == default_provider) {
+        report.push(
+            "Default provider",
+            CheckStatus::Fail,
+            format!(
+                "'{default_provider}' is not a known provider; set gait.defaultprovider to one of: {}",
+                available.join(", ")
+            ),
+        );
+    } else if config.get_provider_config(default_provider).is_none()
+        && provider_requires_api_key(default_provider)
+    {
+        report.push(
+            "Default provider",
+            CheckStatus::Fail,
+            format!(
+                "'{default_provider}' is not configured; run `git config --global gait.{default_provider}-apikey <key>`"
+            ),
+        );
+    } else {
+        report.push(
+            "Default provider",
+            CheckStatus::Pass,
+            format!("'{default_provider}'"),
+        );
+    }
+
+    for fallback in &config.fallback_providers {
+        if !available.contains(fallback) {
+            report.push(
+                format!("Fallback provider {fallback}"),
+                CheckStatus::Fail,
+                "unknown provider; remove it from gait.fallbackproviders",
+            );
+        }
+    }
+
+    let mut providers: Vec<_> = config.providers.iter().collect();
+    providers.sort_by(|a, b| a.0.cmp(b.0));
+    for (name, provider) in providers {
+        let in_use = name == default_provider || config.fallback_providers.contains(name);
+        check_provider(&mut report, name, provider, in_use);
+    }
+
+    if let Some(pattern) = config.ticket_regex() {
+        match extract_ticket_id("", pattern) {
+            Ok(_) => report.push("Ticket pattern", CheckStatus::Pass, format!("'{pattern}'")),
+            Err(e) => report.push(
+                "Ticket pattern",
+                CheckStatus::Fail,
+                format!("{e:#}; fix gait.ticketpattern"),
+            ),
+        }
+    }
+
+    if !config.exclude_paths.is_empty() {
+        match build_exclude_matcher(&config.exclude_paths) {
+            Ok(_) => report.push(
+                "Exclude paths",
+                CheckStatus::Pass,
+                format!("{} pattern(s)", config.exclude_paths.len()),
+            ),
+            Err(e) => report.push(
+                "Exclude paths",
+                CheckStatus::Fail,
+                format!("{e:#}; fix gait.excludepaths"),
+            ),
+        }
+    }
+
+    let overridden: Vec<String> = config
+        .sources
+        .iter()
+        .map(|(key, source)| format!("{key} from {source}"))
+        .collect();
+    if !overridden.is_empty() {
+        report.push("Config sources", CheckStatus::Pass, overridden.join(", "));
+    }
+
+    match create_system_prompt(config) {
+        Ok(_) => report.push("Prompt templates", CheckStatus::Pass, "render successfully"),
+        Err(e) => report.push("Prompt templates", CheckStatus::Fail, format!("{e:#}")),
+    }
+
+    report
+}
+
+/// Check a single provider's model, API key and token limit
+fn check_provider(report: &mut DoctorReport, name: &str, provider: &ProviderConfig, in_use: bool) {
+    let label = format!("Provider {name}");
+
+    if provider.model_name.trim().is_empty() {
+        report.push(
+            &label,
+            CheckStatus::Fail,
+            format!("no model set; run `git config --global gait.{name}-model <model>`"),
+        );
+    }
+
+    if provider.token_limit == Some(0) {
+        report.push(
+            &label,
+            CheckStatus::Fail,
+            format!("token limit must be positive; fix gait.{name}-tokenlimit"),
+        );
+    }
+
+    if name == AZURE_PROVIDER
+        && let Err(e) = AzureDeployment::from_provider_config(provider)
+    {
+        report.push(&label, CheckStatus::Fail, e.to_string());
+    }
+
+    if provider_requires_api_key(name) {
+        let key = provider.api_key.trim();
+        if key.is_empty() {
+            // An unused provider without a key is harmless
+            let status = if in_use {
+                CheckStatus::Fail
+            } else {
+                CheckStatus::Warn
+            };
+            report.push(
+                &label,
+                status,
+                format!("no API key; run `git config --global gait.{name}-apikey <key>`"),
+            );
+            return;
+        }
+        if let Some(problem) = api_key_shape_problem(name, key) {
+            report.push(&label, CheckStatus::Warn, problem);
+            return;
+        }
+    }
+
+    if !report
+        .items
+        .iter()
+        .any(|item| item.name == label && item.status == CheckStatus::Fail)
+    {
+        report.push(
+            &label,
+            CheckStatus::Pass,
+            format!("model '{}'", provider.model_name),
+        );
+    }
+}
+
+/// Describe why an API key doesn't look like one for `provider`, if it doesn't
+fn api_key_shape_problem(provider: &str, key: &str) -> Option<String> {
+    if key.chars().any(char::is_whitespace) {
+        return Some("API key contains whitespace".to_string());
+    }
+    if key.len() < 20 {
+        return Some(format!(
+            "API key looks too short ({} characters)",
+            key.len()
+        ));
+    }
+    API_KEY_PREFIXES
+        .iter()
+        .find(|(name, _)| *name == provider)
+        .filter(|(_, prefix)| !key.starts_with(prefix))
+        .map(|(_, prefix)| {
+            format!("API key doesn't look like a {provider} key (expected '{prefix}...')")
+        })
+}
+
+/// Print the configuration checklist, failing if any hard check failed
+pub fn handle_doctor_command(common: CommonParams) -> Result<()> {
+    let mut config = Config::load()?;
+    common.apply_to_config(&mut config)?;
+
+    let report = diagnose(&config);
+    for item in &report.items {
+        println!("{item}");
+    }
+
+    let failures = report.failure_count();
+    if failures > 0 {
+        return Err(anyhow!("{failures} configuration check(s) failed"));
+    }
+
+    ui::print_success("Configuration looks good");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_with_openai(api_key: &str) -> Config {
+        Config {
+            default_provider: "openai".to_string(),
+            providers: [(
+                "openai".to_string(),
+                ProviderConfig {
+                    api_key: api_key.to_string(),
+                    model_name: "gpt-4o".to_string(),
+                    ..Default::default()
+                },
+            )]
+            .into_iter()
+            .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn status_of(report: &DoctorReport, name: &str) -> Vec<CheckStatus> {
+        report
+            .items
+            .iter()
+            .filter(|item| item.name == name)
+            .map(|item| item.status)
+            .collect()
+    }
+
+    #[test]
+    fn test_valid_provider_passes() {
+        let report = diagnose(&config_with_openai("sk-proj-abcdefghijklmnopqrstuvwxyz"));
+
+        assert_eq!(status_of(&report, "Default provider"), [CheckStatus::Pass]);
+        assert_eq!(status_of(&report, "Provider openai"), [CheckStatus::Pass]);
+        assert_eq!(status_of(&report, "Prompt templates"), [CheckStatus::Pass]);
+    }
+
+    #[test]
+    fn test_missing_key_for_default_provider_fails() {
+        let report = diagnose(&config_with_openai(""));
+
+        assert_eq!(status_of(&report, "Provider openai"), [CheckStatus::Fail]);
+        assert!(report.failure_count() > 0);
+    }
+
+    #[test]
+    fn test_misshapen_key_warns() {
+        let report = diagnose(&config_with_openai("not-an-openai-key-at-all-really"));
+
+        assert_eq!(status_of(&report, "Provider openai"), [CheckStatus::Warn]);
+    }
+
+    #[test]
+    fn test_zero_token_limit_and_bad_pattern_fail() {
+        let mut config = config_with_openai("sk-proj-abcdefghijklmnopqrstuvwxyz");
+        if let Some(openai) = config.providers.get_mut("openai") {
+            openai.token_limit = Some(0);
+        }
+        config.ticket_pattern = Some("(unclosed".to_string());
+        config.default_provider = "nope".to_string();
+
+        let report = diagnose(&config);
+
+        assert_eq!(status_of(&report, "Provider openai"), [CheckStatus::Fail]);
+        assert_eq!(status_of(&report, "Ticket pattern"), [CheckStatus::Fail]);
+        assert_eq!(status_of(&report, "Default provider"), [CheckStatus::Fail]);
+    }
+}
@@ -1,2 +1,5 @@
+pub mod cache;
 pub mod changelog;
 pub mod commit;
+pub mod doctor;
+pub mod presets;
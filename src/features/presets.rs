@@ -0,0 +1,81 @@
+use crate::config::{BUILTIN_PRESETS, Config};
+
+use anyhow::Result;
+use colored::Colorize;
+
+/// One preset as shown by `--list-presets`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PresetSummary {
+    pub name: String,
+    /// Defined in the config file rather than built in
+    pub user: bool,
+    /// First line of the instructions
+    pub summary: String,
+}
+
+/// Built-in presets followed by the user's, skipping built-ins a user preset replaces
+pub fn list_presets(config: &Config) -> Vec<PresetSummary> {
+    let first_line = |text: &str| text.lines().next().unwrap_or_default().trim().to_string();
+
+    BUILTIN_PRESETS
+        .iter()
+        .filter(|(name, _)| !config.presets.contains_key(*name))
+        .map(|(name, instructions)| PresetSummary {
+            name: (*name).to_string(),
+            user: false,
+            summary: first_line(instructions),
+        })
+        .chain(config.presets.iter().map(|(name, preset)| PresetSummary {
+            name: name.clone(),
+            user: true,
+            summary: first_line(&preset.instructions),
+        }))
+        .collect()
+}
+
+/// Print the presets available to `--preset`
+pub fn handle_list_presets_command() -> Result<()> {
+    let config = Config::load()?;
+    for preset in list_presets(&config) {
+        let origin = if preset.user { "user" } else { "built-in" };
+        println!(
+            "{} {} {}",
+            preset.name.bold(),
+            format!("({origin})").dimmed(),
+            preset.summary
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::PresetConfig;
+
+    #[test]
+    fn test_user_presets_replace_builtins_of_the_same_name() {
+        let mut config = Config::default();
+        for (name, instructions) in [("concise", "Our own concise."), ("team", "Team rules.")] {
+            config.presets.insert(
+                name.to_string(),
+                PresetConfig {
+                    instructions: instructions.to_string(),
+                },
+            );
+        }
+
+        let presets = list_presets(&config);
+        let names: Vec<(&str, bool)> = presets.iter().map(|p| (p.name.as_str(), p.user)).collect();
+        assert_eq!(
+            names,
+            vec![
+                ("conventional", false),
+                ("detailed", false),
+                ("concise", true),
+                ("team", true),
+            ]
+        );
+        assert_eq!(presets[2].summary, "Our own concise.");
+    }
+}
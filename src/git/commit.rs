@@ -1,9 +1,11 @@
 use crate::core::context::{ChangeType, RecentCommit, StagedFile};
 use crate::git::utils::is_binary_diff;
-use anyhow::{Result, anyhow};
+use anyhow::{Context, Result, anyhow};
 use chrono;
-use git2::{FileMode, Repository};
+use git2::{FileMode, Repository, StatusOptions};
 use log::debug;
+use std::io::Write;
+use std::process::{Command, Stdio};
 
 use super::ignore_matcher::GitIgnoreMatcher;
 
@@ -16,6 +18,8 @@ pub struct CommitResult {
     pub insertions: usize,
     pub deletions: usize,
     pub new_files: Vec<(String, FileMode)>,
+    /// Whether the commit was created with a GPG/SSH signature
+    pub signed: bool,
 }
 
 /// Collects information about a specific commit
@@ -32,8 +36,9 @@ pub struct CommitInfo {
 ///
 /// * `repo` - The git repository
 /// * `message` - The new commit message
-/// * `commit_ref` - The commit reference to amend (currently only "HEAD" is supported)
+/// * `commit_ref` - The commit to amend; commits below HEAD are reworded with [`reword_commit`]
 /// * `is_remote` - Whether the repository is remote
+/// * `sign` - Whether to sign the commit (also enabled by `commit.gpgsign`)
 ///
 /// # Returns
 ///
@@ -43,6 +48,7 @@ pub fn amend_commit(
     message: &str,
     commit_ref: &str,
     is_remote: bool,
+    sign: bool,
 ) -> Result<CommitResult> {
     if is_remote {
         return Err(anyhow!(
@@ -50,49 +56,58 @@ pub fn amend_commit(
         ));
     }
 
-    // For now, only support amending HEAD
-    if commit_ref != "HEAD" {
-        return Err(anyhow!("Only amending HEAD is currently supported"));
+    let target = repo
+        .revparse_single(commit_ref)
+        .and_then(|object| object.peel_to_commit())
+        .with_context(|| format!("Failed to resolve commit to amend: {commit_ref}"))?;
+    if target.id() != repo.head()?.peel_to_commit()?.id() {
+        return reword_commit(repo, &target, message, sign);
     }
 
-    // Get the current HEAD commit
-    let head_commit = repo.head()?.peel_to_commit()?;
+    let signed = should_sign(repo, sign);
+    let commit_oid = if signed {
+        commit_with_git_cli(repo, message, true)?
+    } else {
+        // Get the current HEAD commit
+        let head_commit = repo.head()?.peel_to_commit()?;
 
-    // Get the signature for the new commit
-    let signature = repo.signature()?;
+        // Get the signature for the new commit
+        let signature = repo.signature()?;
 
-    // Use the HEAD commit's tree (or current index if there are staged changes)
-    let tree = if repo
-        .statuses(None)?
-        .iter()
-        .any(|s| s.status() != git2::Status::CURRENT)
-    {
-        // There are staged changes, use current index
-        let mut index = repo.index()?;
-        let tree_id = index.write_tree()?;
-        repo.find_tree(tree_id)?
-    } else {
-        // No staged changes, use the HEAD commit's tree
-        head_commit.tree()?
-    };
+        // Use the HEAD commit's tree (or current index if there are staged changes)
+        let tree = if repo
+            .statuses(None)?
+            .iter()
+            .any(|s| s.status() != git2::Status::CURRENT)
+        {
+            // There are staged changes, use current index
+            let mut index = repo.index()?;
+            let tree_id = index.write_tree()?;
+            repo.find_tree(tree_id)?
+        } else {
+            // No staged changes, use the HEAD commit's tree
+            head_commit.tree()?
+        };
 
-    // Get all parents of the HEAD commit
-    let parents: Vec<git2::Commit> = head_commit.parents().collect();
-    let parent_refs: Vec<&git2::Commit> = parents.iter().collect();
+        // Get all parents of the HEAD commit
+        let parents: Vec<git2::Commit> = head_commit.parents().collect();
+        let parent_refs: Vec<&git2::Commit> = parents.iter().collect();
 
-    // Create new commit with same parents but new message
-    let commit_oid = repo.commit(
-        None, // Don't update any reference automatically
-        &signature,
-        &signature,
-        message,
-        &tree,
-        &parent_refs,
-    )?;
+        // Create new commit with same parents but new message
+        let commit_oid = repo.commit(
+            None, // Don't update any reference automatically
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parent_refs,
+        )?;
 
-    // Manually update HEAD to point to the new commit
-    repo.head()?
-        .set_target(commit_oid, "amend commit message")?;
+        // Manually update HEAD to point to the new commit
+        repo.head()?
+            .set_target(commit_oid, "amend commit message")?;
+        commit_oid
+    };
 
     let branch_name = repo.head()?.shorthand().unwrap_or("HEAD").to_string();
     let commit = repo.find_commit(commit_oid)?;
@@ -134,17 +149,153 @@ pub fn amend_commit(
         insertions,
         deletions,
         new_files,
+        signed,
     })
 }
 
+/// Reword a commit below HEAD with an automated interactive rebase
+///
+/// An `amend!` commit carrying the new message is added on top of HEAD, and
+/// `git rebase --interactive --autosquash` folds it into the target. Descendants are
+/// replayed onto the reworded commit with their trees and messages intact, so only
+/// their hashes change. Refuses to run with uncommitted changes.
+///
+/// # Arguments
+///
+/// * `repo` - The git repository
+/// * `target` - The commit to reword; must be in the first-parent history of HEAD
+/// * `message` - The new commit message
+/// * `sign` - Whether to sign the rewritten commits (also enabled by `commit.gpgsign`)
+///
+/// # Returns
+///
+/// A Result containing the `CommitResult` for the reworded commit or an error
+pub fn reword_commit(
+    repo: &Repository,
+    target: &git2::Commit<'_>,
+    message: &str,
+    sign: bool,
+) -> Result<CommitResult> {
+    let short_hash = target.id().to_string()[..7].to_string();
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| anyhow!("Cannot reword commits in a bare repository"))?;
+
+    let mut status_options = StatusOptions::new();
+    status_options
+        .include_untracked(false)
+        .include_ignored(false);
+    if repo
+        .statuses(Some(&mut status_options))?
+        .iter()
+        .any(|entry| entry.status() != git2::Status::CURRENT)
+    {
+        return Err(anyhow!(
+            "Cannot amend {short_hash}: the working tree has uncommitted changes. Commit or stash them first."
+        ));
+    }
+
+    let head = repo.head()?;
+    let branch_name = head.shorthand().unwrap_or("HEAD").to_string();
+    let head_commit = head.peel_to_commit()?;
+    let depth = first_parent_distance(&head_commit, target.id()).ok_or_else(|| {
+        anyhow!("Cannot amend {short_hash}: it is not in the first-parent history of HEAD")
+    })?;
+
+    // The `amend!` commit names the target by hash so autosquash can't pick the wrong one
+    let signature = repo.signature()?;
+    repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        &format!("amend! {}\n\n{message}", target.id()),
+        &head_commit.tree()?,
+        &[&head_commit],
+    )?;
+
+    let signed = should_sign(repo, sign);
+    let mut command = Command::new("git");
+    command
+        .current_dir(workdir)
+        // Accept the generated todo list and the amend! message as-is
+        .env("GIT_SEQUENCE_EDITOR", ":")
+        .env("GIT_EDITOR", ":")
+        .args([
+            "rebase",
+            "--interactive",
+            "--autosquash",
+            "--rebase-merges",
+            "--no-verify",
+        ]);
+    if signed {
+        command.arg("--gpg-sign");
+    }
+    if let Ok(parent_id) = target.parent_id(0) {
+        command.arg(parent_id.to_string());
+    } else {
+        command.arg("--root");
+    }
+
+    let output = command
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .output()
+        .context("Failed to run git rebase")?;
+    if !output.status.success() {
+        // Leave the branch exactly as it was before the amend! commit
+        let _ = Command::new("git")
+            .current_dir(workdir)
+            .args(["rebase", "--abort"])
+            .output();
+        repo.reset(head_commit.as_object(), git2::ResetType::Soft, None)?;
+        return Err(anyhow!(
+            "Failed to amend {short_hash}: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    let mut reworded = repo.head()?.peel_to_commit()?;
+    for _ in 0..depth {
+        reworded = reworded.parent(0)?;
+    }
+    debug!("Reworded {short_hash} as {}", reworded.id());
+
+    Ok(CommitResult {
+        branch: branch_name,
+        commit_hash: reworded.id().to_string()[..7].to_string(),
+        files_changed: 0,
+        insertions: 0,
+        deletions: 0,
+        new_files: Vec::new(),
+        signed,
+    })
+}
+
+/// Number of first-parent steps from `head` back to `target`, if it is reachable that way
+fn first_parent_distance(head: &git2::Commit<'_>, target: git2::Oid) -> Option<usize> {
+    let mut commit = head.clone();
+    let mut depth = 0;
+    while commit.id() != target {
+        commit = commit.parent(0).ok()?;
+        depth += 1;
+    }
+    Some(depth)
+}
+
 /// * `repo` - The git repository
 /// * `message` - The commit message.
 /// * `is_remote` - Whether the repository is remote.
+/// * `sign` - Whether to sign the commit (also enabled by `commit.gpgsign`).
 ///
 /// # Returns
 ///
 /// A Result containing the `CommitResult` or an error.
-pub fn commit(repo: &Repository, message: &str, is_remote: bool) -> Result<CommitResult> {
+pub fn commit(
+    repo: &Repository,
+    message: &str,
+    is_remote: bool,
+    sign: bool,
+) -> Result<CommitResult> {
     if is_remote {
         return Err(anyhow!(
             "Cannot commit to a remote repository in read-only mode"
@@ -207,8 +358,14 @@ pub fn commit(repo: &Repository, message: &str, is_remote: bool) -> Result<Commi
     let tree_id = index.write_tree()?;
     let tree = repo.find_tree(tree_id)?;
 
-    // Handle fresh repositories (no HEAD) vs existing repositories
-    let (commit_oid, branch_name) = if let Ok(head) = repo.head() {
+    // Signed commits go through the git CLI; otherwise handle fresh repositories
+    // (no HEAD) vs existing repositories
+    let signed = should_sign(repo, sign);
+    let (commit_oid, branch_name) = if signed {
+        let commit_oid = commit_with_git_cli(repo, message, false)?;
+        let branch_name = repo.head()?.shorthand().unwrap_or("HEAD").to_string();
+        (commit_oid, branch_name)
+    } else if let Ok(head) = repo.head() {
         // Existing repository with HEAD
         let parent_commit = head.peel_to_commit()?;
         let commit_oid = repo.commit(
@@ -236,15 +393,68 @@ pub fn commit(repo: &Repository, message: &str, is_remote: bool) -> Result<Commi
         insertions,
         deletions,
         new_files,
+        signed,
     })
 }
 
+/// Whether a commit should be signed, from the `--sign` flag or `commit.gpgsign`
+pub fn should_sign(repo: &Repository, sign: bool) -> bool {
+    sign || repo
+        .config()
+        .and_then(|config| config.get_bool("commit.gpgsign"))
+        .unwrap_or(false)
+}
+
+/// Create a signed commit from the index by shelling out to `git commit -S`.
+///
+/// libgit2 has no signing support, so this lets git apply `gpg.format`, `gpg.program`
+/// and `user.signingkey`. Hooks are skipped because callers run them explicitly.
+fn commit_with_git_cli(repo: &Repository, message: &str, amend: bool) -> Result<git2::Oid> {
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| anyhow!("Cannot create a signed commit in a bare repository"))?;
+
+    let mut command = Command::new("git");
+    command.current_dir(workdir).args([
+        "commit",
+        "-S",
+        "--no-verify",
+        "--cleanup=verbatim",
+        "--file=-",
+    ]);
+    if amend {
+        command.arg("--amend");
+    }
+
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to run git to create a signed commit")?;
+    child
+        .stdin
+        .take()
+        .ok_or_else(|| anyhow!("Failed to open stdin for git commit"))?
+        .write_all(message.as_bytes())?;
+
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "Signed commit failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    Ok(repo.head()?.peel_to_commit()?.id())
+}
+
 /// Retrieves commits between two Git references.
 ///
 /// # Arguments
 ///
 /// * `repo` - The git repository
-/// * `from` - The starting Git reference.
+/// * `from` - The starting Git reference; empty to start from the first commit.
 /// * `to` - The ending Git reference.
 /// * `callback` - A callback function to process each commit.
 ///
@@ -260,12 +470,7 @@ pub fn get_commits_between_with_callback<T, F>(
 where
     F: FnMut(&RecentCommit) -> Result<T>,
 {
-    let from_commit = repo.revparse_single(from)?.peel_to_commit()?;
-    let to_commit = repo.revparse_single(to)?.peel_to_commit()?;
-
-    let mut revwalk = repo.revwalk()?;
-    revwalk.push(to_commit.id())?;
-    revwalk.hide(from_commit.id())?;
+    let revwalk = revwalk_between(repo, from, to)?;
 
     revwalk
         .filter_map(std::result::Result::ok)
@@ -282,6 +487,24 @@ where
         .collect()
 }
 
+/// Walk the commits reachable from `to` but not from `from`; an empty `from` walks the
+/// whole history of `to`
+fn revwalk_between<'repo>(
+    repo: &'repo Repository,
+    from: &str,
+    to: &str,
+) -> Result<git2::Revwalk<'repo>> {
+    let to_commit = repo.revparse_single(to)?.peel_to_commit()?;
+
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push(to_commit.id())?;
+    if !from.is_empty() {
+        let from_commit = repo.revparse_single(from)?.peel_to_commit()?;
+        revwalk.hide(from_commit.id())?;
+    }
+    Ok(revwalk)
+}
+
 /// Stream commits between two references with a callback that sends results
 ///
 /// # Returns
@@ -296,12 +519,7 @@ pub fn get_commits_between_stream<F>(
 where
     F: FnMut(&RecentCommit) -> Result<()>,
 {
-    let from_commit = repo.revparse_single(from)?.peel_to_commit()?;
-    let to_commit = repo.revparse_single(to)?.peel_to_commit()?;
-
-    let mut revwalk = repo.revwalk()?;
-    revwalk.push(to_commit.id())?;
-    revwalk.hide(from_commit.id())?;
+    let revwalk = revwalk_between(repo, from, to)?;
 
     for oid in revwalk {
         let oid = oid?;
@@ -495,6 +713,12 @@ pub fn get_file_paths_for_commit(repo: &Repository, commit_id: &str) -> Result<V
     Ok(file_paths)
 }
 
+/// Gets the full message of a commit, e.g. to start an amend from it
+pub fn get_commit_message(repo: &Repository, commit_ish: &str) -> Result<String> {
+    let commit = repo.revparse_single(commit_ish)?.peel_to_commit()?;
+    Ok(String::from_utf8_lossy(commit.message_bytes()).into_owned())
+}
+
 /// Gets the date of a commit in YYYY-MM-DD format
 ///
 /// # Arguments
@@ -728,6 +952,27 @@ pub fn get_commits_for_pr(repo: &Repository, from: &str, to: &str) -> Result<Vec
     Ok(result)
 }
 
+/// Lists the full hashes of the commits in `from..to`, oldest first
+///
+/// # Arguments
+///
+/// * `repo` - The git repository
+/// * `from` - The starting Git reference (exclusive)
+/// * `to` - The ending Git reference (inclusive)
+pub fn get_commit_ids_in_range(repo: &Repository, from: &str, to: &str) -> Result<Vec<String>> {
+    let from_commit = repo.revparse_single(from)?.peel_to_commit()?;
+    let to_commit = repo.revparse_single(to)?.peel_to_commit()?;
+
+    let mut revwalk = repo.revwalk()?;
+    revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::REVERSE)?;
+    revwalk.push(to_commit.id())?;
+    revwalk.hide(from_commit.id())?;
+
+    revwalk
+        .map(|oid| Ok(oid?.to_string()))
+        .collect::<Result<Vec<_>>>()
+}
+
 /// Gets the files changed in a commit range (similar to branch diff but for commit range)
 ///
 /// # Arguments
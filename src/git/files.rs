@@ -1,7 +1,9 @@
+use crate::config::Config;
 use crate::core::context::{ChangeType, RecentCommit, StagedFile};
+use crate::git::submodule::submodule_change_diff;
 use crate::git::utils::is_binary_diff;
 use anyhow::{Context, Result};
-use git2::{DiffOptions, Repository, StatusOptions};
+use git2::{Diff, DiffFormat, DiffOptions, Repository, StatusOptions};
 use log::debug;
 use std::fs;
 use std::path::Path;
@@ -17,12 +19,37 @@ pub struct RepoFilesInfo {
     pub file_paths: Vec<String>,
 }
 
+/// Start of the placeholder that replaces a diff too large to include
+const OMITTED_DIFF_PREFIX: &str = "[diff omitted: ";
+
+/// Size limits applied while reading changed files
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileSizeLimits {
+    /// Size above which a file is listed but neither diffed nor read (0 disables)
+    pub max_file_bytes: usize,
+    /// Size past which a file's patch stops being read (0 disables)
+    pub max_diff_bytes: usize,
+}
+
+impl FileSizeLimits {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            max_file_bytes: config.max_file_bytes,
+            max_diff_bytes: config.max_file_diff_bytes,
+        }
+    }
+}
+
 /// Retrieves the status of files in the repository.
 ///
+/// Files with a version larger than `limits.max_file_bytes` are listed with a placeholder
+/// diff and never read. A patch growing past `limits.max_diff_bytes` is cut off the same way.
+///
 /// # Arguments
 ///
 /// * `repo` - The git repository
 /// * `gitignore_matcher` - The gitignore matcher for file exclusion
+/// * `limits` - Sizes above which a file's diff and content are left out
 ///
 /// # Returns
 ///
@@ -30,6 +57,7 @@ pub struct RepoFilesInfo {
 pub fn get_file_statuses(
     repo: &Repository,
     gitignore_matcher: &GitIgnoreMatcher,
+    limits: FileSizeLimits,
 ) -> Result<Vec<StagedFile>> {
     debug!("Getting file statuses");
     let mut staged_files = Vec::new();
@@ -51,31 +79,54 @@ pub fn get_file_statuses(
                 ChangeType::Deleted
             };
 
+            // A submodule pointer change is summarized rather than diffed
+            if let Some(diff) = submodule_change_diff(repo, path)? {
+                staged_files.push(StagedFile {
+                    path: path.to_string(),
+                    change_type,
+                    diff,
+                    content: None,
+                    content_excluded: false,
+                });
+                continue;
+            }
+
             let should_exclude = gitignore_matcher.should_exclude(path);
+            if !should_exclude
+                && let Some(size) = oversized_version(repo, path, limits.max_file_bytes)
+            {
+                debug!("Skipping {path}: a version of it is {size} bytes");
+                staged_files.push(omitted_file(path, change_type, size));
+                continue;
+            }
             let diff = if should_exclude {
                 String::from("[Content excluded]")
             } else {
-                get_diff_for_file(repo, path)?
+                get_diff_for_file(repo, path, limits.max_diff_bytes)?
             };
+            let omitted = diff.starts_with(OMITTED_DIFF_PREFIX);
 
-            let content =
-                if should_exclude || change_type != ChangeType::Modified || is_binary_diff(&diff) {
-                    None
+            let content = if should_exclude
+                || omitted
+                || change_type != ChangeType::Modified
+                || is_binary_diff(&diff)
+            {
+                None
+            } else {
+                let path_obj = Path::new(path);
+                if path_obj.exists() {
+                    Some(fs::read_to_string(path_obj)?)
                 } else {
-                    let path_obj = Path::new(path);
-                    if path_obj.exists() {
-                        Some(fs::read_to_string(path_obj)?)
-                    } else {
-                        None
-                    }
-                };
+                    None
+                }
+            };
 
             staged_files.push(StagedFile {
                 path: path.to_string(),
                 change_type,
                 diff,
                 content,
-                content_excluded: should_exclude,
+                content_excluded: should_exclude || omitted,
             });
         }
     }
@@ -90,11 +141,12 @@ pub fn get_file_statuses(
 ///
 /// * `repo` - The git repository
 /// * `path` - The path of the file to get the diff for.
+/// * `max_bytes` - Size past which the diff is replaced with a placeholder (0 disables)
 ///
 /// # Returns
 ///
 /// A Result containing the diff as a String or an error.
-pub fn get_diff_for_file(repo: &Repository, path: &str) -> Result<String> {
+pub fn get_diff_for_file(repo: &Repository, path: &str, max_bytes: usize) -> Result<String> {
     debug!("Getting diff for file: {}", path);
     let mut diff_options = DiffOptions::new();
     diff_options.pathspec(path);
@@ -109,16 +161,13 @@ pub fn get_diff_for_file(repo: &Repository, path: &str) -> Result<String> {
 
     let diff = repo.diff_tree_to_workdir_with_index(tree.as_ref(), Some(&mut diff_options))?;
 
-    let mut diff_string = String::new();
-    diff.print(git2::DiffFormat::Patch, |_delta, _hunk, line| {
-        let origin = match line.origin() {
-            '+' | '-' | ' ' => line.origin(),
-            _ => ' ',
-        };
-        diff_string.push(origin);
-        diff_string.push_str(&String::from_utf8_lossy(line.content()));
-        true
-    })?;
+    let Some(diff_string) = patch_text(&diff, max_bytes)? else {
+        debug!("Diff for {path} passed {max_bytes} bytes, stopped reading it");
+        return Ok(format!(
+            "{OMITTED_DIFF_PREFIX}over {} KB]",
+            max_bytes / 1024
+        ));
+    };
 
     if is_binary_diff(&diff_string) {
         Ok("[Binary file changed]".to_string())
@@ -130,18 +179,27 @@ pub fn get_diff_for_file(repo: &Repository, path: &str) -> Result<String> {
 
 /// Gets unstaged file changes from the repository
 ///
+/// Untracked files are listed as added files with an empty diff, as git status shows
+/// them. With `include_untracked`, each one inside untracked directories is listed too,
+/// with a diff holding the whole file. Files ignored by `.gitignore` are never included. Files and patches over `limits` are left unread, as in
+/// [`get_file_statuses`].
+///
 /// # Returns
 ///
 /// A Result containing a Vec of `StagedFile` objects for unstaged changes or an error.
 pub fn get_unstaged_file_statuses(
     repo: &Repository,
     gitignore_matcher: &GitIgnoreMatcher,
+    include_untracked: bool,
+    limits: FileSizeLimits,
 ) -> Result<Vec<StagedFile>> {
-    debug!("Getting unstaged file statuses");
+    debug!("Getting unstaged file statuses (include_untracked: {include_untracked})");
     let mut unstaged_files = Vec::new();
 
     let mut opts = StatusOptions::new();
-    opts.include_untracked(true);
+    opts.include_untracked(true)
+        .recurse_untracked_dirs(include_untracked)
+        .include_ignored(false);
     let statuses = repo.statuses(Some(&mut opts))?;
 
     for entry in statuses.iter() {
@@ -159,30 +217,41 @@ pub fn get_unstaged_file_statuses(
             };
 
             let should_exclude = gitignore_matcher.should_exclude(path);
+            if !should_exclude
+                && let Some(size) = oversized_version(repo, path, limits.max_file_bytes)
+            {
+                debug!("Skipping {path}: a version of it is {size} bytes");
+                unstaged_files.push(omitted_file(path, change_type, size));
+                continue;
+            }
             let diff = if should_exclude {
                 String::from("[Content excluded]")
             } else {
-                get_diff_for_unstaged_file(repo, path)?
+                get_diff_for_unstaged_file(repo, path, include_untracked, limits.max_diff_bytes)?
             };
+            let omitted = diff.starts_with(OMITTED_DIFF_PREFIX);
 
-            let content =
-                if should_exclude || change_type != ChangeType::Modified || is_binary_diff(&diff) {
-                    None
+            let content = if should_exclude
+                || omitted
+                || change_type != ChangeType::Modified
+                || is_binary_diff(&diff)
+            {
+                None
+            } else {
+                let path_obj = Path::new(path);
+                if path_obj.exists() {
+                    Some(fs::read_to_string(path_obj)?)
                 } else {
-                    let path_obj = Path::new(path);
-                    if path_obj.exists() {
-                        Some(fs::read_to_string(path_obj)?)
-                    } else {
-                        None
-                    }
-                };
+                    None
+                }
+            };
 
             unstaged_files.push(StagedFile {
                 path: path.to_string(),
                 change_type,
                 diff,
                 content,
-                content_excluded: should_exclude,
+                content_excluded: should_exclude || omitted,
             });
         }
     }
@@ -197,28 +266,37 @@ pub fn get_unstaged_file_statuses(
 ///
 /// * `repo` - The git repository
 /// * `path` - The path of the file to get the diff for.
+/// * `include_untracked` - Whether an untracked file's diff holds its whole content
+/// * `max_bytes` - Size past which the diff is replaced with a placeholder (0 disables)
 ///
 /// # Returns
 ///
 /// A Result containing the diff as a String or an error.
-pub fn get_diff_for_unstaged_file(repo: &Repository, path: &str) -> Result<String> {
+pub fn get_diff_for_unstaged_file(
+    repo: &Repository,
+    path: &str,
+    include_untracked: bool,
+    max_bytes: usize,
+) -> Result<String> {
     debug!("Getting unstaged diff for file: {}", path);
     let mut diff_options = DiffOptions::new();
-    diff_options.pathspec(path);
+    diff_options
+        .pathspec(path)
+        .include_untracked(include_untracked)
+        .recurse_untracked_dirs(include_untracked)
+        .show_untracked_content(include_untracked);
 
-    // For unstaged changes, we compare the index (staged) to the working directory
+    // For unstaged changes, we compare the index (staged) to the working directory.
+    // Untracked files, when included, show up as new files with their full content.
     let diff = repo.diff_index_to_workdir(None, Some(&mut diff_options))?;
 
-    let mut diff_string = String::new();
-    diff.print(git2::DiffFormat::Patch, |_delta, _hunk, line| {
-        let origin = match line.origin() {
-            '+' | '-' | ' ' => line.origin(),
-            _ => ' ',
-        };
-        diff_string.push(origin);
-        diff_string.push_str(&String::from_utf8_lossy(line.content()));
-        true
-    })?;
+    let Some(diff_string) = patch_text(&diff, max_bytes)? else {
+        debug!("Diff for {path} passed {max_bytes} bytes, stopped reading it");
+        return Ok(format!(
+            "{OMITTED_DIFF_PREFIX}over {} KB]",
+            max_bytes / 1024
+        ));
+    };
 
     if is_binary_diff(&diff_string) {
         Ok("[Binary file changed]".to_string())
@@ -231,3 +309,73 @@ pub fn get_diff_for_unstaged_file(repo: &Repository, path: &str) -> Result<Strin
         Ok(diff_string)
     }
 }
+
+/// Patch text of `diff`, or `None` once it grows past `max_bytes` (0 means no limit).
+///
+/// Printing stops at the limit, so a huge diff is never built up in full.
+fn patch_text(diff: &Diff, max_bytes: usize) -> Result<Option<String>> {
+    let mut diff_string = String::new();
+    let mut oversized = false;
+    let printed = diff.print(DiffFormat::Patch, |_delta, _hunk, line| {
+        let origin = match line.origin() {
+            '+' | '-' | ' ' => line.origin(),
+            _ => ' ',
+        };
+        diff_string.push(origin);
+        diff_string.push_str(&String::from_utf8_lossy(line.content()));
+        oversized = max_bytes > 0 && diff_string.len() > max_bytes;
+        !oversized
+    });
+    if oversized {
+        return Ok(None);
+    }
+    printed?;
+    Ok(Some(diff_string))
+}
+
+/// Size of the largest version of `path` in HEAD, the index or the working tree, when it
+/// is over `max_bytes` (0 means no limit).
+///
+/// Sizes come from object headers and file metadata, so no version is loaded.
+fn oversized_version(repo: &Repository, path: &str, max_bytes: usize) -> Option<u64> {
+    let max_bytes = u64::try_from(max_bytes).ok().filter(|&max| max > 0)?;
+    let odb = repo.odb().ok()?;
+    let blob_size = |oid| {
+        odb.read_header(oid)
+            .ok()
+            .and_then(|(size, _)| u64::try_from(size).ok())
+    };
+
+    let head = repo
+        .head()
+        .and_then(|head| head.peel_to_tree())
+        .and_then(|tree| tree.get_path(Path::new(path)))
+        .ok()
+        .and_then(|entry| blob_size(entry.id()));
+    let index = repo
+        .index()
+        .ok()
+        .and_then(|index| index.get_path(Path::new(path), 0))
+        .and_then(|entry| blob_size(entry.id));
+    let workdir = repo
+        .workdir()
+        .and_then(|dir| fs::metadata(dir.join(path)).ok())
+        .map(|metadata| metadata.len());
+
+    [head, index, workdir]
+        .into_iter()
+        .flatten()
+        .max()
+        .filter(|&size| size > max_bytes)
+}
+
+/// Entry for a file too large to read, with the placeholder `omit_oversized_diffs` uses
+fn omitted_file(path: &str, change_type: ChangeType, size: u64) -> StagedFile {
+    StagedFile {
+        path: path.to_string(),
+        change_type,
+        diff: format!("{OMITTED_DIFF_PREFIX}{} KB]", size.div_ceil(1024)),
+        content: None,
+        content_excluded: true,
+    }
+}
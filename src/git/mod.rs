@@ -6,13 +6,16 @@ mod commit;
 mod files;
 #[allow(clippy::uninlined_format_args)]
 mod ignore_matcher;
+mod patch;
 mod repository;
+mod submodule;
 mod utils;
 
 // Re-export primary types for public use
 pub use commit::CommitInfo;
 pub use commit::CommitResult;
-pub use repository::GitRepo;
+pub use patch::parse_unified_diff;
+pub use repository::{DETACHED_HEAD_LABEL, GitRepo};
 
 // Re-export utility functions
 pub use utils::*;
@@ -0,0 +1,189 @@
+use crate::core::context::{ChangeType, StagedFile};
+use crate::git::utils::is_binary_diff;
+
+/// Parse a unified diff, such as `git diff` output or a patch file, into per-file changes.
+///
+/// Both `diff --git` sections and plain `---`/`+++` file pairs are recognized; text before
+/// the first file (a mail header, for instance) is ignored. Binary sections get the same
+/// placeholder diff as staged binary files. File contents are never read, so this works
+/// without a working tree.
+pub fn parse_unified_diff(diff: &str) -> Vec<StagedFile> {
+    split_file_sections(diff)
+        .into_iter()
+        .filter_map(|section| parse_file_section(&section))
+        .collect()
+}
+
+/// Split a diff into one list of lines per file
+fn split_file_sections(diff: &str) -> Vec<Vec<&str>> {
+    let lines: Vec<&str> = diff.lines().collect();
+    let mut sections: Vec<Vec<&str>> = Vec::new();
+    let mut current: Option<Vec<&str>> = None;
+    let mut seen_hunk = false;
+
+    for (i, &line) in lines.iter().enumerate() {
+        let starts_git_section = line.starts_with("diff --git ");
+        // A plain diff starts each file with a `---`/`+++` pair; inside a `diff --git`
+        // section that pair comes before the first hunk and must not split it
+        let starts_plain_section = line.starts_with("--- ")
+            && lines
+                .get(i + 1)
+                .is_some_and(|next| next.starts_with("+++ "))
+            && (current.is_none() || seen_hunk);
+
+        if starts_git_section || starts_plain_section {
+            sections.extend(current.take());
+            current = Some(Vec::new());
+            seen_hunk = false;
+        }
+        if let Some(section) = current.as_mut() {
+            seen_hunk |= line.starts_with("@@");
+            section.push(line);
+        }
+    }
+
+    sections.extend(current);
+    sections
+}
+
+fn parse_file_section(lines: &[&str]) -> Option<StagedFile> {
+    let old_path = header_path(lines, "--- ");
+    let new_path = header_path(lines, "+++ ");
+    let created = lines.iter().any(|line| line.starts_with("new file mode "))
+        || old_path.as_deref() == Some("/dev/null");
+    let deleted = lines
+        .iter()
+        .any(|line| line.starts_with("deleted file mode "))
+        || new_path.as_deref() == Some("/dev/null");
+
+    let path = [new_path, old_path]
+        .into_iter()
+        .flatten()
+        .find(|path| path != "/dev/null")
+        .or_else(|| git_header_path(lines))?;
+
+    let change_type = if created {
+        ChangeType::Added
+    } else if deleted {
+        ChangeType::Deleted
+    } else {
+        ChangeType::Modified
+    };
+
+    let mut diff = lines.join("\n");
+    diff.push('\n');
+    if is_binary_diff(&diff) {
+        diff = "[Binary file changed]".to_string();
+    }
+
+    Some(StagedFile {
+        path,
+        change_type,
+        diff,
+        content: None,
+        content_excluded: false,
+    })
+}
+
+/// Path from a `--- a/path` or `+++ b/path` header, without the timestamp plain diffs add
+fn header_path(lines: &[&str], marker: &str) -> Option<String> {
+    let raw = lines
+        .iter()
+        .take_while(|line| !line.starts_with("@@"))
+        .find_map(|line| line.strip_prefix(marker))?;
+    let raw = raw.split('\t').next().unwrap_or(raw).trim_end();
+    Some(strip_side_prefix(raw).to_string())
+}
+
+/// Path from `diff --git a/path b/path`, for sections without `---`/`+++` (binary or mode-only)
+fn git_header_path(lines: &[&str]) -> Option<String> {
+    let rest = lines.first()?.strip_prefix("diff --git ")?;
+    let (_, new) = rest.split_once(" b/")?;
+    Some(new.to_string())
+}
+
+fn strip_side_prefix(path: &str) -> &str {
+    path.strip_prefix("a/")
+        .or_else(|| path.strip_prefix("b/"))
+        .unwrap_or(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MULTI_FILE_DIFF: &str = "\
+diff --git a/src/lib.rs b/src/lib.rs
+index 1111111..2222222 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,3 +1,3 @@
+ pub fn answer() -> u32 {
+-    41
++    42
+ }
+diff --git a/docs/new.md b/docs/new.md
+new file mode 100644
+index 0000000..3333333
+--- /dev/null
++++ b/docs/new.md
+@@ -0,0 +1 @@
++# New docs
+diff --git a/assets/logo.png b/assets/logo.png
+index 4444444..5555555 100644
+Binary files a/assets/logo.png and b/assets/logo.png differ
+diff --git a/old.txt b/old.txt
+deleted file mode 100644
+index 6666666..0000000
+--- a/old.txt
++++ /dev/null
+@@ -1 +0,0 @@
+-gone
+";
+
+    #[test]
+    fn test_parses_multi_file_git_diff() {
+        let files = parse_unified_diff(MULTI_FILE_DIFF);
+        let summary: Vec<(&str, &ChangeType)> = files
+            .iter()
+            .map(|file| (file.path.as_str(), &file.change_type))
+            .collect();
+
+        assert_eq!(
+            summary,
+            vec![
+                ("src/lib.rs", &ChangeType::Modified),
+                ("docs/new.md", &ChangeType::Added),
+                ("assets/logo.png", &ChangeType::Modified),
+                ("old.txt", &ChangeType::Deleted),
+            ]
+        );
+        assert!(files[0].diff.contains("+    42"));
+        assert!(!files[0].diff.contains("docs/new.md"));
+        assert_eq!(files[2].diff, "[Binary file changed]");
+        assert!(files.iter().all(|file| file.content.is_none()));
+    }
+
+    #[test]
+    fn test_parses_plain_unified_diff() {
+        let diff = "\
+--- a.txt\t2024-01-01 00:00:00.000000000 +0000
++++ a.txt\t2024-01-02 00:00:00.000000000 +0000
+@@ -1 +1 @@
+-one
++two
+--- b.txt\t2024-01-01 00:00:00.000000000 +0000
++++ b.txt\t2024-01-02 00:00:00.000000000 +0000
+@@ -1 +1 @@
+-three
++four
+";
+        let files = parse_unified_diff(diff);
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].path, "a.txt");
+        assert_eq!(files[1].path, "b.txt");
+        assert!(files[1].diff.contains("+four"));
+
+        assert!(parse_unified_diff("not a diff\n").is_empty());
+    }
+}
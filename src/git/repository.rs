@@ -1,13 +1,18 @@
 use crate::config::Config;
 use crate::core::commit_cache::{CachedCommitMessage, CommitMessageCache};
 use crate::core::context::{CommitContext, RecentCommit, StagedFile};
+use crate::core::context_cache::ContextCache;
+use crate::core::semantic_similarity::SemanticSimilarity;
 
 use crate::git::commit::{self, CommitResult};
-use crate::git::files::{RepoFilesInfo, get_file_statuses, get_unstaged_file_statuses};
-use crate::git::utils::is_inside_work_tree;
+use crate::git::files::{
+    FileSizeLimits, RepoFilesInfo, get_file_statuses, get_unstaged_file_statuses,
+};
+use crate::git::utils::{is_inside_work_tree, require_workdir, resolve_repo_root};
 use anyhow::{Context as AnyhowContext, Result, anyhow};
-use git2::{Repository, Tree};
-use std::collections::HashSet;
+use chrono::Utc;
+use git2::{IndexAddOption, Oid, Repository, Tree};
+use std::collections::{HashMap, HashSet};
 use std::env;
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
@@ -18,6 +23,9 @@ use url::Url;
 
 use super::ignore_matcher::GitIgnoreMatcher;
 
+/// Branch label used in commit contexts when HEAD is detached
+pub const DETACHED_HEAD_LABEL: &str = "HEAD (detached)";
+
 /// Represents a Git repository and provides methods for interacting with it.
 pub struct GitRepo {
     repo_path: PathBuf,
@@ -66,7 +74,7 @@ impl GitRepo {
             Self::clone_remote_repository(&url)
         } else {
             let current_dir = env::current_dir()?;
-            Self::new(&current_dir)
+            Self::new(&resolve_repo_root(&current_dir))
         }
     }
 
@@ -134,6 +142,21 @@ impl GitRepo {
         &self.repo_path
     }
 
+    /// Working tree of the repository; a linked worktree has its own.
+    ///
+    /// Fails for a bare repository, which has none.
+    pub fn workdir(&self) -> Result<PathBuf> {
+        let repo = self.open_repo()?;
+        Ok(require_workdir(&repo)?.to_path_buf())
+    }
+
+    /// Git directory shared by all worktrees of the repository, holding hooks, refs and
+    /// objects. The same as the git directory outside linked worktrees.
+    pub fn common_dir(&self) -> Result<PathBuf> {
+        let repo = self.open_repo()?;
+        Ok(repo.commondir().to_path_buf())
+    }
+
     /// Updates the remote repository by fetching the latest changes
     pub fn update_remote(&self) -> Result<()> {
         if !self.is_remote {
@@ -161,26 +184,34 @@ impl GitRepo {
 
     /// Retrieves the current branch name.
     ///
-    /// # Arguments
-    ///
-    /// * `count` - The number of recent commits to retrieve.
-    ///
     /// # Returns
     ///
-    /// A Result containing the branch name as a String or an error.
-    pub fn get_current_branch(&self) -> Result<String> {
+    /// A Result containing the branch name, or `None` when HEAD is detached.
+    pub fn get_current_branch(&self) -> Result<Option<String>> {
         let repo = self.open_repo()?;
+        if repo.head_detached().unwrap_or(false) {
+            debug!("HEAD is detached");
+            return Ok(None);
+        }
+
         if let Ok(head) = repo.head() {
-            let branch_name = head.shorthand().unwrap_or("HEAD detached").to_string();
-            debug!("Current branch: {branch_name}");
+            let branch_name = head.shorthand().map(ToString::to_string);
+            debug!("Current branch: {branch_name:?}");
             Ok(branch_name)
         } else {
             // For fresh repos with no commits, default to "main"
             debug!("No HEAD found (fresh repository), defaulting to 'main'");
-            Ok("main".to_string())
+            Ok(Some("main".to_string()))
         }
     }
 
+    /// Current branch name for display in commit contexts, labelling a detached HEAD
+    fn current_branch_label(&self) -> Result<String> {
+        Ok(self
+            .get_current_branch()?
+            .unwrap_or_else(|| DETACHED_HEAD_LABEL.to_string()))
+    }
+
     /// Executes a Git hook.
     ///
     /// # Arguments
@@ -197,16 +228,15 @@ impl GitRepo {
         }
 
         let repo = self.open_repo()?;
-        let hook_path = repo.path().join("hooks").join(hook_name);
+        // Linked worktrees share the hooks of the main repository
+        let hook_path = repo.commondir().join("hooks").join(hook_name);
 
         if hook_path.exists() {
             debug!("Executing hook: {hook_name}");
             debug!("Hook path: {}", hook_path.display());
 
             // Get the repository's working directory (top level)
-            let repo_workdir = repo
-                .workdir()
-                .context("Repository has no working directory")?;
+            let repo_workdir = require_workdir(&repo)?;
             debug!("Repository working directory: {}", repo_workdir.display());
 
             // Create a command with the proper environment and working directory
@@ -263,10 +293,7 @@ impl GitRepo {
 
         // Use git2 to find the repository root
         let repo = Repository::discover(".").context("Failed to discover git repository")?;
-        let workdir = repo
-            .workdir()
-            .context("Repository has no working directory")?;
-        Ok(workdir.to_path_buf())
+        Ok(require_workdir(&repo)?.to_path_buf())
     }
 
     /// Retrieves the README content at a specific commit.
@@ -329,17 +356,25 @@ impl GitRepo {
     }
 
     /// Extract files info without crossing async boundaries
-    pub fn extract_files_info(&self, include_unstaged: bool) -> Result<RepoFilesInfo> {
+    pub fn extract_files_info(
+        &self,
+        include_unstaged: bool,
+        config: &Config,
+    ) -> Result<RepoFilesInfo> {
         let repo = self.open_repo()?;
 
         // Get basic repo info
-        let branch = self.get_current_branch()?;
+        let branch = self.current_branch_label()?;
         let recent_commits = self.get_recent_commits(5)?;
 
         // Get staged and unstaged files
-        let mut staged_files = get_file_statuses(&repo, &self.gitignore_matcher)?;
+        let mut staged_files = get_file_statuses(
+            &repo,
+            &self.gitignore_matcher,
+            FileSizeLimits::from_config(config),
+        )?;
         if include_unstaged {
-            let unstaged_files = self.get_unstaged_files()?;
+            let unstaged_files = self.get_unstaged_files(config)?;
             staged_files.extend(unstaged_files);
             debug!("Combined {} files (staged + unstaged)", staged_files.len());
         }
@@ -355,10 +390,16 @@ impl GitRepo {
         })
     }
 
-    /// Gets unstaged file changes from the repository
-    pub fn get_unstaged_files(&self) -> Result<Vec<StagedFile>> {
+    /// Gets unstaged file changes from the repository, with the content of untracked
+    /// files when `config.include_untracked` is set
+    pub fn get_unstaged_files(&self, config: &Config) -> Result<Vec<StagedFile>> {
         let repo = self.open_repo()?;
-        get_unstaged_file_statuses(&repo, &self.gitignore_matcher)
+        get_unstaged_file_statuses(
+            &repo,
+            &self.gitignore_matcher,
+            config.include_untracked,
+            FileSizeLimits::from_config(config),
+        )
     }
 
     /// Helper method for creating `CommitContext`
@@ -399,10 +440,13 @@ impl GitRepo {
     }
 
     /// Enhance context with cached commit messages from other repositories
+    ///
+    /// The merged history is ranked by similarity to the staged changes, decayed by age
+    /// with the configured half-life, so a recent message can outrank an older, closer one.
     fn enhance_context_with_cache(
         &self,
         context: &mut CommitContext,
-        _config: &Config,
+        config: &Config,
     ) -> Result<()> {
         let cache = CommitMessageCache::new()?;
 
@@ -410,29 +454,42 @@ impl GitRepo {
         let cached_messages =
             cache.get_commit_messages(&context.user_email, &self.repo_path.to_string_lossy());
 
-        // Convert cached messages to strings for the context
-        let cached_history: Vec<String> =
-            cached_messages.into_iter().map(|msg| msg.message).collect();
-
-        // Merge with existing history
-        let mut enhanced_history = context.author_history.clone();
-        enhanced_history.extend(cached_history);
+        // The author's latest commits are cached as they are read, so their timestamps are
+        // there; any message that isn't counts as new
+        let now = Utc::now().timestamp();
+        let timestamps: HashMap<&str, i64> = cached_messages
+            .iter()
+            .map(|msg| (msg.message.as_str(), msg.timestamp.parse().unwrap_or(now)))
+            .collect();
 
-        // Remove duplicates and limit size
-        let mut unique_history = Vec::new();
+        // Merge with existing history, removing duplicates
         let mut seen = HashSet::new();
-        for msg in enhanced_history {
-            if seen.insert(msg.clone()) {
-                unique_history.push(msg);
-            }
-        }
-
-        // Keep only the most recent 100 messages to avoid context overflow
-        if unique_history.len() > 100 {
-            unique_history = unique_history.into_iter().take(100).collect();
-        }
+        let unique_history: Vec<(String, i64)> = context
+            .author_history
+            .iter()
+            .chain(cached_messages.iter().map(|msg| &msg.message))
+            .filter(|&msg| seen.insert(msg.as_str()))
+            .map(|msg| {
+                let timestamp = timestamps.get(msg.as_str()).copied().unwrap_or(now);
+                (msg.clone(), timestamp)
+            })
+            .collect();
+
+        let similarity = SemanticSimilarity::new();
+        let change_keywords = similarity.extract_keywords(&context.staged_files);
+        let ranked = similarity.rank_with_recency(
+            &change_keywords,
+            &unique_history,
+            config.history_half_life_days,
+            now,
+        );
 
-        context.author_history = unique_history;
+        // Keep only the 100 most relevant messages to avoid context overflow
+        context.author_history = ranked
+            .into_iter()
+            .take(100)
+            .map(|(idx, _)| unique_history[idx].0.clone())
+            .collect();
 
         Ok(())
     }
@@ -451,29 +508,86 @@ impl GitRepo {
         // Get data that doesn't cross async boundaries
         let repo = self.open_repo()?;
         debug!("Getting git info for repo path: {}", repo.path().display());
+        require_workdir(&repo)?;
 
-        let branch = self.get_current_branch()?;
-        let recent_commits = self.get_recent_commits(10)?;
-        let staged_files = get_file_statuses(&repo, &self.gitignore_matcher)?;
+        let branch = self.current_branch_label()?;
+        let recent_commits = self.get_recent_commits(config.recent_commits_count)?;
+        let staged_files = get_file_statuses(
+            &repo,
+            &self.gitignore_matcher,
+            FileSizeLimits::from_config(config),
+        )?;
 
         // Create and return the context
         let mut context = self.create_commit_context(branch, recent_commits, staged_files)?;
 
+        if let Some(pattern) = config.ticket_regex() {
+            context.infer_ticket_id(pattern)?;
+        }
+
+        // Drop excluded paths, then keep huge generated or vendored diffs out of the prompt
+        context.exclude_paths(&config.exclude_paths)?;
+        context.omit_oversized_diffs(config.max_file_diff_bytes);
+
         // Filter recent commits to most relevant ones (max 4)
         context.filter_relevant_recent_commits(4);
 
-        // Enhance with cached commit messages
-        self.enhance_context_with_cache(&mut context, config)?;
+        // Enhance with cached commit messages, unless the cache is bypassed
+        if !config.no_cache {
+            self.enhance_context_with_cache(&mut context, config)?;
+        }
 
         Ok(context)
     }
 
+    /// OID of the tree the index would commit as, a fingerprint of what's staged.
+    ///
+    /// Writes the index to a tree object, as `git write-tree` does, without committing.
+    /// Staging the same content again gives the same OID.
+    pub fn get_staged_tree_oid(&self) -> Result<Oid> {
+        let repo = self.open_repo()?;
+        let oid = repo.index()?.write_tree()?;
+        Ok(oid)
+    }
+
+    /// OID of the tree the working tree would commit as if every change were staged, a
+    /// fingerprint of what's staged and unstaged together.
+    ///
+    /// Stages into an in-memory copy of the index, which is never written back, so the
+    /// real index is left alone. Untracked files count only with `include_untracked`.
+    pub fn get_working_tree_fingerprint(&self, include_untracked: bool) -> Result<Oid> {
+        let repo = self.open_repo()?;
+        let mut index = repo.index()?;
+        index.update_all(["*"], None)?;
+        if include_untracked {
+            index.add_all(["*"], IndexAddOption::DEFAULT, None)?;
+        }
+        Ok(index.write_tree()?)
+    }
+
+    /// Key under which [`ContextCache`] stores the context [`get_git_info`](Self::get_git_info)
+    /// would build now: the staged tree, the commit it sits on and the branch.
+    pub fn context_cache_key(&self, config: &Config) -> Result<String> {
+        let staged_tree = self.get_staged_tree_oid()?;
+        let repo = self.open_repo()?;
+        let head = repo.head().ok().and_then(|head| head.target());
+        let branch = self.current_branch_label()?;
+        Ok(ContextCache::key(
+            &self.repo_path,
+            staged_tree,
+            head,
+            &branch,
+            config,
+        ))
+    }
+
     /// Get Git information including unstaged changes
     ///
     /// # Arguments
     ///
     /// * `config` - The configuration object.
-    /// * `include_unstaged` - Whether to include unstaged changes.
+    /// * `include_unstaged` - Whether to include unstaged changes. Untracked files are only
+    ///   read when `config.include_untracked` is set.
     ///
     /// # Returns
     ///
@@ -487,18 +601,29 @@ impl GitRepo {
         // Get data that doesn't cross async boundaries
         let repo = self.open_repo()?;
         debug!(
-            "Getting git info for repo path: {}, include_unstaged: {}",
+            "Getting git info for repo path: {}, include_unstaged: {}, include_untracked: {}",
             repo.path().display(),
-            include_unstaged
+            include_unstaged,
+            config.include_untracked
         );
+        require_workdir(&repo)?;
 
-        let branch = self.get_current_branch()?;
-        let recent_commits = self.get_recent_commits(10)?;
-        let mut staged_files = get_file_statuses(&repo, &self.gitignore_matcher)?;
+        let branch = self.current_branch_label()?;
+        let recent_commits = self.get_recent_commits(config.recent_commits_count)?;
+        let mut staged_files = get_file_statuses(
+            &repo,
+            &self.gitignore_matcher,
+            FileSizeLimits::from_config(config),
+        )?;
 
         // Add unstaged files if requested
         if include_unstaged {
-            let unstaged_files = get_unstaged_file_statuses(&repo, &self.gitignore_matcher)?;
+            let unstaged_files = get_unstaged_file_statuses(
+                &repo,
+                &self.gitignore_matcher,
+                config.include_untracked,
+                FileSizeLimits::from_config(config),
+            )?;
             staged_files.extend(unstaged_files);
             debug!("Combined {} files (staged + unstaged)", staged_files.len());
         }
@@ -506,11 +631,21 @@ impl GitRepo {
         // Create and return the context
         let mut context = self.create_commit_context(branch, recent_commits, staged_files)?;
 
+        if let Some(pattern) = config.ticket_regex() {
+            context.infer_ticket_id(pattern)?;
+        }
+
+        // Drop excluded paths, then keep huge generated or vendored diffs out of the prompt
+        context.exclude_paths(&config.exclude_paths)?;
+        context.omit_oversized_diffs(config.max_file_diff_bytes);
+
         // Filter recent commits to most relevant ones (max 4)
         context.filter_relevant_recent_commits(4);
 
-        // Enhance with cached commit messages
-        self.enhance_context_with_cache(&mut context, config)?;
+        // Enhance with cached commit messages, unless the cache is bypassed
+        if !config.no_cache {
+            self.enhance_context_with_cache(&mut context, config)?;
+        }
 
         Ok(context)
     }
@@ -533,6 +668,11 @@ impl GitRepo {
         target_branch: &str,
     ) -> Result<CommitContext> {
         debug!("Getting git info for branch diff: {base_branch} -> {target_branch}");
+        if target_branch == "HEAD" && self.get_current_branch()?.is_none() {
+            return Err(anyhow!(
+                "You are in detached HEAD state; specify a branch to compare against {base_branch}"
+            ));
+        }
         let repo = self.open_repo()?;
 
         // Extract branch diff info
@@ -592,6 +732,12 @@ impl GitRepo {
         commit::get_commits_for_pr(&repo, from, to)
     }
 
+    /// Get the hashes of the commits in `from..to`, oldest first
+    pub fn get_commit_ids_in_range(&self, from: &str, to: &str) -> Result<Vec<String>> {
+        let repo = self.open_repo()?;
+        commit::get_commit_ids_in_range(&repo, from, to)
+    }
+
     /// Get files changed in a commit range
     pub fn get_commit_range_files(&self, from: &str, to: &str) -> Result<Vec<StagedFile>> {
         let repo = self.open_repo()?;
@@ -754,7 +900,7 @@ impl GitRepo {
         let repo = self.open_repo()?;
 
         // Get branch name
-        let branch = self.get_current_branch()?;
+        let branch = self.current_branch_label()?;
 
         // Extract commit info
         let commit_info = commit::extract_commit_info(&repo, commit_id, &branch)?;
@@ -766,6 +912,28 @@ impl GitRepo {
         self.create_commit_context(commit_info.branch, vec![commit_info.commit], commit_files)
     }
 
+    /// The `core.commentChar` setting, e.g. `;` or `auto`, if set
+    pub fn comment_char_setting(&self) -> Option<String> {
+        self.config_string("core.commentChar")
+    }
+
+    /// The `core.editor` setting, if set
+    pub fn editor_setting(&self) -> Option<String> {
+        self.config_string("core.editor")
+    }
+
+    fn config_string(&self, name: &str) -> Option<String> {
+        let repo = self.open_repo().ok()?;
+        let config = repo.config().ok()?;
+        config.get_string(name).ok()
+    }
+
+    /// Get the full message of a commit
+    pub fn get_commit_message(&self, commit_ish: &str) -> Result<String> {
+        let repo = self.open_repo()?;
+        commit::get_commit_message(&repo, commit_ish)
+    }
+
     /// Get the commit date for a reference
     pub fn get_commit_date(&self, commit_ish: &str) -> Result<String> {
         let repo = self.open_repo()?;
@@ -795,16 +963,34 @@ impl GitRepo {
         commit::get_commits_between_stream(&repo, from, to, callback)
     }
 
-    /// Commit changes to the repository
-    pub fn commit(&self, message: &str) -> Result<CommitResult> {
+    /// Commit changes to the repository, signing when `sign` is set or `commit.gpgsign` is enabled
+    pub fn commit(&self, message: &str, sign: bool) -> Result<CommitResult> {
+        let repo = self.open_repo()?;
+        commit::commit(&repo, message, self.is_remote, sign)
+    }
+
+    /// Amend a commit with a new message, signing when `sign` is set or `commit.gpgsign` is enabled
+    pub fn amend_commit(
+        &self,
+        message: &str,
+        commit_ref: &str,
+        sign: bool,
+    ) -> Result<CommitResult> {
         let repo = self.open_repo()?;
-        commit::commit(&repo, message, self.is_remote)
+        commit::amend_commit(&repo, message, commit_ref, self.is_remote, sign)
     }
 
-    /// Amend a commit with a new message
-    pub fn amend_commit(&self, message: &str, commit_ref: &str) -> Result<CommitResult> {
+    /// Stage modifications and deletions of tracked files, like `git add -u`
+    pub fn stage_tracked_changes(&self) -> Result<()> {
+        if self.is_remote {
+            return Err(anyhow!("Cannot stage changes in a remote repository"));
+        }
+
         let repo = self.open_repo()?;
-        commit::amend_commit(&repo, message, commit_ref, self.is_remote)
+        let mut index = repo.index()?;
+        index.update_all(["*"], None)?;
+        index.write()?;
+        Ok(())
     }
 
     /// Check if inside a working tree
@@ -0,0 +1,93 @@
+use anyhow::Result;
+use git2::{FileMode, Oid, Repository, Sort};
+use std::fmt::Write;
+use std::path::Path;
+
+/// Most submodule commits listed for one pointer change; the rest are only counted as more
+const MAX_SUBMODULE_COMMITS: usize = 20;
+
+/// Summary standing in for the diff of a staged submodule pointer change at `path`.
+///
+/// A gitlink diff is a bare `Subproject commit` line, so this gives the old and new short
+/// hashes instead, plus the subjects of the commits in between when the submodule is
+/// checked out, in the style of `git diff --submodule=log`. Returns `None` when `path`
+/// isn't a submodule on either side.
+pub fn submodule_change_diff(repo: &Repository, path: &str) -> Result<Option<String>> {
+    let old = head_gitlink(repo, path);
+    let new = repo
+        .index()?
+        .get_path(Path::new(path), 0)
+        .filter(|entry| entry.mode == u32::from(FileMode::Commit))
+        .map(|entry| entry.id);
+    if old.is_none() && new.is_none() {
+        return Ok(None);
+    }
+
+    let short = |oid: Option<Oid>| oid.map_or_else(|| "0000000".to_string(), short_hash);
+    let mut summary = format!("Submodule {path} {}..{}", short(old), short(new));
+    let (Some(old), Some(new)) = (old, new) else {
+        summary.push_str(if old.is_none() {
+            " (new submodule)"
+        } else {
+            " (submodule deleted)"
+        });
+        return Ok(Some(summary));
+    };
+
+    let Some(submodule) = repo
+        .find_submodule(path)
+        .and_then(|submodule| submodule.open())
+        .ok()
+    else {
+        summary.push_str(" (not checked out, commits not listed)");
+        return Ok(Some(summary));
+    };
+
+    summary.push(':');
+    match commit_subjects(&submodule, old, new) {
+        Ok((subjects, more)) => {
+            for subject in subjects {
+                let _ = write!(summary, "\n  > {subject}");
+            }
+            if more {
+                let _ = write!(
+                    summary,
+                    "\n  > ... and later commits, not shown past the first {MAX_SUBMODULE_COMMITS}"
+                );
+            }
+        }
+        Err(_) => summary.push_str("\n  (commits not found in the checked out submodule)"),
+    }
+    Ok(Some(summary))
+}
+
+/// Commit the submodule at `path` points to in HEAD, if it is one there
+fn head_gitlink(repo: &Repository, path: &str) -> Option<Oid> {
+    let tree = repo.head().ok()?.peel_to_tree().ok()?;
+    let entry = tree.get_path(Path::new(path)).ok()?;
+    (entry.filemode() == i32::from(FileMode::Commit)).then_some(entry.id())
+}
+
+/// Subjects of the commits in `old..new`, oldest first, and whether any were left out
+fn commit_subjects(submodule: &Repository, old: Oid, new: Oid) -> Result<(Vec<String>, bool)> {
+    let mut revwalk = submodule.revwalk()?;
+    revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)?;
+    revwalk.push(new)?;
+    revwalk.hide(old)?;
+
+    let mut subjects = Vec::new();
+    let mut more = false;
+    for oid in revwalk {
+        if subjects.len() == MAX_SUBMODULE_COMMITS {
+            more = true;
+            break;
+        }
+        let commit = submodule.find_commit(oid?)?;
+        subjects.push(commit.summary().unwrap_or_default().to_string());
+    }
+    Ok((subjects, more))
+}
+
+fn short_hash(oid: Oid) -> String {
+    oid.to_string()[..7].to_string()
+}
@@ -1,5 +1,38 @@
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 use git2::Repository;
+use regex::Regex;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+// Regex for git's `Binary files a/... and b/... differ` line
+static BINARY_FILES_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?m)^\s?Binary files (?:a/\S.*|/dev/null) and (?:b/\S.*|/dev/null) differ\s*$")
+        .expect("Failed to compile binary files regex pattern - this is a bug")
+});
+
+/// Extended header lines git emits between `diff --git` and the first hunk
+const EXTENDED_HEADER_PREFIXES: &[&str] = &[
+    "diff --git ",
+    "index ",
+    "old mode ",
+    "new mode ",
+    "deleted file mode ",
+    "new file mode ",
+    "similarity index ",
+    "dissimilarity index ",
+    "rename from ",
+    "rename to ",
+    "copy from ",
+    "copy to ",
+    "--- ",
+    "+++ ",
+];
+
+/// Share of control or undecodable characters above which a hunk body is treated as binary
+const BINARY_CHAR_RATIO: f64 = 0.1;
+
+/// Minimum hunk body length before the character ratio check applies
+const MIN_BINARY_SAMPLE_CHARS: usize = 32;
 
 /// Checks if the current directory is inside a Git work tree.
 ///
@@ -13,9 +46,137 @@ pub fn is_inside_work_tree() -> Result<bool> {
     }
 }
 
+/// Top-level directory of the repository containing `path`.
+///
+/// For a linked worktree this is the worktree's own checkout, not the main one; a bare
+/// repository resolves to its git directory. Falls back to `path` itself outside a
+/// repository, leaving the error to whatever opens it.
+pub fn resolve_repo_root(path: &Path) -> PathBuf {
+    Repository::discover(path).map_or_else(
+        |_| path.to_path_buf(),
+        |repo| {
+            repo.workdir()
+                .map_or_else(|| repo.path().to_path_buf(), Path::to_path_buf)
+        },
+    )
+}
+
+/// Working tree of `repo`, or an error explaining that a bare repository has none
+pub fn require_workdir(repo: &Repository) -> Result<&Path> {
+    repo.workdir().ok_or_else(|| {
+        anyhow!(
+            "{} is a bare repository, which has no working tree: run this from a checkout or a linked worktree",
+            repo.path().display()
+        )
+    })
+}
+
 /// Determines if the given diff represents a binary file.
+///
+/// Besides git's own binary markers, this treats a diff as binary when a `diff --git`
+/// header is followed by content but no `@@` hunks (which catches binary notices in any
+/// locale), or when the hunk bodies are dominated by control or undecodable characters.
 pub fn is_binary_diff(diff: &str) -> bool {
-    diff.contains("Binary files")
-        || diff.contains("GIT binary patch")
+    diff.contains("GIT binary patch")
         || diff.contains("[Binary file changed]")
+        || BINARY_FILES_RE.is_match(diff)
+        || has_header_without_hunks(diff)
+        || has_binary_hunk_content(diff)
+}
+
+/// Whether a `diff --git` section carries non-header content but no `@@` hunks
+fn has_header_without_hunks(diff: &str) -> bool {
+    let mut in_section = false;
+    let mut has_hunk = false;
+    let mut has_content = false;
+
+    for line in diff.lines().map(str::trim_start) {
+        if line.starts_with("diff --git ") {
+            if in_section && has_content && !has_hunk {
+                return true;
+            }
+            in_section = true;
+            has_hunk = false;
+            has_content = false;
+        } else if !in_section || has_hunk {
+            continue;
+        } else if line.starts_with("@@") {
+            has_hunk = true;
+        } else if !line.is_empty()
+            && !EXTENDED_HEADER_PREFIXES
+                .iter()
+                .any(|prefix| line.starts_with(prefix))
+        {
+            has_content = true;
+        }
+    }
+
+    in_section && has_content && !has_hunk
+}
+
+/// Whether the hunk bodies contain a high ratio of control or undecodable characters
+fn has_binary_hunk_content(diff: &str) -> bool {
+    let body = diff.find("@@").map_or("", |start| &diff[start..]);
+
+    let mut total = 0usize;
+    let mut suspicious = 0usize;
+    for c in body.chars() {
+        total += 1;
+        if c == char::REPLACEMENT_CHARACTER || (c.is_control() && !matches!(c, '\n' | '\r' | '\t'))
+        {
+            suspicious += 1;
+        }
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    let ratio = suspicious as f64 / total.max(1) as f64;
+    total >= MIN_BINARY_SAMPLE_CHARS && ratio > BINARY_CHAR_RATIO
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_png_content_is_binary() {
+        let png: &[u8] = &[
+            0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, b'I', b'H',
+            b'D', b'R', 0x00, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00, 0x10, 0x08, 0x06, 0x00, 0x00,
+            0x00, 0x1f, 0xf3, 0xff, 0x61, 0x00, 0x00, 0x00, 0x04, 0x67, 0x41, 0x4d, 0x41, 0x00,
+        ];
+        let diff = format!(
+            "diff --git a/logo.png b/logo.png\n@@ -0,0 +1 @@\n+{}\n",
+            String::from_utf8_lossy(png)
+        );
+
+        assert!(is_binary_diff(&diff));
+        assert!(is_binary_diff(
+            "diff --git a/logo.png b/logo.png\nBinary files a/logo.png and b/logo.png differ\n"
+        ));
+    }
+
+    #[test]
+    fn test_translated_binary_message_is_binary() {
+        let diff = "diff --git a/logo.png b/logo.png\n\
+                    index 1234567..89abcde 100644\n\
+                    Binärdateien a/logo.png und b/logo.png sind verschieden.\n";
+
+        assert!(is_binary_diff(diff));
+    }
+
+    #[test]
+    fn test_text_diff_is_not_binary() {
+        let diff = "diff --git a/src/lib.rs b/src/lib.rs\n\
+                    index 1234567..89abcde 100644\n\
+                    --- a/src/lib.rs\n\
+                    +++ b/src/lib.rs\n\
+                    @@ -1,3 +1,4 @@\n\
+                    \x20fn main() {\n\
+                    +\tprintln!(\"Binary files are handled elsewhere – ok\");\n\
+                    \x20}\n";
+        let mode_change = "diff --git a/run.sh b/run.sh\nold mode 100644\nnew mode 100755\n";
+
+        assert!(!is_binary_diff(diff));
+        assert!(!is_binary_diff(mode_change));
+    }
 }
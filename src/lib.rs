@@ -8,8 +8,10 @@ pub mod app;
     clippy::missing_panics_doc
 )]
 pub mod common;
+pub mod completions;
 pub mod config;
 pub mod core;
+pub mod error;
 pub mod features;
 pub mod git;
 pub mod remote;
@@ -31,5 +33,6 @@ pub use features::commit::types::{
 
 // Re-exports from wire
 pub use remote::{
-    CacheManager, CachedRepository, RepositoryConfiguration, WireOperation, init_logger,
+    CacheManager, CachedRepository, RepositoryConfiguration, RepositoryConfigurationBuilder,
+    WireOperation, init_logger, init_logger_with,
 };
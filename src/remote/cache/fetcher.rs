@@ -1,46 +1,70 @@
 use crate::remote::models::repo_config::RepositoryConfiguration;
 
-use super::super::common::{ErrorType, Method};
+use super::super::common::{
+    ErrorType, Method,
+    command::{clone_repository, git_failure, git_output, stderr_summary},
+    fetch::partial_clone_args,
+};
 use cause::{Cause, cause};
 use git2::Repository;
+use log::{debug, info};
+use std::path::Path;
 use std::process::Command;
 
+/// Length of a full SHA-1 commit hash
+const FULL_HASH_LEN: usize = 40;
+/// Shortest abbreviation git accepts
+const MIN_HASH_LEN: usize = 4;
+
 #[derive(Clone)]
 pub struct RepositoryFetcher;
 
 impl RepositoryFetcher {
     /// Fetch a repository to a temporary directory
-    /// This is a wrapper around the existing fetch functionality with caching logic
+    /// This is a wrapper around the existing fetch functionality with caching logic.
+    /// Returns `true` when the cached copy was reused instead of fetched.
     pub async fn fetch_repository(
         &self,
         config: &RepositoryConfiguration,
         cache_path: &str,
-    ) -> Result<(), Cause<ErrorType>> {
+    ) -> Result<bool, Cause<ErrorType>> {
         let config = config.clone();
         let cache_path = cache_path.to_string();
 
         // Check if the repository is already cached and up-to-date
         if Self::is_cache_valid(&config, &cache_path) {
-            println!("Using cached repository: {}", config.url);
-            return Ok(());
+            info!("Using cached repository: {}", config.url);
+            return Ok(true);
         }
 
-        println!("Fetching repository: {} to cache", config.url);
+        info!("Fetching repository: {} to cache", config.url);
 
         // Wrap blocking operations in spawn_blocking
         let cache_path_clone = cache_path.clone();
         tokio::task::spawn_blocking(move || {
             Self::execute_git_clone(&config, &cache_path_clone)?;
-            if !matches!(config.mtd, Some(Method::ShallowNoSparse)) {
-                Self::execute_git_checkout(&cache_path_clone, &config.branch)?;
+            let rev = config.commit_hash.as_deref().unwrap_or(&config.branch);
+            match &config.mtd {
+                // The shallow clone only has the branch tip, so fetch a pinned commit first
+                Some(Method::ShallowNoSparse) => {
+                    if let Some(commit) = &config.commit_hash {
+                        Self::execute_git_cli_fetch(&config, &cache_path_clone, commit)?;
+                        Self::execute_git_cli_checkout(&config, &cache_path_clone, "FETCH_HEAD")?;
+                    }
+                }
+                // libgit2 can't fetch the blobs a filtered clone left out, so use the git CLI
+                Some(Method::Partial { .. }) => {
+                    Self::execute_git_cli_checkout(&config, &cache_path_clone, rev)?;
+                }
+                _ => Self::execute_git_checkout(&cache_path_clone, rev)?,
             }
             Ok(())
         })
         .await
-        .map_err(|e| cause!(ErrorType::GitCloneCommand).msg(format!("Task join error: {e:?}")))??;
+        .map_err(|e| cause!(ErrorType::TaskJoin).msg(format!("Task join error: {e:?}")))??;
 
-        println!("Repository fetched and cached at: {cache_path}");
-        Ok(())
+        debug!("Repository fetched and cached at: {cache_path}");
+        Ok(false)
     }
 
     /// Execute the git clone command with error handling
@@ -54,10 +78,41 @@ impl RepositoryFetcher {
                 .map_err(|e| cause!(ErrorType::GitCloneCommand).src(e))?;
         }
 
-        if matches!(config.mtd, Some(Method::ShallowNoSparse)) {
+        if let Some(args) = Self::git_clone_args(config, cache_path)? {
+            let output = git_output(
+                Command::new("git").args(&args),
+                &config.url,
+                config.token_env.as_deref(),
+                ErrorType::GitCloneCommand,
+            )?;
+            if !output.status.success() {
+                return Err(git_failure(
+                    ErrorType::GitCloneCommandExitStatus,
+                    "git clone",
+                    &config.url,
+                    &output,
+                ));
+            }
+        } else {
+            clone_repository(
+                &config.url,
+                config.token_env.as_deref(),
+                Path::new(cache_path),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Arguments for a clone that needs extra git options, or `None` for a plain clone
+    fn git_clone_args(
+        config: &RepositoryConfiguration,
+        cache_path: &str,
+    ) -> Result<Option<Vec<String>>, Cause<ErrorType>> {
+        match &config.mtd {
             // Use git command for shallow clone with branch
-            let output = Command::new("git")
-                .args([
+            Some(Method::ShallowNoSparse) => Ok(Some(
+                [
                     "clone",
                     "--depth",
                     "1",
@@ -65,17 +120,141 @@ impl RepositoryFetcher {
                     &config.branch,
                     &config.url,
                     cache_path,
-                ])
-                .output()
-                .map_err(|e| cause!(ErrorType::GitCloneCommand).src(e))?;
-            if !output.status.success() {
-                return Err(
-                    cause!(ErrorType::GitCloneCommand).msg(String::from_utf8_lossy(&output.stderr))
-                );
+                ]
+                .map(String::from)
+                .to_vec(),
+            )),
+            Some(method @ Method::Partial { .. }) => {
+                let filter = method.partial_filter().unwrap_or_default();
+                partial_clone_args(filter, &config.url, cache_path).map(Some)
             }
+            Some(Method::Shallow) | None => Ok(None),
+        }
+    }
+
+    /// Fetch a single commit into a shallow clone
+    fn execute_git_cli_fetch(
+        config: &RepositoryConfiguration,
+        cache_path: &str,
+        commit: &str,
+    ) -> Result<(), Cause<ErrorType>> {
+        let output = git_output(
+            Command::new("git").args(["-C", cache_path, "fetch", "--depth", "1", "origin", commit]),
+            &config.url,
+            config.token_env.as_deref(),
+            ErrorType::GitFetchCommand,
+        )?;
+        if !output.status.success() {
+            return Err(git_failure(
+                ErrorType::GitFetchCommandExitStatus,
+                "git fetch",
+                &config.url,
+                &output,
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Clone just the commit graph of `url` into `path`, enough to resolve commit hashes.
+    ///
+    /// `token_env` names the environment variable holding the access token for `url`.
+    pub fn clone_for_resolving(
+        url: &str,
+        token_env: Option<&str>,
+        path: &Path,
+    ) -> Result<(), Cause<ErrorType>> {
+        let output = git_output(
+            Command::new("git")
+                .args(["clone", "--bare", "--quiet", "--filter=tree:0", url])
+                .arg(path),
+            url,
+            token_env,
+            ErrorType::GitCloneCommand,
+        )?;
+        if !output.status.success() {
+            return Err(git_failure(
+                ErrorType::GitCloneCommandExitStatus,
+                "git clone",
+                url,
+                &output,
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Whether `hash` is a full 40-character commit hash
+    pub fn is_full_commit_hash(hash: &str) -> bool {
+        hash.len() == FULL_HASH_LEN && hash.bytes().all(|b| b.is_ascii_hexdigit())
+    }
+
+    /// Resolve a full or abbreviated commit hash against the repository at `repo_path`
+    /// with `git rev-parse`, erroring if it is ambiguous or doesn't name a commit.
+    pub fn resolve_commit_hash(repo_path: &Path, hash: &str) -> Result<String, Cause<ErrorType>> {
+        if !(MIN_HASH_LEN..=FULL_HASH_LEN).contains(&hash.len())
+            || !hash.bytes().all(|b| b.is_ascii_hexdigit())
+        {
+            return Err(cause!(
+                ErrorType::CommitHashInvalid,
+                format!(
+                    "'{hash}' is not a commit hash (expected {MIN_HASH_LEN} to {FULL_HASH_LEN} hex digits)"
+                )
+            ));
+        }
+
+        let output = git_output(
+            Command::new("git")
+                .arg("-C")
+                .arg(repo_path)
+                .args(["rev-parse", "--verify"])
+                .arg(format!("{hash}^{{commit}}")),
+            &repo_path.to_string_lossy(),
+            None,
+            ErrorType::GitCheckoutCommand,
+        )?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let resolved = stdout.trim();
+        if output.status.success() && Self::is_full_commit_hash(resolved) {
+            return Ok(resolved.to_lowercase());
+        }
+
+        if String::from_utf8_lossy(&output.stderr).contains("ambiguous") {
+            Err(cause!(
+                ErrorType::CommitHashAmbiguous,
+                format!("Commit hash '{hash}' is ambiguous; use more characters")
+            ))
         } else {
-            Repository::clone(&config.url, cache_path)
-                .map_err(|e| cause!(ErrorType::GitCloneCommand).src(e))?;
+            Err(cause!(
+                ErrorType::CommitHashNotFound,
+                format!(
+                    "Commit hash '{hash}' was not found in the repository\n{}",
+                    stderr_summary(&output.stderr)
+                )
+            ))
+        }
+    }
+
+    /// Check out `rev` with the git CLI, which fetches missing blobs on demand
+    fn execute_git_cli_checkout(
+        config: &RepositoryConfiguration,
+        cache_path: &str,
+        rev: &str,
+    ) -> Result<(), Cause<ErrorType>> {
+        let output = git_output(
+            Command::new("git").args(["-C", cache_path, "checkout", "--detach", rev]),
+            &config.url,
+            config.token_env.as_deref(),
+            ErrorType::GitCheckoutCommand,
+        )?;
+        if !output.status.success() {
+            return Err(git_failure(
+                ErrorType::GitCheckoutCommandExitStatus,
+                "git checkout",
+                &config.url,
+                &output,
+            ));
         }
 
         Ok(())
@@ -103,8 +282,8 @@ impl RepositoryFetcher {
 
     /// Check if the cached repository is still valid (up-to-date)
     fn is_cache_valid(_config: &RepositoryConfiguration, cache_path: &str) -> bool {
-        // Check if the directory exists
-        std::path::Path::new(cache_path).exists()
+        // An empty directory left by an interrupted or discarded clone doesn't count
+        Repository::open(cache_path).is_ok()
     }
 }
 
@@ -134,4 +313,108 @@ mod tests {
         // It should return false since the path doesn't exist
         assert!(!result);
     }
+
+    fn commit_empty(repo: &Repository, message: &str) -> git2::Oid {
+        let signature =
+            git2::Signature::now("Test", "test@example.com").expect("Failed to create signature");
+        let tree_id = repo
+            .treebuilder(None)
+            .and_then(|builder| builder.write())
+            .expect("Failed to write tree");
+        let tree = repo.find_tree(tree_id).expect("Failed to find tree");
+        let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+        let parents: Vec<_> = parent.iter().collect();
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parents,
+        )
+        .expect("Failed to commit")
+    }
+
+    #[test]
+    fn test_resolve_short_commit_hash() {
+        let dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let repo = Repository::init(dir.path()).expect("Failed to init repo");
+        let full = commit_empty(&repo, "initial").to_string();
+
+        let resolved = RepositoryFetcher::resolve_commit_hash(dir.path(), &full[..7])
+            .expect("short hash should resolve");
+        assert_eq!(resolved, full);
+        assert!(RepositoryFetcher::is_full_commit_hash(&resolved));
+
+        let err = RepositoryFetcher::resolve_commit_hash(dir.path(), "0000000")
+            .expect_err("nonexistent hash");
+        assert!(err.to_string().contains("not found"));
+
+        assert!(RepositoryFetcher::resolve_commit_hash(dir.path(), "not-a-hash").is_err());
+    }
+
+    #[test]
+    fn test_resolve_ambiguous_commit_hash() {
+        let dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let repo = Repository::init(dir.path()).expect("Failed to init repo");
+
+        // Commit until two commits share a 4-character prefix
+        let mut seen = std::collections::HashSet::new();
+        let prefix = (0..20_000)
+            .find_map(|i| {
+                let id = commit_empty(&repo, &format!("commit {i}")).to_string();
+                let prefix = id[..MIN_HASH_LEN].to_string();
+                (!seen.insert(prefix.clone())).then_some(prefix)
+            })
+            .expect("a shared prefix should turn up");
+
+        let err = RepositoryFetcher::resolve_commit_hash(dir.path(), &prefix)
+            .expect_err("shared prefix should be ambiguous");
+        assert!(err.to_string().contains("ambiguous"));
+    }
+
+    #[test]
+    fn test_clone_failure_surfaces_git_stderr() {
+        let dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let missing = dir.path().join("missing.git");
+        let url = missing.to_string_lossy();
+
+        let err =
+            RepositoryFetcher::clone_for_resolving(&url, None, &dir.path().join("resolver.git"))
+                .expect_err("cloning a nonexistent remote fails");
+        let message = err.to_string();
+        assert!(message.contains("git clone failed"));
+        assert!(message.contains("fatal:"));
+        assert!(message.contains("does not exist"));
+    }
+
+    #[test]
+    fn test_partial_clone_uses_configured_filter() {
+        let mut config = RepositoryConfiguration::new(
+            "https://github.com/example/repo.git".to_string(),
+            "main".to_string(),
+            "./src/module1".to_string(),
+            vec!["src/".to_string()],
+            None,
+            Some(Method::Partial {
+                filter: Some("blob:limit=1m".to_string()),
+            }),
+        );
+
+        let args = RepositoryFetcher::git_clone_args(&config, "/tmp/cache")
+            .expect("valid filter")
+            .expect("partial clones use the git CLI");
+        assert!(args.contains(&"--filter=blob:limit=1m".to_string()));
+
+        config.mtd = Some(Method::Partial { filter: None });
+        let args = RepositoryFetcher::git_clone_args(&config, "/tmp/cache")
+            .expect("default filter")
+            .expect("partial clones use the git CLI");
+        assert!(args.contains(&"--filter=blob:none".to_string()));
+
+        config.mtd = Some(Method::Partial {
+            filter: Some("blob:everything".to_string()),
+        });
+        assert!(RepositoryFetcher::git_clone_args(&config, "/tmp/cache").is_err());
+    }
 }
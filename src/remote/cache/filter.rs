@@ -1,5 +1,7 @@
 use std::{fs, path::Path};
 
+use log::warn;
+
 pub struct RepositoryFilter;
 
 impl RepositoryFilter {
@@ -51,7 +53,7 @@ impl RepositoryFilter {
             }
         } else {
             // The filter path doesn't exist in the source; skip it.
-            eprintln!("Warning: Filter path '{filter_path}' does not exist in source repository");
+            warn!("Filter path '{filter_path}' does not exist in source repository");
         }
 
         Ok(())
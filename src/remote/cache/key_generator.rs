@@ -14,7 +14,8 @@ impl CacheKeyGenerator {
         config.url.hash(&mut hasher);
         config.branch.hash(&mut hasher);
 
-        // If commit hash is specified, include it in the key
+        // If commit hash is specified, include it in the key. Sync expands abbreviated
+        // hashes first, so every spelling of a commit maps to the same key.
         if let Some(ref commit) = config.commit_hash {
             commit.hash(&mut hasher);
         }
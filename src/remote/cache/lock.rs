@@ -1,13 +1,18 @@
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 
+use tokio::sync::{Mutex as AsyncMutex, OwnedMutexGuard};
+
 // Type alias for repository URL
 type RepoUrl = String;
 
+/// Exclusive hold on one repository URL, released when dropped
+pub type RepositoryLock = OwnedMutexGuard<()>;
+
 #[derive(Default)]
 pub struct RepositoryLockManager {
     // Tracks locks for each repository
-    locks: Arc<Mutex<HashMap<RepoUrl, Arc<Mutex<bool>>>>>,
+    locks: Arc<Mutex<HashMap<RepoUrl, Arc<AsyncMutex<()>>>>>,
 }
 
 impl RepositoryLockManager {
@@ -17,60 +22,23 @@ impl RepositoryLockManager {
         }
     }
 
-    /// Acquire a lock for a specific repository, blocking until available
-    pub fn acquire_lock(&self, repo_url: &str) -> Result<(), String> {
+    /// The lock for `repo_url`, created on first use
+    fn lock_for(&self, repo_url: &str) -> Arc<AsyncMutex<()>> {
         let mut locks = self
             .locks
             .lock()
             .expect("Failed to acquire global lock for repository locks");
-
-        // Check if we already have a lock for this URL
-        let repo_lock = locks
-            .entry(repo_url.to_string())
-            .or_insert_with(|| Arc::new(Mutex::new(false)));
-
-        // Clone the Arc to use for locking
-        let lock_clone = Arc::clone(repo_lock);
-        drop(locks); // Release the global lock
-
-        // Acquire the specific repository lock
-        let guard = lock_clone
-            .lock()
-            .unwrap_or_else(|_| panic!("Failed to acquire repository lock for URL: {repo_url}"));
-
-        // Hold the lock for the duration of the function, then release it
-        // In a real implementation, you'd want to return a guard that manages the lock lifetime
-        // For now, just simulate the lock being held briefly
-        std::mem::drop(guard);
-
-        Ok(())
+        Arc::clone(locks.entry(repo_url.to_string()).or_default())
     }
 
-    /// Try to acquire a lock for a specific repository without blocking
-    pub fn try_acquire_lock(&self, repo_url: &str) -> Result<bool, String> {
-        let mut locks = self
-            .locks
-            .lock()
-            .expect("Failed to acquire global lock for repository locks");
-
-        // Check if we already have a lock for this URL
-        let repo_lock = locks
-            .entry(repo_url.to_string())
-            .or_insert_with(|| Arc::new(Mutex::new(false)));
-
-        // Clone the Arc to use for locking
-        let lock_clone = Arc::clone(repo_lock);
-        drop(locks); // Release the global lock
+    /// Wait until no one else holds `repo_url`, then hold it until the returned guard drops
+    pub async fn acquire_lock(&self, repo_url: &str) -> RepositoryLock {
+        self.lock_for(repo_url).lock_owned().await
+    }
 
-        // Try to acquire the specific repository lock
-        match lock_clone.try_lock() {
-            Ok(guard) => {
-                // Successfully acquired the lock
-                std::mem::drop(guard); // Release immediately for this simplified version
-                Ok(true)
-            }
-            Err(_) => Ok(false), // Lock is already held by another thread
-        }
+    /// Hold `repo_url` if it is free, without waiting
+    pub fn try_acquire_lock(&self, repo_url: &str) -> Option<RepositoryLock> {
+        self.lock_for(repo_url).try_lock_owned().ok()
     }
 }
 
@@ -91,13 +59,22 @@ mod tests {
         );
     }
 
-    #[test]
-    fn test_acquire_lock() {
+    #[tokio::test]
+    async fn test_acquire_lock() {
         let lock_manager = RepositoryLockManager::new();
         let repo_url = "https://github.com/example/repo.git";
 
-        let result = lock_manager.acquire_lock(repo_url);
-        assert!(result.is_ok());
+        let guard = lock_manager.acquire_lock(repo_url).await;
+        // The lock is held until the guard drops, for this URL only
+        assert!(lock_manager.try_acquire_lock(repo_url).is_none());
+        assert!(
+            lock_manager
+                .try_acquire_lock("https://github.com/other/repo.git")
+                .is_some()
+        );
+
+        drop(guard);
+        assert!(lock_manager.try_acquire_lock(repo_url).is_some());
     }
 
     #[test]
@@ -107,9 +84,8 @@ mod tests {
 
         // Initially should be able to acquire
         let result = lock_manager.try_acquire_lock(repo_url);
-        assert!(result.is_ok());
         assert!(
-            result.expect("Failed to unwrap try_acquire_lock result"),
+            result.is_some(),
             "Expected to acquire lock successfully on first attempt"
         );
     }
@@ -1,7 +1,18 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 
+use cause::{Cause, cause};
+use git2::Repository;
+use log::debug;
+use serde::{Deserialize, Serialize};
+
+use super::fetcher::RepositoryFetcher;
 use super::key_generator::CacheKeyGenerator;
+use super::lock::RepositoryLockManager;
+use crate::remote::common::ErrorType;
 use crate::remote::models::{
     cached_repo::CachedRepository, repo_config::RepositoryConfiguration,
     wire_operation::WireOperation,
@@ -9,16 +20,103 @@ use crate::remote::models::{
 
 type CacheKey = String;
 
+/// Manifest file kept next to the cached clones
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Version written to new manifests; manifests with another version are ignored
+const MANIFEST_VERSION: u32 = 1;
+
+/// Serialized [`CacheManager`] state, so CI can carry cached clones between runs
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CacheManifest {
+    pub version: u32,
+    /// Cached clones by cache key, with the commit each was checked out at
+    pub repositories: BTreeMap<CacheKey, CachedRepository>,
+}
+
+/// Outcome of [`CacheManager::restore_manifest`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ManifestRestore {
+    /// Keys whose clone is still in place at the recorded commit
+    pub reused: HashSet<CacheKey>,
+    /// Keys whose clone is missing or has moved, and must be fetched again
+    pub stale: HashSet<CacheKey>,
+}
+
+/// How [`CacheManager::fetch`] calls were served
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheMetrics {
+    /// Repositories pulled from their remote
+    pub unique_pulls: usize,
+    /// Fetches answered by a clone already in the cache
+    pub cache_hits: usize,
+}
+
 #[derive(Default)]
 pub struct CacheManager {
     // Maps cache key (hash of URL + branch + optional commit) to cached repository info
     cache: Arc<Mutex<HashMap<CacheKey, CachedRepository>>>,
+    // Serializes fetches of the same URL
+    locks: RepositoryLockManager,
+    unique_pulls: AtomicUsize,
+    cache_hits: AtomicUsize,
 }
 
 impl CacheManager {
     pub fn new() -> Self {
         Self {
             cache: Arc::new(Mutex::new(HashMap::new())),
+            locks: RepositoryLockManager::new(),
+            unique_pulls: AtomicUsize::new(0),
+            cache_hits: AtomicUsize::new(0),
+        }
+    }
+
+    /// Make sure `cache_dir` holds a clone for `config`, pulling it only if it isn't there.
+    ///
+    /// Fetches of the same URL wait for each other, so concurrent entries sharing a
+    /// repository pull it once and then all read from that clone. A `stale` clone is
+    /// discarded and pulled again. Returns the clone's path.
+    pub async fn fetch(
+        &self,
+        config: &RepositoryConfiguration,
+        cache_dir: &Path,
+        stale: bool,
+    ) -> Result<String, Cause<ErrorType>> {
+        let _lock = self.locks.acquire_lock(&config.url).await;
+
+        if stale && cache_dir.exists() {
+            debug!("Discarding stale cached clone {}", cache_dir.display());
+            fs::remove_dir_all(cache_dir).map_err(|e| cause!(ErrorType::TempDirCreation).src(e))?;
+        }
+        fs::create_dir_all(cache_dir).map_err(|e| cause!(ErrorType::TempDirCreation).src(e))?;
+        let cache_path = cache_dir.to_string_lossy().to_string();
+
+        debug!(
+            "Fetching repository {} to cache path {}",
+            config.url, cache_path
+        );
+        let reused = RepositoryFetcher
+            .fetch_repository(config, &cache_path)
+            .await?;
+        let counter = if reused {
+            &self.cache_hits
+        } else {
+            &self.unique_pulls
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+
+        if let Err(e) = self.record_fetch(config, &cache_path) {
+            debug!("Not recording {} in the cache manifest: {e}", config.url);
+        }
+        Ok(cache_path)
+    }
+
+    /// Pulls and cache hits of every [`fetch`](Self::fetch) so far
+    pub fn metrics(&self) -> CacheMetrics {
+        CacheMetrics {
+            unique_pulls: self.unique_pulls.load(Ordering::Relaxed),
+            cache_hits: self.cache_hits.load(Ordering::Relaxed),
         }
     }
 
@@ -58,7 +156,9 @@ impl CacheManager {
 
     /// Get a unique cache path for a given cache key.
     fn get_cache_path(key: &str) -> Result<String, String> {
-        let cache_dir = std::env::temp_dir().join("git-wire-cache").join(key);
+        let cache_dir = crate::core::cache::repos_dir()
+            .map_err(|e| format!("Failed to locate cache directory: {e}"))?
+            .join(key);
 
         std::fs::create_dir_all(&cache_dir)
             .map_err(|e| format!("Failed to create cache directory: {e}"))?;
@@ -66,6 +166,92 @@ impl CacheManager {
         Ok(cache_dir.to_string_lossy().to_string())
     }
 
+    /// Record the commit a freshly fetched or reused clone is checked out at
+    pub fn record_fetch(
+        &self,
+        config: &RepositoryConfiguration,
+        cache_path: &str,
+    ) -> Result<(), String> {
+        let commit_hash = clone_head(cache_path)
+            .ok_or_else(|| format!("No checked-out commit in cached clone {cache_path}"))?;
+        let key = CacheKeyGenerator::generate_key(config);
+        self.cache
+            .lock()
+            .expect("Failed to lock cache mutex, likely due to a poisoned mutex (another thread panicked while holding the lock)")
+            .insert(
+                key,
+                CachedRepository::new(
+                    config.url.clone(),
+                    config.branch.clone(),
+                    cache_path.to_string(),
+                    commit_hash,
+                ),
+            );
+        Ok(())
+    }
+
+    /// Write every fetched clone to a manifest at `path`.
+    ///
+    /// Entries only scheduled for fetching, which have no commit yet, are left out.
+    pub fn save_manifest(&self, path: &Path) -> Result<(), String> {
+        let repositories = self
+            .cache
+            .lock()
+            .expect("Failed to lock cache mutex, likely due to a poisoned mutex (another thread panicked while holding the lock)")
+            .iter()
+            .filter(|(_, repo)| RepositoryFetcher::is_full_commit_hash(&repo.commit_hash))
+            .map(|(key, repo)| (key.clone(), repo.clone()))
+            .collect();
+        let manifest = CacheManifest {
+            version: MANIFEST_VERSION,
+            repositories,
+        };
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create manifest directory: {e}"))?;
+        }
+        let json = serde_json::to_string_pretty(&manifest)
+            .map_err(|e| format!("Failed to serialize cache manifest: {e}"))?;
+        fs::write(path, json).map_err(|e| format!("Failed to write cache manifest: {e}"))
+    }
+
+    /// Load the clones recorded in the manifest at `path`.
+    ///
+    /// A clone is reused only if its directory still opens as a repository checked out at
+    /// the recorded commit; anything else is reported stale and not loaded, so it gets
+    /// fetched again. A missing manifest restores nothing.
+    pub fn restore_manifest(&self, path: &Path) -> Result<ManifestRestore, String> {
+        if !path.exists() {
+            return Ok(ManifestRestore::default());
+        }
+        let json =
+            fs::read_to_string(path).map_err(|e| format!("Failed to read cache manifest: {e}"))?;
+        let manifest: CacheManifest = serde_json::from_str(&json)
+            .map_err(|e| format!("Failed to parse cache manifest: {e}"))?;
+        if manifest.version != MANIFEST_VERSION {
+            return Err(format!(
+                "Unsupported cache manifest version {} (expected {MANIFEST_VERSION})",
+                manifest.version
+            ));
+        }
+
+        let mut restore = ManifestRestore::default();
+        let mut cache = self
+            .cache
+            .lock()
+            .expect("Failed to lock cache mutex, likely due to a poisoned mutex (another thread panicked while holding the lock)");
+        for (key, repo) in manifest.repositories {
+            if clone_head(&repo.local_cache_path).as_deref() == Some(repo.commit_hash.as_str()) {
+                restore.reused.insert(key.clone());
+                cache.insert(key, repo);
+            } else {
+                restore.stale.insert(key);
+            }
+        }
+        Ok(restore)
+    }
+
     /// Process a list of repository configurations to determine the optimal fetching strategy.
     /// Identifies unique repositories based on URL, branch, and optional commit hash.
     /// Returns a list of unique configurations to fetch and corresponding wire operations.
@@ -98,6 +284,13 @@ impl CacheManager {
     }
 }
 
+/// Commit checked out in the clone at `path`, if it opens as a repository
+fn clone_head(path: &str) -> Option<String> {
+    let repo = Repository::open(path).ok()?;
+    let commit = repo.head().ok()?.peel_to_commit().ok()?;
+    Some(commit.id().to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -181,4 +374,79 @@ mod tests {
         // Should have 3 operations (one for each original config)
         assert_eq!(operations.len(), 3);
     }
+
+    fn commit_file(repo: &Repository, message: &str) -> git2::Oid {
+        let signature =
+            git2::Signature::now("Test", "test@example.com").expect("Failed to create signature");
+        let tree_id = repo
+            .treebuilder(None)
+            .and_then(|builder| builder.write())
+            .expect("Failed to write tree");
+        let tree = repo.find_tree(tree_id).expect("Failed to find tree");
+        let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+        let parents: Vec<_> = parent.iter().collect();
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parents,
+        )
+        .expect("Failed to commit")
+    }
+
+    #[tokio::test]
+    async fn test_restored_manifest_reuses_clone_without_fetching() {
+        let dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let clone_path = dir.path().join("clone");
+        let repo = Repository::init(&clone_path).expect("Failed to init repo");
+        commit_file(&repo, "initial");
+        let clone_path = clone_path.to_string_lossy().to_string();
+        let manifest_path = dir.path().join(MANIFEST_FILE);
+
+        // An unreachable URL: any network fetch would fail the test
+        let config = RepositoryConfiguration::new(
+            "https://invalid.example/repo.git".to_string(),
+            "main".to_string(),
+            "./vendor".to_string(),
+            vec!["src/".to_string()],
+            None,
+            None,
+        );
+        let key = CacheKeyGenerator::generate_key(&config);
+
+        let first_run = CacheManager::new();
+        first_run
+            .record_fetch(&config, &clone_path)
+            .expect("clone has a commit");
+        first_run
+            .save_manifest(&manifest_path)
+            .expect("manifest saves");
+
+        let next_run = CacheManager::new();
+        let restore = next_run
+            .restore_manifest(&manifest_path)
+            .expect("manifest restores");
+        assert!(restore.reused.contains(&key));
+        assert!(restore.stale.is_empty());
+        assert_eq!(
+            next_run.get_or_schedule_fetch(&config),
+            Ok(clone_path.clone())
+        );
+        let reused = RepositoryFetcher
+            .fetch_repository(&config, &clone_path)
+            .await
+            .expect("cached clone is reused");
+        assert!(reused);
+
+        // Moving the clone off the recorded commit makes the entry stale
+        commit_file(&repo, "local change");
+        let stale_run = CacheManager::new();
+        let restore = stale_run
+            .restore_manifest(&manifest_path)
+            .expect("manifest restores");
+        assert!(restore.stale.contains(&key));
+        assert!(stale_run.cache.lock().expect("lock cache").is_empty());
+    }
 }
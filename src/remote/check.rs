@@ -1,8 +1,13 @@
-use std::{path::Path, sync::Arc};
+use std::{
+    io::{self, IsTerminal},
+    path::Path,
+    sync::Arc,
+};
 
 use cause::{Cause, cause};
 use colored::Colorize;
 use folder_compare::FolderCompare;
+use log::{info, warn};
 use temp_dir::TempDir;
 
 use crate::remote::common::{ErrorType, sequence};
@@ -12,9 +17,13 @@ use super::common::{
     Parsed, Target,
     sequence::Operation,
 };
+use super::reconcile;
 
 #[derive(Debug)]
-struct CheckOperation {}
+struct CheckOperation {
+    /// Offer to resolve each difference after reporting it
+    interactive: bool,
+}
 
 impl Operation for CheckOperation {
     fn operate(
@@ -24,24 +33,64 @@ impl Operation for CheckOperation {
         rootdir: &str,
         tempdir: &TempDir,
     ) -> Result<bool, Cause<ErrorType>> {
-        compare_with_temp(prefix, parsed, rootdir, tempdir.path())
+        let matched = compare_with_temp(prefix, parsed, rootdir, tempdir.path())?;
+        if matched || !self.interactive {
+            return Ok(matched);
+        }
+        reconcile_with_temp(parsed, rootdir, tempdir.path())
     }
 }
 
-pub fn check(target: Target, mode: &sequence::Mode) -> Result<bool, Cause<ErrorType>> {
-    println!("git-wire check started\n");
-    let operation: Arc<dyn Operation + Send + Sync + 'static> = Arc::new(CheckOperation {});
+/// Compare each entry's `dst` with its source.
+///
+/// With `interactive`, drifted files are offered one by one for taking upstream, keeping
+/// local or skipping, and entries run one at a time. Without a terminal on stdin and stdout
+/// this falls back to the plain report.
+pub fn check(
+    target: Target,
+    mode: &sequence::Mode,
+    interactive: bool,
+) -> Result<bool, Cause<ErrorType>> {
+    info!("git-wire check started");
+    let interactive = interactive && {
+        let tty = io::stdin().is_terminal() && io::stdout().is_terminal();
+        if !tty {
+            warn!("Not a terminal; reporting differences without resolving them");
+        }
+        tty
+    };
+    let operation: Arc<dyn Operation + Send + Sync + 'static> =
+        Arc::new(CheckOperation { interactive });
+    let mode = if interactive {
+        &sequence::Mode::Single
+    } else {
+        mode
+    };
     let result = sequence::sequence(target, &operation, mode)?;
     Ok(result)
 }
 
+/// Let the user resolve the differences of one entry, returning whether all were resolved
+fn reconcile_with_temp(parsed: &Parsed, root: &str, temp: &Path) -> Result<bool, Cause<ErrorType>> {
+    let upstream = temp.join(parsed.src.as_str());
+    let local = Path::new(root).join(parsed.dst.as_str());
+    let io_error = |e: io::Error| {
+        cause!(CheckDifferenceExecution)
+            .src(e)
+            .msg(format!("Could not reconcile {}", local.display()))
+    };
+
+    let differences = reconcile::find_differences(&upstream, &local).map_err(io_error)?;
+    reconcile::resolve_interactively(&upstream, &local, &differences).map_err(io_error)
+}
+
 fn compare_with_temp(
     prefix: &str,
     parsed: &Parsed,
     root: &str,
     temp: &Path,
 ) -> Result<bool, Cause<ErrorType>> {
-    println!("  - {prefix}compare `src` and `dst`");
+    info!("{prefix}compare `src` and `dst`");
 
     let temp_root = temp;
     let temp = temp.join(parsed.src.as_str());
@@ -0,0 +1,187 @@
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+use cause::{Cause, cause};
+use log::info;
+use sha2::{Digest, Sha256};
+
+use super::common::{ErrorType, Target, fetch, parse};
+use super::sync::source_files;
+
+/// Compute an order-independent SHA-256 checksum of every file the sync copies from `root`.
+///
+/// Each file contributes its path relative to `root` (with `/` separators) and the
+/// hash of its contents, so renames and content changes both alter the checksum while
+/// directory iteration order does not. Symlinks are resolved as the copy resolves them
+/// with `follow_symlinks`, so a linked file counts with the contents it points to.
+pub fn tree_checksum(root: &Path, follow_symlinks: bool) -> Result<String, Cause<ErrorType>> {
+    let mut entries = Vec::new();
+    for (relative, path) in source_files(root, follow_symlinks)? {
+        let relative = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        let contents = fs::read(&path).map_err(|e| {
+            cause!(ErrorType::ChecksumCompute)
+                .src(e)
+                .msg(format!("Could not compute checksum of {}", path.display()))
+        })?;
+        entries.push((relative, to_hex(&Sha256::digest(&contents))));
+    }
+    entries.sort();
+
+    let mut hasher = Sha256::new();
+    for (path, file_hash) in &entries {
+        hasher.update(path.as_bytes());
+        hasher.update(b"\0");
+        hasher.update(file_hash.as_bytes());
+        hasher.update([b'\n']);
+    }
+    Ok(format!("sha256:{}", to_hex(&hasher.finalize())))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .fold(String::with_capacity(bytes.len() * 2), |mut hex, b| {
+            let _ = write!(hex, "{b:02x}");
+            hex
+        })
+}
+
+/// Verify that the files under `dir` match `expected`, naming `entry` on mismatch
+pub fn verify_checksum(
+    entry: &str,
+    dir: &Path,
+    follow_symlinks: bool,
+    expected: &str,
+) -> Result<(), Cause<ErrorType>> {
+    let actual = tree_checksum(dir, follow_symlinks)?;
+
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(cause!(
+            ErrorType::ChecksumMismatch,
+            format!("Checksum mismatch for {entry}: expected {expected}, got {actual}")
+        ))
+    }
+}
+
+/// Record the checksum of each entry's upstream `src` in `.gitwire`.
+///
+/// Sync verifies the fetched `src` before copying it to `dst` or running `post_sync`
+/// hooks, so each entry is fetched here and its `src` tree hashed.
+pub fn lock(target: &Target) -> Result<bool, Cause<ErrorType>> {
+    info!("git-wire lock started");
+
+    let name = match target {
+        Target::Declared(name) => name.as_ref(),
+        Target::Direct(_) => {
+            return Err(cause!(
+                ErrorType::NoItemToOperate,
+                "lock only applies to entries declared in .gitwire"
+            ));
+        }
+    };
+
+    let (root, mut parsed) = parse::parse_gitwire()?;
+    let mut locked = 0;
+    for item in &mut parsed {
+        if name.is_some_and(|name| item.name.as_ref() != Some(name)) {
+            continue;
+        }
+
+        let tempdir = fetch::fetch_target_to_tempdir("", item)?;
+        let src = tempdir.path().join(&item.src);
+        let checksum = tree_checksum(&src, item.follow_symlinks)?;
+        info!("{}: {checksum}", item.dst);
+        item.checksum = Some(checksum);
+        locked += 1;
+    }
+
+    if locked == 0 {
+        return Err(cause!(
+            ErrorType::NoItemToOperate,
+            "There are no items to operate."
+        ));
+    }
+
+    parse::write_gitwire(&root, &parsed)?;
+    info!("Locked {locked} entries");
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_tree(root: &Path, files: &[(&str, &str)]) {
+        for (path, contents) in files {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().expect("file has a parent"))
+                .expect("Failed to create directory");
+            fs::write(path, contents).expect("Failed to write file");
+        }
+    }
+
+    #[test]
+    fn test_matching_checksum_verifies() {
+        let first = TempDir::new().expect("Failed to create temp dir");
+        let second = TempDir::new().expect("Failed to create temp dir");
+        write_tree(first.path(), &[("a.proto", "a"), ("nested/b.proto", "b")]);
+        write_tree(second.path(), &[("nested/b.proto", "b"), ("a.proto", "a")]);
+
+        let checksum = tree_checksum(first.path(), false).expect("Failed to hash tree");
+        assert!(checksum.starts_with("sha256:"));
+        assert!(verify_checksum("proto", second.path(), false, &checksum).is_ok());
+    }
+
+    #[test]
+    fn test_mismatched_checksum_names_entry() {
+        let dir = TempDir::new().expect("Failed to create temp dir");
+        write_tree(dir.path(), &[("a.proto", "a")]);
+        let checksum = tree_checksum(dir.path(), false).expect("Failed to hash tree");
+
+        write_tree(dir.path(), &[("a.proto", "tampered")]);
+        let err = verify_checksum("proto", dir.path(), false, &checksum)
+            .expect_err("modified contents should not verify");
+        assert!(err.to_string().contains("proto"));
+
+        // Moving a file changes the checksum even with identical contents
+        let moved = TempDir::new().expect("Failed to create temp dir");
+        write_tree(moved.path(), &[("b.proto", "a")]);
+        assert_ne!(
+            tree_checksum(moved.path(), false).expect("Failed to hash tree"),
+            checksum
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_checksum_covers_symlinked_files() {
+        let outside = TempDir::new().expect("Failed to create temp dir");
+        let source = TempDir::new().expect("Failed to create temp dir");
+        write_tree(outside.path(), &[("shared.proto", "shared")]);
+        write_tree(source.path(), &[("a.proto", "a")]);
+        std::os::unix::fs::symlink(
+            outside.path().join("shared.proto"),
+            source.path().join("shared.proto"),
+        )
+        .expect("Failed to create symlink");
+
+        let checksum = tree_checksum(source.path(), true).expect("Failed to hash tree");
+        assert!(verify_checksum("proto", source.path(), true, &checksum).is_ok());
+
+        // The copy reads through the link, so changing its target must fail verification
+        write_tree(outside.path(), &[("shared.proto", "tampered")]);
+        assert!(verify_checksum("proto", source.path(), true, &checksum).is_err());
+
+        // A link the copy would refuse can't be checksummed either
+        let err = tree_checksum(source.path(), false).expect_err("link leaves the source");
+        assert!(err.to_string().contains("outside the wired source"));
+    }
+}
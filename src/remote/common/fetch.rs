@@ -4,18 +4,21 @@ use std::process::Command;
 
 use cause::Cause;
 use cause::cause;
-use git2::Repository;
+use log::{info, warn};
 use regex::Regex;
 use temp_dir::TempDir;
 
 use super::ErrorType;
 use super::ErrorType::{
     GitCheckoutChangeDirectory, GitCheckoutCommand, GitCheckoutCommandExitStatus, GitCloneCommand,
-    GitFetchCommand, GitFetchCommandExitStatus, GitLsRemoteCommand, GitLsRemoteCommandExitStatus,
-    GitLsRemoteCommandStdoutDecode, GitLsRemoteCommandStdoutRegex, TempDirCreation,
+    GitCloneCommandExitStatus, GitFetchCommand, GitFetchCommandExitStatus, GitLsRemoteCommand,
+    GitLsRemoteCommandExitStatus, GitLsRemoteCommandStdoutDecode, GitLsRemoteCommandStdoutRegex,
+    PartialCloneFilterInvalid, TempDirCreation,
 };
 use super::Method;
 use super::Parsed;
+use super::command::{clone_repository, git_failure, git_output};
+use super::is_valid_filter_spec;
 
 pub fn fetch_target_to_tempdir(prefix: &str, parsed: &Parsed) -> Result<TempDir, Cause<ErrorType>> {
     let tempdir = TempDir::with_prefix(prefix).map_err(|e| cause!(TempDirCreation).src(e))?;
@@ -26,7 +29,7 @@ pub fn fetch_target_to_tempdir(prefix: &str, parsed: &Parsed) -> Result<TempDir,
     git_clone(prefix, tempdir.path(), parsed)?;
 
     let method = match parsed.mtd.as_ref() {
-        Some(Method::Partial) => git_checkout_partial,
+        Some(Method::Partial { .. }) => git_checkout_partial,
         Some(Method::ShallowNoSparse) => git_checkout_shallow_no_sparse,
         Some(Method::Shallow) | None => git_checkout_shallow_with_sparse,
     };
@@ -37,15 +40,50 @@ pub fn fetch_target_to_tempdir(prefix: &str, parsed: &Parsed) -> Result<TempDir,
 }
 
 fn git_clone(prefix: &str, path: &Path, parsed: &Parsed) -> Result<(), Cause<ErrorType>> {
-    println!("  - {prefix}clone --no-checkout: {}", parsed.url);
-
     std::env::set_current_dir(path).map_err(|e| cause!(GitCloneCommand).src(e))?;
 
-    Repository::clone(&parsed.url, ".").map_err(|e| cause!(GitCloneCommand).src(e))?;
+    if let Some(filter) = parsed.mtd.as_ref().and_then(Method::partial_filter) {
+        info!("{prefix}clone --filter={filter}: {}", parsed.url);
+        let out = git_output(
+            Command::new("git").args(partial_clone_args(filter, &parsed.url, ".")?),
+            &parsed.url,
+            parsed.token_env.as_deref(),
+            GitCloneCommand,
+        )?;
+        return handle_git_output(&out, "git clone", &parsed.url, GitCloneCommandExitStatus);
+    }
+
+    info!("{prefix}clone --no-checkout: {}", parsed.url);
+
+    clone_repository(&parsed.url, parsed.token_env.as_deref(), Path::new("."))?;
 
     Ok(())
 }
 
+/// Arguments for `git clone` of a partial clone, rejecting malformed filter specs
+pub fn partial_clone_args(
+    filter: &str,
+    url: &str,
+    dest: &str,
+) -> Result<Vec<String>, Cause<ErrorType>> {
+    if !is_valid_filter_spec(filter) {
+        return Err(cause!(
+            PartialCloneFilterInvalid,
+            format!(
+                "'{filter}' is not a valid partial clone filter (e.g. blob:none, blob:limit=1m, tree:0)"
+            )
+        ));
+    }
+
+    Ok(vec![
+        "clone".to_string(),
+        format!("--filter={filter}"),
+        "--no-checkout".to_string(),
+        url.to_string(),
+        dest.to_string(),
+    ])
+}
+
 fn git_checkout_partial(
     prefix: &str,
     path: &Path,
@@ -53,15 +91,15 @@ fn git_checkout_partial(
 ) -> Result<(), Cause<ErrorType>> {
     let rev = identify_commit_hash(path, parsed)?;
     let rev = if let Some(r) = rev {
-        println!("  - {prefix}checkout partial: {} ({})", r, parsed.rev);
+        info!("{prefix}checkout partial: {} ({})", r, parsed.rev);
         r
     } else {
-        println!("  - {prefix}checkout partial: {}", parsed.rev);
+        info!("{prefix}checkout partial: {}", parsed.rev);
         parsed.rev.clone()
     };
 
-    let out = Command::new("git")
-        .args([
+    let out = git_output(
+        Command::new("git").args([
             "-C",
             path.to_str().expect("Failed to convert path to string for git checkout; path contains invalid Unicode characters"),
             "checkout",
@@ -69,11 +107,18 @@ fn git_checkout_partial(
             rev.as_ref(),
             "--",
             parsed.src.as_ref(),
-        ])
-        .output()
-        .map_err(|e| cause!(GitCheckoutCommand).src(e))?;
-
-    handle_git_output(out, "git checkout", GitCheckoutCommandExitStatus)
+        ]),
+        &parsed.url,
+        parsed.token_env.as_deref(),
+        GitCheckoutCommand,
+    )?;
+
+    handle_git_output(
+        &out,
+        "git checkout",
+        &parsed.url,
+        GitCheckoutCommandExitStatus,
+    )
 }
 
 fn git_checkout_shallow_no_sparse(
@@ -101,13 +146,10 @@ fn git_checkout_shallow_core(
     let rev = identify_commit_hash(path, parsed)?;
     let no_sparse = if use_sparse { "" } else { " (no sparse)" };
     let rev = if let Some(r) = rev {
-        println!(
-            "  - {prefix}checkout shallow{no_sparse}: {r} ({})",
-            parsed.rev
-        );
+        info!("{prefix}checkout shallow{no_sparse}: {r} ({})", parsed.rev);
         r
     } else {
-        println!("  - {prefix}checkout shallow{no_sparse}: {}", parsed.rev);
+        info!("{prefix}checkout shallow{no_sparse}: {}", parsed.rev);
         parsed.rev.clone()
     };
 
@@ -119,8 +161,8 @@ fn git_checkout_shallow_core(
             format!("/{}", &parsed.src).into()
         };
 
-        let out = Command::new("git")
-            .args([
+        let output = git_output(
+            Command::new("git").args([
                 "-C",
                 path.to_str()
                     .expect("Failed to convert path to string for sparse checkout; path contains invalid Unicode characters"),
@@ -128,27 +170,28 @@ fn git_checkout_shallow_core(
                 "set",
                 "--no-cone",
                 &sparse_path,
-            ])
-            .output();
-
-        let output = out.expect("Failed to execute git sparse-checkout command. Ensure 'git' is installed and in your PATH, and you have necessary permissions.");
+            ]),
+            &parsed.url,
+            parsed.token_env.as_deref(),
+            GitCheckoutCommand,
+        )?;
 
         if !output.status.success() {
             // sparse-checkout command is optional, even if it failed,
             // subsequent sequence will be performed without any problem.
-            println!("    - {prefix}Could not activate sparse-checkout feature.");
-            println!("    - {prefix}Your git client might not support this feature.");
+            warn!("{prefix}Could not activate sparse-checkout feature.");
+            warn!("{prefix}Your git client might not support this feature.");
 
             // Print stderr for more context, as the command did run but failed.
             let stderr = String::from_utf8_lossy(&output.stderr);
             if !stderr.trim().is_empty() {
-                println!("    - {prefix}  stderr: {}", stderr.trim());
+                warn!("{prefix}sparse-checkout stderr: {}", stderr.trim());
             }
         }
     }
 
-    let out = Command::new("git")
-        .args([
+    let out = git_output(
+        Command::new("git").args([
             "-C",
             path.to_str().expect("Failed to convert path to string for git fetch; path contains invalid Unicode characters"),
             "fetch",
@@ -157,63 +200,72 @@ fn git_checkout_shallow_core(
             "--progress",
             "origin",
             rev.as_ref(),
-        ])
-        .output()
-        .map_err(|e| cause!(GitFetchCommand).src(e))?;
-
-    if !out.status.success() {
-        let error = String::from_utf8(out.stderr)
-            .unwrap_or("Could not get even a error output of git fetch command".to_string());
-        return Err(cause!(GitFetchCommandExitStatus, error));
-    }
+        ]),
+        &parsed.url,
+        parsed.token_env.as_deref(),
+        GitFetchCommand,
+    )?;
 
-    let out = Command::new("git")
-        .args([
+    handle_git_output(&out, "git fetch", &parsed.url, GitFetchCommandExitStatus)?;
+
+    let out = git_output(
+        Command::new("git").args([
             "-C",
             path.to_str().expect("Failed to convert path to string for git checkout; path contains invalid Unicode characters"),
             "checkout",
             "--progress",
             "FETCH_HEAD",
-        ])
-        .output()
-        .map_err(|e| cause!(GitCheckoutCommand).src(e))?;
-
-    handle_git_output(out, "git checkout", GitCheckoutCommandExitStatus)
+        ]),
+        &parsed.url,
+        parsed.token_env.as_deref(),
+        GitCheckoutCommand,
+    )?;
+
+    handle_git_output(
+        &out,
+        "git checkout",
+        &parsed.url,
+        GitCheckoutCommandExitStatus,
+    )
 }
 
 fn handle_git_output(
-    out: std::process::Output,
+    out: &std::process::Output,
     command_name: &str,
+    url: &str,
     error_variant: ErrorType,
 ) -> Result<(), Cause<ErrorType>> {
     if out.status.success() {
         Ok(())
     } else {
-        let error = String::from_utf8(out.stderr).unwrap_or(format!(
-            "Could not get even a error output of {command_name} command"
-        ));
-        Err(cause!(error_variant, error))
+        Err(git_failure(error_variant, command_name, url, out))
     }
 }
 
-fn identify_commit_hash(path: &Path, parsed: &Parsed) -> Result<Option<String>, Cause<ErrorType>> {
-    let out = Command::new("git")
-        .args([
+pub fn identify_commit_hash(
+    path: &Path,
+    parsed: &Parsed,
+) -> Result<Option<String>, Cause<ErrorType>> {
+    let out = git_output(
+        Command::new("git").args([
             "-C",
             path.to_str().expect("Failed to convert path to string for git ls-remote; path contains invalid Unicode characters"),
             "ls-remote",
             "--heads",
             "--tags",
             parsed.url.as_ref(),
-        ])
-        .output()
-        .map_err(|e| cause!(GitLsRemoteCommand).src(e))?;
-
-    if !out.status.success() {
-        let error = String::from_utf8(out.stderr)
-            .unwrap_or("Could not get even a error output of git ls-remote command".to_string());
-        return Err(cause!(GitLsRemoteCommandExitStatus).msg(error));
-    }
+        ]),
+        &parsed.url,
+        parsed.token_env.as_deref(),
+        GitLsRemoteCommand,
+    )?;
+
+    handle_git_output(
+        &out,
+        "git ls-remote",
+        &parsed.url,
+        GitLsRemoteCommandExitStatus,
+    )?;
 
     let stdout =
         String::from_utf8(out.stdout).map_err(|e| cause!(GitLsRemoteCommandStdoutDecode).src(e))?;
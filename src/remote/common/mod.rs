@@ -1,9 +1,12 @@
+pub mod command;
 pub mod fetch;
 pub mod parse;
 pub mod sequence;
 
 use serde::{Deserialize, Serialize};
 
+use crate::error::ErrorCategory;
+
 #[derive(Debug)]
 pub enum ErrorType {
     RepositoryRootPathCommand,
@@ -30,6 +33,50 @@ pub enum ErrorType {
     GitLsRemoteCommandExitStatus,
     GitLsRemoteCommandStdoutDecode,
     GitLsRemoteCommandStdoutRegex,
+    PartialCloneFilterInvalid,
+    DotGitWireFileWrite,
+    ChecksumCompute,
+    ChecksumMismatch,
+    PostSyncHookCommand,
+    PostSyncHookExitStatus,
+    IgnorePatternInvalid,
+    CommitHashInvalid,
+    CommitHashAmbiguous,
+    CommitHashNotFound,
+    SyncStateWrite,
+    GitCommandTimeout,
+    SymlinkOutsideSource,
+    RepositoryConfigurationInvalid,
+    AccessTokenMissing,
+    WireLimitExceeded,
+    GitRemoteAuthentication,
+    GitRemoteNotFound,
+    GitRemoteUnreachable,
+    TaskJoin,
+}
+
+impl ErrorType {
+    /// Exit code category: network failures of git commands are worth retrying, failures
+    /// to make sense of our own command output are bugs, and the rest need user action.
+    ///
+    /// A git command that exits unsuccessfully is only retriable once its stderr shows a
+    /// network failure, see [`command::classify_git_failure`].
+    pub const fn category(&self) -> ErrorCategory {
+        match self {
+            Self::GitCloneCommand
+            | Self::GitFetchCommand
+            | Self::GitLsRemoteCommand
+            | Self::GitCommandTimeout
+            | Self::GitRemoteUnreachable => ErrorCategory::Transient,
+            Self::RepositoryRootPathParse
+            | Self::CurrentDirConvert
+            | Self::CheckDifferenceStringReplace
+            | Self::GitLsRemoteCommandStdoutDecode
+            | Self::GitLsRemoteCommandStdoutRegex
+            | Self::TaskJoin => ErrorCategory::Internal,
+            _ => ErrorCategory::User,
+        }
+    }
 }
 
 /*# [derive(Debug)]
@@ -60,7 +107,11 @@ pub enum ErrorType {
     GitLsRemoteCommandStdoutRegex,
 } */
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// Object filter used by partial clones when `.gitwire` doesn't set one
+pub const DEFAULT_PARTIAL_FILTER: &str = "blob:none";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(from = "MethodRepr")]
 pub enum Method {
     #[serde(rename = "shallow")]
     Shallow,
@@ -68,22 +119,196 @@ pub enum Method {
     #[serde(rename = "shallow_no_sparse")]
     ShallowNoSparse,
 
+    /// Blobless (or otherwise filtered) clone, written as `"partial"` or
+    /// `{"partial": {"filter": "blob:limit=1m"}}` in `.gitwire`
+    #[serde(rename = "partial")]
+    Partial {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        filter: Option<String>,
+    },
+}
+
+impl Method {
+    /// The `--filter` spec for a partial clone, or `None` for other methods
+    pub fn partial_filter(&self) -> Option<&str> {
+        match self {
+            Self::Partial { filter } => Some(filter.as_deref().unwrap_or(DEFAULT_PARTIAL_FILTER)),
+            Self::Shallow | Self::ShallowNoSparse => None,
+        }
+    }
+}
+
+// Accepts both the bare method name and the `{"partial": {...}}` object form
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum MethodRepr {
+    Name(MethodName),
+    Partial { partial: PartialOptions },
+}
+
+#[derive(Deserialize)]
+enum MethodName {
+    #[serde(rename = "shallow")]
+    Shallow,
+    #[serde(rename = "shallow_no_sparse")]
+    ShallowNoSparse,
     #[serde(rename = "partial")]
     Partial,
 }
 
+#[derive(Deserialize)]
+struct PartialOptions {
+    #[serde(default)]
+    filter: Option<String>,
+}
+
+impl From<MethodRepr> for Method {
+    fn from(repr: MethodRepr) -> Self {
+        match repr {
+            MethodRepr::Name(MethodName::Shallow) => Self::Shallow,
+            MethodRepr::Name(MethodName::ShallowNoSparse) => Self::ShallowNoSparse,
+            MethodRepr::Name(MethodName::Partial) => Self::Partial { filter: None },
+            MethodRepr::Partial { partial } => Self::Partial {
+                filter: partial.filter,
+            },
+        }
+    }
+}
+
+/// Check a `git clone --filter` spec, e.g. `blob:none`, `blob:limit=1m` or `tree:0`
+pub fn is_valid_filter_spec(spec: &str) -> bool {
+    if let Some(parts) = spec.strip_prefix("combine:") {
+        return parts
+            .split('+')
+            .all(|part| !part.starts_with("combine:") && is_valid_filter_spec(part));
+    }
+
+    let is_number = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    match spec.split_once(':') {
+        Some(("blob", "none")) => true,
+        Some(("blob", limit)) => limit.strip_prefix("limit=").is_some_and(|size| {
+            let digits = size
+                .strip_suffix(['k', 'm', 'g', 'K', 'M', 'G'])
+                .unwrap_or(size);
+            is_number(digits)
+        }),
+        Some(("tree", depth)) => is_number(depth),
+        Some(("object", kind)) => {
+            matches!(kind, "type=blob" | "type=tree" | "type=commit" | "type=tag")
+        }
+        Some(("sparse", oid)) => oid
+            .strip_prefix("oid=")
+            .is_some_and(|oid| !oid.is_empty() && !oid.contains(char::is_whitespace)),
+        _ => false,
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Parsed {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub dsc: Option<String>,
     pub url: String,
     pub rev: String,
     pub src: String,
     pub dst: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub mtd: Option<Method>,
+    /// Commit to pin `rev` to, full or abbreviated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit_hash: Option<String>,
+    /// Expected checksum of the fetched `src` tree, written by `git wire lock`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<String>,
+    /// Shell commands run from the repository root after this entry is synced
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post_sync: Vec<String>,
+    /// Remove files under `dst` that no longer exist under `src`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub prune: bool,
+    /// Follow symlinks under `src` that point outside it, for trusted upstreams only
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub follow_symlinks: bool,
+    /// Environment variable holding an access token for a private HTTPS `url`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_env: Option<String>,
+    /// Most files a sync of this entry may copy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_files: Option<usize>,
+    /// Most bytes a sync of this entry may copy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_total_bytes: Option<u64>,
+    /// Gitignore-style patterns, relative to `dst`, for local files `prune` keeps
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignore: Vec<String>,
 }
 
 pub enum Target {
     Declared(Option<String>),
     Direct(Parsed),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_git_failure_categories() {
+        assert_eq!(
+            ErrorType::GitRemoteUnreachable.category(),
+            ErrorCategory::Transient
+        );
+        assert_eq!(
+            ErrorType::GitRemoteAuthentication.category(),
+            ErrorCategory::User
+        );
+        assert_eq!(ErrorType::GitRemoteNotFound.category(), ErrorCategory::User);
+        assert_eq!(
+            ErrorType::GitCloneCommandExitStatus.category(),
+            ErrorCategory::User
+        );
+        assert_eq!(ErrorType::TaskJoin.category(), ErrorCategory::Internal);
+    }
+
+    #[test]
+    fn test_partial_method_forms() {
+        let bare: Method = serde_json::from_str(r#""partial""#).expect("bare method name");
+        assert_eq!(bare.partial_filter(), Some(DEFAULT_PARTIAL_FILTER));
+
+        let custom: Method = serde_json::from_str(r#"{"partial": {"filter": "blob:limit=1m"}}"#)
+            .expect("partial with filter");
+        assert_eq!(custom.partial_filter(), Some("blob:limit=1m"));
+
+        let shallow: Method = serde_json::from_str(r#""shallow""#).expect("shallow method");
+        assert_eq!(shallow.partial_filter(), None);
+    }
+
+    #[test]
+    fn test_filter_spec_validation() {
+        for spec in [
+            "blob:none",
+            "blob:limit=1m",
+            "blob:limit=1024",
+            "tree:0",
+            "object:type=blob",
+            "sparse:oid=main:.sparse",
+            "combine:blob:none+tree:2",
+        ] {
+            assert!(is_valid_filter_spec(spec), "{spec} should be valid");
+        }
+        for spec in [
+            "",
+            "blob",
+            "blob:all",
+            "blob:limit=",
+            "blob:limit=1x",
+            "tree:",
+            "tree:-1",
+            "nope:0",
+            "combine:",
+        ] {
+            assert!(!is_valid_filter_spec(spec), "{spec} should be invalid");
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use std::collections::HashSet;
 use std::ffi::OsStr;
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::BufReader;
 use std::path::{Component, Path};
 
@@ -10,9 +10,10 @@ use git2::Repository;
 
 use super::ErrorType::{
     self, DotGitWireFileNameNotUnique, DotGitWireFileOpen, DotGitWireFileParse,
-    DotGitWireFileSoundness, RepositoryRootPathCommand,
+    DotGitWireFileSoundness, DotGitWireFileWrite, PartialCloneFilterInvalid,
+    RepositoryRootPathCommand,
 };
-use super::Parsed;
+use super::{Method, Parsed, is_valid_filter_spec};
 
 const DOT_GIT_WIRE: &str = ".gitwire";
 
@@ -21,11 +22,31 @@ pub fn parse_gitwire() -> Result<(String, Vec<Parsed>), Cause<ErrorType>> {
     Ok((root, parse_dotgitwire_file(&file)?))
 }
 
+/// Overwrite the `.gitwire` file under `root` with `parsed`
+pub fn write_gitwire(root: &str, parsed: &[Parsed]) -> Result<(), Cause<ErrorType>> {
+    let file = format!("{root}/{DOT_GIT_WIRE}");
+    let mut contents = serde_json::to_string_pretty(parsed)
+        .map_err(|e| cause!(DotGitWireFileWrite, "could not serialize .gitwire").src(e))?;
+    contents.push('\n');
+    fs::write(&file, contents)
+        .map_err(|e| cause!(DotGitWireFileWrite, "no .gitwire file write permission").src(e))
+}
+
 fn get_dotgitwire_file_path() -> Result<(String, String), Cause<ErrorType>> {
-    let repo = Repository::discover(".").map_err(|e| cause!(RepositoryRootPathCommand).src(e))?;
-    let workdir = repo
-        .workdir()
-        .ok_or_else(|| cause!(RepositoryRootPathCommand))?;
+    let repo = Repository::discover(".").map_err(|e| {
+        cause!(RepositoryRootPathCommand)
+            .msg(format!(
+                "Could not find the git repository: {}",
+                e.message()
+            ))
+            .src(e)
+    })?;
+    let workdir = repo.workdir().ok_or_else(|| {
+        cause!(
+            RepositoryRootPathCommand,
+            "git wire needs a repository with a working tree"
+        )
+    })?;
     let root = workdir.to_string_lossy().to_string();
 
     let file = format!("{root}/{DOT_GIT_WIRE}");
@@ -54,6 +75,17 @@ fn parse_dotgitwire_file(file: &str) -> Result<Vec<Parsed>, Cause<ErrorType>> {
         }
     }
 
+    for item in &parsed {
+        if let Some(filter) = item.mtd.as_ref().and_then(Method::partial_filter)
+            && !is_valid_filter_spec(filter)
+        {
+            Err(cause!(
+                PartialCloneFilterInvalid,
+                format!(".gitwire file's partial clone filter '{filter}' is not valid.")
+            ))?;
+        }
+    }
+
     let mut name_set: HashSet<&str> = HashSet::new();
     for p in &parsed {
         if let Some(ref name) = p.name
@@ -1,7 +1,11 @@
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
 
 use cause::Cause;
 use cause::cause;
+use log::{info, warn};
 use temp_dir::TempDir;
 
 use super::ErrorType;
@@ -11,7 +15,21 @@ use super::Target;
 
 pub enum Mode {
     Single,
+    /// One worker per entry
     Parallel,
+    /// At most this many entries at once; `Jobs(1)` behaves like `Single`
+    Jobs(NonZeroUsize),
+}
+
+impl Mode {
+    /// Number of entries processed concurrently out of `len`
+    pub fn workers(&self, len: usize) -> usize {
+        match self {
+            Self::Single => 1,
+            Self::Parallel => len.max(1),
+            Self::Jobs(jobs) => jobs.get().min(len.max(1)),
+        }
+    }
 }
 
 pub trait Operation {
@@ -59,7 +77,15 @@ pub fn sequence(
 
     match mode {
         Mode::Single => single(parsed.as_slice(), rootdir.as_str(), operation.as_ref()),
-        Mode::Parallel => parallel(parsed, rootdir.as_str(), operation),
+        Mode::Jobs(jobs) if jobs.get() == 1 => {
+            single(parsed.as_slice(), rootdir.as_str(), operation.as_ref())
+        }
+        Mode::Parallel | Mode::Jobs(_) => parallel(
+            &parsed,
+            rootdir.as_str(),
+            operation.as_ref(),
+            mode.workers(len),
+        ),
     }
 }
 
@@ -72,80 +98,59 @@ fn single(
 
     let mut result = true;
     for (i, parsed) in parsed.iter().enumerate() {
-        println!(">> {}/{} started{}", i + 1, len, additional_message(parsed));
+        info!("{}/{} started{}", i + 1, len, additional_message(parsed));
         let tempdir = super::fetch::fetch_target_to_tempdir("", parsed)?;
         let success = operation.operate("", parsed, rootdir, &tempdir)?;
         if !success {
             result = false;
         }
     }
-    println!(">> All check tasks have done!\n");
+    info!("All check tasks have done!");
     Ok(result)
 }
 
+/// Run entries on a fixed number of workers.
+///
+/// Entries that share a URL are serialized by a per-URL lock, so they never run
+/// concurrently however many workers there are.
 fn parallel(
-    parsed: Vec<Parsed>,
+    parsed: &[Parsed],
     rootdir: &str,
-    operation: &Arc<dyn Operation + Send + Sync>,
+    operation: &(dyn Operation + Send + Sync),
+    workers: usize,
 ) -> Result<bool, Cause<ErrorType>> {
-    use colored::Colorize;
-
     let len = parsed.len();
-    let operation = operation.clone();
+    let next = AtomicUsize::new(0);
+    let url_locks: HashMap<&str, Mutex<()>> = parsed
+        .iter()
+        .map(|p| (p.url.as_str(), Mutex::new(())))
+        .collect();
 
     let results: Vec<_> = std::thread::scope(|s| {
-        let results: Vec<_> = parsed
-            .into_iter()
-            .enumerate()
-            .map(|(i, parsed)| {
-                s.spawn({
-                    let operation = operation.clone();
-                    move || -> Result<bool, Cause<ErrorType>> {
-                        let prefix = format!("No.{i} ");
-                        println!(
-                            "{}",
-                            format!(
-                                ">> {prefix}({}/{len}) started{}",
-                                i + 1,
-                                additional_message(&parsed)
-                            )
-                            .blue()
-                        );
-                        let tempdir = super::fetch::fetch_target_to_tempdir(&prefix, &parsed)?;
-                        let success = operation.operate(&prefix, &parsed, rootdir, &tempdir)?;
-                        if success {
-                            println!(
-                                "{}",
-                                format!(
-                                    ">> {prefix}({}/{len}) succeeded{}",
-                                    i + 1,
-                                    additional_message(&parsed)
-                                )
-                                .blue()
-                            );
-                            Ok(true)
-                        } else {
-                            println!(
-                                "{}",
-                                format!(
-                                    ">> {prefix}({}/{len}) failed{}",
-                                    i + 1,
-                                    additional_message(&parsed)
-                                )
-                                .magenta()
-                            );
-                            Ok(false)
-                        }
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                s.spawn(|| {
+                    let mut results = Vec::new();
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(parsed) = parsed.get(i) else {
+                            break;
+                        };
+                        let _url_guard = url_locks
+                            .get(parsed.url.as_str())
+                            .map(|lock| lock.lock().unwrap_or_else(PoisonError::into_inner));
+                        results.push(run_entry(i, len, parsed, rootdir, operation));
                     }
+                    results
                 })
             })
             .collect();
-        results
+        handles
             .into_iter()
-            .map(|h| h.join().expect("A thread panicked during execution"))
+            .flat_map(|h| h.join().expect("A thread panicked during execution"))
             .collect()
     });
-    println!("{}", ">> All check tasks have done!\n".to_string().blue());
+    info!("All check tasks have done!");
 
     let result = if results.iter().any(|r| matches!(r, Ok(false))) {
         Ok(false)
@@ -158,6 +163,37 @@ fn parallel(
     result
 }
 
+fn run_entry(
+    i: usize,
+    len: usize,
+    parsed: &Parsed,
+    rootdir: &str,
+    operation: &dyn Operation,
+) -> Result<bool, Cause<ErrorType>> {
+    let prefix = format!("No.{i} ");
+    info!(
+        "{prefix}({}/{len}) started{}",
+        i + 1,
+        additional_message(parsed)
+    );
+    let tempdir = super::fetch::fetch_target_to_tempdir(&prefix, parsed)?;
+    let success = operation.operate(&prefix, parsed, rootdir, &tempdir)?;
+    if success {
+        info!(
+            "{prefix}({}/{len}) succeeded{}",
+            i + 1,
+            additional_message(parsed)
+        );
+    } else {
+        warn!(
+            "{prefix}({}/{len}) failed{}",
+            i + 1,
+            additional_message(parsed)
+        );
+    }
+    Ok(success)
+}
+
 fn additional_message(parsed: &Parsed) -> String {
     match (&parsed.name, &parsed.dsc) {
         (Some(name), Some(dsc)) => format!(" ({name}: {dsc})"),
@@ -166,3 +202,19 @@ fn additional_message(parsed: &Parsed) -> String {
         (None, None) => String::new(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_workers_are_capped_by_jobs_and_entries() {
+        let jobs = |n| Mode::Jobs(NonZeroUsize::new(n).expect("non-zero job count"));
+
+        assert_eq!(Mode::Single.workers(8), 1);
+        assert_eq!(Mode::Parallel.workers(8), 8);
+        assert_eq!(jobs(4).workers(8), 4);
+        assert_eq!(jobs(4).workers(2), 2);
+        assert_eq!(jobs(1).workers(8), 1);
+    }
+}
@@ -0,0 +1,102 @@
+use std::path::Path;
+use std::process::Command;
+
+use cause::{Cause, cause};
+use log::{debug, info};
+
+use super::common::ErrorType;
+
+/// Environment variable holding the absolute `dst` path while hooks run
+pub const GITWIRE_DST_ENV: &str = "GITWIRE_DST";
+
+/// Run an entry's `post_sync` commands in order from `root`.
+///
+/// Each command runs through the platform shell with [`GITWIRE_DST_ENV`] set to `dst`.
+/// The first command that can't start or exits non-zero stops the sequence.
+pub fn run_post_sync(
+    entry: &str,
+    commands: &[String],
+    root: &Path,
+    dst: &Path,
+) -> Result<(), Cause<ErrorType>> {
+    for command in commands {
+        info!("post_sync ({entry}): {command}");
+        debug!("Running post_sync hook for {entry} in {}", root.display());
+
+        let output = shell(command)
+            .current_dir(root)
+            .env(GITWIRE_DST_ENV, dst)
+            .output()
+            .map_err(|e| {
+                cause!(ErrorType::PostSyncHookCommand).src(e).msg(format!(
+                    "Could not run post_sync command for {entry}: {command}"
+                ))
+            })?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(cause!(
+                ErrorType::PostSyncHookExitStatus,
+                format!(
+                    "post_sync command for {entry} failed ({}): {command}\n{}",
+                    output.status,
+                    stderr.trim()
+                )
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("cmd");
+    cmd.args(["/C", command]);
+    cmd
+}
+
+#[cfg(not(windows))]
+fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.args(["-c", command]);
+    cmd
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_post_sync_creates_marker_under_dst() {
+        let root = TempDir::new().expect("Failed to create temp dir");
+        let dst = root.path().join("proto");
+        std::fs::create_dir_all(&dst).expect("Failed to create dst");
+
+        run_post_sync(
+            "proto",
+            &[r#"touch "$GITWIRE_DST/generated.marker""#.to_string()],
+            root.path(),
+            &dst,
+        )
+        .expect("post_sync command should succeed");
+
+        assert!(dst.join("generated.marker").exists());
+    }
+
+    #[test]
+    fn test_failing_command_stops_and_is_reported() {
+        let root = TempDir::new().expect("Failed to create temp dir");
+
+        let err = run_post_sync(
+            "proto",
+            &["exit 3".to_string(), "touch never-run".to_string()],
+            root.path(),
+            root.path(),
+        )
+        .expect_err("non-zero exit should fail the entry");
+
+        assert!(err.to_string().contains("exit 3"));
+        assert!(!root.path().join("never-run").exists());
+    }
+}
@@ -1,14 +1,29 @@
 pub mod cache;
 pub mod check;
+pub mod checksum;
 pub mod common;
+pub mod hooks;
 pub mod models;
+pub mod reconcile;
+pub mod state;
+pub mod status;
+pub mod summary;
 pub mod sync;
 
 pub use cache::manager::CacheManager;
 pub use models::cached_repo::CachedRepository;
-pub use models::repo_config::RepositoryConfiguration;
-pub use models::wire_operation::WireOperation;
+pub use models::repo_config::{RepositoryConfiguration, RepositoryConfigurationBuilder};
+pub use models::wire_operation::{SyncCheckOutcome, WireOperation};
 
+/// Log to stderr at `info`, so `RUST_LOG` can raise or lower wire's verbosity
 pub fn init_logger() {
-    env_logger::init();
+    init_logger_with(log::LevelFilter::Info);
+}
+
+/// Like [`init_logger`], defaulting to `level` instead of `info`
+pub fn init_logger_with(level: log::LevelFilter) {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(level.as_str()))
+        .format_timestamp(None)
+        .format_target(false)
+        .init();
 }
@@ -1,6 +1,10 @@
+use cause::{Cause, cause};
 use serde::{Deserialize, Serialize};
 
-use crate::remote::common::Method;
+use crate::remote::common::{ErrorType, Method};
+
+/// Branch used when a builder sets neither a branch nor a commit
+pub const DEFAULT_BRANCH: &str = "main";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RepositoryConfiguration {
@@ -16,6 +20,30 @@ pub struct RepositoryConfiguration {
     pub commit_hash: Option<String>,
     /// Method for cloning
     pub mtd: Option<Method>,
+    /// Expected checksum of the fetched source files, verified before copying (optional)
+    #[serde(default)]
+    pub checksum: Option<String>,
+    /// Commands run after the files are copied, only with `--allow-hooks`
+    #[serde(default)]
+    pub post_sync: Vec<String>,
+    /// Remove files from the target that are gone from the source
+    #[serde(default)]
+    pub prune: bool,
+    /// Follow symlinks that leave the source directory instead of refusing them
+    #[serde(default)]
+    pub follow_symlinks: bool,
+    /// Environment variable holding an access token for a private HTTPS `url` (optional)
+    #[serde(default)]
+    pub token_env: Option<String>,
+    /// Most files a sync may copy from the source (optional, unlimited by default)
+    #[serde(default)]
+    pub max_files: Option<usize>,
+    /// Most bytes a sync may copy from the source (optional, unlimited by default)
+    #[serde(default)]
+    pub max_total_bytes: Option<u64>,
+    /// Patterns for target files that pruning must keep
+    #[serde(default)]
+    pub ignore: Vec<String>,
 }
 
 impl RepositoryConfiguration {
@@ -34,6 +62,188 @@ impl RepositoryConfiguration {
             filters,
             commit_hash,
             mtd,
+            checksum: None,
+            post_sync: Vec::new(),
+            prune: false,
+            follow_symlinks: false,
+            token_env: None,
+            max_files: None,
+            max_total_bytes: None,
+            ignore: Vec::new(),
+        }
+    }
+
+    /// Size guardrails for syncing this repository
+    pub const fn limits(&self) -> WireLimits {
+        WireLimits {
+            max_files: self.max_files,
+            max_total_bytes: self.max_total_bytes,
         }
     }
+
+    /// Start building a configuration with named setters instead of positional arguments
+    pub fn builder() -> RepositoryConfigurationBuilder {
+        RepositoryConfigurationBuilder::default()
+    }
+}
+
+/// Caps on how much one sync may copy, so a too-broad `src` fails instead of filling the
+/// disk. `None` means unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WireLimits {
+    pub max_files: Option<usize>,
+    pub max_total_bytes: Option<u64>,
+}
+
+/// Fluent builder for [`RepositoryConfiguration`], validated by [`build`](Self::build)
+#[derive(Debug, Clone, Default)]
+pub struct RepositoryConfigurationBuilder {
+    url: String,
+    branch: Option<String>,
+    target_path: String,
+    filters: Vec<String>,
+    commit_hash: Option<String>,
+    mtd: Option<Method>,
+    checksum: Option<String>,
+    post_sync: Vec<String>,
+    prune: bool,
+    follow_symlinks: bool,
+    token_env: Option<String>,
+    max_files: Option<usize>,
+    max_total_bytes: Option<u64>,
+    ignore: Vec<String>,
+}
+
+impl RepositoryConfigurationBuilder {
+    #[must_use]
+    pub fn url(mut self, url: impl Into<String>) -> Self {
+        self.url = url.into();
+        self
+    }
+
+    /// Branch to sync; can't be combined with [`commit_hash`](Self::commit_hash)
+    #[must_use]
+    pub fn branch(mut self, branch: impl Into<String>) -> Self {
+        self.branch = Some(branch.into());
+        self
+    }
+
+    #[must_use]
+    pub fn target_path(mut self, target_path: impl Into<String>) -> Self {
+        self.target_path = target_path.into();
+        self
+    }
+
+    /// Add a path to include from the repository
+    #[must_use]
+    pub fn filter(mut self, filter: impl Into<String>) -> Self {
+        self.filters.push(filter.into());
+        self
+    }
+
+    /// Commit to pin to; can't be combined with [`branch`](Self::branch)
+    #[must_use]
+    pub fn commit_hash(mut self, commit_hash: impl Into<String>) -> Self {
+        self.commit_hash = Some(commit_hash.into());
+        self
+    }
+
+    #[must_use]
+    pub fn method(mut self, method: Method) -> Self {
+        self.mtd = Some(method);
+        self
+    }
+
+    #[must_use]
+    pub fn checksum(mut self, checksum: impl Into<String>) -> Self {
+        self.checksum = Some(checksum.into());
+        self
+    }
+
+    /// Add a command to run after the files are copied
+    #[must_use]
+    pub fn post_sync(mut self, command: impl Into<String>) -> Self {
+        self.post_sync.push(command.into());
+        self
+    }
+
+    #[must_use]
+    pub const fn prune(mut self, prune: bool) -> Self {
+        self.prune = prune;
+        self
+    }
+
+    #[must_use]
+    pub const fn follow_symlinks(mut self, follow_symlinks: bool) -> Self {
+        self.follow_symlinks = follow_symlinks;
+        self
+    }
+
+    /// Authenticate with the token in this environment variable; the token never enters
+    /// the URL, command line or logs
+    #[must_use]
+    pub fn token_env(mut self, var: impl Into<String>) -> Self {
+        self.token_env = Some(var.into());
+        self
+    }
+
+    #[must_use]
+    pub const fn max_files(mut self, max_files: usize) -> Self {
+        self.max_files = Some(max_files);
+        self
+    }
+
+    #[must_use]
+    pub const fn max_total_bytes(mut self, max_total_bytes: u64) -> Self {
+        self.max_total_bytes = Some(max_total_bytes);
+        self
+    }
+
+    /// Add a pattern for target files that pruning must keep
+    #[must_use]
+    pub fn ignore(mut self, pattern: impl Into<String>) -> Self {
+        self.ignore.push(pattern.into());
+        self
+    }
+
+    /// Check the settings and build the configuration.
+    ///
+    /// `url` and `target_path` are required, and at most one of `branch` and `commit_hash`
+    /// may pin the revision. Without either, [`DEFAULT_BRANCH`] is synced.
+    pub fn build(self) -> Result<RepositoryConfiguration, Cause<ErrorType>> {
+        let invalid = |message: &str| {
+            cause!(
+                ErrorType::RepositoryConfigurationInvalid,
+                message.to_string()
+            )
+        };
+        if self.url.trim().is_empty() {
+            return Err(invalid("Repository configuration needs a url"));
+        }
+        if self.target_path.trim().is_empty() {
+            return Err(invalid("Repository configuration needs a target_path"));
+        }
+        if self.branch.is_some() && self.commit_hash.is_some() {
+            return Err(invalid(
+                "Repository configuration can pin either a branch or a commit_hash, not both",
+            ));
+        }
+
+        Ok(RepositoryConfiguration {
+            url: self.url,
+            branch: self.branch.unwrap_or_else(|| DEFAULT_BRANCH.to_string()),
+            target_path: self.target_path,
+            filters: self.filters,
+            commit_hash: self.commit_hash,
+            mtd: self.mtd,
+            checksum: self.checksum,
+            post_sync: self.post_sync,
+            prune: self.prune,
+            follow_symlinks: self.follow_symlinks,
+            token_env: self.token_env,
+            max_files: self.max_files,
+            max_total_bytes: self.max_total_bytes,
+            ignore: self.ignore,
+        })
+    }
 }
@@ -1,7 +1,15 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use cause::{Cause, cause};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use super::repo_config::RepositoryConfiguration;
+use crate::remote::common::ErrorType;
+use crate::remote::reconcile::{self, DifferenceKind, FileDifference};
+use crate::remote::summary::FileCounts;
+use crate::remote::sync::wire_files;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WireOperation {
@@ -13,6 +21,21 @@ pub struct WireOperation {
     pub operation_id: Uuid,
 }
 
+/// Result of [`WireOperation::sync_then_check`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncCheckOutcome {
+    /// Files the sync wrote or removed
+    pub files: FileCounts,
+    /// Files that still differ from the source afterwards
+    pub differences: Vec<FileDifference>,
+}
+
+impl SyncCheckOutcome {
+    pub fn is_clean(&self) -> bool {
+        self.differences.is_empty()
+    }
+}
+
 impl WireOperation {
     pub fn new(source_config: RepositoryConfiguration, cached_repo_path: String) -> Self {
         Self {
@@ -21,4 +44,80 @@ impl WireOperation {
             operation_id: Uuid::new_v4(),
         }
     }
+
+    /// The wired `src` inside the cached clone, or `None` without filters
+    pub fn source_path(&self) -> Option<PathBuf> {
+        let source = self.source_config.filters.first()?;
+        Some(Path::new(&self.cached_repo_path).join(source))
+    }
+
+    /// Copy the source out of the cached clone into `target_path` under `root_dir`
+    pub fn sync(&self, root_dir: &Path) -> Result<FileCounts, Cause<ErrorType>> {
+        let source = self.existing_source()?;
+        wire_files(
+            &source,
+            &root_dir.join(&self.source_config.target_path),
+            self.source_config.prune,
+            &self.source_config.ignore,
+            self.source_config.follow_symlinks,
+            self.source_config.limits(),
+        )
+    }
+
+    /// Files where the copy under `root_dir` differs from the cached source.
+    ///
+    /// Compares the same clone [`sync`](Self::sync) copies from, so nothing is fetched.
+    pub fn check(&self, root_dir: &Path) -> Result<Vec<FileDifference>, Cause<ErrorType>> {
+        let source = self.existing_source()?;
+        let name = source.file_name().ok_or_else(|| {
+            cause!(
+                ErrorType::CheckDifferenceExecution,
+                format!("{} has no file name", source.display())
+            )
+        })?;
+        let copy = root_dir.join(&self.source_config.target_path).join(name);
+        let compare_error = |e| {
+            cause!(ErrorType::CheckDifferenceExecution)
+                .src(e)
+                .msg(format!("Could not compare {}", copy.display()))
+        };
+
+        if source.is_dir() {
+            return reconcile::find_differences(&source, &copy).map_err(compare_error);
+        }
+        let kind = match fs::read(&copy) {
+            Err(_) => Some(DifferenceKind::MissingLocally),
+            Ok(local) => (fs::read(&source).map_err(compare_error)? != local)
+                .then_some(DifferenceKind::Changed),
+        };
+        Ok(kind
+            .map(|kind| FileDifference {
+                path: PathBuf::from(name),
+                kind,
+            })
+            .into_iter()
+            .collect())
+    }
+
+    /// Sync, then check the result against the same cached clone in one pass
+    pub fn sync_then_check(&self, root_dir: &Path) -> Result<SyncCheckOutcome, Cause<ErrorType>> {
+        let files = self.sync(root_dir)?;
+        let differences = self.check(root_dir)?;
+        Ok(SyncCheckOutcome { files, differences })
+    }
+
+    fn existing_source(&self) -> Result<PathBuf, Cause<ErrorType>> {
+        self.source_path()
+            .filter(|source| source.exists())
+            .ok_or_else(|| {
+                cause!(
+                    ErrorType::NoItemToOperate,
+                    format!(
+                        "Source {:?} does not exist in cached repo {}",
+                        self.source_config.filters.first(),
+                        self.source_config.url
+                    )
+                )
+            })
+    }
 }
@@ -0,0 +1,401 @@
+use std::collections::BTreeSet;
+use std::fmt::{self, Write as _};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use ratatui::{
+    Frame, Terminal,
+    backend::CrosstermBackend,
+    crossterm::{
+        event::{self, Event, KeyCode, KeyEventKind},
+        execute,
+        terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+    },
+    layout::{Constraint, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Text},
+    widgets::{Block, Paragraph},
+};
+
+/// How a file under `dst` differs from the upstream `src`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DifferenceKind {
+    /// Upstream has the file, `dst` doesn't
+    MissingLocally,
+    /// `dst` has a file upstream doesn't
+    OnlyLocal,
+    /// Both have the file with different contents
+    Changed,
+}
+
+impl fmt::Display for DifferenceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::MissingLocally => "missing locally",
+            Self::OnlyLocal => "only local",
+            Self::Changed => "changed",
+        })
+    }
+}
+
+/// One differing file, relative to both `src` and `dst`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileDifference {
+    pub path: PathBuf,
+    pub kind: DifferenceKind,
+}
+
+/// What to do with a differing file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    /// Make `dst` match upstream, writing or deleting the file
+    TakeUpstream,
+    /// Accept the local file as it is
+    KeepLocal,
+    /// Leave the difference unresolved
+    Skip,
+}
+
+/// Files that differ between the `upstream` and `local` directories, sorted by path
+pub fn find_differences(upstream: &Path, local: &Path) -> io::Result<Vec<FileDifference>> {
+    let upstream_files = relative_files(upstream)?;
+    let local_files = relative_files(local)?;
+
+    let mut differences = Vec::new();
+    for path in upstream_files.union(&local_files) {
+        let kind = match (upstream_files.contains(path), local_files.contains(path)) {
+            (true, false) => DifferenceKind::MissingLocally,
+            (false, true) => DifferenceKind::OnlyLocal,
+            _ if fs::read(upstream.join(path))? != fs::read(local.join(path))? => {
+                DifferenceKind::Changed
+            }
+            _ => continue,
+        };
+        differences.push(FileDifference {
+            path: path.clone(),
+            kind,
+        });
+    }
+    Ok(differences)
+}
+
+fn relative_files(root: &Path) -> io::Result<BTreeSet<PathBuf>> {
+    let mut files = BTreeSet::new();
+    if root.is_dir() {
+        collect_relative_files(root, root, &mut files)?;
+    }
+    Ok(files)
+}
+
+fn collect_relative_files(
+    root: &Path,
+    dir: &Path,
+    files: &mut BTreeSet<PathBuf>,
+) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_relative_files(root, &path, files)?;
+        } else if let Ok(relative) = path.strip_prefix(root) {
+            files.insert(relative.to_path_buf());
+        }
+    }
+    Ok(())
+}
+
+/// Apply `resolution` to one differing file under `local`
+pub fn apply_resolution(
+    upstream: &Path,
+    local: &Path,
+    difference: &FileDifference,
+    resolution: Resolution,
+) -> io::Result<()> {
+    if resolution != Resolution::TakeUpstream {
+        return Ok(());
+    }
+
+    let target = local.join(&difference.path);
+    match difference.kind {
+        DifferenceKind::MissingLocally | DifferenceKind::Changed => {
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::copy(upstream.join(&difference.path), &target)?;
+        }
+        DifferenceKind::OnlyLocal => fs::remove_file(&target)?,
+    }
+    Ok(())
+}
+
+/// Line diff from the local file to the upstream one, for display.
+///
+/// Common leading and trailing lines are dropped and the rest shown as removed (`-`, local)
+/// and added (`+`, upstream) lines.
+pub fn render_diff(upstream: &Path, local: &Path, difference: &FileDifference) -> String {
+    let read = |root: &Path| {
+        let bytes = fs::read(root.join(&difference.path)).unwrap_or_default();
+        String::from_utf8(bytes).ok()
+    };
+    let (old, new) = match difference.kind {
+        DifferenceKind::MissingLocally => (Some(String::new()), read(upstream)),
+        DifferenceKind::OnlyLocal => (read(local), Some(String::new())),
+        DifferenceKind::Changed => (read(local), read(upstream)),
+    };
+    let (Some(old), Some(new)) = (old, new) else {
+        return "[Binary file differs]".to_string();
+    };
+
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut diff = format!(
+        "--- local/{0}\n+++ upstream/{0}\n",
+        difference.path.display()
+    );
+    for line in &old[prefix..old.len() - suffix] {
+        let _ = writeln!(&mut diff, "-{line}");
+    }
+    for line in &new[prefix..new.len() - suffix] {
+        let _ = writeln!(&mut diff, "+{line}");
+    }
+    diff
+}
+
+/// Ask for a resolution of each difference in a full-screen terminal UI, then apply them.
+///
+/// Returns `true` when nothing was skipped, i.e. `dst` no longer counts as drifted.
+pub fn resolve_interactively(
+    upstream: &Path,
+    local: &Path,
+    differences: &[FileDifference],
+) -> io::Result<bool> {
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout))?;
+
+    let chosen = choose_resolutions(&mut terminal, upstream, local, differences);
+
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+
+    let mut resolved = true;
+    for (difference, resolution) in differences.iter().zip(chosen?) {
+        apply_resolution(upstream, local, difference, resolution)?;
+        println!(
+            "    {}: {}",
+            difference.path.display(),
+            resolution_label(resolution)
+        );
+        resolved &= resolution != Resolution::Skip;
+    }
+    Ok(resolved)
+}
+
+fn choose_resolutions<B: ratatui::backend::Backend>(
+    terminal: &mut Terminal<B>,
+    upstream: &Path,
+    local: &Path,
+    differences: &[FileDifference],
+) -> io::Result<Vec<Resolution>> {
+    let mut chosen = Vec::with_capacity(differences.len());
+    let mut scroll: u16 = 0;
+
+    while let Some(difference) = differences.get(chosen.len()) {
+        let diff = render_diff(upstream, local, difference);
+        terminal.draw(|frame| {
+            draw(
+                frame,
+                difference,
+                chosen.len(),
+                differences.len(),
+                &diff,
+                scroll,
+            );
+        })?;
+
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        let resolution = match key.code {
+            KeyCode::Char('u') => Resolution::TakeUpstream,
+            KeyCode::Char('l') => Resolution::KeepLocal,
+            KeyCode::Char('s') => Resolution::Skip,
+            KeyCode::Char('q') | KeyCode::Esc => {
+                chosen.resize(differences.len(), Resolution::Skip);
+                break;
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                scroll = scroll.saturating_add(1);
+                continue;
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                scroll = scroll.saturating_sub(1);
+                continue;
+            }
+            _ => continue,
+        };
+        chosen.push(resolution);
+        scroll = 0;
+    }
+    Ok(chosen)
+}
+
+fn draw(
+    frame: &mut Frame,
+    difference: &FileDifference,
+    index: usize,
+    total: usize,
+    diff: &str,
+    scroll: u16,
+) {
+    let [header, body, help] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Min(1),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    let title = format!(
+        "({}/{total}) {} [{}]",
+        index + 1,
+        difference.path.display(),
+        difference.kind
+    );
+    frame.render_widget(
+        Paragraph::new(title).style(Style::default().add_modifier(Modifier::BOLD)),
+        header,
+    );
+
+    let lines: Vec<Line> = diff
+        .lines()
+        .map(|line| {
+            let color = match line.chars().next() {
+                Some('+') => Color::Green,
+                Some('-') => Color::Red,
+                _ => Color::Reset,
+            };
+            Line::styled(line.to_string(), Style::default().fg(color))
+        })
+        .collect();
+    frame.render_widget(
+        Paragraph::new(Text::from(lines))
+            .block(Block::bordered())
+            .scroll((scroll, 0)),
+        body,
+    );
+
+    frame.render_widget(
+        Paragraph::new("u take upstream · l keep local · s skip · ↑/↓ scroll · q skip the rest")
+            .style(Style::default().fg(Color::DarkGray)),
+        help,
+    );
+}
+
+const fn resolution_label(resolution: Resolution) -> &'static str {
+    match resolution {
+        Resolution::TakeUpstream => "took upstream",
+        Resolution::KeepLocal => "kept local",
+        Resolution::Skip => "skipped",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(path: &Path, contents: &str) {
+        fs::create_dir_all(path.parent().expect("file has a parent"))
+            .expect("Failed to create directory");
+        fs::write(path, contents).expect("Failed to write file");
+    }
+
+    fn drifted() -> (TempDir, TempDir) {
+        let upstream = TempDir::new().expect("Failed to create temp dir");
+        let local = TempDir::new().expect("Failed to create temp dir");
+        write(&upstream.path().join("same.txt"), "same");
+        write(&local.path().join("same.txt"), "same");
+        write(&upstream.path().join("nested/new.txt"), "new");
+        write(&upstream.path().join("changed.txt"), "a\nupstream\nz\n");
+        write(&local.path().join("changed.txt"), "a\nlocal\nz\n");
+        write(&local.path().join("extra.txt"), "extra");
+        (upstream, local)
+    }
+
+    #[test]
+    fn test_finds_each_kind_of_difference() {
+        let (upstream, local) = drifted();
+        let differences =
+            find_differences(upstream.path(), local.path()).expect("directories compare");
+
+        let summary: Vec<(&Path, DifferenceKind)> = differences
+            .iter()
+            .map(|d| (d.path.as_path(), d.kind))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (Path::new("changed.txt"), DifferenceKind::Changed),
+                (Path::new("extra.txt"), DifferenceKind::OnlyLocal),
+                (Path::new("nested/new.txt"), DifferenceKind::MissingLocally),
+            ]
+        );
+
+        let diff = render_diff(upstream.path(), local.path(), &differences[0]);
+        assert!(diff.contains("-local\n+upstream\n"));
+        assert!(!diff.contains("-a\n"));
+    }
+
+    #[test]
+    fn test_take_upstream_makes_dst_match() {
+        let (upstream, local) = drifted();
+        for difference in find_differences(upstream.path(), local.path()).expect("compare") {
+            apply_resolution(
+                upstream.path(),
+                local.path(),
+                &difference,
+                Resolution::TakeUpstream,
+            )
+            .expect("resolution applies");
+        }
+
+        assert!(
+            find_differences(upstream.path(), local.path())
+                .expect("compare")
+                .is_empty()
+        );
+        assert!(!local.path().join("extra.txt").exists());
+    }
+
+    #[test]
+    fn test_keep_local_and_skip_leave_dst_untouched() {
+        let (upstream, local) = drifted();
+        let differences = find_differences(upstream.path(), local.path()).expect("compare");
+        for (difference, resolution) in differences.iter().zip([
+            Resolution::KeepLocal,
+            Resolution::Skip,
+            Resolution::KeepLocal,
+        ]) {
+            apply_resolution(upstream.path(), local.path(), difference, resolution)
+                .expect("resolution applies");
+        }
+
+        assert_eq!(
+            find_differences(upstream.path(), local.path()).expect("compare"),
+            differences
+        );
+    }
+}
@@ -0,0 +1,85 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use cause::{Cause, cause};
+use git2::Repository;
+use serde::{Deserialize, Serialize};
+
+use super::common::ErrorType;
+
+/// File under the git directory recording what each entry was last synced to
+const STATE_FILE: &str = "gitwire-state.json";
+
+/// What an entry's `dst` held after its last sync
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntryState {
+    pub url: String,
+    pub rev: String,
+    /// Commit the files were copied from
+    pub commit: String,
+    /// [`tree_checksum`](super::checksum::tree_checksum) of `dst` right after the sync
+    pub checksum: String,
+    /// Seconds since the Unix epoch
+    pub synced_at: u64,
+}
+
+/// Sync state of every entry, keyed by `dst`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncState {
+    pub entries: BTreeMap<String, EntryState>,
+}
+
+impl SyncState {
+    /// Location of the state file for the repository at `root`
+    pub fn path(root: &Path) -> Result<PathBuf, Cause<ErrorType>> {
+        let repo = Repository::discover(root).map_err(|e| {
+            cause!(ErrorType::RepositoryRootPathCommand)
+                .msg(format!(
+                    "Could not find the git repository: {}",
+                    e.message()
+                ))
+                .src(e)
+        })?;
+        Ok(repo.path().join(STATE_FILE))
+    }
+
+    /// Load the recorded state, treating a missing or unreadable file as empty
+    pub fn load(root: &Path) -> Self {
+        Self::path(root)
+            .ok()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, root: &Path) -> Result<(), Cause<ErrorType>> {
+        let path = Self::path(root)?;
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| cause!(ErrorType::SyncStateWrite).src(e))?;
+        fs::write(&path, json).map_err(|e| {
+            cause!(ErrorType::SyncStateWrite)
+                .src(e)
+                .msg(format!("Could not write {}", path.display()))
+        })
+    }
+
+    /// Record that `dst` now holds `checksum`, copied from `commit`
+    pub fn record(&mut self, dst: &str, url: &str, rev: &str, commit: &str, checksum: String) {
+        let synced_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or_default();
+        self.entries.insert(
+            dst.to_string(),
+            EntryState {
+                url: url.to_string(),
+                rev: rev.to_string(),
+                commit: commit.to_string(),
+                checksum,
+                synced_at,
+            },
+        );
+    }
+}
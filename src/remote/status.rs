@@ -0,0 +1,202 @@
+use std::fmt;
+use std::path::Path;
+
+use cause::{Cause, cause};
+use colored::Colorize;
+use git2::{Oid, Repository};
+
+use crate::core::cache::repos_dir;
+
+use super::cache::key_generator::CacheKeyGenerator;
+use super::checksum::tree_checksum;
+use super::common::{ErrorType, Parsed, Target, fetch::identify_commit_hash, parse};
+use super::models::repo_config::RepositoryConfiguration;
+use super::state::{EntryState, SyncState};
+
+/// How far an entry's `dst` has drifted from its source
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryStatus {
+    pub never_synced: bool,
+    /// Commits the source has gained since the last sync; `Some(None)` when the count
+    /// isn't known without fetching
+    pub behind: Option<Option<usize>>,
+    pub modified_locally: bool,
+}
+
+impl EntryStatus {
+    pub fn is_up_to_date(&self) -> bool {
+        !self.never_synced && self.behind.is_none() && !self.modified_locally
+    }
+}
+
+impl fmt::Display for EntryStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.never_synced {
+            return write!(f, "never synced");
+        }
+        if self.is_up_to_date() {
+            return write!(f, "up-to-date");
+        }
+
+        let mut parts = Vec::new();
+        match self.behind {
+            Some(Some(1)) => parts.push("behind (1 commit)".to_string()),
+            Some(Some(n)) => parts.push(format!("behind ({n} commits)")),
+            Some(None) => parts.push("behind".to_string()),
+            None => {}
+        }
+        if self.modified_locally {
+            parts.push("modified locally".to_string());
+        }
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+/// Compare an entry's recorded state with the source tip and the files in `dst`
+pub fn entry_status(
+    recorded: Option<&EntryState>,
+    source_tip: &str,
+    dst_checksum: Option<&str>,
+    count_behind: impl FnOnce(&str, &str) -> Option<usize>,
+) -> EntryStatus {
+    let Some(recorded) = recorded else {
+        return EntryStatus {
+            never_synced: true,
+            behind: None,
+            modified_locally: false,
+        };
+    };
+
+    EntryStatus {
+        never_synced: false,
+        // A pinned `commit_hash` may be abbreviated
+        behind: (!recorded
+            .commit
+            .to_lowercase()
+            .starts_with(&source_tip.to_lowercase()))
+        .then(|| count_behind(&recorded.commit, source_tip)),
+        modified_locally: dst_checksum != Some(recorded.checksum.as_str()),
+    }
+}
+
+/// Print a per-entry drift table without downloading any trees.
+///
+/// The source tip comes from `git ls-remote` (or the pinned commit), and `dst` is compared
+/// with the checksum recorded by the last sync. Returns `false` if any entry has drifted.
+pub fn status(target: &Target) -> Result<bool, Cause<ErrorType>> {
+    let Target::Declared(name) = target else {
+        return Err(cause!(
+            ErrorType::NoItemToOperate,
+            "status only applies to entries declared in .gitwire"
+        ));
+    };
+
+    let (root, parsed) = parse::parse_gitwire()?;
+    let parsed: Vec<Parsed> = parsed
+        .into_iter()
+        .filter(|p| name.is_none() || p.name == *name)
+        .collect();
+    if parsed.is_empty() {
+        return Err(cause!(
+            ErrorType::NoItemToOperate,
+            "There are no items to operate."
+        ));
+    }
+
+    let state = SyncState::load(Path::new(&root));
+    let mut rows = Vec::new();
+    for item in &parsed {
+        let source_tip = match &item.commit_hash {
+            Some(commit) => commit.clone(),
+            None => {
+                identify_commit_hash(Path::new(&root), item)?.unwrap_or_else(|| item.rev.clone())
+            }
+        };
+        let dst_checksum =
+            tree_checksum(&Path::new(&root).join(&item.dst), item.follow_symlinks).ok();
+        let status = entry_status(
+            state.entries.get(&item.dst),
+            &source_tip,
+            dst_checksum.as_deref(),
+            |recorded, tip| count_behind_in_cache(item, recorded, tip),
+        );
+        rows.push((
+            item.name.clone().unwrap_or_else(|| item.dst.clone()),
+            status,
+        ));
+    }
+
+    let width = rows
+        .iter()
+        .map(|(entry, _)| entry.len())
+        .chain(["entry".len()])
+        .max()
+        .unwrap_or_default();
+    println!("{:<width$}  status", "entry");
+    for (entry, status) in &rows {
+        let text = status.to_string();
+        let text = if status.is_up_to_date() {
+            text.green()
+        } else {
+            text.yellow()
+        };
+        println!("{entry:<width$}  {text}");
+    }
+
+    Ok(rows.iter().all(|(_, status)| status.is_up_to_date()))
+}
+
+/// Count commits between `recorded` and `tip` using the sync cache, if it already has both
+fn count_behind_in_cache(item: &Parsed, recorded: &str, tip: &str) -> Option<usize> {
+    let config = RepositoryConfiguration::new(
+        item.url.clone(),
+        item.rev.clone(),
+        item.dst.clone(),
+        vec![item.src.clone()],
+        item.commit_hash.clone(),
+        item.mtd.clone(),
+    );
+    let cache_path = repos_dir()
+        .ok()?
+        .join(CacheKeyGenerator::generate_key(&config));
+    let repo = Repository::open(cache_path).ok()?;
+    let (ahead, _) = repo
+        .graph_ahead_behind(Oid::from_str(tip).ok()?, Oid::from_str(recorded).ok()?)
+        .ok()?;
+    Some(ahead)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recorded(commit: &str, checksum: &str) -> EntryState {
+        EntryState {
+            url: "https://github.com/example/repo.git".to_string(),
+            rev: "main".to_string(),
+            commit: commit.to_string(),
+            checksum: checksum.to_string(),
+            synced_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_entry_status_reports_drift() {
+        let state = recorded("aaaa", "sha256:1");
+
+        let up_to_date = entry_status(Some(&state), "aaaa", Some("sha256:1"), |_, _| None);
+        assert_eq!(up_to_date.to_string(), "up-to-date");
+        let pinned = entry_status(Some(&state), "AAA", Some("sha256:1"), |_, _| None);
+        assert!(pinned.is_up_to_date());
+
+        let behind = entry_status(Some(&state), "bbbb", Some("sha256:1"), |_, _| Some(3));
+        assert_eq!(behind.to_string(), "behind (3 commits)");
+
+        let both = entry_status(Some(&state), "bbbb", Some("sha256:2"), |_, _| None);
+        assert_eq!(both.to_string(), "behind, modified locally");
+
+        let never = entry_status(None, "aaaa", None, |_, _| None);
+        assert_eq!(never.to_string(), "never synced");
+        assert!(!never.is_up_to_date());
+    }
+}
@@ -0,0 +1,159 @@
+use std::fmt;
+
+use serde::Serialize;
+
+/// File changes made while wiring one entry
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct FileCounts {
+    pub created: usize,
+    pub updated: usize,
+    pub deleted: usize,
+    pub bytes_copied: u64,
+}
+
+/// Outcome of syncing one `.gitwire` entry
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct EntrySummary {
+    /// The entry's `dst`
+    pub target_path: String,
+    #[serde(flatten)]
+    pub files: FileCounts,
+}
+
+/// Outcome of a whole `git wire sync`, printed as a table at the end of the run
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct SyncSummary {
+    pub entries: Vec<EntrySummary>,
+    /// Repositories reused from the cache
+    pub cache_hits: usize,
+    /// Repositories cloned for this run
+    pub fresh_fetches: usize,
+    /// Entries still differing from their source after the sync, when it was checked
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub drifted: Option<Vec<String>>,
+}
+
+impl SyncSummary {
+    /// Sum of the file counts over all entries
+    pub fn totals(&self) -> FileCounts {
+        self.entries
+            .iter()
+            .fold(FileCounts::default(), |total, entry| FileCounts {
+                created: total.created + entry.files.created,
+                updated: total.updated + entry.files.updated,
+                deleted: total.deleted + entry.files.deleted,
+                bytes_copied: total.bytes_copied + entry.files.bytes_copied,
+            })
+    }
+
+    /// Whether every entry matched its source, or the sync wasn't checked
+    pub fn is_clean(&self) -> bool {
+        self.drifted.as_ref().is_none_or(Vec::is_empty)
+    }
+}
+
+impl fmt::Display for SyncSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self
+            .entries
+            .iter()
+            .map(|entry| entry.target_path.len())
+            .chain(["entry".len(), "total".len()])
+            .max()
+            .unwrap_or_default();
+        let row = |f: &mut fmt::Formatter<'_>, name: &str, files: &FileCounts| {
+            writeln!(
+                f,
+                "{name:<width$}  {:>7}  {:>7}  {:>7}  {:>12}",
+                files.created, files.updated, files.deleted, files.bytes_copied
+            )
+        };
+
+        writeln!(
+            f,
+            "{:<width$}  {:>7}  {:>7}  {:>7}  {:>12}",
+            "entry", "created", "updated", "deleted", "bytes"
+        )?;
+        for entry in &self.entries {
+            row(f, &entry.target_path, &entry.files)?;
+        }
+        row(f, "total", &self.totals())?;
+        write!(
+            f,
+            "{} entries, {} repositories reused from cache, {} freshly fetched",
+            self.entries.len(),
+            self.cache_hits,
+            self.fresh_fetches
+        )?;
+        match &self.drifted {
+            Some(drifted) if drifted.is_empty() => {
+                write!(f, "\nChecked: every entry matches its source")
+            }
+            Some(drifted) => write!(f, "\nChecked: drifted from source: {}", drifted.join(", ")),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_totals_and_table() {
+        let summary = SyncSummary {
+            entries: vec![
+                EntrySummary {
+                    target_path: "vendor/proto".to_string(),
+                    files: FileCounts {
+                        created: 2,
+                        updated: 1,
+                        deleted: 0,
+                        bytes_copied: 300,
+                    },
+                },
+                EntrySummary {
+                    target_path: "vendor/openapi".to_string(),
+                    files: FileCounts {
+                        created: 0,
+                        updated: 3,
+                        deleted: 1,
+                        bytes_copied: 700,
+                    },
+                },
+            ],
+            cache_hits: 1,
+            fresh_fetches: 1,
+            drifted: None,
+        };
+
+        assert_eq!(
+            summary.totals(),
+            FileCounts {
+                created: 2,
+                updated: 4,
+                deleted: 1,
+                bytes_copied: 1000,
+            }
+        );
+
+        let table = summary.to_string();
+        assert!(table.lines().any(|line| line.starts_with("vendor/openapi")));
+        assert!(table.ends_with("2 entries, 1 repositories reused from cache, 1 freshly fetched"));
+
+        let json = serde_json::to_value(&summary).expect("summary serializes");
+        assert_eq!(json["entries"][0]["created"], 2);
+        assert!(json.get("drifted").is_none());
+
+        let checked = SyncSummary {
+            drifted: Some(vec!["vendor/proto".to_string()]),
+            ..summary
+        };
+        assert!(!checked.is_clean());
+        assert!(
+            checked
+                .to_string()
+                .ends_with("Checked: drifted from source: vendor/proto")
+        );
+    }
+}
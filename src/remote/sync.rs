@@ -1,17 +1,27 @@
+use std::collections::{HashMap, HashSet};
 use std::env;
 use std::fs;
-use std::path::Path;
+use std::io;
+use std::path::{Path, PathBuf};
 
 use cause::{Cause, cause};
-use fs_extra::{copy_items, dir::CopyOptions, remove_items};
-use futures::future::join_all;
-use log::{debug, info};
+use futures::stream::{self, StreamExt};
+use ignore::gitignore::GitignoreBuilder;
+use log::{debug, info, warn};
+
+use crate::core::cache::repos_dir;
 
 use super::cache::{
-    fetcher::RepositoryFetcher, key_generator::CacheKeyGenerator, manager::CacheManager,
+    fetcher::RepositoryFetcher,
+    key_generator::CacheKeyGenerator,
+    manager::{CacheManager, MANIFEST_FILE, ManifestRestore},
 };
+use super::checksum::{tree_checksum, verify_checksum};
 use super::common::{ErrorType, Target, parse};
-use super::models::repo_config::RepositoryConfiguration;
+use super::hooks::run_post_sync;
+use super::models::repo_config::{RepositoryConfiguration, WireLimits};
+use super::state::SyncState;
+use super::summary::{EntrySummary, FileCounts, SyncSummary};
 
 fn get_repo_configs(
     target: &Target,
@@ -31,14 +41,23 @@ fn get_repo_configs(
             let repo_configs = parsed_items
                 .into_iter()
                 .map(|parsed| {
-                    RepositoryConfiguration::new(
+                    let mut config = RepositoryConfiguration::new(
                         parsed.url,
                         parsed.rev,
                         parsed.dst,
                         vec![parsed.src],
-                        None,
+                        parsed.commit_hash,
                         parsed.mtd,
-                    )
+                    );
+                    config.checksum = parsed.checksum;
+                    config.post_sync = parsed.post_sync;
+                    config.prune = parsed.prune;
+                    config.follow_symlinks = parsed.follow_symlinks;
+                    config.token_env = parsed.token_env;
+                    config.max_files = parsed.max_files;
+                    config.max_total_bytes = parsed.max_total_bytes;
+                    config.ignore = parsed.ignore;
+                    config
                 })
                 .collect();
             Ok((root, repo_configs))
@@ -48,33 +67,81 @@ fn get_repo_configs(
                 .or(Err(cause!(ErrorType::CurrentDirRetrieve)))?
                 .to_string_lossy()
                 .to_string();
-            let repo_configs = vec![RepositoryConfiguration::new(
+            let mut config = RepositoryConfiguration::new(
                 parsed.url.clone(),
                 parsed.rev.clone(),
                 parsed.dst.clone(),
                 vec![parsed.src.clone()],
-                None,
+                parsed.commit_hash.clone(),
                 parsed.mtd.clone(),
-            )];
+            );
+            config.checksum.clone_from(&parsed.checksum);
+            config.post_sync.clone_from(&parsed.post_sync);
+            config.prune = parsed.prune;
+            config.follow_symlinks = parsed.follow_symlinks;
+            config.token_env.clone_from(&parsed.token_env);
+            config.max_files = parsed.max_files;
+            config.max_total_bytes = parsed.max_total_bytes;
+            config.ignore.clone_from(&parsed.ignore);
+            let repo_configs = vec![config];
             Ok((root, repo_configs))
         }
     }
 }
 
-// Enhanced sync functionality that integrates caching
+// Enhanced sync functionality that integrates caching.
+// `post_sync` hooks only run when `allow_hooks` is set, so syncing someone else's
+// `.gitwire` never executes commands by surprise.
 pub async fn sync_with_caching(
     target: &Target,
-    _mode: super::common::sequence::Mode,
-) -> Result<bool, Cause<ErrorType>> {
+    mode: super::common::sequence::Mode,
+    allow_hooks: bool,
+) -> Result<SyncSummary, Cause<ErrorType>> {
+    run_with_caching(target, mode, allow_hooks, false).await
+}
+
+/// Sync like [`sync_with_caching`], then check each entry against the clone it was just
+/// copied from, so verifying costs no second round of fetching.
+pub async fn sync_then_check_with_caching(
+    target: &Target,
+    mode: super::common::sequence::Mode,
+    allow_hooks: bool,
+) -> Result<SyncSummary, Cause<ErrorType>> {
+    run_with_caching(target, mode, allow_hooks, true).await
+}
+
+async fn run_with_caching(
+    target: &Target,
+    mode: super::common::sequence::Mode,
+    allow_hooks: bool,
+    check: bool,
+) -> Result<SyncSummary, Cause<ErrorType>> {
     info!("git-wire sync with caching started");
 
     let (root_dir, repo_configs) = get_repo_configs(target)?;
+    let repo_configs = tokio::task::spawn_blocking(move || resolve_commit_hashes(repo_configs))
+        .await
+        .map_err(|e| cause!(ErrorType::TaskJoin).msg(format!("Task join error: {e:?}")))??;
 
     info!("Found {} repository configurations", repo_configs.len());
 
-    // Create components needed for caching
+    // Create components needed for caching, picking up clones a previous run (possibly
+    // on another CI job) recorded in the manifest
+    let manifest_path = repos_dir()
+        .map_err(|e| cause!(ErrorType::TempDirCreation).msg(e.to_string()))?
+        .join(MANIFEST_FILE);
     let cache_manager = CacheManager::new();
-    let fetcher = RepositoryFetcher;
+    let restored = cache_manager
+        .restore_manifest(&manifest_path)
+        .unwrap_or_else(|e| {
+            warn!("Ignoring git wire cache manifest: {e}");
+            ManifestRestore::default()
+        });
+    debug!(
+        "Cache manifest: {} clones reusable, {} stale",
+        restored.reused.len(),
+        restored.stale.len()
+    );
 
     // Plan fetch operations to identify unique repositories
     let (unique_configs, mut wire_operations) = cache_manager
@@ -87,32 +154,34 @@ pub async fn sync_with_caching(
         repo_configs.len().saturating_sub(unique_configs.len())
     );
 
-    // Fetch each unique repository to its cache location in parallel
+    // Fetch each unique repository to its cache location, at most `mode.workers` at once.
+    // The cache manager serializes fetches of the same URL whatever the job count.
     let fetch_futures = unique_configs
         .iter()
         .map(|config| {
-            let config = config.clone();
-            let fetcher = fetcher.clone();
+            let cache_key = CacheKeyGenerator::generate_key(config);
+            // A clone that moved since the manifest was written is fetched again
+            let stale = restored.stale.contains(&cache_key);
+            let cache_manager = &cache_manager;
             async move {
-                let cache_key = CacheKeyGenerator::generate_key(&config);
-                let cache_dir = env::temp_dir().join("git-wire-cache").join(cache_key);
-                fs::create_dir_all(&cache_dir)
-                    .map_err(|e| cause!(ErrorType::TempDirCreation).src(e))?;
-                let cache_path = cache_dir.to_string_lossy().to_string();
-
-                debug!(
-                    "Fetching repository {} to cache path {}",
-                    config.url, cache_path
-                );
-
-                fetcher.fetch_repository(&config, &cache_path).await?;
+                let cache_dir = repos_dir()
+                    .map_err(|e| cause!(ErrorType::TempDirCreation).msg(e.to_string()))?
+                    .join(cache_key);
+                let cache_path = cache_manager.fetch(config, &cache_dir, stale).await?;
                 debug!("Repository {} successfully cached", config.url);
-                Ok((config, cache_path))
+                Ok::<_, Cause<ErrorType>>((config, cache_path))
             }
         })
         .collect::<Vec<_>>();
 
-    let fetch_results = join_all(fetch_futures).await;
+    let fetch_results: Vec<_> = stream::iter(fetch_futures)
+        .buffer_unordered(mode.workers(unique_configs.len()))
+        .collect()
+        .await;
+
+    let mut summary = SyncSummary::default();
+    let mut state = SyncState::load(Path::new(&root_dir));
+    let mut drifted = Vec::new();
 
     // Collect successful fetches and update wire operations
     for result in fetch_results {
@@ -124,6 +193,14 @@ pub async fn sync_with_caching(
         }
     }
 
+    let metrics = cache_manager.metrics();
+    summary.cache_hits = metrics.cache_hits;
+    summary.fresh_fetches = metrics.unique_pulls;
+
+    if let Err(e) = cache_manager.save_manifest(&manifest_path) {
+        warn!("Could not save git wire cache manifest: {e}");
+    }
+
     // Execute wire operations using cached repositories
     for wire_op in &wire_operations {
         if wire_op.source_config.filters.is_empty() {
@@ -144,38 +221,656 @@ pub async fn sync_with_caching(
             continue;
         }
 
+        // Refuse an unexpected upstream tree before any of it reaches `dst` or a hook
+        if let Some(expected) = &wire_op.source_config.checksum {
+            verify_checksum(
+                &wire_op.source_config.target_path,
+                &source_content,
+                wire_op.source_config.follow_symlinks,
+                expected,
+            )?;
+            debug!(
+                "Checksum verified for {}",
+                wire_op.source_config.target_path
+            );
+        }
+
         let dest_dir = Path::new(&root_dir).join(&wire_op.source_config.target_path);
+        let files = wire_files(
+            &source_content,
+            &dest_dir,
+            wire_op.source_config.prune,
+            &wire_op.source_config.ignore,
+            wire_op.source_config.follow_symlinks,
+            wire_op.source_config.limits(),
+        )?;
 
-        // Remove destination if it exists
-        if dest_dir.exists() {
-            remove_items(&[dest_dir.as_path()]).map_err(|e| {
-                cause!(ErrorType::MoveFromTempToDest)
-                    .src(e)
-                    .msg(format!("Could not remove {}", dest_dir.display()))
-            })?;
+        debug!(
+            "Copied contents of {source_subdir} to {}",
+            wire_op.source_config.target_path
+        );
+
+        let post_sync = &wire_op.source_config.post_sync;
+        if !post_sync.is_empty() {
+            if allow_hooks {
+                run_post_sync(
+                    &wire_op.source_config.target_path,
+                    post_sync,
+                    Path::new(&root_dir),
+                    &dest_dir,
+                )?;
+            } else {
+                warn!(
+                    "Skipping {} post_sync command(s) for {}; pass --allow-hooks to run them",
+                    post_sync.len(),
+                    wire_op.source_config.target_path
+                );
+            }
+        }
+
+        if check {
+            let differences = wire_op.check(Path::new(&root_dir))?;
+            for difference in &differences {
+                warn!(
+                    "{}: {} is {} after sync",
+                    wire_op.source_config.target_path,
+                    difference.path.display(),
+                    difference.kind
+                );
+            }
+            if !differences.is_empty() {
+                drifted.push(wire_op.source_config.target_path.clone());
+            }
         }
 
-        // Create destination directory
-        fs::create_dir_all(&dest_dir).map_err(|e| cause!(ErrorType::MoveFromTempToDest).src(e))?;
+        record_state(
+            &mut state,
+            &wire_op.source_config,
+            &wire_op.cached_repo_path,
+            &dest_dir,
+        );
+        summary.entries.push(EntrySummary {
+            target_path: wire_op.source_config.target_path.clone(),
+            files,
+        });
+    }
 
-        let mut opt = CopyOptions::new();
-        opt.overwrite = true;
-        opt.copy_inside = true;
+    if let Err(e) = state.save(Path::new(&root_dir)) {
+        warn!("Could not save sync state for git wire status: {e:?}");
+    }
+    if check {
+        summary.drifted = Some(drifted);
+    }
 
-        copy_items(&[source_content.as_path()], &dest_dir, &opt).map_err(|e| {
-            cause!(ErrorType::MoveFromTempToDest).src(e).msg(format!(
-                "Could not copy {} to {}",
-                source_content.display(),
-                dest_dir.display()
-            ))
+    info!("git-wire sync with caching completed");
+    Ok(summary)
+}
+
+/// Remember the commit and `dst` checksum an entry was synced to, for `git wire status`
+fn record_state(
+    state: &mut SyncState,
+    config: &RepositoryConfiguration,
+    cached_repo_path: &str,
+    dest_dir: &Path,
+) {
+    let head_commit = |path: &str| -> Result<git2::Oid, git2::Error> {
+        let repo = git2::Repository::open(path)?;
+        let commit = repo.head()?.peel_to_commit()?;
+        Ok(commit.id())
+    };
+    let commit = match head_commit(cached_repo_path) {
+        Ok(commit) => commit.to_string(),
+        Err(e) => {
+            debug!("Not recording state for {}: {e}", config.target_path);
+            return;
+        }
+    };
+    let checksum = match tree_checksum(dest_dir, config.follow_symlinks) {
+        Ok(checksum) => checksum,
+        Err(e) => {
+            debug!("Not recording state for {}: {e}", config.target_path);
+            return;
+        }
+    };
+    state.record(
+        &config.target_path,
+        &config.url,
+        &config.branch,
+        &commit,
+        checksum,
+    );
+}
+
+/// Expand abbreviated `commit_hash`es to full hashes.
+///
+/// Cache keys include the commit hash, so this lets entries that abbreviate the same
+/// commit differently share a cache slot. Each URL is cloned once, without trees or
+/// blobs, to resolve against.
+fn resolve_commit_hashes(
+    mut configs: Vec<RepositoryConfiguration>,
+) -> Result<Vec<RepositoryConfiguration>, Cause<ErrorType>> {
+    let mut resolvers: HashMap<String, temp_dir::TempDir> = HashMap::new();
+
+    for config in &mut configs {
+        let Some(hash) = &config.commit_hash else {
+            continue;
+        };
+        if RepositoryFetcher::is_full_commit_hash(hash) {
+            config.commit_hash = Some(hash.to_lowercase());
+            continue;
+        }
+
+        if !resolvers.contains_key(&config.url) {
+            let dir =
+                temp_dir::TempDir::new().map_err(|e| cause!(ErrorType::TempDirCreation).src(e))?;
+            let path = dir.path().join("repo.git");
+            debug!("Cloning {} to resolve commit hashes", config.url);
+            RepositoryFetcher::clone_for_resolving(
+                &config.url,
+                config.token_env.as_deref(),
+                &path,
+            )?;
+            resolvers.insert(config.url.clone(), dir);
+        }
+        let resolver = resolvers[&config.url].path().join("repo.git");
+
+        let resolved = RepositoryFetcher::resolve_commit_hash(&resolver, hash)?;
+        debug!("Resolved commit {hash} of {} to {resolved}", config.url);
+        config.commit_hash = Some(resolved);
+    }
+
+    Ok(configs)
+}
+
+/// Copy `source` into `dest_dir`, then with `prune` remove files it didn't write.
+///
+/// Files matching an `ignore` pattern (relative to `dest_dir`) survive pruning, so local
+/// additions can be kept. Without `prune` the copy is purely additive. Symlinks under
+/// `source` are followed only while they stay inside it, unless `follow_symlinks` is set.
+/// The copy stops with an error as soon as `source` turns out to exceed `limits`.
+pub(crate) fn wire_files(
+    source: &Path,
+    dest_dir: &Path,
+    prune: bool,
+    ignore: &[String],
+    follow_symlinks: bool,
+    limits: WireLimits,
+) -> Result<FileCounts, Cause<ErrorType>> {
+    let copy_error = |e: io::Error| {
+        cause!(ErrorType::MoveFromTempToDest).src(e).msg(format!(
+            "Could not copy {} to {}",
+            source.display(),
+            dest_dir.display()
+        ))
+    };
+
+    fs::create_dir_all(dest_dir).map_err(copy_error)?;
+    let mut counts = FileCounts::default();
+    let written = copy_into(source, dest_dir, follow_symlinks, limits, &mut counts)?;
+    if !prune {
+        return Ok(counts);
+    }
+
+    let mut builder = GitignoreBuilder::new(dest_dir);
+    for pattern in ignore {
+        builder.add_line(None, pattern).map_err(|e| {
+            cause!(ErrorType::IgnorePatternInvalid)
+                .src(e)
+                .msg(format!("Invalid ignore pattern '{pattern}'"))
         })?;
+    }
+    let ignored = builder
+        .build()
+        .map_err(|e| cause!(ErrorType::IgnorePatternInvalid).src(e))?;
 
-        debug!(
-            "Copied contents of {source_subdir} to {}",
-            wire_op.source_config.target_path
+    let mut existing = Vec::new();
+    collect_files(dest_dir, dest_dir, &mut existing).map_err(copy_error)?;
+
+    for relative in existing {
+        if written.contains(&relative)
+            || ignored
+                .matched_path_or_any_parents(&relative, false)
+                .is_ignore()
+        {
+            continue;
+        }
+        let path = dest_dir.join(&relative);
+        fs::remove_file(&path).map_err(|e| {
+            cause!(ErrorType::MoveFromTempToDest)
+                .src(e)
+                .msg(format!("Could not remove {}", path.display()))
+        })?;
+        debug!("Pruned {}", path.display());
+        counts.deleted += 1;
+    }
+    remove_empty_dirs(dest_dir).map_err(copy_error)?;
+
+    Ok(counts)
+}
+
+/// Copy `source` into `dest_dir` like `cp -r`, returning the files written relative to
+/// `dest_dir`. A directory lands at `dest_dir/<name>`.
+fn copy_into(
+    source: &Path,
+    dest_dir: &Path,
+    follow_symlinks: bool,
+    limits: WireLimits,
+    counts: &mut FileCounts,
+) -> Result<HashSet<PathBuf>, Cause<ErrorType>> {
+    let name = PathBuf::from(source.file_name().ok_or_else(|| {
+        cause!(
+            ErrorType::MoveFromTempToDest,
+            format!("{} has no file name", source.display())
+        )
+    })?);
+    let root = fs::canonicalize(source).map_err(|e| copy_io_error(source, e))?;
+    let mut walk = Walk {
+        root: root.clone(),
+        follow_symlinks,
+        active_dirs: Vec::new(),
+        limits,
+        files: 0,
+        bytes: 0,
+    };
+    let mut written = HashSet::new();
+
+    if root.is_dir() {
+        copy_dir(&root, dest_dir, &name, &mut walk, &mut written, counts)?;
+    } else {
+        walk.admit(&root)?;
+        copy_file(&root, &dest_dir.join(&name), counts).map_err(|e| copy_io_error(source, e))?;
+        written.insert(name);
+    }
+    debug!(
+        "{}: {} of {} files, {} of {} bytes",
+        source.display(),
+        walk.files,
+        limit_text(limits.max_files),
+        walk.bytes,
+        limit_text(limits.max_total_bytes)
+    );
+    Ok(written)
+}
+
+/// Every file a copy of the directory `source` reads, as its path relative to `source`
+/// and the file that path resolves to, following symlinks exactly as the copy does
+pub(crate) fn source_files(
+    source: &Path,
+    follow_symlinks: bool,
+) -> Result<Vec<(PathBuf, PathBuf)>, Cause<ErrorType>> {
+    let root = fs::canonicalize(source).map_err(|e| copy_io_error(source, e))?;
+    let mut walk = Walk {
+        root: root.clone(),
+        follow_symlinks,
+        active_dirs: Vec::new(),
+        limits: WireLimits::default(),
+        files: 0,
+        bytes: 0,
+    };
+    let mut files = Vec::new();
+    list_dir(&root, Path::new(""), &mut walk, &mut files)?;
+    Ok(files)
+}
+
+fn list_dir(
+    source: &Path,
+    relative: &Path,
+    walk: &mut Walk,
+    files: &mut Vec<(PathBuf, PathBuf)>,
+) -> Result<(), Cause<ErrorType>> {
+    let io_error = |e| copy_io_error(source, e);
+    walk.active_dirs.push(source.to_path_buf());
+    for entry in fs::read_dir(source).map_err(io_error)? {
+        let entry = entry.map_err(io_error)?;
+        let mut path = entry.path();
+        if entry.file_type().map_err(io_error)?.is_symlink() {
+            path = walk.resolve(&path)?;
+        }
+        let child = relative.join(entry.file_name());
+        if path.is_dir() {
+            list_dir(&path, &child, walk, files)?;
+        } else {
+            files.push((child, path));
+        }
+    }
+    walk.active_dirs.pop();
+    Ok(())
+}
+
+fn limit_text<T: ToString>(limit: Option<T>) -> String {
+    limit.map_or_else(|| "unlimited".to_string(), |limit| limit.to_string())
+}
+
+/// Symlink handling and limit accounting for one copy of a canonicalized source `root`
+struct Walk {
+    root: PathBuf,
+    follow_symlinks: bool,
+    /// Canonical directories currently being copied, to stop symlink loops
+    active_dirs: Vec<PathBuf>,
+    limits: WireLimits,
+    /// Files and bytes seen so far, changed or not
+    files: usize,
+    bytes: u64,
+}
+
+impl Walk {
+    /// Count the file at `path` towards the limits, erroring once one is exceeded
+    fn admit(&mut self, path: &Path) -> Result<(), Cause<ErrorType>> {
+        let size = fs::metadata(path)
+            .map_err(|e| copy_io_error(path, e))?
+            .len();
+        self.files += 1;
+        self.bytes += size;
+
+        if let Some(max) = self.limits.max_files
+            && self.files > max
+        {
+            return Err(cause!(
+                ErrorType::WireLimitExceeded,
+                format!(
+                    "{} has more than max_files = {max} files; narrow `src` or raise the limit",
+                    self.root.display()
+                )
+            ));
+        }
+        if let Some(max) = self.limits.max_total_bytes
+            && self.bytes > max
+        {
+            return Err(cause!(
+                ErrorType::WireLimitExceeded,
+                format!(
+                    "{} holds more than max_total_bytes = {max} bytes; narrow `src` or raise the limit",
+                    self.root.display()
+                )
+            ));
+        }
+        Ok(())
+    }
+
+    /// Where the symlink at `link` points, refusing targets outside `root` unless trusted
+    fn resolve(&self, link: &Path) -> Result<PathBuf, Cause<ErrorType>> {
+        let target = fs::canonicalize(link).map_err(|e| {
+            cause!(ErrorType::MoveFromTempToDest)
+                .src(e)
+                .msg(format!("Could not resolve symlink {}", link.display()))
+        })?;
+        if !self.follow_symlinks && !target.starts_with(&self.root) {
+            return Err(cause!(
+                ErrorType::SymlinkOutsideSource,
+                format!(
+                    "Symlink {} points to {}, outside the wired source {}; set follow_symlinks to copy it anyway",
+                    link.display(),
+                    target.display(),
+                    self.root.display()
+                )
+            ));
+        }
+        if self.active_dirs.contains(&target) {
+            return Err(cause!(
+                ErrorType::MoveFromTempToDest,
+                format!(
+                    "Symlink {} loops back to {}",
+                    link.display(),
+                    target.display()
+                )
+            ));
+        }
+        Ok(target)
+    }
+}
+
+fn copy_dir(
+    source: &Path,
+    dest_dir: &Path,
+    relative: &Path,
+    walk: &mut Walk,
+    written: &mut HashSet<PathBuf>,
+    counts: &mut FileCounts,
+) -> Result<(), Cause<ErrorType>> {
+    let io_error = |e| copy_io_error(source, e);
+    fs::create_dir_all(dest_dir.join(relative)).map_err(io_error)?;
+    walk.active_dirs.push(source.to_path_buf());
+    for entry in fs::read_dir(source).map_err(io_error)? {
+        let entry = entry.map_err(io_error)?;
+        let mut path = entry.path();
+        if entry.file_type().map_err(io_error)?.is_symlink() {
+            path = walk.resolve(&path)?;
+        }
+        let child = relative.join(entry.file_name());
+        if path.is_dir() {
+            copy_dir(&path, dest_dir, &child, walk, written, counts)?;
+        } else {
+            walk.admit(&path)?;
+            copy_file(&path, &dest_dir.join(&child), counts)
+                .map_err(|e| copy_io_error(&path, e))?;
+            written.insert(child);
+        }
+    }
+    walk.active_dirs.pop();
+    Ok(())
+}
+
+fn copy_io_error(source: &Path, e: io::Error) -> Cause<ErrorType> {
+    cause!(ErrorType::MoveFromTempToDest)
+        .src(e)
+        .msg(format!("Could not copy {}", source.display()))
+}
+
+/// Copy one file, counting it as created or, when its contents changed, updated
+fn copy_file(source: &Path, dest: &Path, counts: &mut FileCounts) -> io::Result<()> {
+    let previous = fs::read(dest).ok();
+    if previous.is_some() && previous == Some(fs::read(source)?) {
+        return Ok(());
+    }
+
+    counts.bytes_copied += fs::copy(source, dest)?;
+    if previous.is_some() {
+        counts.updated += 1;
+    } else {
+        counts.created += 1;
+    }
+    Ok(())
+}
+
+fn collect_files(root: &Path, dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(root, &path, files)?;
+        } else if let Ok(relative) = path.strip_prefix(root) {
+            files.push(relative.to_path_buf());
+        }
+    }
+    Ok(())
+}
+
+/// Remove directories under `dir` left empty by pruning, keeping `dir` itself
+fn remove_empty_dirs(dir: &Path) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() && !path.is_symlink() {
+            remove_empty_dirs(&path)?;
+            if fs::read_dir(&path)?.next().is_none() {
+                fs::remove_dir(&path)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(path: &Path, contents: &str) {
+        fs::create_dir_all(path.parent().expect("file has a parent"))
+            .expect("Failed to create directory");
+        fs::write(path, contents).expect("Failed to write file");
+    }
+
+    #[test]
+    fn test_upstream_deletion_is_pruned_only_with_prune() {
+        for prune in [false, true] {
+            let upstream = TempDir::new().expect("Failed to create temp dir");
+            let dst = TempDir::new().expect("Failed to create temp dir");
+            let src = upstream.path().join("proto");
+            write(&src.join("kept.proto"), "kept");
+            write(&src.join("deleted.proto"), "deleted");
+            wire_files(&src, dst.path(), prune, &[], false, WireLimits::default())
+                .expect("initial sync");
+
+            // The file disappears upstream, and a local file is added next to the wired ones
+            fs::remove_file(src.join("deleted.proto")).expect("Failed to delete upstream file");
+            write(&dst.path().join("proto/local.txt"), "local");
+            let counts = wire_files(
+                &src,
+                dst.path(),
+                prune,
+                &["local.txt".to_string()],
+                false,
+                WireLimits::default(),
+            )
+            .expect("second sync");
+
+            assert!(dst.path().join("proto/kept.proto").exists());
+            assert!(dst.path().join("proto/local.txt").exists());
+            assert_eq!(dst.path().join("proto/deleted.proto").exists(), !prune);
+            assert_eq!(counts.deleted, usize::from(prune));
+        }
+    }
+
+    #[test]
+    fn test_wire_files_counts_changes() {
+        let upstream = TempDir::new().expect("Failed to create temp dir");
+        let dst = TempDir::new().expect("Failed to create temp dir");
+        let src = upstream.path().join("api");
+        write(&src.join("a.yaml"), "aaaa");
+        write(&src.join("nested/b.yaml"), "bb");
+        write(&src.join("c.yaml"), "c");
+
+        let first = wire_files(&src, dst.path(), true, &[], false, WireLimits::default())
+            .expect("initial sync");
+        assert_eq!(
+            first,
+            FileCounts {
+                created: 3,
+                updated: 0,
+                deleted: 0,
+                bytes_copied: 7,
+            }
+        );
+
+        write(&src.join("a.yaml"), "changed");
+        fs::remove_file(src.join("c.yaml")).expect("Failed to delete upstream file");
+        let second = wire_files(&src, dst.path(), true, &[], false, WireLimits::default())
+            .expect("second sync");
+        assert_eq!(
+            second,
+            FileCounts {
+                created: 0,
+                updated: 1,
+                deleted: 1,
+                bytes_copied: 7,
+            }
         );
     }
 
-    info!("git-wire sync with caching completed");
-    Ok(true)
+    #[test]
+    fn test_wire_limits_stop_runaway_sync() {
+        let upstream = TempDir::new().expect("Failed to create temp dir");
+        let src = upstream.path().join("vendor");
+        write(&src.join("a.txt"), "aaaa");
+        write(&src.join("b.txt"), "bbbb");
+        write(&src.join("nested/c.txt"), "cccc");
+
+        let too_many = WireLimits {
+            max_files: Some(2),
+            max_total_bytes: None,
+        };
+        let dst = TempDir::new().expect("Failed to create temp dir");
+        let err = wire_files(&src, dst.path(), false, &[], false, too_many)
+            .expect_err("file limit trips");
+        assert!(err.to_string().contains("max_files = 2"));
+
+        let too_big = WireLimits {
+            max_files: None,
+            max_total_bytes: Some(10),
+        };
+        let dst = TempDir::new().expect("Failed to create temp dir");
+        let err =
+            wire_files(&src, dst.path(), false, &[], false, too_big).expect_err("size limit trips");
+        assert!(err.to_string().contains("max_total_bytes = 10"));
+
+        let exact = WireLimits {
+            max_files: Some(3),
+            max_total_bytes: Some(12),
+        };
+        let dst = TempDir::new().expect("Failed to create temp dir");
+        let counts =
+            wire_files(&src, dst.path(), false, &[], false, exact).expect("within the limits");
+        assert_eq!(counts.created, 3);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_leaving_source_is_refused_unless_trusted() {
+        use std::os::unix::fs::symlink;
+
+        let upstream = TempDir::new().expect("Failed to create temp dir");
+        let src = upstream.path().join("proto");
+        write(&src.join("api.proto"), "api");
+        write(&src.join("nested/types.proto"), "types");
+        write(&upstream.path().join("secrets/token"), "hunter2");
+        // A link inside the source is fine; one escaping it would leak unrelated files
+        symlink(src.join("nested"), src.join("alias")).expect("Failed to create symlink");
+        symlink(upstream.path().join("secrets"), src.join("leak"))
+            .expect("Failed to create symlink");
+
+        let dst = TempDir::new().expect("Failed to create temp dir");
+        let err = wire_files(&src, dst.path(), false, &[], false, WireLimits::default())
+            .expect_err("escaping symlink is refused");
+        let message = err.to_string();
+        assert!(message.contains("outside the wired source"));
+        assert!(message.contains("leak"));
+        assert!(!dst.path().join("proto/leak/token").exists());
+
+        fs::remove_file(src.join("leak")).expect("Failed to remove symlink");
+        wire_files(&src, dst.path(), false, &[], false, WireLimits::default())
+            .expect("internal symlink is followed");
+        assert_eq!(
+            fs::read_to_string(dst.path().join("proto/alias/types.proto")).expect("copied"),
+            "types"
+        );
+
+        symlink(upstream.path().join("secrets"), src.join("leak"))
+            .expect("Failed to create symlink");
+        let trusted = TempDir::new().expect("Failed to create temp dir");
+        wire_files(
+            &src,
+            trusted.path(),
+            false,
+            &[],
+            true,
+            WireLimits::default(),
+        )
+        .expect("trusted symlink is followed");
+        assert!(trusted.path().join("proto/leak/token").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_loop_is_reported() {
+        use std::os::unix::fs::symlink;
+
+        let upstream = TempDir::new().expect("Failed to create temp dir");
+        let src = upstream.path().join("proto");
+        write(&src.join("nested/types.proto"), "types");
+        symlink(&src, src.join("nested/again")).expect("Failed to create symlink");
+
+        let dst = TempDir::new().expect("Failed to create temp dir");
+        let err = wire_files(&src, dst.path(), false, &[], false, WireLimits::default())
+            .expect_err("loop is refused");
+        assert!(err.to_string().contains("loops back"));
+    }
 }
@@ -3,8 +3,14 @@ use super::spinner::SpinnerState;
 use super::state::{Mode, TuiState};
 use super::theme::init_theme;
 use super::ui::draw_ui;
+use crate::core::llm::StreamEvent;
+use crate::core::saved_instructions::SavedInstructions;
 use crate::features::commit::{
-    CommitService, completion::CompletionService, format_commit_result, types::GeneratedMessage,
+    CommitService,
+    completion::{CompletionRegion, CompletionService},
+    editor::edit_message,
+    format_commit_result,
+    types::{GeneratedMessage, format_commit_message},
 };
 use anyhow::{Error, Result};
 use ratatui::{
@@ -22,11 +28,24 @@ use std::io;
 use std::panic;
 use std::sync::Arc;
 use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// Number of completion suggestions requested when Tab is pressed while editing
+const COMPLETION_SUGGESTION_COUNT: usize = 3;
+
+/// The commit a TUI session amends (`--amend`)
+pub struct AmendTarget {
+    /// Commit to amend; `None` amends HEAD
+    pub commit_ref: Option<String>,
+    /// Its current message, which the session starts from
+    pub original: GeneratedMessage,
+}
 
 pub struct TuiCommit {
     pub state: TuiState,
     service: Arc<CommitService>,
     completion_service: Arc<CompletionService>,
+    amend_ref: Option<String>,
 }
 
 impl TuiCommit {
@@ -35,19 +54,32 @@ impl TuiCommit {
         custom_instructions: String,
         service: Arc<CommitService>,
         completion_service: Arc<CompletionService>,
+        amend: Option<AmendTarget>,
     ) -> Self {
-        let state = TuiState::new(initial_messages, custom_instructions);
+        let mut state = TuiState::new(initial_messages, custom_instructions);
+        state.confirm_commit = service.config().confirm_commit;
+        state.comment_prefix =
+            service.comment_prefix(&format_commit_message(&state.messages[state.current_index]));
+        let amend_ref = amend.and_then(|amend| {
+            state.start_amend(amend.original);
+            amend.commit_ref
+        });
 
         Self {
             state,
             service,
             completion_service,
+            amend_ref,
         }
     }
 
     /// Initialize context for selection (call this after creation)
     pub async fn initialize_context(&mut self) -> Result<(), anyhow::Error> {
-        let context = self.service.get_git_info().await?;
+        // Rewording an older commit describes its own diff, not what is staged
+        let context = match self.amend_ref.as_deref().filter(|r| *r != "HEAD") {
+            Some(reword_ref) => self.service.get_git_info_for_commit(reword_ref).await?,
+            None => self.service.get_git_info().await?,
+        };
         self.state.initialize_context(context);
         Ok(())
     }
@@ -58,23 +90,40 @@ impl TuiCommit {
         custom_instructions: String,
         service: Arc<CommitService>,
         completion_service: Arc<CompletionService>,
+        amend: Option<AmendTarget>,
     ) -> Result<()> {
         let mut app = Self::new(
             initial_messages,
-            custom_instructions,
+            custom_instructions.clone(),
             service,
             completion_service,
+            amend,
         );
 
         // Initialize context for selection (ignore errors, regeneration will fall back to default)
         let _ = app.initialize_context().await;
 
-        app.run_app().await.map_err(Error::from)
+        let result = app.run_app().await.map_err(Error::from);
+        app.save_instructions(&custom_instructions);
+        result
+    }
+
+    /// Keep instructions edited this session for the next one in this repository
+    fn save_instructions(&self, initial: &str) {
+        if self.state.custom_instructions == initial {
+            return;
+        }
+        let saved = SavedInstructions::new().and_then(|saved| {
+            saved.save(self.service.repo_path(), &self.state.custom_instructions)
+        });
+        if let Err(e) = saved {
+            debug!("Failed to save the edited instructions: {e}");
+        }
     }
 
     pub async fn run_app(&mut self) -> io::Result<()> {
-        // Initialize adaptive theme
-        init_theme();
+        // Initialize adaptive theme with any overrides from the config file
+        init_theme(self.service.config());
 
         // Setup
         let default_hook = panic::take_hook();
@@ -123,7 +172,10 @@ impl TuiCommit {
         &mut self,
         terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     ) -> anyhow::Result<ExitStatus> {
-        let (tx, mut rx) = tokio::sync::mpsc::channel::<Result<GeneratedMessage, anyhow::Error>>(1);
+        let (mut tx, mut rx) =
+            tokio::sync::mpsc::channel::<Result<GeneratedMessage, anyhow::Error>>(1);
+        let (mut chunk_tx, mut chunk_rx) = tokio::sync::mpsc::channel::<StreamEvent>(32);
+        let mut generation_task: Option<JoinHandle<()>> = None;
         let (completion_tx, mut completion_rx) =
             tokio::sync::mpsc::channel::<Result<Vec<String>, anyhow::Error>>(1);
         let mut task_spawned = false;
@@ -142,40 +194,47 @@ impl TuiCommit {
                 let instructions = self.state.custom_instructions.clone();
                 let filtered_context = self.state.get_filtered_context();
                 let tx = tx.clone();
+                let chunk_tx = chunk_tx.clone();
 
-                tokio::spawn(async move {
+                self.state.streaming_text.clear();
+                generation_task = Some(tokio::spawn(async move {
                     // Use filtered context if available, otherwise use default
-                    let result = if let Some(context) = filtered_context {
-                        service
-                            .generate_message_with_context(&instructions, context)
-                            .await
-                    } else {
-                        service.generate_message(&instructions).await
-                    };
+                    let result = service
+                        .generate_message_streaming(&instructions, filtered_context, &chunk_tx)
+                        .await;
                     let _ = tx.send(result).await;
-                });
+                }));
 
                 task_spawned = true; // Ensure we only spawn the task once
             }
 
             // Spawn completion task if there's a pending completion request
-            if let Some(prefix) = &self.state.pending_completion_prefix.clone()
+            if let Some(request) = &self.state.pending_completion.clone()
                 && !completion_task_spawned
             {
                 let completion_service = self.completion_service.clone();
-                let prefix = prefix.clone();
+                let request = request.clone();
                 let completion_tx = completion_tx.clone();
 
                 tokio::spawn(async move {
+                    let prefix = request.prefix();
                     debug!("Generating completion for prefix: {prefix}");
                     // Generate real completion suggestions using the completion service
-                    match completion_service.complete_message(&prefix, 0.5).await {
-                        Ok(completed_message) => {
-                            // Extract the completed title as a suggestion
-                            let suggestion = completed_message.title;
-                            let suggestions = vec![suggestion];
+                    match completion_service
+                        .complete_messages(&request, 0.5, COMPLETION_SUGGESTION_COUNT)
+                        .await
+                    {
+                        Ok(completed_messages) => {
+                            // Keep only the completed region of each message as a suggestion
+                            let suggestions = completed_messages
+                                .iter()
+                                .map(|message| request.suggestion(message))
+                                .collect();
                             let _ = completion_tx.send(Ok(suggestions)).await;
                         }
+                        Err(e) if request.region() == CompletionRegion::Body => {
+                            let _ = completion_tx.send(Err(e)).await;
+                        }
                         Err(e) => {
                             debug!("Completion failed: {e}");
                             // Fallback to basic suggestions if completion fails
@@ -190,7 +249,18 @@ impl TuiCommit {
                 });
 
                 completion_task_spawned = true;
-                self.state.pending_completion_prefix = None; // Clear the pending request
+                self.state.pending_completion = None; // Clear the pending request
+            }
+
+            // Render any partial output received from the generation task
+            while let Ok(event) = chunk_rx.try_recv() {
+                if self.state.mode == Mode::Generating {
+                    match event {
+                        StreamEvent::Chunk(chunk) => self.state.streaming_text.push_str(&chunk),
+                        StreamEvent::Restart => self.state.streaming_text.clear(),
+                    }
+                    self.state.dirty = true;
+                }
             }
 
             // Check if a message has been received from the generation task
@@ -198,6 +268,7 @@ impl TuiCommit {
                 Ok(result) => match result {
                     Ok(new_message) => {
                         // Add the new message to the list and switch to it
+                        self.state.remember_current_message();
                         self.state.messages.push(new_message);
                         self.state.current_index = self.state.messages.len() - 1;
 
@@ -210,6 +281,8 @@ impl TuiCommit {
                             self.state.messages.len()
                         ));
                         task_spawned = false; // Reset for future regenerations
+                        generation_task = None;
+                        self.state.streaming_text.clear();
                     }
                     Err(e) => {
                         self.state.mode = Mode::Normal; // Exit Generating mode
@@ -218,6 +291,8 @@ impl TuiCommit {
                             "Generation failed: {e}. Press 'R' to retry or 'Esc' to exit."
                         ));
                         task_spawned = false; // Reset for future regenerations
+                        generation_task = None;
+                        self.state.streaming_text.clear();
                     }
                 },
                 Err(tokio::sync::mpsc::error::TryRecvError::Empty) => {
@@ -286,8 +361,21 @@ impl TuiCommit {
                             self.state.dirty = true;
                         }
                     },
+                    InputResult::OpenEditor => self.edit_in_external_editor(terminal)?,
                     InputResult::Continue => self.state.dirty = true,
                 }
+
+                // Leaving Generating mode (e.g. Esc) drops the stream to abort the request
+                if self.state.mode != Mode::Generating
+                    && let Some(task) = generation_task.take()
+                {
+                    task.abort();
+                    // Swap in new channels, so nothing the aborted task already sent is applied
+                    (tx, rx) = tokio::sync::mpsc::channel(1);
+                    (chunk_tx, chunk_rx) = tokio::sync::mpsc::channel(32);
+                    task_spawned = false;
+                    self.state.streaming_text.clear();
+                }
             }
 
             // Update the spinner state and redraw if in generating mode
@@ -305,16 +393,53 @@ impl TuiCommit {
         Ok(ExitStatus::Cancelled)
     }
 
+    /// Edit the message in view in the external editor, leaving raw mode and the
+    /// alternate screen while it runs so the editor gets a normal terminal
+    fn edit_in_external_editor(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    ) -> io::Result<()> {
+        let message = format_commit_message(&self.state.messages[self.state.current_index]);
+        let editor = self.service.editor();
+
+        disable_raw_mode()?;
+        execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+        terminal.show_cursor()?;
+        let edited = edit_message(&editor, &message, &self.state.comment_prefix);
+        enable_raw_mode()?;
+        execute!(terminal.backend_mut(), EnterAlternateScreen)?;
+        terminal.clear()?;
+
+        match edited {
+            Ok(Some(text)) => {
+                self.state.apply_edited_message(&text);
+                self.state
+                    .set_status(format!("Commit message updated in {editor}."));
+            }
+            Ok(None) => self.state.set_status(String::from(
+                "Editor exited with an error or an empty message; nothing changed.",
+            )),
+            Err(e) => self
+                .state
+                .set_status(format!("Could not open the editor: {e}")),
+        }
+        self.state.dirty = true;
+        Ok(())
+    }
+
     pub fn handle_regenerate(&mut self) {
         self.state.mode = Mode::Generating;
-        self.state.spinner = Some(SpinnerState::new());
+        self.state.spinner = Some(SpinnerState::for_generation(&self.service.provider_label()));
         self.state
             .set_status(String::from("Regenerating commit message..."));
         self.state.dirty = true; // Make sure UI updates
     }
 
     pub fn perform_commit(&self, message: &str) -> Result<ExitStatus, Error> {
-        match self.service.perform_commit(message, false, None) {
+        match self
+            .service
+            .perform_commit(message, self.state.amending, self.amend_ref.as_deref())
+        {
             Ok(result) => {
                 let output = format_commit_result(&result, message);
                 Ok(ExitStatus::Committed(output))
@@ -330,12 +455,14 @@ pub async fn run_tui_commit(
     custom_instructions: String,
     service: Arc<CommitService>,
     completion_service: Arc<CompletionService>,
+    amend: Option<AmendTarget>,
 ) -> Result<()> {
     TuiCommit::run(
         initial_messages,
         custom_instructions,
         service,
         completion_service,
+        amend,
     )
     .await
 }
@@ -368,10 +495,12 @@ mod tests {
         // Test that regeneration adds a new message and switches to it
         let initial_messages = vec![
             GeneratedMessage {
+                emoji: None,
                 title: "Initial commit".to_string(),
                 message: "Initial message".to_string(),
             },
             GeneratedMessage {
+                emoji: None,
                 title: "Second commit".to_string(),
                 message: "Second message".to_string(),
             },
@@ -384,6 +513,7 @@ mod tests {
 
         // Simulate regeneration result: add new message
         let new_message = GeneratedMessage {
+            emoji: None,
             title: "Regenerated commit".to_string(),
             message: "Regenerated message".to_string(),
         };
@@ -424,6 +554,7 @@ mod tests {
 
         // Simulate regeneration result
         let new_message = GeneratedMessage {
+            emoji: None,
             title: "New commit".to_string(),
             message: "New message".to_string(),
         };
@@ -442,6 +573,7 @@ mod tests {
     fn test_regeneration_always_adds_message() {
         // Test that regeneration always adds a new message regardless of current_index
         let initial_messages = vec![GeneratedMessage {
+            emoji: None,
             title: "First commit".to_string(),
             message: "First message".to_string(),
         }];
@@ -451,6 +583,7 @@ mod tests {
         assert_eq!(state.current_index, 0);
 
         let new_message = GeneratedMessage {
+            emoji: None,
             title: "New commit".to_string(),
             message: "New message".to_string(),
         };
@@ -1,6 +1,6 @@
 use super::app::TuiCommit;
 use super::spinner::SpinnerState;
-use super::state::Mode;
+use super::state::{Mode, TuiState};
 
 pub trait TuiApp {
     fn get_state(&mut self) -> &mut super::state::TuiState;
@@ -17,6 +17,8 @@ impl TuiApp for TuiCommit {
     }
 }
 
+use crate::features::commit::comments::strip_comment_lines;
+use crate::features::commit::completion::CompletionRequest;
 use crate::features::commit::types::format_commit_message;
 use crossterm::event::{KeyCode, KeyEvent};
 
@@ -37,6 +39,8 @@ pub async fn handle_input<A: TuiApp>(app: &mut A, key: KeyEvent) -> InputResult
         Mode::Help => handle_help(app, key),
         Mode::Completing => handle_completing(app, key),
         Mode::ContextSelection => handle_context_selection(app, key),
+        Mode::Searching => handle_searching(app, key),
+        Mode::ConfirmingCommit => handle_confirming_commit(app, key),
         Mode::Generating => {
             if key.code == KeyCode::Esc {
                 let state = app.get_state();
@@ -79,6 +83,7 @@ fn handle_normal_mode<A: TuiApp>(app: &mut A, key: KeyEvent) -> InputResult {
             InputResult::Continue
         }
         KeyCode::Left | KeyCode::Char('l') => {
+            state.remember_current_message();
             if state.current_index > 0 {
                 state.current_index -= 1;
             } else {
@@ -93,6 +98,7 @@ fn handle_normal_mode<A: TuiApp>(app: &mut A, key: KeyEvent) -> InputResult {
             InputResult::Continue
         }
         KeyCode::Right | KeyCode::Char('r') => {
+            state.remember_current_message();
             if state.current_index < state.messages.len() - 1 {
                 state.current_index += 1;
             } else {
@@ -106,13 +112,34 @@ fn handle_normal_mode<A: TuiApp>(app: &mut A, key: KeyEvent) -> InputResult {
             ));
             InputResult::Continue
         }
-        KeyCode::Enter => {
+        KeyCode::Char('v') => InputResult::OpenEditor,
+        KeyCode::Char('u') => {
+            if state.undo_message() {
+                state.set_status(format!(
+                    "Restored the previous version of message {}/{} ({} more to undo)",
+                    state.current_index + 1,
+                    state.messages.len(),
+                    state.undo_history.len()
+                ));
+            } else {
+                state.set_status(String::from("Nothing to undo."));
+            }
+            InputResult::Continue
+        }
+        KeyCode::Char('y') => {
             let commit_message = format_commit_message(&state.messages[state.current_index]);
-            state.set_status(String::from("Committing..."));
-            state.spinner = Some(SpinnerState::new());
-
-            InputResult::Commit(commit_message)
+            match copy_to_clipboard(&mut state.clipboard, &commit_message) {
+                Ok(()) => state.set_status(String::from("Copied to clipboard")),
+                Err(e) => state.set_status(format!("Failed to copy to clipboard: {e}")),
+            }
+            InputResult::Continue
+        }
+        KeyCode::Enter if state.confirm_commit => {
+            state.mode = Mode::ConfirmingCommit;
+            state.set_status(String::from("Commit? [y/N]"));
+            InputResult::Continue
         }
+        KeyCode::Enter => commit_current_message(state),
         KeyCode::Char('?') => {
             state.nav_bar_visible = !state.nav_bar_visible;
             state.set_status(if state.nav_bar_visible {
@@ -127,16 +154,114 @@ fn handle_normal_mode<A: TuiApp>(app: &mut A, key: KeyEvent) -> InputResult {
             state.set_status(String::from("Viewing help. Press any key to close."));
             InputResult::Continue
         }
+        KeyCode::Char('/') => {
+            state.clear_search();
+            state.mode = Mode::Searching;
+            state.set_status(String::from(
+                "/ Type to filter candidates, Enter to keep, Esc to clear",
+            ));
+            InputResult::Continue
+        }
+        KeyCode::Char('n') if !state.search_query.is_empty() => {
+            state.next_match();
+            let status = state.search_status();
+            state.set_status(status);
+            InputResult::Continue
+        }
+        KeyCode::Char('N') if !state.search_query.is_empty() => {
+            state.previous_match();
+            let status = state.search_status();
+            state.set_status(status);
+            InputResult::Continue
+        }
+        KeyCode::Esc if !state.search_query.is_empty() => {
+            state.clear_search();
+            state.set_status(String::from("Search cleared."));
+            InputResult::Continue
+        }
         KeyCode::Esc => InputResult::Exit,
         _ => InputResult::Continue,
     }
 }
 
+/// Commit the message in view
+fn commit_current_message(state: &mut TuiState) -> InputResult {
+    let commit_message = format_commit_message(&state.messages[state.current_index]);
+    state.set_status(String::from("Committing..."));
+    state.spinner = Some(SpinnerState::new());
+
+    InputResult::Commit(commit_message)
+}
+
+fn handle_confirming_commit<A: TuiApp>(app: &mut A, key: KeyEvent) -> InputResult {
+    let state = app.get_state();
+    state.mode = Mode::Normal;
+    if matches!(key.code, KeyCode::Char('y' | 'Y')) {
+        commit_current_message(state)
+    } else {
+        state.set_status(String::from("Commit cancelled."));
+        InputResult::Continue
+    }
+}
+
+fn handle_searching<A: TuiApp>(app: &mut A, key: KeyEvent) -> InputResult {
+    let state = app.get_state();
+    match key.code {
+        KeyCode::Char(c) => {
+            state.search_query.push(c);
+            state.update_search();
+            let status = state.search_status();
+            state.set_status(status);
+        }
+        KeyCode::Backspace => {
+            state.search_query.pop();
+            state.update_search();
+            let status = state.search_status();
+            state.set_status(status);
+        }
+        KeyCode::Enter => {
+            // Keep the filter so n/N can jump between the matches
+            state.mode = Mode::Normal;
+            if state.search_query.is_empty() {
+                state.set_status(String::from("Search cleared."));
+            } else {
+                let status = format!("{} | n/N: next/previous match", state.search_status());
+                state.set_status(status);
+            }
+        }
+        KeyCode::Esc => {
+            state.clear_search();
+            state.mode = Mode::Normal;
+            state.set_status(String::from("Search cleared."));
+        }
+        _ => {}
+    }
+    InputResult::Continue
+}
+
+/// Copy text to the system clipboard, opening it into `clipboard` on first use.
+///
+/// Fails instead of panicking when no clipboard is available (e.g. headless sessions).
+fn copy_to_clipboard(clipboard: &mut Option<arboard::Clipboard>, text: &str) -> anyhow::Result<()> {
+    if clipboard.is_none() {
+        *clipboard = Some(arboard::Clipboard::new()?);
+    }
+    if let Some(clipboard) = clipboard {
+        clipboard.set_text(text)?;
+    }
+    Ok(())
+}
+
 fn handle_editing_message<A: TuiApp>(app: &mut A, key: KeyEvent) -> InputResult {
     let state = app.get_state();
     if key.code == KeyCode::Esc {
         state.mode = Mode::Normal;
-        let edited_content = state.message_textarea.lines().join("\n");
+        state.remember_current_message();
+        // Comment lines are notes to self, as in git's own editor
+        let edited_content = strip_comment_lines(
+            &state.message_textarea.lines().join("\n"),
+            &state.comment_prefix,
+        );
         if let Some(message) = state.messages.get_mut(state.current_index) {
             // Split the edited content into title and message
             let mut lines = edited_content.lines();
@@ -153,16 +278,19 @@ fn handle_editing_message<A: TuiApp>(app: &mut A, key: KeyEvent) -> InputResult
         state.update_message_textarea();
         InputResult::Continue
     } else if key.code == KeyCode::Tab {
-        // Trigger completion
-        let current_text = state.message_textarea.lines().join("\n");
-        let prefix = current_text.lines().next().unwrap_or("").to_string();
+        // Trigger completion of the title or body, wherever the cursor is
+        let request = CompletionRequest::new(
+            state.message_textarea.lines().join("\n"),
+            state.message_textarea.cursor(),
+        );
 
-        if prefix.trim().is_empty() {
+        if request.prefix().trim().is_empty() {
             state.set_status(String::from(
-                "Cannot complete empty message. Type something first.",
+                "Cannot complete empty text. Type something first.",
             ));
         } else {
-            state.pending_completion_prefix = Some(prefix);
+            state.completion_request = Some(request.clone());
+            state.pending_completion = Some(request);
             state.mode = Mode::Completing;
             state.set_status(String::from("Generating completion suggestions..."));
         }
@@ -223,12 +351,12 @@ fn handle_completing<A: TuiApp>(app: &mut A, key: KeyEvent) -> InputResult {
         KeyCode::Enter => {
             // Accept the current completion
             if !state.completion_suggestions.is_empty() {
-                let suggestion = state.completion_suggestions[state.completion_index].clone();
-                // Apply the suggestion to the message
-                if let Some(message) = state.messages.get_mut(state.current_index) {
-                    message.title = suggestion;
+                let suggestion = &state.completion_suggestions[state.completion_index];
+                // Splice the suggestion into the region being completed, keeping the rest
+                if let Some(request) = state.completion_request.take() {
+                    let text = request.splice(suggestion);
+                    state.set_message_text(&text);
                 }
-                state.update_message_textarea();
                 state.completion_suggestions.clear();
                 state.mode = Mode::EditingMessage;
                 state.set_status(String::from("Completion accepted."));
@@ -295,4 +423,111 @@ pub enum InputResult {
     Continue,
     Exit,
     Commit(String),
+    /// Suspend the TUI to edit the message in view in the external editor
+    OpenEditor,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::commit::types::GeneratedMessage;
+
+    struct TestApp {
+        state: TuiState,
+    }
+
+    impl TuiApp for TestApp {
+        fn get_state(&mut self) -> &mut TuiState {
+            &mut self.state
+        }
+
+        fn handle_regenerate(&mut self) {}
+    }
+
+    #[tokio::test]
+    async fn test_confirmation_before_commit() {
+        let message = GeneratedMessage {
+            emoji: None,
+            title: "fix: confirm before committing".to_string(),
+            message: String::new(),
+        };
+        let mut state = TuiState::new(vec![message.clone()], String::new());
+        state.confirm_commit = true;
+        let mut app = TestApp { state };
+
+        let result = handle_input(&mut app, KeyEvent::from(KeyCode::Enter)).await;
+        assert!(matches!(result, InputResult::Continue));
+        assert_eq!(app.state.mode, Mode::ConfirmingCommit);
+
+        let result = handle_input(&mut app, KeyEvent::from(KeyCode::Char('n'))).await;
+        assert!(matches!(result, InputResult::Continue));
+        assert_eq!(app.state.mode, Mode::Normal);
+
+        handle_input(&mut app, KeyEvent::from(KeyCode::Enter)).await;
+        let result = handle_input(&mut app, KeyEvent::from(KeyCode::Char('y'))).await;
+        assert!(
+            matches!(result, InputResult::Commit(committed) if committed == format_commit_message(&message))
+        );
+        assert_eq!(app.state.mode, Mode::Normal);
+    }
+
+    #[tokio::test]
+    async fn test_comment_lines_are_stripped_from_the_committed_message() {
+        let edited = "fix: strip comments\n# why this changed\n\nBody line.\n; kept by default";
+        for (prefix, expected) in [
+            (
+                "#",
+                "fix: strip comments\n\nBody line.\n; kept by default\n",
+            ),
+            (
+                ";",
+                "fix: strip comments\n\n# why this changed\n\nBody line.\n",
+            ),
+        ] {
+            let mut state = TuiState::new(vec![], String::new());
+            state.comment_prefix = prefix.to_string();
+            state.mode = Mode::EditingMessage;
+            state.set_message_text(edited);
+            let mut app = TestApp { state };
+
+            handle_input(&mut app, KeyEvent::from(KeyCode::Esc)).await;
+            let result = handle_input(&mut app, KeyEvent::from(KeyCode::Enter)).await;
+            assert!(
+                matches!(&result, InputResult::Commit(message) if message == expected),
+                "comment prefix {prefix:?}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_undo_walks_back_through_message_versions() {
+        let messages = ["feat: first", "feat: second", "feat: third"]
+            .map(|title| GeneratedMessage {
+                emoji: None,
+                title: title.to_string(),
+                message: String::new(),
+            })
+            .to_vec();
+        let mut app = TestApp {
+            state: TuiState::new(messages, String::new()),
+        };
+
+        // Switch candidates twice, then regenerate as the app does
+        handle_input(&mut app, KeyEvent::from(KeyCode::Right)).await;
+        handle_input(&mut app, KeyEvent::from(KeyCode::Right)).await;
+        app.state.remember_current_message();
+        app.state.messages[2].title = "feat: regenerated".to_string();
+
+        for expected in ["feat: third", "feat: second", "feat: first"] {
+            handle_input(&mut app, KeyEvent::from(KeyCode::Char('u'))).await;
+            assert_eq!(app.state.messages[app.state.current_index].title, expected);
+        }
+        handle_input(&mut app, KeyEvent::from(KeyCode::Char('u'))).await;
+        assert_eq!(app.state.status, "Nothing to undo.");
+
+        for _ in 0..15 {
+            handle_input(&mut app, KeyEvent::from(KeyCode::Right)).await;
+        }
+        assert_eq!(app.state.undo_history.len(), 10);
+    }
 }
@@ -10,6 +10,7 @@ mod state;
 mod theme;
 mod ui;
 
+pub use app::AmendTarget;
 pub use app::TuiCommit;
 pub use app::run_tui_commit;
 pub use theme::Theme;
@@ -1,11 +1,15 @@
 use crate::core::messages::{ColoredMessage, get_waiting_message};
 use ratatui::style::Color;
+use std::time::Instant;
 use unicode_width::UnicodeWidthStr;
 
 pub struct SpinnerState {
     frames: Vec<&'static str>,
     current_frame: usize,
     message: ColoredMessage,
+    started_at: Instant,
+    /// Append the seconds since the spinner started to the message
+    show_elapsed: bool,
 }
 
 impl Default for SpinnerState {
@@ -21,6 +25,8 @@ impl SpinnerState {
             frames: vec!["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"],
             current_frame: 0,
             message: get_waiting_message().clone(),
+            started_at: Instant::now(),
+            show_elapsed: false,
         }
     }
 
@@ -33,6 +39,16 @@ impl SpinnerState {
                 text: message.to_string(),
                 color: ratatui::style::Color::Cyan, // Default color for custom messages
             },
+            started_at: Instant::now(),
+            show_elapsed: false,
+        }
+    }
+
+    // Spinner for an LLM call, e.g. "Generating with anthropic/claude-3-5-sonnet (4s)"
+    pub fn for_generation(provider_label: &str) -> Self {
+        Self {
+            show_elapsed: true,
+            ..Self::with_message(&format!("Generating with {provider_label}"))
         }
     }
 
@@ -43,6 +59,8 @@ impl SpinnerState {
             frames: vec!["⠁", "⠂", "⠄", "⡀", "⢀", "⠠", "⠐", "⠈"],
             current_frame: 0,
             message: get_waiting_message().clone(),
+            started_at: Instant::now(),
+            show_elapsed: false,
         }
     }
 
@@ -53,6 +71,8 @@ impl SpinnerState {
             frames: vec!["◜", "◠", "◝", "◞", "◡", "◟"],
             current_frame: 0,
             message: get_waiting_message().clone(),
+            started_at: Instant::now(),
+            show_elapsed: false,
         }
     }
 
@@ -63,6 +83,8 @@ impl SpinnerState {
             frames: vec!["←", "↖", "↑", "↗", "→", "↘", "↓", "↙"],
             current_frame: 0,
             message: get_waiting_message().clone(),
+            started_at: Instant::now(),
+            show_elapsed: false,
         }
     }
 
@@ -73,6 +95,8 @@ impl SpinnerState {
             frames: vec!["┤", "┘", "┴", "└", "├", "┌", "┬", "┐"],
             current_frame: 0,
             message: get_waiting_message().clone(),
+            started_at: Instant::now(),
+            show_elapsed: false,
         }
     }
 
@@ -83,6 +107,8 @@ impl SpinnerState {
             frames: vec!["-", "\\", "|", "/"],
             current_frame: 0,
             message: get_waiting_message().clone(),
+            started_at: Instant::now(),
+            show_elapsed: false,
         }
     }
 
@@ -91,13 +117,41 @@ impl SpinnerState {
         self.current_frame = (self.current_frame + 1) % self.frames.len();
 
         let spinner_with_space = format!("{frame} ");
-        let width = spinner_with_space.width() + self.message.text.width();
-
-        (
-            spinner_with_space,
-            self.message.text.clone(),
-            self.message.color,
-            width,
-        )
+        let text = if self.show_elapsed {
+            format!(
+                "{} ({}s)",
+                self.message.text,
+                self.started_at.elapsed().as_secs()
+            )
+        } else {
+            self.message.text.clone()
+        };
+        let width = spinner_with_space.width() + text.width();
+
+        (spinner_with_space, text, self.message.color, width)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_generation_spinner_shows_provider_and_elapsed_time() {
+        let mut spinner = SpinnerState::for_generation("anthropic/claude-3-5-sonnet");
+        spinner.started_at -= Duration::from_secs(4);
+
+        let (frame, text, _, width) = spinner.tick();
+
+        assert_eq!(frame, "⠋ ");
+        assert_eq!(text, "Generating with anthropic/claude-3-5-sonnet (4s)");
+        assert_eq!(width, frame.width() + text.width());
+    }
+
+    #[test]
+    fn test_custom_message_has_no_elapsed_time() {
+        let (_, text, _, _) = SpinnerState::with_message("Committing...").tick();
+        assert_eq!(text, "Committing...");
     }
 }
@@ -1,9 +1,17 @@
 use super::spinner::SpinnerState;
 use crate::core::context::CommitContext;
-use crate::features::commit::types::{GeneratedMessage, format_commit_message};
+use crate::features::commit::comments::DEFAULT_COMMENT_PREFIX;
+use crate::features::commit::completion::CompletionRequest;
+use crate::features::commit::types::{
+    GeneratedMessage, format_commit_message, parse_commit_message,
+};
 
+use std::collections::VecDeque;
 use tui_textarea::TextArea;
 
+/// Most message versions `u` can step back through
+const UNDO_HISTORY_LIMIT: usize = 10;
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum Mode {
     Normal,
@@ -13,6 +21,10 @@ pub enum Mode {
     Help,
     Completing,
     ContextSelection,
+    /// Typing a query to filter the candidates by
+    Searching,
+    /// Waiting for "Commit? [y/N]" to be answered
+    ConfirmingCommit,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -36,13 +48,34 @@ pub struct TuiState {
     pub nav_bar_visible: bool,
     pub completion_suggestions: Vec<String>,
     pub completion_index: usize,
-    pub pending_completion_prefix: Option<String>,
+    /// Completion waiting to be sent to the model
+    pub pending_completion: Option<CompletionRequest>,
+    /// Completion the current suggestions answer, spliced in when one is accepted
+    pub completion_request: Option<CompletionRequest>,
+    /// Partial response text received while a message is being generated
+    pub streaming_text: String,
     // Context selection fields
     pub context: Option<CommitContext>,
     pub selected_files: Vec<bool>,   // Which staged files are selected
     pub selected_commits: Vec<bool>, // Which recent commits are selected
     pub context_selection_index: usize, // Current selection index in context selection UI
     pub context_selection_category: ContextSelectionCategory, // Files or commits
+    /// Text the candidates are filtered by, empty when not searching
+    pub search_query: String,
+    /// Indices of the candidates matching `search_query`, in order
+    pub search_matches: Vec<usize>,
+    /// Ask for confirmation before Enter commits (`gait.confirmcommit`)
+    pub confirm_commit: bool,
+    /// Committing rewrites an existing commit (`--amend`)
+    pub amending: bool,
+    /// Messages in view before the last regenerations, candidate switches and edits,
+    /// with the candidate index each was at; the newest is last
+    pub undo_history: VecDeque<(usize, GeneratedMessage)>,
+    /// Lines starting with this are dropped from edited messages (`core.commentChar`)
+    pub comment_prefix: String,
+    /// System clipboard, opened on the first copy. On X11 and Wayland copied text is served
+    /// by this handle, so it is kept for the whole session.
+    pub clipboard: Option<arboard::Clipboard>,
 }
 
 impl TuiState {
@@ -50,6 +83,7 @@ impl TuiState {
         let mut message_textarea = TextArea::default();
         let messages = if initial_messages.is_empty() {
             vec![GeneratedMessage {
+                emoji: None,
                 title: String::new(),
                 message: String::new(),
             }]
@@ -78,14 +112,86 @@ impl TuiState {
             nav_bar_visible: true,
             completion_suggestions: Vec::new(),
             completion_index: 0,
-            pending_completion_prefix: None,
+            pending_completion: None,
+            completion_request: None,
+            streaming_text: String::new(),
             // Context selection fields
             context: None,
             selected_files: Vec::new(),
             selected_commits: Vec::new(),
             context_selection_index: 0,
             context_selection_category: ContextSelectionCategory::Files,
+            search_query: String::new(),
+            search_matches: Vec::new(),
+            confirm_commit: false,
+            amending: false,
+            undo_history: VecDeque::new(),
+            comment_prefix: DEFAULT_COMMENT_PREFIX.to_string(),
+            clipboard: None,
+        }
+    }
+
+    /// Remember the message in view so `u` can come back to it.
+    ///
+    /// Only the last [`UNDO_HISTORY_LIMIT`] versions are kept.
+    pub fn remember_current_message(&mut self) {
+        let Some(message) = self.messages.get(self.current_index) else {
+            return;
+        };
+        let version = (self.current_index, message.clone());
+        let unchanged = self.undo_history.back().is_some_and(|(index, previous)| {
+            *index == version.0 && format_commit_message(previous) == format_commit_message(message)
+        });
+        if unchanged {
+            return;
+        }
+        if self.undo_history.len() == UNDO_HISTORY_LIMIT {
+            self.undo_history.pop_front();
+        }
+        self.undo_history.push_back(version);
+    }
+
+    /// Replace the message in view with text edited outside the TUI, keeping the old one
+    /// for undo
+    pub fn apply_edited_message(&mut self, text: &str) {
+        self.remember_current_message();
+        self.messages[self.current_index] = parse_commit_message(text);
+        self.update_message_textarea();
+    }
+
+    /// Go back to the last remembered message, restoring its text and candidate.
+    ///
+    /// Returns `false` when there is nothing left to undo.
+    pub fn undo_message(&mut self) -> bool {
+        while let Some((index, message)) = self.undo_history.pop_back() {
+            if let Some(slot) = self.messages.get_mut(index) {
+                *slot = message;
+                self.current_index = index;
+                self.update_message_textarea();
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Switch to amending, starting from the commit's current message.
+    ///
+    /// It becomes the first candidate, ahead of any generated ones, so it can be tweaked
+    /// instead of rewritten.
+    pub fn start_amend(&mut self, original: GeneratedMessage) {
+        let blank = self.messages.len() == 1
+            && self.messages[0].title.is_empty()
+            && self.messages[0].message.is_empty();
+        if blank {
+            self.messages.clear();
         }
+        self.messages.insert(0, original);
+        self.current_index = 0;
+        self.amending = true;
+        self.set_message_text(&format_commit_message(&self.messages[0]));
+        self.set_status(String::from(
+            "AMEND: editing the existing message | '←/→': generated ones",
+        ));
     }
 
     pub fn set_status(&mut self, new_status: String) {
@@ -101,9 +207,13 @@ impl TuiState {
             current_message.title,
             current_message.message.trim()
         );
+        self.set_message_text(&message_content);
+    }
 
+    /// Replace the text in the message editor
+    pub fn set_message_text(&mut self, text: &str) {
         let mut new_textarea = TextArea::default();
-        new_textarea.insert_str(&message_content);
+        new_textarea.insert_str(text);
         self.message_textarea = new_textarea;
         self.dirty = true;
     }
@@ -198,6 +308,92 @@ impl TuiState {
         self.dirty = true;
     }
 
+    /// Refilter the candidates by `search_query`, moving to the first match if the current
+    /// message isn't one.
+    ///
+    /// Matching is a case-insensitive substring search over the title and body.
+    pub fn update_search(&mut self) {
+        let query = self.search_query.to_lowercase();
+        self.search_matches = if query.is_empty() {
+            Vec::new()
+        } else {
+            self.messages
+                .iter()
+                .enumerate()
+                .filter(|(_, message)| {
+                    message.title.to_lowercase().contains(&query)
+                        || message.message.to_lowercase().contains(&query)
+                })
+                .map(|(index, _)| index)
+                .collect()
+        };
+
+        if let Some(&first) = self.search_matches.first()
+            && !self.search_matches.contains(&self.current_index)
+        {
+            self.current_index = first;
+            self.update_message_textarea();
+        }
+        self.dirty = true;
+    }
+
+    /// Jump to the next match after the current message, wrapping around
+    pub fn next_match(&mut self) {
+        self.update_search();
+        let next = self
+            .search_matches
+            .iter()
+            .find(|&&index| index > self.current_index)
+            .or_else(|| self.search_matches.first());
+        if let Some(&index) = next {
+            self.current_index = index;
+            self.update_message_textarea();
+        }
+    }
+
+    /// Jump to the previous match before the current message, wrapping around
+    pub fn previous_match(&mut self) {
+        self.update_search();
+        let previous = self
+            .search_matches
+            .iter()
+            .rfind(|&&index| index < self.current_index)
+            .or_else(|| self.search_matches.last());
+        if let Some(&index) = previous {
+            self.current_index = index;
+            self.update_message_textarea();
+        }
+    }
+
+    /// Drop the search, leaving the current message in view
+    pub fn clear_search(&mut self) {
+        self.search_query.clear();
+        self.search_matches.clear();
+        self.dirty = true;
+    }
+
+    /// Status line for the search, e.g. `/retry: match 1 of 2 (5 candidates)`
+    pub fn search_status(&self) -> String {
+        let position = self
+            .search_matches
+            .iter()
+            .position(|&index| index == self.current_index);
+        match position {
+            Some(position) => format!(
+                "/{}: match {} of {} ({} candidates)",
+                self.search_query,
+                position + 1,
+                self.search_matches.len(),
+                self.messages.len()
+            ),
+            None => format!(
+                "/{}: no matches ({} candidates)",
+                self.search_query,
+                self.messages.len()
+            ),
+        }
+    }
+
     /// Get filtered context based on selections
     pub fn get_filtered_context(&self) -> Option<CommitContext> {
         self.context.as_ref().map(|ctx| {
@@ -227,6 +423,7 @@ impl TuiState {
 mod tests {
     use super::*;
     use crate::core::context::{ChangeType, RecentCommit, StagedFile};
+    use crate::core::saved_instructions::SavedInstructions;
 
     #[test]
     fn test_get_filtered_context_filters_files_and_commits() {
@@ -263,6 +460,7 @@ mod tests {
                     content_excluded: false,
                 },
             ],
+            ticket_id: None,
             user_name: "Test User".to_string(),
             user_email: "test@example.com".to_string(),
             author_history: vec![],
@@ -310,6 +508,7 @@ mod tests {
                 content: None,
                 content_excluded: false,
             }],
+            ticket_id: None,
             user_name: "Test User".to_string(),
             user_email: "test@example.com".to_string(),
             author_history: vec![],
@@ -343,6 +542,7 @@ mod tests {
                 timestamp: "1234567890".to_string(),
             }],
             staged_files: vec![],
+            ticket_id: None,
             user_name: "Test User".to_string(),
             user_email: "test@example.com".to_string(),
             author_history: vec![],
@@ -360,4 +560,122 @@ mod tests {
         state.toggle_current_selection();
         assert!(!state.selected_commits[0]);
     }
+
+    fn candidates(titles: &[&str]) -> Vec<GeneratedMessage> {
+        titles
+            .iter()
+            .map(|title| GeneratedMessage {
+                emoji: None,
+                title: (*title).to_string(),
+                message: "Body.".to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_search_filters_candidates() {
+        let mut state = TuiState::new(
+            candidates(&[
+                "feat: add retry to uploads",
+                "fix: handle empty diff",
+                "feat: Retry failed uploads",
+                "docs: explain upload limits",
+            ]),
+            "test".to_string(),
+        );
+
+        state.search_query = "retry".to_string();
+        state.update_search();
+        assert_eq!(state.search_matches, vec![0, 2]);
+        assert_eq!(state.current_index, 0);
+        assert_eq!(state.search_status(), "/retry: match 1 of 2 (4 candidates)");
+
+        // n and N cycle through the matches only
+        state.next_match();
+        assert_eq!(state.current_index, 2);
+        state.next_match();
+        assert_eq!(state.current_index, 0);
+        state.previous_match();
+        assert_eq!(state.current_index, 2);
+
+        // Narrowing away from the current message moves to the first match
+        state.search_query = "limits".to_string();
+        state.update_search();
+        assert_eq!(state.search_matches, vec![3]);
+        assert_eq!(state.current_index, 3);
+
+        state.search_query = "nothing like this".to_string();
+        state.update_search();
+        assert!(state.search_matches.is_empty());
+        assert_eq!(state.current_index, 3);
+        assert_eq!(
+            state.search_status(),
+            "/nothing like this: no matches (4 candidates)"
+        );
+
+        state.clear_search();
+        assert!(state.search_query.is_empty());
+        assert!(state.search_matches.is_empty());
+    }
+
+    #[test]
+    fn test_saved_instructions_prefill_the_next_session() {
+        let cache_dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let repo = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let saved = SavedInstructions::with_dir(cache_dir.path().to_path_buf());
+
+        // Edit the instructions as the TUI does, then save them on exit
+        let mut state = TuiState::new(vec![], "Use the imperative mood.".to_string());
+        state
+            .instructions_textarea
+            .insert_str("\nMention the ticket.");
+        state.custom_instructions = state.instructions_textarea.lines().join("\n");
+        saved
+            .save(repo.path(), &state.custom_instructions)
+            .expect("Failed to save instructions");
+
+        let restored = saved.load(repo.path()).expect("instructions were saved");
+        let state = TuiState::new(vec![], restored);
+        assert_eq!(
+            state.custom_instructions,
+            "Use the imperative mood.\nMention the ticket."
+        );
+        assert_eq!(
+            state.instructions_textarea.lines().join("\n"),
+            state.custom_instructions
+        );
+
+        saved
+            .clear(repo.path())
+            .expect("Failed to clear instructions");
+        assert_eq!(saved.load(repo.path()), None);
+    }
+
+    #[test]
+    fn test_amend_seeds_the_prior_message() {
+        let generated = GeneratedMessage {
+            emoji: None,
+            title: "feat: generated title".to_string(),
+            message: "Generated body.".to_string(),
+        };
+        let original =
+            parse_commit_message("fix(tui): keep the prior message\n\nAmending starts from it.\n");
+
+        let mut state = TuiState::new(vec![generated], String::new());
+        state.start_amend(original);
+
+        assert!(state.amending);
+        assert_eq!(state.current_index, 0);
+        assert_eq!(state.messages.len(), 2);
+        assert_eq!(
+            state.message_textarea.lines(),
+            [
+                "fix(tui): keep the prior message",
+                "",
+                "Amending starts from it.",
+                ""
+            ]
+        );
+        assert!(state.status.starts_with("AMEND"));
+    }
 }
@@ -3,8 +3,11 @@
 //! This module provides an adaptive theming system that works across different
 //! terminal capabilities and user preferences.
 
+use crate::config::{Config, ThemeConfig};
+use log::warn;
 use ratatui::style::{Color, Modifier};
 use std::env;
+use std::str::FromStr;
 
 /// Terminal color capability levels
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -171,6 +174,48 @@ impl Theme {
         Self::modern_theme(ColorCapability::TrueColor)
     }
 
+    /// Create a theme from the `[theme]` table of the user's config
+    ///
+    /// Starts from the detected built-in theme and replaces every color that is
+    /// set in the config. Invalid color values are logged and ignored.
+    pub fn from_config(config: &Config) -> Self {
+        Self::detect_and_create().with_overrides(&config.theme)
+    }
+
+    /// Apply color overrides on top of this theme
+    #[must_use]
+    pub fn with_overrides(mut self, overrides: &ThemeConfig) -> Self {
+        let fields = [
+            (&overrides.brand_primary, &mut self.brand_primary),
+            (&overrides.text_default, &mut self.text_default),
+            (&overrides.text_dimmed, &mut self.text_dimmed),
+            (&overrides.background_default, &mut self.background_default),
+            (
+                &overrides.background_elevated,
+                &mut self.background_elevated,
+            ),
+            (&overrides.state_success, &mut self.state_success),
+            (&overrides.state_error, &mut self.state_error),
+            (&overrides.state_warning, &mut self.state_warning),
+            (&overrides.state_info, &mut self.state_info),
+            (&overrides.accent, &mut self.accent),
+            (&overrides.accent_active, &mut self.accent_active),
+            (&overrides.border, &mut self.border),
+            (&overrides.border_active, &mut self.border_active),
+        ];
+
+        for (value, target) in fields {
+            if let Some(value) = value {
+                match parse_color(value) {
+                    Some(color) => *target = color,
+                    None => warn!("Ignoring invalid theme color: {value}"),
+                }
+            }
+        }
+
+        self
+    }
+
     /// Get appropriate color based on capability
     pub fn adaptive_color(&self, modern: Color, basic: Color) -> Color {
         match self.capability {
@@ -193,6 +238,11 @@ impl Theme {
     }
 }
 
+/// Parse a named color (`"cyan"`) or hex color (`"#89b4fa"`)
+fn parse_color(value: &str) -> Option<Color> {
+    Color::from_str(value.trim()).ok()
+}
+
 use std::sync::OnceLock;
 
 static THEME: OnceLock<Theme> = OnceLock::new();
@@ -208,9 +258,9 @@ pub fn set_theme(theme: Theme) {
     let _ = THEME.set(theme);
 }
 
-/// Initialize theme detection
-pub fn init_theme() {
-    let _ = get_theme(); // This will initialize the theme
+/// Initialize the global theme from the user's config
+pub fn init_theme(config: &Config) {
+    let _ = THEME.set(Theme::from_config(config));
 }
 
 #[cfg(test)]
@@ -257,6 +307,27 @@ mod tests {
         assert_eq!(color, Color::Rgb(255, 0, 0)); // Modern theme should use RGB color
     }
 
+    #[test]
+    fn test_theme_overrides_from_config() {
+        let overrides: ThemeConfig = toml::from_str(
+            r##"
+            brand_primary = "#89b4fa"
+            accent_active = "cyan"
+            border = "not-a-color"
+            "##,
+        )
+        .expect("sample theme table should parse");
+
+        let base = Theme::basic_theme();
+        let theme = base.clone().with_overrides(&overrides);
+
+        assert_eq!(theme.brand_primary, Color::Rgb(0x89, 0xb4, 0xfa));
+        assert_eq!(theme.accent_active, Color::Cyan);
+        // Invalid and unset values fall back to the defaults
+        assert_eq!(theme.border, base.border);
+        assert_eq!(theme.text_default, base.text_default);
+    }
+
     #[test]
     fn test_capability_detection() {
         // Test basic detection - should not panic
@@ -148,13 +148,18 @@ fn draw_nav_bar(f: &mut Frame, state: &TuiState, area: Rect) {
         Mode::EditingMessage => vec![("Tab", "Complete"), ("Esc", "Finish")],
         Mode::EditingInstructions => vec![("Esc", "Finish")],
         Mode::Help => vec![("Any Key", "Close")],
+        Mode::Searching => vec![("Type", "Filter"), ("Enter", "Keep"), ("Esc", "Clear")],
+        Mode::ConfirmingCommit => vec![("Y", "Commit"), ("Any Key", "Cancel")],
         _ => vec![
             ("←/→", "Navigate"),
             ("E", "Edit Msg"),
+            ("V", "Editor"),
             ("I", "Edit Instr"),
             ("C", "Context"),
             ("R", "Regen"),
-            ("Enter", "Commit"),
+            ("U", "Undo"),
+            ("Y", "Copy"),
+            ("Enter", if state.amending { "Amend" } else { "Commit" }),
             ("Esc", "Cancel"),
         ],
     };
@@ -194,6 +199,9 @@ fn draw_commit_message(f: &mut Frame, state: &mut TuiState, area: Rect) {
         Mode::Help => draw_help(f, state, area),
         Mode::Completing => draw_completion(f, state, area),
         Mode::ContextSelection => draw_context_selection(f, state, area),
+        Mode::Generating if !state.streaming_text.is_empty() => {
+            draw_streaming_message(f, state, area);
+        }
         _ => {
             let is_editing = state.mode == Mode::EditingMessage;
             let border_color = if is_editing {
@@ -202,10 +210,11 @@ fn draw_commit_message(f: &mut Frame, state: &mut TuiState, area: Rect) {
                 border_color()
             };
 
-            let title = if is_editing {
-                " Edit Message "
-            } else {
-                " Commit Message "
+            let title = match (is_editing, state.amending) {
+                (true, false) => " Edit Message ",
+                (true, true) => " Edit Message (AMEND) ",
+                (false, false) => " Commit Message ",
+                (false, true) => " Commit Message (AMEND) ",
             };
 
             let message_block = Block::default()
@@ -278,6 +287,28 @@ fn render_commit_message_content(f: &mut Frame, state: &TuiState, block: Block,
     f.render_widget(message, area);
 }
 
+/// Renders the partial response while a message is streaming in
+fn draw_streaming_message(f: &mut Frame, state: &TuiState, area: Rect) {
+    let block = Block::default()
+        .title(Span::styled(
+            " Generating... ",
+            Style::default()
+                .fg(accent_color_active())
+                .add_modifier(font_weight_bold()),
+        ))
+        .title_alignment(ratatui::layout::Alignment::Center)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(border_color_active()));
+
+    let content = Paragraph::new(state.streaming_text.as_str())
+        .block(block)
+        .style(Style::default().fg(subtle_color()))
+        .wrap(Wrap { trim: false });
+
+    f.render_widget(content, area);
+}
+
 fn draw_instructions(f: &mut Frame, state: &mut TuiState, area: Rect) {
     let is_editing = state.mode == Mode::EditingInstructions;
     let border_color = if is_editing {
@@ -377,6 +408,8 @@ fn draw_help(f: &mut Frame, _state: &mut TuiState, area: Rect) {
         )]),
         Line::from(vec![Span::raw("  ←/→        Next/Prev Message")]),
         Line::from(vec![Span::raw("  ↑/↓        Scroll Content")]),
+        Line::from(vec![Span::raw("  /          Search Messages")]),
+        Line::from(vec![Span::raw("  n/N        Next/Prev Match")]),
         Line::from(""),
         Line::from(vec![Span::styled(
             "Actions",
@@ -386,8 +419,11 @@ fn draw_help(f: &mut Frame, _state: &mut TuiState, area: Rect) {
         )]),
         Line::from(vec![Span::raw("  Enter      Commit")]),
         Line::from(vec![Span::raw("  E          Edit Message")]),
+        Line::from(vec![Span::raw("  V          Edit in $EDITOR")]),
         Line::from(vec![Span::raw("  I          Edit Instructions")]),
         Line::from(vec![Span::raw("  R          Regenerate")]),
+        Line::from(vec![Span::raw("  U          Undo Message Change")]),
+        Line::from(vec![Span::raw("  Y          Copy to Clipboard")]),
         Line::from(vec![Span::raw("  Esc        Cancel/Back")]),
     ];
 
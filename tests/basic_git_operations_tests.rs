@@ -74,7 +74,7 @@ async fn test_commit() {
         .expect("Failed to create and stage file");
 
     // Perform commit
-    let result = git_repo.commit("Test commit message");
+    let result = git_repo.commit("Test commit message", false);
     assert!(result.is_ok(), "Failed to perform commit");
 
     // Verify commit
@@ -269,3 +269,336 @@ async fn test_fresh_repo_commit_generation() {
         ChangeType::Added
     ));
 }
+
+#[tokio::test]
+async fn test_detached_head_message_generation() {
+    let (temp_dir, git_repo) = setup_git_repo();
+    let config = MockDataBuilder::config();
+    let helper = GitTestHelper::new(&temp_dir).expect("Failed to create GitTestHelper");
+
+    // Detach HEAD at the current commit, as CI checkouts do
+    let head_oid = helper
+        .repo
+        .head()
+        .expect("Failed to get HEAD")
+        .target()
+        .expect("HEAD should point to a commit");
+    helper
+        .repo
+        .set_head_detached(head_oid)
+        .expect("Failed to detach HEAD");
+
+    assert_eq!(
+        git_repo
+            .get_current_branch()
+            .expect("Failed to get current branch"),
+        None
+    );
+
+    helper
+        .create_and_stage_file("ci.txt", "Built in CI")
+        .expect("Failed to create and stage file");
+
+    let context = git_repo
+        .get_git_info(&config)
+        .await
+        .expect("Failed to get git info in detached HEAD state");
+    assert_eq!(context.branch, gait::git::DETACHED_HEAD_LABEL);
+    assert_eq!(context.staged_files.len(), 1);
+
+    let prompt = gait::features::commit::prompt::create_user_prompt(&context);
+    assert!(prompt.contains("ci.txt"));
+
+    // Branch comparisons against HEAD need an explicit branch
+    let error = git_repo
+        .get_git_info_for_branch_diff(&config, "main", "HEAD")
+        .expect_err("Branch diff should fail in detached HEAD state");
+    assert!(error.to_string().contains("detached HEAD"));
+}
+
+#[tokio::test]
+async fn test_untracked_files_included_on_request() {
+    let (temp_dir, git_repo) = setup_git_repo();
+    let mut config = MockDataBuilder::config();
+
+    fs::create_dir_all(temp_dir.path().join("src")).expect("Failed to create src dir");
+    fs::write(
+        temp_dir.path().join("src/new_module.rs"),
+        "pub fn answer() -> u32 {\n    42\n}\n",
+    )
+    .expect("Failed to write untracked file");
+    fs::write(temp_dir.path().join(".gitignore"), "*.log\n").expect("Failed to write .gitignore");
+    fs::write(temp_dir.path().join("debug.log"), "noise").expect("Failed to write ignored file");
+
+    // By default untracked paths are only listed, without their content
+    let context = git_repo
+        .get_git_info_with_unstaged(&config, true)
+        .await
+        .expect("Failed to get git info");
+    let listed = context
+        .staged_files
+        .iter()
+        .find(|file| file.path == "src/")
+        .expect("Untracked directory should be listed");
+    assert!(matches!(listed.change_type, ChangeType::Added));
+    assert!(listed.diff.is_empty());
+    assert!(
+        context
+            .staged_files
+            .iter()
+            .all(|file| file.path != "src/new_module.rs")
+    );
+
+    config.include_untracked = true;
+    let context = git_repo
+        .get_git_info_with_unstaged(&config, true)
+        .await
+        .expect("Failed to get git info with untracked files");
+
+    let module = context
+        .staged_files
+        .iter()
+        .find(|file| file.path == "src/new_module.rs")
+        .expect("Untracked source file should be in the context");
+    assert!(matches!(module.change_type, ChangeType::Added));
+    assert!(module.diff.contains("+pub fn answer() -> u32 {"));
+
+    assert!(
+        context
+            .staged_files
+            .iter()
+            .all(|file| file.path != "debug.log"),
+        "Ignored files must not be included"
+    );
+}
+
+#[tokio::test]
+async fn test_recent_commits_count_caps_context() {
+    let (temp_dir, git_repo) = setup_git_repo();
+    let helper = GitTestHelper::new(&temp_dir).expect("Failed to create GitTestHelper");
+
+    for i in 0..5 {
+        helper
+            .create_and_stage_file(&format!("file_{i}.txt"), "content")
+            .expect("Failed to create and stage file");
+        helper
+            .commit(&format!("Add file {i}"))
+            .expect("Failed to commit");
+    }
+
+    let mut config = MockDataBuilder::config();
+    config.recent_commits_count = 2;
+
+    let context = git_repo
+        .get_git_info(&config)
+        .await
+        .expect("Failed to get git info");
+
+    assert_eq!(context.recent_commits.len(), 2);
+}
+
+#[test]
+fn test_staged_tree_oid_tracks_the_index() {
+    let (temp_dir, git_repo) = setup_git_repo();
+    let helper = GitTestHelper::new(&temp_dir).expect("Failed to create GitTestHelper");
+
+    let initial = git_repo
+        .get_staged_tree_oid()
+        .expect("Failed to read staged tree");
+    helper
+        .create_and_stage_file("notes.txt", "first")
+        .expect("Failed to create and stage file");
+    let staged = git_repo
+        .get_staged_tree_oid()
+        .expect("Failed to read staged tree");
+    assert_ne!(initial, staged);
+
+    // Restaging identical content leaves the fingerprint alone
+    helper
+        .create_and_stage_file("notes.txt", "first")
+        .expect("Failed to restage file");
+    assert_eq!(
+        git_repo
+            .get_staged_tree_oid()
+            .expect("Failed to read staged tree"),
+        staged
+    );
+
+    // Unstaged edits move the working tree fingerprint, not the staged one
+    fs::write(temp_dir.path().join("notes.txt"), "second").expect("Failed to edit file");
+    let working = git_repo
+        .get_working_tree_fingerprint(false)
+        .expect("Failed to fingerprint working tree");
+    assert_ne!(working, staged);
+    assert_eq!(
+        git_repo
+            .get_staged_tree_oid()
+            .expect("Failed to read staged tree"),
+        staged
+    );
+}
+
+#[tokio::test]
+async fn test_get_git_info_in_linked_worktree() {
+    let (temp_dir, _git_repo) = setup_git_repo();
+    let repo = Repository::open(temp_dir.path()).expect("Failed to open repository");
+    let worktrees = TempDir::new().expect("Failed to create temporary directory");
+    let worktree_path = worktrees.path().join("feature");
+    repo.worktree("feature", &worktree_path, None)
+        .expect("Failed to add worktree");
+
+    // Stage a file in the worktree's own index
+    fs::write(worktree_path.join("feature.txt"), "Worktree content").expect("Failed to write file");
+    let worktree_repo = Repository::open(&worktree_path).expect("Failed to open worktree");
+    let mut index = worktree_repo.index().expect("Failed to get worktree index");
+    index
+        .add_path(Path::new("feature.txt"))
+        .expect("Failed to stage file");
+    index.write().expect("Failed to write index");
+
+    let git_repo = GitRepo::new(&worktree_path).expect("Failed to create GitRepo");
+    let context = git_repo
+        .get_git_info(&MockDataBuilder::config())
+        .await
+        .expect("Failed to get git info in a linked worktree");
+
+    assert_eq!(context.branch, "feature");
+    assert_eq!(context.staged_files.len(), 1);
+    assert_eq!(context.staged_files[0].path, "feature.txt");
+    assert!(context.recent_commits[0].message.contains("Initial commit"));
+
+    let canonical = |path: &Path| fs::canonicalize(path).expect("Failed to canonicalize path");
+    let workdir = git_repo.workdir().expect("Worktree has a working tree");
+    assert_eq!(canonical(&workdir), canonical(&worktree_path));
+    let common_dir = git_repo.common_dir().expect("Failed to get common dir");
+    assert_eq!(
+        canonical(&common_dir),
+        canonical(&temp_dir.path().join(".git"))
+    );
+}
+
+#[tokio::test]
+async fn test_bare_repository_has_no_working_tree() {
+    let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+    Repository::init_bare(temp_dir.path()).expect("Failed to initialize bare repository");
+    let git_repo = GitRepo::new(temp_dir.path()).expect("Failed to create GitRepo");
+
+    let error = git_repo
+        .get_git_info(&MockDataBuilder::config())
+        .await
+        .expect_err("A bare repository has nothing staged");
+    assert!(
+        error
+            .to_string()
+            .contains("bare repository, which has no working tree"),
+        "Unexpected error: {error}"
+    );
+    assert!(git_repo.workdir().is_err());
+}
+
+/// Commit `content` as `name` in `repo`, returning the new commit
+fn commit_file(repo: &Repository, name: &str, content: &str, message: &str) -> git2::Oid {
+    let workdir = repo.workdir().expect("Repository has a working tree");
+    fs::write(workdir.join(name), content).expect("Failed to write file");
+    let mut index = repo.index().expect("Failed to get index");
+    index
+        .add_path(Path::new(name))
+        .expect("Failed to stage file");
+    index.write().expect("Failed to write index");
+    let tree = repo
+        .find_tree(index.write_tree().expect("Failed to write tree"))
+        .expect("Failed to find tree");
+    let signature =
+        git2::Signature::now("Test User", "test@example.com").expect("Failed to create signature");
+    let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+    let parents: Vec<&git2::Commit> = parent.iter().collect();
+    repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        message,
+        &tree,
+        &parents,
+    )
+    .expect("Failed to commit")
+}
+
+/// Point the gitlink at `path` in `repo`'s index to `commit`
+fn stage_gitlink(repo: &Repository, path: &str, commit: git2::Oid) {
+    let mut index = repo.index().expect("Failed to get index");
+    index
+        .add(&git2::IndexEntry {
+            ctime: git2::IndexTime::new(0, 0),
+            mtime: git2::IndexTime::new(0, 0),
+            dev: 0,
+            ino: 0,
+            mode: u32::from(git2::FileMode::Commit),
+            uid: 0,
+            gid: 0,
+            file_size: 0,
+            id: commit,
+            flags: 0,
+            flags_extended: 0,
+            path: path.as_bytes().to_vec(),
+        })
+        .expect("Failed to stage gitlink");
+    index.write().expect("Failed to write index");
+}
+
+#[tokio::test]
+async fn test_submodule_bump_lists_the_new_commits() {
+    let (temp_dir, git_repo) = setup_git_repo();
+    let repo = Repository::open(temp_dir.path()).expect("Failed to open repository");
+
+    // A checked out submodule at libs/retry, registered in .gitmodules
+    let submodule_path = temp_dir.path().join("libs/retry");
+    fs::create_dir_all(&submodule_path).expect("Failed to create submodule directory");
+    let submodule = Repository::init(&submodule_path).expect("Failed to init submodule");
+    let old = commit_file(&submodule, "lib.rs", "fn run() {}", "Initial library");
+    fs::write(
+        temp_dir.path().join(".gitmodules"),
+        "[submodule \"libs/retry\"]\n\tpath = libs/retry\n\turl = https://example.com/retry.git\n",
+    )
+    .expect("Failed to write .gitmodules");
+    let mut index = repo.index().expect("Failed to get index");
+    index
+        .add_path(Path::new(".gitmodules"))
+        .expect("Failed to stage .gitmodules");
+    index.write().expect("Failed to write index");
+    stage_gitlink(&repo, "libs/retry", old);
+    let helper = GitTestHelper::new(&temp_dir).expect("Failed to create GitTestHelper");
+    helper
+        .commit("Add the retry submodule")
+        .expect("Failed to commit");
+
+    // Bump it past two new commits
+    commit_file(&submodule, "retry.rs", "fn retry() {}", "Add retry logic");
+    let new = commit_file(
+        &submodule,
+        "timeout.rs",
+        "fn timeout() {}",
+        "Handle timeouts",
+    );
+    stage_gitlink(&repo, "libs/retry", new);
+
+    let context = git_repo
+        .get_git_info(&MockDataBuilder::config())
+        .await
+        .expect("Failed to get git info");
+    let bump = context
+        .staged_files
+        .iter()
+        .find(|file| file.path == "libs/retry")
+        .expect("The submodule bump is staged");
+
+    assert!(matches!(bump.change_type, ChangeType::Modified));
+    assert!(bump.content.is_none());
+    assert_eq!(
+        bump.diff,
+        format!(
+            "Submodule libs/retry {}..{}:\n  > Add retry logic\n  > Handle timeouts",
+            &old.to_string()[..7],
+            &new.to_string()[..7]
+        )
+    );
+}
@@ -355,3 +355,31 @@ async fn test_branch_comparison_with_merge_base() {
         ChangeType::Added
     ));
 }
+
+#[test]
+fn test_commit_ids_in_range_are_oldest_first() {
+    let (temp_dir, git_repo) = setup_git_repo();
+    let helper = GitTestHelper::new(&temp_dir).expect("Failed to create GitTestHelper");
+
+    helper
+        .create_branch("feature-per-commit")
+        .expect("Failed to create feature branch");
+    helper
+        .checkout_branch("feature-per-commit")
+        .expect("Failed to checkout feature branch");
+
+    let mut expected = Vec::new();
+    for (file, message) in [("one.txt", "Add one"), ("two.txt", "Add two")] {
+        helper
+            .create_and_stage_file(file, message)
+            .expect("Failed to create and stage file");
+        let oid = helper.commit(message).expect("Failed to commit");
+        expected.push(oid.to_string());
+    }
+
+    let commit_ids = git_repo
+        .get_commit_ids_in_range("main", "feature-per-commit")
+        .expect("Failed to list commits in range");
+
+    assert_eq!(commit_ids, expected);
+}
@@ -0,0 +1,248 @@
+use gait::features::changelog::{
+    ChangelogGenerator, CommitFilter,
+    filter::{parse_time_bound, parse_until_bound},
+};
+
+#[path = "test_utils.rs"]
+mod test_utils;
+use test_utils::{GitTestHelper, MockDataBuilder, setup_git_repo};
+
+/// Build a repo with commits in `packages/foo` and `packages/bar` and return the
+/// initial commit hash to use as the range start
+fn setup_monorepo() -> (tempfile::TempDir, gait::git::GitRepo, String) {
+    let (temp_dir, git_repo) = setup_git_repo();
+    let helper = GitTestHelper::new(&temp_dir).expect("Failed to create GitTestHelper");
+    let initial = helper
+        .repo
+        .head()
+        .expect("Failed to get HEAD")
+        .peel_to_commit()
+        .expect("Failed to peel HEAD")
+        .id()
+        .to_string();
+
+    let commits = [
+        ("packages/foo/src/lib.rs", "feat: add foo parser"),
+        ("packages/bar/src/lib.rs", "feat: add bar client"),
+        (
+            "packages/bar/README.md",
+            "docs(foo): mention foo in bar docs",
+        ),
+        ("packages/foobar/main.rs", "fix: unrelated foobar fix"),
+    ];
+    for (path, message) in commits {
+        helper
+            .create_and_stage_file(path, message)
+            .expect("Failed to stage file");
+        helper.commit(message).expect("Failed to commit");
+    }
+
+    (temp_dir, git_repo, initial)
+}
+
+fn subjects(filter: &CommitFilter) -> Vec<String> {
+    let (_temp_dir, git_repo, initial) = setup_monorepo();
+    let document = ChangelogGenerator::generate_document(
+        &git_repo,
+        &initial,
+        "HEAD",
+        &MockDataBuilder::config(),
+        None,
+        filter,
+    )
+    .expect("Failed to generate changelog document");
+
+    let mut subjects: Vec<String> = document.entries().map(|e| e.subject.clone()).collect();
+    subjects.sort();
+    subjects
+}
+
+#[test]
+fn test_path_filter_only_includes_matching_subtree() {
+    let filter = CommitFilter::new(Some("packages/foo".to_string()), None);
+
+    assert_eq!(subjects(&filter), ["add foo parser"]);
+}
+
+#[test]
+fn test_scope_filter_only_includes_matching_scope() {
+    let filter = CommitFilter::new(None, Some("foo".to_string()));
+
+    assert_eq!(subjects(&filter), ["mention foo in bar docs"]);
+}
+
+#[test]
+fn test_path_and_scope_filters_include_either_match() {
+    let filter = CommitFilter::new(Some("packages/foo/**".to_string()), Some("foo".to_string()));
+
+    assert_eq!(
+        subjects(&filter),
+        ["add foo parser", "mention foo in bar docs"]
+    );
+}
+
+#[test]
+fn test_empty_filter_includes_all_commits() {
+    assert_eq!(subjects(&CommitFilter::default()).len(), 4);
+}
+
+/// Build a repo with one commit in each of January, February and March 2024 and return
+/// the February commit hash
+fn setup_dated_repo() -> (tempfile::TempDir, gait::git::GitRepo, String) {
+    let (temp_dir, git_repo) = setup_git_repo();
+    let helper = GitTestHelper::new(&temp_dir).expect("Failed to create GitTestHelper");
+
+    let mut february = String::new();
+    for (path, message, date) in [
+        ("jan.txt", "feat: january feature", "2024-01-15T10:00:00Z"),
+        ("feb.txt", "fix: february fix", "2024-02-15T10:00:00Z"),
+        ("mar.txt", "feat: march feature", "2024-03-15T10:00:00Z"),
+    ] {
+        helper
+            .create_and_stage_file(path, message)
+            .expect("Failed to stage file");
+        let seconds = chrono::DateTime::parse_from_rfc3339(date)
+            .expect("valid date")
+            .timestamp();
+        let signature = git2::Signature::new(
+            "Test User",
+            "test@example.com",
+            &git2::Time::new(seconds, 0),
+        )
+        .expect("Failed to create signature");
+        let tree_id = helper
+            .repo
+            .index()
+            .and_then(|mut index| index.write_tree())
+            .expect("Failed to write tree");
+        let tree = helper.repo.find_tree(tree_id).expect("Failed to find tree");
+        let parent = helper
+            .repo
+            .head()
+            .and_then(|head| head.peel_to_commit())
+            .expect("Failed to get HEAD");
+        let oid = helper
+            .repo
+            .commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                message,
+                &tree,
+                &[&parent],
+            )
+            .expect("Failed to commit");
+        if path == "feb.txt" {
+            february = oid.to_string();
+        }
+    }
+
+    (temp_dir, git_repo, february)
+}
+
+/// Subjects in the dated repo, optionally starting the range at the February commit
+fn dated_subjects(from_february: bool, since: Option<&str>, until: Option<&str>) -> Vec<String> {
+    let (_temp_dir, git_repo, february) = setup_dated_repo();
+    let since = since.map(|v| parse_time_bound(v).expect("valid time"));
+    let until = until.map(|v| parse_until_bound(v).expect("valid time"));
+    let filter = CommitFilter::default().with_dates(since, until);
+    let from = if from_february {
+        february
+    } else {
+        String::new()
+    };
+
+    let document = ChangelogGenerator::generate_document(
+        &git_repo,
+        &from,
+        "HEAD",
+        &MockDataBuilder::config(),
+        None,
+        &filter,
+    )
+    .expect("Failed to generate changelog document");
+
+    let mut subjects: Vec<String> = document.entries().map(|e| e.subject.clone()).collect();
+    subjects.sort();
+    subjects
+}
+
+#[test]
+fn test_since_and_until_select_commits_by_date() {
+    assert_eq!(
+        dated_subjects(false, Some("2024-02-01"), Some("2024-03-31")),
+        ["february fix", "march feature"]
+    );
+    assert_eq!(
+        dated_subjects(false, None, Some("2024-01-31T23:59:59Z")),
+        ["january feature"]
+    );
+    // A date-only `--until` includes commits made later that day
+    assert_eq!(
+        dated_subjects(false, Some("2024-03-01"), Some("2024-03-15")),
+        ["march feature"]
+    );
+}
+
+#[test]
+fn test_since_intersects_with_from() {
+    // `--from` the February commit excludes it and everything before it
+    assert_eq!(
+        dated_subjects(true, Some("2024-01-01"), Some("2024-12-31")),
+        ["march feature"]
+    );
+}
+
+/// Subjects from a range holding a cherry-picked fix and a reverted feature
+fn deduplicated_subjects(collapse: bool) -> Vec<String> {
+    let (temp_dir, git_repo) = setup_git_repo();
+    let helper = GitTestHelper::new(&temp_dir).expect("Failed to create GitTestHelper");
+    let initial = helper
+        .repo
+        .head()
+        .and_then(|head| head.peel_to_commit())
+        .expect("Failed to get HEAD")
+        .id()
+        .to_string();
+
+    let commit = |path: &str, message: &str| {
+        helper
+            .create_and_stage_file(path, message)
+            .expect("Failed to stage file");
+        helper.commit(message).expect("Failed to commit")
+    };
+    commit("src/diff.rs", "fix: handle empty diff");
+    let feature = commit("src/cache.rs", "feat: add cache");
+    commit(
+        "release/diff.rs",
+        "fix:  handle empty diff\n\n(cherry picked from commit 1234567)",
+    );
+    commit(
+        "src/cache.rs",
+        &format!("Revert \"feat: add cache\"\n\nThis reverts commit {feature}."),
+    );
+
+    let document = ChangelogGenerator::generate_document(
+        &git_repo,
+        &initial,
+        "HEAD",
+        &MockDataBuilder::config(),
+        None,
+        &CommitFilter::default().with_collapsed_duplicates(collapse),
+    )
+    .expect("Failed to generate changelog document");
+
+    let mut subjects: Vec<String> = document.entries().map(|e| e.subject.clone()).collect();
+    subjects.sort();
+    subjects
+}
+
+#[test]
+fn test_cherry_picks_and_revert_pairs_are_collapsed() {
+    assert_eq!(deduplicated_subjects(true), ["handle empty diff"]);
+}
+
+#[test]
+fn test_duplicates_are_kept_when_not_collapsing() {
+    assert_eq!(deduplicated_subjects(false).len(), 4);
+}
@@ -1,11 +1,16 @@
 use gait::{
     config::Config,
     core::context::CommitContext,
-    features::commit::prompt::{
-        create_completion_system_prompt, create_completion_user_prompt, create_pr_system_prompt,
-        create_system_prompt, create_user_prompt,
+    features::commit::{
+        GeneratedMessage,
+        prompt::{
+            create_completion_system_prompt, create_completion_user_prompt,
+            create_pr_system_prompt, create_system_prompt, create_user_prompt,
+        },
+        types::GENERATED_MESSAGE_SCHEMA_VERSION,
     },
 };
+use serde_json::Value;
 
 // Use our centralized test infrastructure
 #[path = "test_utils.rs"]
@@ -95,6 +100,74 @@ fn test_system_prompt_includes_valid_json_schema() {
     assert!(schema_part.contains("\"string\""));
 }
 
+/// Check `value` against the subset of JSON Schema that `GeneratedMessage` uses
+fn assert_matches_schema(value: &Value, schema: &Value) {
+    let object = value.as_object().expect("message serializes to an object");
+    let properties = schema["properties"]
+        .as_object()
+        .expect("schema lists properties");
+    for required in schema["required"]
+        .as_array()
+        .expect("schema lists required")
+    {
+        let key = required.as_str().expect("required keys are strings");
+        assert!(object.contains_key(key), "missing required field {key}");
+    }
+    for (key, field) in object {
+        let property = properties
+            .get(key)
+            .unwrap_or_else(|| panic!("field {key} is not in the schema"));
+        let allowed: Vec<&str> = match &property["type"] {
+            Value::String(kind) => vec![kind.as_str()],
+            Value::Array(kinds) => kinds.iter().filter_map(Value::as_str).collect(),
+            other => panic!("unexpected type for {key}: {other}"),
+        };
+        let actual = match field {
+            Value::String(_) => "string",
+            Value::Null => "null",
+            _ => "other",
+        };
+        assert!(
+            allowed.contains(&actual),
+            "{key} is {actual}, expected {allowed:?}"
+        );
+    }
+}
+
+#[test]
+fn test_generated_message_round_trips_against_its_schema() {
+    let schema = GeneratedMessage::json_schema();
+    assert_eq!(schema["x-schema-version"], GENERATED_MESSAGE_SCHEMA_VERSION);
+    let required: Vec<&str> = schema["required"]
+        .as_array()
+        .expect("schema lists required")
+        .iter()
+        .filter_map(Value::as_str)
+        .collect();
+    assert!(required.contains(&"title"));
+    assert!(!required.contains(&"emoji"));
+
+    for emoji in [None, Some("🐛".to_string())] {
+        let original = GeneratedMessage {
+            emoji: emoji.clone(),
+            title: "fix: handle empty diff".to_string(),
+            message: "Return early when nothing is staged.".to_string(),
+        };
+        let value = serde_json::to_value(&original).expect("message serializes");
+        assert_matches_schema(&value, &schema);
+
+        let parsed: GeneratedMessage = serde_json::from_value(value).expect("message deserializes");
+        assert_eq!(parsed.emoji, emoji);
+        assert_eq!(parsed.title, original.title);
+        assert_eq!(parsed.message, original.message);
+    }
+
+    // The prompt embeds the same schema the parser uses
+    let prompt = create_system_prompt(&create_mock_config()).expect("Failed to create prompt");
+    let embedded = serde_json::to_string_pretty(&schema).expect("schema serializes");
+    assert!(prompt.contains(&embedded));
+}
+
 #[test]
 fn test_user_prompt_context_elements_are_properly_formatted() {
     let context = create_mock_commit_context();
@@ -381,3 +454,65 @@ fn test_pr_system_prompt_structure() {
         "Should have structured guidelines"
     );
 }
+
+#[test]
+fn test_create_user_prompt_requests_ticket_trailer() {
+    let mut context = create_mock_commit_context();
+    context.branch = "feature/PROJ-1234-add-widget".to_string();
+    context.ticket_id = Some("PROJ-1234".to_string());
+    let prompt = create_user_prompt(&context);
+
+    assert!(prompt.contains("**Ticket:** PROJ-1234"));
+    assert!(prompt.contains("`Refs: PROJ-1234`"));
+}
+
+#[test]
+fn test_create_user_prompt_omits_ticket_without_match() {
+    let context = create_mock_commit_context();
+    let prompt = create_user_prompt(&context);
+
+    assert!(!prompt.contains("**Ticket:**"));
+    assert!(!prompt.contains("Refs:"));
+}
+
+#[test]
+fn test_system_suffixes_are_appended_after_instructions() {
+    let mut config = create_mock_config();
+    config.commit_system_suffix = "Always write in the imperative mood.".to_string();
+    config.completion_system_suffix = "Use British spelling.".to_string();
+    config.pr_system_suffix = "Mention the ticket in the title.".to_string();
+
+    let system_prompt = create_system_prompt(&config).expect("Failed to create system prompt");
+    assert!(system_prompt.contains("Write clear, concise commit messages"));
+    assert!(
+        system_prompt
+            .trim_end()
+            .ends_with("Always write in the imperative mood.")
+    );
+    assert!(!system_prompt.contains("Use British spelling."));
+
+    let completion_prompt =
+        create_completion_system_prompt(&config).expect("Failed to create completion prompt");
+    assert!(
+        completion_prompt
+            .trim_end()
+            .ends_with("Use British spelling.")
+    );
+
+    let pr_prompt = create_pr_system_prompt(&config).expect("Failed to create PR prompt");
+    assert!(
+        pr_prompt
+            .trim_end()
+            .ends_with("Mention the ticket in the title.")
+    );
+
+    // Unset suffixes leave the built-in prompts unchanged
+    assert_eq!(
+        create_system_prompt(&create_mock_config()).expect("Failed to create system prompt"),
+        create_system_prompt(&Config {
+            commit_system_suffix: "   ".to_string(),
+            ..create_mock_config()
+        })
+        .expect("Failed to create system prompt")
+    );
+}
@@ -0,0 +1,60 @@
+#![cfg(feature = "integration")]
+
+use std::process::Command;
+
+#[path = "test_utils.rs"]
+mod test_utils;
+use test_utils::{GitTestHelper, setup_git_repo};
+
+#[test]
+fn test_commit_is_signed_with_ssh_key() {
+    let (temp_dir, git_repo) = setup_git_repo();
+    let helper = GitTestHelper::new(&temp_dir).expect("Failed to create GitTestHelper");
+
+    // Generate a throwaway SSH key and configure git to sign with it
+    let key_path = temp_dir.path().join("signing_key");
+    let status = Command::new("ssh-keygen")
+        .args(["-q", "-t", "ed25519", "-N", "", "-f"])
+        .arg(&key_path)
+        .status()
+        .expect("Failed to run ssh-keygen");
+    assert!(status.success(), "ssh-keygen failed");
+
+    let mut config = helper
+        .repo
+        .config()
+        .expect("Failed to get repository config");
+    config
+        .set_str("gpg.format", "ssh")
+        .expect("Failed to set gpg.format");
+    config
+        .set_str(
+            "user.signingkey",
+            &key_path.with_extension("pub").to_string_lossy(),
+        )
+        .expect("Failed to set user.signingkey");
+
+    helper
+        .create_and_stage_file("signed.txt", "Signed content")
+        .expect("Failed to create and stage file");
+
+    let result = git_repo
+        .commit("feat: add signed file", true)
+        .expect("Failed to create signed commit");
+    assert!(result.signed);
+
+    let head = helper
+        .repo
+        .head()
+        .expect("Failed to get HEAD")
+        .peel_to_commit()
+        .expect("Failed to peel HEAD");
+    assert_eq!(
+        head.message().map(str::trim_end),
+        Some("feat: add signed file")
+    );
+    assert!(
+        helper.repo.extract_signature(&head.id(), None).is_ok(),
+        "HEAD commit should carry a signature"
+    );
+}
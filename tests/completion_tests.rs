@@ -4,8 +4,10 @@ use gait::core::context::{ChangeType, CommitContext, RecentCommit, StagedFile};
 mod tests {
     use super::*;
     use gait::Config;
+    use gait::features::commit::completion::{CompletionRegion, CompletionRequest};
     use gait::features::commit::prompt::{
-        create_completion_system_prompt, create_completion_user_prompt,
+        clamp_context_ratio, context_item_count, create_completion_system_prompt,
+        create_completion_user_prompt,
     };
 
     fn create_test_config() -> Config {
@@ -116,6 +118,37 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_context_ratio_is_clamped_and_sizes_history() {
+        assert!((clamp_context_ratio(1.5) - 1.0).abs() < f32::EPSILON);
+        assert!(clamp_context_ratio(-0.2).abs() < f32::EPSILON);
+        assert!(clamp_context_ratio(f32::NAN).abs() < f32::EPSILON);
+        assert_eq!(context_item_count(4, 0.5), 2);
+        assert_eq!(context_item_count(3, 0.1), 1);
+        assert_eq!(context_item_count(3, 7.0), 3);
+
+        let context = create_mock_commit_context();
+
+        let over = create_completion_user_prompt(&context, "feat: add user", 1.5);
+        assert!(over.contains("**Context Ratio:** 100%"));
+        assert!(over.contains("feat: add new feature"));
+
+        let none = create_completion_user_prompt(&context, "feat: add user", -0.2);
+        assert!(none.contains("**Context Ratio:** 0%"));
+        assert!(
+            !none.contains("feat: add new feature"),
+            "0% context should leave recent commits out"
+        );
+        assert!(
+            !none.contains("fix: bug fix"),
+            "0% context should leave author history out"
+        );
+        assert!(
+            none.contains("src/main.rs"),
+            "staged changes are always kept"
+        );
+    }
+
     #[test]
     fn test_completion_system_prompt_structure() {
         let config = create_test_config();
@@ -167,6 +200,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_no_cache_skips_enhanced_history() {
+        let mut context = create_mock_commit_context();
+        context.author_history = vec![
+            "feat: add user authentication".to_string(),
+            "fix: resolve login issue".to_string(),
+        ];
+
+        assert!(!context.prompt_history(10, false).is_empty());
+        assert!(
+            context.prompt_history(10, true).is_empty(),
+            "--no-cache should leave the author history out of the prompt"
+        );
+    }
+
     #[test]
     fn test_semantic_similarity_scoring() {
         let context = CommitContext::new(
@@ -283,4 +331,36 @@ mod tests {
         // Should work without panicking even with empty history
         assert_eq!(similar.len(), 0);
     }
+
+    #[test]
+    fn test_completing_the_body_keeps_the_title() {
+        let text = "feat(auth): add login\n\nAdds the\n\nRefs: #42";
+        let request = CompletionRequest::new(text, (2, 8));
+        assert_eq!(request.region(), CompletionRegion::Body);
+        assert_eq!(request.prefix(), "Adds the");
+
+        let spliced = request.splice("Adds the login form and session handling.");
+        assert_eq!(
+            spliced,
+            "feat(auth): add login\n\nAdds the login form and session handling.\n\nRefs: #42"
+        );
+    }
+
+    #[test]
+    fn test_completing_the_title_keeps_the_body() {
+        let text = "fix: parser\n\nThe body stays as written.";
+        let request = CompletionRequest::new(text, (0, 11));
+        assert_eq!(request.region(), CompletionRegion::Title);
+        assert_eq!(request.prefix(), "fix: parser");
+
+        let spliced = request.splice("fix: parser handles empty input\nignored second line");
+        assert_eq!(
+            spliced,
+            "fix: parser handles empty input\n\nThe body stays as written."
+        );
+        assert_eq!(
+            CompletionRequest::title("feat: add").splice("feat: add caching"),
+            "feat: add caching"
+        );
+    }
 }
@@ -1,5 +1,12 @@
-use gait::{common::CommonParams, config::ProviderConfig};
+use gait::{
+    common::CommonParams,
+    config::{
+        Config, ConfigSource, ProfileConfig, ProviderConfig, ProviderOverride, expand_env_vars,
+        expand_env_vars_with,
+    },
+};
 use std::env;
+use std::fs;
 use std::path::Path;
 use std::process::Command;
 
@@ -153,3 +160,250 @@ fn test_project_config_security() {
     // Clean up - restore original directory
     env::set_current_dir(original_dir).expect("Failed to restore original directory");
 }
+
+/// Base config using `openai` with a key and token limit, plus a `cheap` profile
+/// switching to a local model
+fn config_with_cheap_profile() -> Config {
+    let mut config = MockDataBuilder::test_config_with_api_key("openai", "base-key");
+    if let Some(openai) = config.providers.get_mut("openai") {
+        openai.token_limit = Some(8000);
+    }
+
+    let profile = ProfileConfig {
+        default_provider: Some("ollama".to_string()),
+        providers: [
+            (
+                "openai".to_string(),
+                ProviderOverride {
+                    model: Some("gpt-4o-mini".to_string()),
+                    ..Default::default()
+                },
+            ),
+            (
+                "ollama".to_string(),
+                ProviderOverride {
+                    model: Some("llama3".to_string()),
+                    token_limit: Some(4000),
+                    ..Default::default()
+                },
+            ),
+        ]
+        .into_iter()
+        .collect(),
+        ..Default::default()
+    };
+    config.profiles.insert("cheap".to_string(), profile);
+    config
+}
+
+#[test]
+fn test_profile_overrides_base_and_base_fills_gaps() {
+    let mut config = config_with_cheap_profile();
+    config
+        .apply_profile("cheap")
+        .expect("Failed to apply profile");
+
+    // Profile values win
+    assert_eq!(config.default_provider, "ollama");
+    let ollama = config
+        .get_provider_config("ollama")
+        .expect("ollama provider config");
+    assert_eq!(ollama.model_name, "llama3");
+    assert_eq!(ollama.token_limit, Some(4000));
+
+    // Unset profile fields keep the base values
+    let openai = config
+        .get_provider_config("openai")
+        .expect("openai provider config");
+    assert_eq!(openai.model_name, "gpt-4o-mini");
+    assert_eq!(openai.api_key, "base-key");
+    assert_eq!(openai.token_limit, Some(8000));
+}
+
+#[test]
+fn test_cli_provider_overrides_profile() {
+    let mut config = config_with_cheap_profile();
+    let common = CommonParams {
+        provider: Some("openai".to_string()),
+        profile: Some("cheap".to_string()),
+        ..Default::default()
+    };
+    common
+        .apply_to_config(&mut config)
+        .expect("Failed to apply common params");
+
+    assert_eq!(config.default_provider, "openai");
+    assert_eq!(
+        config
+            .get_provider_config("openai")
+            .map(|p| p.model_name.as_str()),
+        Some("gpt-4o-mini")
+    );
+}
+
+fn global_config() -> Config {
+    let mut config = Config {
+        default_provider: "openai".to_string(),
+        instructions: "Global instructions.".to_string(),
+        ..Default::default()
+    };
+    config.providers.insert(
+        "anthropic".to_string(),
+        ProviderConfig {
+            api_key: "global-key".to_string(),
+            model_name: "global-model".to_string(),
+            ..Default::default()
+        },
+    );
+    config
+}
+
+const REPO_CONFIG: &str = r#"
+default_provider = "anthropic"
+instructions = "Follow the team style."
+exclude_paths = ["vendor/**"]
+
+[providers.anthropic]
+model = "repo-model"
+api_key = "repo-key"
+base_url = "https://elsewhere.example"
+"#;
+
+#[test]
+fn test_repo_config_overrides_global_config() {
+    let (temp_dir, _git_repo) = setup_git_repo();
+    fs::write(temp_dir.path().join(".gitai.toml"), REPO_CONFIG)
+        .expect("Failed to write .gitai.toml");
+    let nested = temp_dir.path().join("src/nested");
+    fs::create_dir_all(&nested).expect("Failed to create directory");
+
+    let mut config = global_config();
+    let path = config
+        .apply_repo_config(&nested)
+        .expect("Failed to apply repo config")
+        .expect("repo config is found from a subdirectory");
+
+    assert_eq!(config.default_provider, "anthropic");
+    assert_eq!(config.instructions, "Follow the team style.");
+    assert_eq!(config.exclude_paths, vec!["vendor/**".to_string()]);
+    let anthropic = config
+        .get_provider_config("anthropic")
+        .expect("anthropic provider config");
+    assert_eq!(anthropic.model_name, "repo-model");
+    // A committed file can't swap the key or send it elsewhere
+    assert_eq!(anthropic.api_key, "global-key");
+    assert_eq!(anthropic.base_url, None);
+
+    assert_eq!(
+        config.source_of("default_provider"),
+        ConfigSource::Repo(path)
+    );
+    assert_eq!(config.source_of("emoji"), ConfigSource::Global);
+}
+
+#[test]
+fn test_flags_override_repo_config() {
+    let (temp_dir, _git_repo) = setup_git_repo();
+    fs::create_dir_all(temp_dir.path().join(".gitai")).expect("Failed to create .gitai");
+    fs::write(temp_dir.path().join(".gitai/config.toml"), REPO_CONFIG)
+        .expect("Failed to write .gitai/config.toml");
+
+    let mut config = global_config();
+    config
+        .apply_repo_config(temp_dir.path())
+        .expect("Failed to apply repo config");
+    let common = CommonParams {
+        provider: Some("openai".to_string()),
+        // Already applied above, for the temp repo rather than the current directory
+        no_project_config: true,
+        ..Default::default()
+    };
+    common
+        .apply_to_config(&mut config)
+        .expect("Failed to apply common params");
+
+    assert_eq!(config.default_provider, "openai");
+    assert_eq!(config.source_of("default_provider"), ConfigSource::Flag);
+    // Settings without a flag keep the repo value
+    assert_eq!(config.instructions, "Follow the team style.");
+}
+
+#[test]
+fn test_include_untracked_flag() {
+    let mut config = global_config();
+    assert!(
+        !config.include_untracked,
+        "untracked files are only listed by default"
+    );
+
+    let common = CommonParams {
+        include_untracked: true,
+        no_project_config: true,
+        ..Default::default()
+    };
+    common
+        .apply_to_config(&mut config)
+        .expect("Failed to apply common params");
+    assert!(config.include_untracked);
+}
+
+#[test]
+fn test_unknown_profile_errors_clearly() {
+    let mut config = config_with_cheap_profile();
+    let err = config
+        .apply_profile("expensive")
+        .expect_err("Unknown profile should fail");
+
+    assert_eq!(
+        err.to_string(),
+        "Unknown profile 'expensive'. Available profiles: cheap"
+    );
+}
+
+#[test]
+fn test_env_var_expansion_uses_process_environment() {
+    let path = env::var("PATH").expect("PATH should be set for tests");
+
+    assert_eq!(
+        expand_env_vars("${PATH}").expect("PATH should expand"),
+        path
+    );
+    assert_eq!(
+        expand_env_vars("prefix:$PATH").expect("PATH should expand"),
+        format!("prefix:{path}")
+    );
+}
+
+#[test]
+fn test_env_var_expansion_in_api_key_and_base_url() {
+    let lookup = |name: &str| match name {
+        "OPENAI_API_KEY" => Some("sk-from-env".to_string()),
+        "PROXY_HOST" => Some("llm.internal".to_string()),
+        _ => None,
+    };
+
+    assert_eq!(
+        expand_env_vars_with("${OPENAI_API_KEY}", lookup).expect("key should expand"),
+        "sk-from-env"
+    );
+    assert_eq!(
+        expand_env_vars_with("https://$PROXY_HOST:8443/v1", lookup).expect("url should expand"),
+        "https://llm.internal:8443/v1"
+    );
+    // A `$` that doesn't start a variable name is kept
+    assert_eq!(
+        expand_env_vars_with("cost: $5", lookup).expect("no variables"),
+        "cost: $5"
+    );
+}
+
+#[test]
+fn test_env_var_expansion_errors_on_missing_variable() {
+    let err = expand_env_vars("${GAIT_TEST_VARIABLE_THAT_IS_NEVER_SET}")
+        .expect_err("Unset variable should fail");
+
+    assert_eq!(
+        err.to_string(),
+        "Environment variable 'GAIT_TEST_VARIABLE_THAT_IS_NEVER_SET' referenced in config is not set"
+    );
+}
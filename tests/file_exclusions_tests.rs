@@ -1,4 +1,4 @@
-use gait::{config::Config, git::GitRepo};
+use gait::{config::Config, features::commit::context_from_diff, git::GitRepo};
 use git2::Repository;
 use std::fs;
 use std::path::Path;
@@ -297,3 +297,229 @@ async fn test_nested_gitignore_files() {
     // src/important.rs should be included
     assert!(included_files.contains(&"src/important.rs"));
 }
+
+#[tokio::test]
+async fn test_oversized_diff_is_omitted_from_context() {
+    let (temp_dir, git_repo) = setup_git_repo();
+    let config = Config::default();
+
+    // A ~2MB generated file, well above the default diff size limit
+    let generated: String = (0..35_000)
+        .map(|i| format!("generated_entry_{i:06} = \"{i:032x}\"\n"))
+        .collect();
+    assert!(generated.len() > 2 * 1024 * 1024);
+    fs::write(temp_dir.path().join("generated.lock"), &generated)
+        .expect("Failed to write generated file");
+    fs::write(temp_dir.path().join("main.rs"), "fn main() {}\n").expect("Failed to write main.rs");
+
+    let repo = Repository::open(temp_dir.path()).expect("Failed to open repository");
+    let mut index = repo.index().expect("Failed to get repository index");
+    index
+        .add_all(["*"].iter(), git2::IndexAddOption::DEFAULT, None)
+        .expect("Failed to add all files to index");
+    index.write().expect("Failed to write index");
+
+    let context = git_repo
+        .get_git_info(&config)
+        .await
+        .expect("Failed to get git info");
+
+    let generated_file = context
+        .staged_files
+        .iter()
+        .find(|file| file.path == "generated.lock")
+        .expect("Generated file should still be listed");
+    assert!(generated_file.content_excluded);
+    assert!(generated_file.content.is_none());
+    assert!(
+        generated_file.diff.starts_with("[diff omitted: ") && generated_file.diff.ends_with(" KB]"),
+        "Unexpected placeholder: {}",
+        generated_file.diff
+    );
+
+    let source_file = context
+        .staged_files
+        .iter()
+        .find(|file| file.path == "main.rs")
+        .expect("Source file should be listed");
+    assert!(!source_file.content_excluded);
+    assert!(source_file.diff.contains("fn main()"));
+}
+
+#[tokio::test]
+async fn test_small_edit_to_large_file_keeps_its_diff() {
+    let (temp_dir, git_repo) = setup_git_repo();
+    let config = Config::default();
+
+    // A ~1MB file: over the diff size limit, but well under the file size limit
+    let lines: Vec<String> = (0..20_000)
+        .map(|i| format!("entry_{i:06} = \"{i:032x}\""))
+        .collect();
+    let path = temp_dir.path().join("large.txt");
+    fs::write(&path, lines.join("\n")).expect("Failed to write large file");
+
+    let repo = Repository::open(temp_dir.path()).expect("Failed to open repository");
+    let mut index = repo.index().expect("Failed to get repository index");
+    index
+        .add_path(Path::new("large.txt"))
+        .expect("Failed to add file to index");
+    index.write().expect("Failed to write index");
+    let tree = repo
+        .find_tree(index.write_tree().expect("Failed to write tree"))
+        .expect("Failed to find tree");
+    let signature = repo.signature().expect("Failed to create signature");
+    let parent = repo
+        .head()
+        .and_then(|head| head.peel_to_commit())
+        .expect("Failed to get HEAD commit");
+    repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        "Add large file",
+        &tree,
+        &[&parent],
+    )
+    .expect("Failed to commit large file");
+
+    // Change a single line, so the patch stays small
+    let mut edited = lines;
+    edited[10_000] = "entry_010000 = \"edited\"".to_string();
+    fs::write(&path, edited.join("\n")).expect("Failed to edit large file");
+    index
+        .add_path(Path::new("large.txt"))
+        .expect("Failed to add file to index");
+    index.write().expect("Failed to write index");
+
+    let context = git_repo
+        .get_git_info(&config)
+        .await
+        .expect("Failed to get git info");
+
+    let file = context
+        .staged_files
+        .iter()
+        .find(|file| file.path == "large.txt")
+        .expect("Large file should be listed");
+    assert!(!file.content_excluded);
+    assert!(
+        file.diff.contains("+entry_010000 = \"edited\""),
+        "{}",
+        file.diff
+    );
+}
+
+#[tokio::test]
+async fn test_oversized_file_is_never_read_into_context() {
+    let (temp_dir, git_repo) = setup_git_repo();
+    let config = Config::default();
+
+    // Stage a small edit, then leave a sparse 1 GiB file in the working tree: reading it
+    // for the diff or the content would allocate all of it
+    let path = temp_dir.path().join("initial.txt");
+    fs::write(&path, "Edited content").expect("Failed to edit initial file");
+    let repo = Repository::open(temp_dir.path()).expect("Failed to open repository");
+    let mut index = repo.index().expect("Failed to get repository index");
+    index
+        .add_path(Path::new("initial.txt"))
+        .expect("Failed to add file to index");
+    index.write().expect("Failed to write index");
+    fs::File::options()
+        .write(true)
+        .open(&path)
+        .and_then(|file| file.set_len(1 << 30))
+        .expect("Failed to grow initial file");
+
+    let context = git_repo
+        .get_git_info(&config)
+        .await
+        .expect("Failed to get git info");
+
+    let file = context
+        .staged_files
+        .iter()
+        .find(|file| file.path == "initial.txt")
+        .expect("Oversized file should still be listed");
+    assert!(file.content_excluded);
+    assert!(file.content.is_none());
+    assert_eq!(file.diff, "[diff omitted: 1048576 KB]");
+}
+
+#[tokio::test]
+async fn test_exclude_paths_drops_matching_files_from_context() {
+    let (temp_dir, git_repo) = setup_git_repo();
+    let config = Config {
+        exclude_paths: vec!["*.lock".to_string(), "dist/".to_string()],
+        ..Config::default()
+    };
+
+    fs::write(temp_dir.path().join("Cargo.lock"), "# generated\n")
+        .expect("Failed to write Cargo.lock");
+    fs::create_dir_all(temp_dir.path().join("dist")).expect("Failed to create dist directory");
+    fs::write(
+        temp_dir.path().join("dist/app.js"),
+        "console.log('bundle');\n",
+    )
+    .expect("Failed to write dist/app.js");
+    fs::write(temp_dir.path().join("lib.rs"), "pub fn lib() {}\n").expect("Failed to write lib.rs");
+
+    let repo = Repository::open(temp_dir.path()).expect("Failed to open repository");
+    let mut index = repo.index().expect("Failed to get repository index");
+    index
+        .add_all(["*"].iter(), git2::IndexAddOption::DEFAULT, None)
+        .expect("Failed to add all files to index");
+    index.write().expect("Failed to write index");
+
+    let context = git_repo
+        .get_git_info(&config)
+        .await
+        .expect("Failed to get git info");
+
+    let paths: Vec<_> = context
+        .staged_files
+        .iter()
+        .map(|file| file.path.as_str())
+        .collect();
+    assert_eq!(paths, ["lib.rs"]);
+
+    // Excluded files remain staged and are still committed
+    let index = repo.index().expect("Failed to get repository index");
+    assert!(index.get_path(Path::new("Cargo.lock"), 0).is_some());
+}
+
+#[test]
+fn test_context_from_piped_diff_applies_exclusions() {
+    let config = Config {
+        exclude_paths: vec!["*.lock".to_string()],
+        ..Config::default()
+    };
+    let diff = "\
+diff --git a/Cargo.lock b/Cargo.lock
+index 1111111..2222222 100644
+--- a/Cargo.lock
++++ b/Cargo.lock
+@@ -1 +1 @@
+-version = 1
++version = 2
+diff --git a/src/main.rs b/src/main.rs
+index 3333333..4444444 100644
+--- a/src/main.rs
++++ b/src/main.rs
+@@ -1 +1 @@
+-fn main() {}
++fn main() { run(); }
+diff --git a/logo.png b/logo.png
+index 5555555..6666666 100644
+Binary files a/logo.png and b/logo.png differ
+";
+
+    let context = context_from_diff(diff, &config).expect("Failed to build context from diff");
+    let paths: Vec<_> = context
+        .staged_files
+        .iter()
+        .map(|file| file.path.as_str())
+        .collect();
+    assert_eq!(paths, ["src/main.rs", "logo.png"]);
+    assert!(context.recent_commits.is_empty());
+    assert!(context_from_diff("", &config).is_err());
+}
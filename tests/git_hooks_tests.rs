@@ -1,6 +1,7 @@
 // only run this test on Linux
 #![cfg(target_os = "linux")]
 use anyhow::Result;
+use gait::git::GitRepo;
 use git2::Repository;
 
 // Use our centralized test infrastructure
@@ -161,3 +162,23 @@ fn test_verify_and_commit_no_hooks() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_linked_worktree_runs_the_shared_hooks() -> Result<()> {
+    let (temp_dir, _git_repo) = setup_git_repo();
+    GitHooksTestHelper::create_hook(temp_dir.path(), "pre-commit", "touch hook-ran", false)?;
+
+    let repo = Repository::open(temp_dir.path())?;
+    let worktrees = tempfile::TempDir::new()?;
+    let worktree_path = worktrees.path().join("feature");
+    repo.worktree("feature", &worktree_path, None)?;
+
+    let git_repo = GitRepo::new(&worktree_path)?;
+    git_repo.execute_hook("pre-commit")?;
+
+    // Hooks come from the main repository and run in the worktree's checkout
+    assert!(worktree_path.join("hook-ran").exists());
+    assert!(!temp_dir.path().join("hook-ran").exists());
+
+    Ok(())
+}
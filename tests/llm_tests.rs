@@ -1,11 +1,21 @@
 // Use our centralized test infrastructure
 #[path = "test_utils.rs"]
 mod test_utils;
+use anyhow::anyhow;
+use gait::common::CommonParams;
 use gait::core::llm::{
-    get_available_provider_names, get_default_model_for_provider,
-    get_default_token_limit_for_provider, validate_provider_config,
+    ContextLengthExceeded, SamplingParams, context_length_exceeded, get_available_provider_names,
+    get_default_model_for_provider, get_default_token_limit_for_provider, get_message,
+    is_retriable_error, provider_endpoint, provider_supports_seed, sampling_params,
+    seed_request_body, validate_provider_config, with_context_shrink, with_provider_fallback,
+    with_response_cache,
 };
+use gait::core::response_cache::ResponseCache;
+use llm::builder::LLMBackend;
+use std::time::Duration;
 use test_utils::MockDataBuilder;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
 
 #[test]
 fn test_get_available_providers() {
@@ -75,3 +85,309 @@ fn test_validate_provider_config() {
         .api_key = String::new();
     assert!(validate_provider_config(&invalid_config, "openai").is_err());
 }
+
+#[tokio::test]
+async fn test_provider_fallback_returns_fallback_result() {
+    let providers = vec!["primary".to_string(), "fallback".to_string()];
+    let mut attempted = Vec::new();
+
+    let result: anyhow::Result<String> = with_provider_fallback(&providers, |name| {
+        attempted.push(name.to_string());
+        let name = name.to_string();
+        async move {
+            if name == "primary" {
+                Err(anyhow!("Provider error: 429 Too Many Requests"))
+            } else {
+                Ok(format!("message from {name}"))
+            }
+        }
+    })
+    .await;
+
+    assert_eq!(
+        result.expect("fallback provider should succeed"),
+        "message from fallback"
+    );
+    assert_eq!(attempted, vec!["primary", "fallback"]);
+}
+
+#[tokio::test]
+async fn test_provider_fallback_reports_every_attempt() {
+    let providers = vec!["primary".to_string(), "fallback".to_string()];
+
+    let result: anyhow::Result<String> = with_provider_fallback(&providers, |name| {
+        let name = name.to_string();
+        async move {
+            Err(anyhow!(
+                "Provider error: 503 Service Unavailable from {name}"
+            ))
+        }
+    })
+    .await;
+
+    let error = result.expect_err("all providers fail").to_string();
+    assert!(error.contains("primary"));
+    assert!(error.contains("fallback"));
+}
+
+#[tokio::test]
+async fn test_provider_fallback_stops_on_non_retriable_error() {
+    let providers = vec!["primary".to_string(), "fallback".to_string()];
+    let mut attempts = 0;
+
+    let result: anyhow::Result<String> = with_provider_fallback(&providers, |_| {
+        attempts += 1;
+        async { Err(anyhow!("Provider error: 401 Unauthorized")) }
+    })
+    .await;
+
+    assert!(result.is_err());
+    assert_eq!(attempts, 1, "Non-retriable errors should not fall back");
+}
+
+#[tokio::test]
+async fn test_fallback_responses_are_not_cached() {
+    let dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+    let cache = ResponseCache::with_dir(dir.path().to_path_buf(), Duration::from_secs(60));
+    let key = ResponseCache::key("primary", "model", "system", "user");
+
+    let answer = |provider: &'static str| async move {
+        anyhow::Ok((provider.to_string(), format!("message from {provider}")))
+    };
+
+    let message: String =
+        with_response_cache(Some(&cache), Some(&key), "primary", || answer("fallback"))
+            .await
+            .expect("fallback answers");
+    assert_eq!(message, "message from fallback");
+    assert_eq!(cache.get::<String>(&key), None);
+
+    let message: String =
+        with_response_cache(Some(&cache), Some(&key), "primary", || answer("primary"))
+            .await
+            .expect("primary answers");
+    assert_eq!(message, "message from primary");
+    assert_eq!(
+        cache.get::<String>(&key).as_deref(),
+        Some("message from primary")
+    );
+}
+
+#[test]
+fn test_is_retriable_error() {
+    assert!(is_retriable_error(&anyhow!("Provider timed out")));
+    assert!(is_retriable_error(&anyhow!("HTTP status 502 Bad Gateway")));
+    assert!(!is_retriable_error(&anyhow!(
+        "JSON parse error: expected value"
+    )));
+}
+
+#[tokio::test]
+async fn test_context_length_error_retries_once_with_shrunk_budget() {
+    let mut budgets = Vec::new();
+
+    let result: anyhow::Result<String> = with_context_shrink(16_000, |token_limit| {
+        budgets.push(token_limit);
+        let first = budgets.len() == 1;
+        async move {
+            if first {
+                Err(anyhow!(
+                    "Provider error: This model's maximum context length is 8192 tokens. However, your messages resulted in 9000 tokens."
+                ))
+            } else {
+                Ok(format!("message within {token_limit} tokens"))
+            }
+        }
+    })
+    .await;
+
+    assert_eq!(
+        result.expect("shrunk retry should succeed"),
+        "message within 6554 tokens"
+    );
+    assert_eq!(budgets, vec![16_000, 6554]);
+
+    // Only one shrink is attempted
+    let mut attempts = 0;
+    let result: anyhow::Result<String> = with_context_shrink(16_000, |_| {
+        attempts += 1;
+        async { Err(anyhow!("Provider error: context_length_exceeded")) }
+    })
+    .await;
+    assert!(result.is_err());
+    assert_eq!(attempts, 2);
+}
+
+#[test]
+fn test_context_length_exceeded_parses_reported_tokens() {
+    assert_eq!(
+        context_length_exceeded(&anyhow!(
+            "Provider error: prompt is too long: 210000 tokens > 200000 maximum"
+        )),
+        Some(ContextLengthExceeded {
+            max_tokens: Some(200_000),
+            requested_tokens: Some(210_000),
+        })
+    );
+    assert_eq!(
+        context_length_exceeded(&anyhow!("Provider error: context_length_exceeded")),
+        Some(ContextLengthExceeded::default())
+    );
+    assert_eq!(
+        context_length_exceeded(&anyhow!("Provider error: 429 Too Many Requests")),
+        None
+    );
+}
+
+#[test]
+fn test_sampling_overrides_are_reproducible() {
+    let common = CommonParams {
+        temperature: Some(0.0),
+        seed: Some(42),
+        ..Default::default()
+    };
+
+    let mut runs = Vec::new();
+    for _ in 0..2 {
+        let mut config = MockDataBuilder::test_config_with_api_key("openai", "dummy-api-key");
+        common
+            .apply_to_config(&mut config)
+            .expect("Failed to apply sampling overrides");
+        runs.push(sampling_params(&config, "openai"));
+    }
+
+    let expected = SamplingParams {
+        temperature: Some(0.0),
+        seed: Some(42),
+    };
+    assert_eq!(runs, [expected, expected]);
+
+    // Without overrides nothing is pinned, so provider defaults apply
+    let config = MockDataBuilder::test_config_with_api_key("openai", "dummy-api-key");
+    assert_eq!(
+        sampling_params(&config, "openai"),
+        SamplingParams::default()
+    );
+}
+
+/// Answers a single chat completion on `listener` and returns the JSON body it was sent
+async fn capture_chat_request(listener: TcpListener) -> serde_json::Value {
+    let (mut socket, _) = listener.accept().await.expect("Failed to accept request");
+
+    let mut request = Vec::new();
+    let mut buf = [0u8; 4096];
+    let body_start = loop {
+        let read = socket.read(&mut buf).await.expect("Failed to read request");
+        assert!(read > 0, "Connection closed before the request headers");
+        request.extend_from_slice(&buf[..read]);
+        if let Some(end) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+            break end + 4;
+        }
+    };
+
+    let headers = String::from_utf8_lossy(&request[..body_start]).to_lowercase();
+    let content_length: usize = headers
+        .lines()
+        .find_map(|line| line.strip_prefix("content-length:"))
+        .and_then(|len| len.trim().parse().ok())
+        .expect("Request should have a Content-Length");
+    while request.len() < body_start + content_length {
+        let read = socket.read(&mut buf).await.expect("Failed to read body");
+        assert!(read > 0, "Connection closed before the request body");
+        request.extend_from_slice(&buf[..read]);
+    }
+
+    let response = serde_json::json!({
+        "id": "chatcmpl-test",
+        "object": "chat.completion",
+        "created": 0,
+        "model": "test-model",
+        "choices": [{
+            "index": 0,
+            "message": { "role": "assistant", "content": "seeded reply" },
+            "finish_reason": "stop"
+        }]
+    })
+    .to_string();
+    let reply = format!(
+        "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{response}",
+        response.len()
+    );
+    socket
+        .write_all(reply.as_bytes())
+        .await
+        .expect("Failed to write response");
+
+    serde_json::from_slice(&request[body_start..]).expect("Request body should be JSON")
+}
+
+#[tokio::test]
+async fn test_seed_is_sent_with_the_request() {
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind local server");
+    let address = listener.local_addr().expect("Failed to read local address");
+    let server = tokio::spawn(capture_chat_request(listener));
+
+    let mut config = MockDataBuilder::test_config_with_api_key("openai", "dummy-api-key");
+    config.cache_responses = false;
+    config.set_sampling_overrides(Some(0.7), Some(42));
+    config
+        .providers
+        .get_mut("openai")
+        .expect("OpenAI provider should exist in config")
+        .base_url = Some(format!("http://{address}/v1/"));
+
+    let message: String = get_message(&config, "openai", "system prompt", "user prompt")
+        .await
+        .expect("Local provider should answer");
+    assert_eq!(message, "seeded reply");
+
+    let body = server.await.expect("Server task panicked");
+    assert_eq!(body["seed"], 42);
+}
+
+#[test]
+fn test_seed_is_only_sent_to_providers_that_accept_it() {
+    assert!(provider_supports_seed("openai"));
+    assert_eq!(
+        seed_request_body("OpenRouter", 7),
+        Some(serde_json::json!({ "seed": 7 }))
+    );
+
+    assert!(!provider_supports_seed("anthropic"));
+    assert_eq!(seed_request_body("anthropic", 7), None);
+}
+
+#[test]
+fn test_azure_provider_is_built_for_its_deployment() {
+    let mut config = MockDataBuilder::test_config_with_api_key("azure", "secret");
+    let azure = config
+        .providers
+        .get_mut("azure")
+        .expect("Azure provider should exist in config");
+    azure.resource = Some("contoso".to_string());
+    azure.deployment = Some("gpt-4o-prod".to_string());
+    azure.api_version = Some("2024-10-21".to_string());
+
+    let endpoint = provider_endpoint(&config, "azure").expect("Azure settings are complete");
+    assert!(matches!(endpoint.backend, LLMBackend::AzureOpenAI));
+    assert_eq!(
+        endpoint.base_url.as_deref(),
+        Some("https://contoso.openai.azure.com/")
+    );
+    assert_eq!(endpoint.deployment_id.as_deref(), Some("gpt-4o-prod"));
+    assert_eq!(endpoint.api_version.as_deref(), Some("2024-10-21"));
+
+    // Other providers keep their configured base URL and no deployment
+    let mut config = MockDataBuilder::test_config_with_api_key("ollama", "");
+    config
+        .providers
+        .get_mut("ollama")
+        .expect("Ollama provider should exist in config")
+        .base_url = Some("http://localhost:11434".to_string());
+    let endpoint = provider_endpoint(&config, "ollama").expect("Ollama is a known backend");
+    assert!(matches!(endpoint.backend, LLMBackend::Ollama));
+    assert_eq!(endpoint.base_url.as_deref(), Some("http://localhost:11434"));
+    assert_eq!(endpoint.deployment_id, None);
+}
@@ -65,7 +65,7 @@ async fn test_remote_repository_support() {
         assert!(update_result.is_ok(), "Should be able to update remote");
 
         // Commit operations should fail for remote repositories
-        let result = git_repo.commit("Test commit message");
+        let result = git_repo.commit("Test commit message", false);
         assert!(
             result.is_err(),
             "Commit should fail for remote repositories"
@@ -1,7 +1,11 @@
 #![cfg(feature = "integration")]
 
 use anyhow::Result;
-use gait::{app::Gait, common::CommonParams, git::GitRepo};
+use gait::{
+    app::Gait,
+    common::{CommonParams, LogParams},
+    git::GitRepo,
+};
 use std::env;
 
 // Test the CLI with a remote repository URL
@@ -28,8 +32,17 @@ async fn test_cli_with_remote_repository() -> Result<()> {
         debug_llm: false,
         provider: Some("mock".to_string()), // Use mock provider to avoid real API calls
         instructions: None,
+        instructions_file: None,
+        preset: None,
         detail_level: "minimal".to_string(),
         repository_url: Some(repo_url.to_string()),
+        no_cache: false,
+        include_untracked: false,
+        profile: None,
+        no_project_config: false,
+        temperature: None,
+        seed: None,
+        log: LogParams::default(),
     };
 
     let release_notes_command = Gait::ReleaseNotes {
@@ -1,5 +1,16 @@
 use anyhow::Result;
-use gait::{config::Config, features::commit::CommitService, git::GitRepo};
+use gait::{
+    config::Config,
+    core::context_cache::ContextCache,
+    features::commit::{
+        CommitService, GeneratedMessage, NOTHING_STAGED_MESSAGE, commit_generated_message,
+        completion::CompletionService, ensure_staged_changes, format_commit_explanation,
+        format_commit_message,
+    },
+    git::GitRepo,
+    tui::{AmendTarget, TuiCommit},
+};
+use std::fs;
 use std::path::PathBuf;
 use std::sync::Arc;
 use tempfile::TempDir;
@@ -85,3 +96,316 @@ async fn test_perform_amend_commit() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_perform_commit_with_co_author_trailers() -> Result<()> {
+    let (temp_dir, _git_repo) = setup_test_repo()?;
+    let config = Config {
+        co_authors: vec!["Jane Doe <jane@example.com>".to_string()],
+        ..Config::default()
+    };
+    let repo_path = PathBuf::from(temp_dir.path());
+
+    let service_repo = GitRepo::new(temp_dir.path())?;
+    let service = CommitService::new(config, &repo_path, "test", true, service_repo)?;
+
+    service.perform_commit("feat: pair on parser\n\nAdds the parser.", false, None)?;
+
+    let repo = git2::Repository::open(&repo_path)?;
+    let message = repo
+        .head()?
+        .peel_to_commit()?
+        .message()
+        .expect("Failed to get commit message")
+        .to_string();
+    assert_eq!(
+        message,
+        "feat: pair on parser\n\nAdds the parser.\n\nCo-authored-by: Jane Doe <jane@example.com>\n"
+    );
+
+    // Amending with the edited message must not duplicate the trailer
+    service.perform_commit(&message, true, Some("HEAD"))?;
+    let amended = repo.head()?.peel_to_commit()?;
+    assert_eq!(amended.message(), Some(message.as_str()));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_amend_older_commit_preserves_descendants() -> Result<()> {
+    let (temp_dir, _git_repo) = setup_test_repo()?;
+    let repo_path = PathBuf::from(temp_dir.path());
+    let repo = git2::Repository::open(&repo_path)?;
+
+    let service_repo = GitRepo::new(temp_dir.path())?;
+    let service = CommitService::new(Config::default(), &repo_path, "test", true, service_repo)?;
+
+    // Two more commits on top of the setup history, so the target is the grandparent
+    let grandparent = repo.head()?.peel_to_commit()?;
+    for (file, message) in [("a.txt", "Add a"), ("b.txt", "Add b")] {
+        std::fs::write(temp_dir.path().join(file), message)?;
+        let mut index = repo.index()?;
+        index.add_path(std::path::Path::new(file))?;
+        index.write()?;
+        service.perform_commit(message, false, None)?;
+    }
+    let old_head = repo.head()?.peel_to_commit()?;
+
+    let result = service.perform_commit(
+        "Reworded grandparent",
+        true,
+        Some(&grandparent.id().to_string()),
+    )?;
+
+    let head = repo.head()?.peel_to_commit()?;
+    let parent = head.parent(0)?;
+    let reworded = parent.parent(0)?;
+
+    assert_eq!(head.message().map(str::trim_end), Some("Add b"));
+    assert_eq!(parent.message().map(str::trim_end), Some("Add a"));
+    assert_eq!(
+        reworded.message().map(str::trim_end),
+        Some("Reworded grandparent")
+    );
+    assert_eq!(result.commit_hash, reworded.id().to_string()[..7]);
+
+    // Descendants keep their trees but get new hashes; history below is untouched
+    assert_ne!(head.id(), old_head.id());
+    assert_eq!(head.tree_id(), old_head.tree_id());
+    assert_eq!(reworded.tree_id(), grandparent.tree_id());
+    assert_eq!(reworded.parent_id(0)?, grandparent.parent_id(0)?);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_tui_reword_context_uses_the_amended_commit() -> Result<()> {
+    let (temp_dir, _git_repo) = setup_test_repo()?;
+    let repo_path = PathBuf::from(temp_dir.path());
+    let repo = git2::Repository::open(&repo_path)?;
+
+    let service_repo = GitRepo::new(temp_dir.path())?;
+    let service = CommitService::new(Config::default(), &repo_path, "test", true, service_repo)?;
+    let stage = |file: &str| -> Result<()> {
+        std::fs::write(temp_dir.path().join(file), file)?;
+        let mut index = repo.index()?;
+        index.add_path(std::path::Path::new(file))?;
+        index.write()?;
+        Ok(())
+    };
+    for (file, message) in [("a.txt", "Add a"), ("b.txt", "Add b")] {
+        stage(file)?;
+        service.perform_commit(message, false, None)?;
+    }
+    // Staged changes belong to the next commit, not the one being reworded
+    stage("staged.txt")?;
+
+    let completion_service = CompletionService::new(
+        Config::default(),
+        &repo_path,
+        "test",
+        true,
+        GitRepo::new(temp_dir.path())?,
+    )?;
+    let original = GeneratedMessage {
+        emoji: None,
+        title: "Add a".to_string(),
+        message: String::new(),
+    };
+    let mut app = TuiCommit::new(
+        vec![original.clone()],
+        String::new(),
+        Arc::new(service),
+        Arc::new(completion_service),
+        Some(AmendTarget {
+            commit_ref: Some("HEAD~1".to_string()),
+            original,
+        }),
+    );
+    app.initialize_context().await?;
+
+    // Regenerating with `R` uses the selected context
+    let context = app
+        .state
+        .get_filtered_context()
+        .expect("context was initialized");
+    let files: Vec<&str> = context
+        .staged_files
+        .iter()
+        .map(|file| file.path.as_str())
+        .collect();
+    assert_eq!(files, ["a.txt"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_amend_older_commit_refuses_dirty_tree() -> Result<()> {
+    let (temp_dir, _git_repo) = setup_test_repo()?;
+    let repo_path = PathBuf::from(temp_dir.path());
+    let repo = git2::Repository::open(&repo_path)?;
+
+    let service_repo = GitRepo::new(temp_dir.path())?;
+    let service = CommitService::new(Config::default(), &repo_path, "test", true, service_repo)?;
+
+    let parent = repo.head()?.peel_to_commit()?.parent(0)?;
+    std::fs::write(temp_dir.path().join("README.md"), "# Edited")?;
+
+    let error = service
+        .perform_commit("Reworded", true, Some(&parent.id().to_string()))
+        .expect_err("amending an older commit with a dirty tree must fail");
+    assert!(error.to_string().contains("uncommitted changes"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_nothing_staged_errors_before_generation() -> Result<()> {
+    let (temp_dir, _git_repo) = setup_test_repo()?;
+    let mut config = Config::default();
+    config.no_cache = true;
+    let repo_path = PathBuf::from(temp_dir.path());
+    let service_repo = GitRepo::new(temp_dir.path())?;
+    let service = CommitService::new(config, &repo_path, "test", false, service_repo)?;
+
+    let context = service.get_git_info().await?;
+    let err = ensure_staged_changes(&context).expect_err("nothing is staged");
+    assert_eq!(err.to_string(), NOTHING_STAGED_MESSAGE);
+
+    // --all stages modified tracked files, like `git commit -a`
+    fs::write(temp_dir.path().join("src/main.rs"), "fn main() {}")?;
+    service.stage_tracked_changes().await?;
+    let context = service.get_git_info().await?;
+    assert!(ensure_staged_changes(&context).is_ok());
+    assert_eq!(context.staged_files.len(), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_unchanged_staged_tree_reuses_cached_context() -> Result<()> {
+    let (temp_dir, _git_repo) = setup_test_repo()?;
+    let cache_dir = TempDir::new()?;
+    let repo_path = PathBuf::from(temp_dir.path());
+    // A fresh service each time, so only the on-disk cache carries over
+    let new_service = || -> Result<CommitService> {
+        let service_repo = GitRepo::new(temp_dir.path())?;
+        Ok(
+            CommitService::new(Config::default(), &repo_path, "test", false, service_repo)?
+                .with_context_cache(ContextCache::with_dir(cache_dir.path().to_path_buf())),
+        )
+    };
+
+    fs::write(temp_dir.path().join("src/main.rs"), "fn main() {}")?;
+    let service = new_service()?;
+    service.stage_tracked_changes().await?;
+    let first = service.get_git_info().await?;
+    assert_eq!(first.staged_files.len(), 1);
+
+    // Mark the stored context so a cache hit can be told apart from a rebuild
+    let entries = fs::read_dir(cache_dir.path())?.collect::<std::io::Result<Vec<_>>>()?;
+    assert_eq!(entries.len(), 1);
+    let entry = entries.first().expect("one cached context").path();
+    let marked = fs::read_to_string(&entry)?.replacen(
+        &format!("\"branch\":\"{}\"", first.branch),
+        "\"branch\":\"from-cache\"",
+        1,
+    );
+    fs::write(&entry, marked)?;
+
+    assert_eq!(new_service()?.get_git_info().await?.branch, "from-cache");
+
+    // Staging something else changes the tree, so the context is built again
+    fs::write(temp_dir.path().join("src/main.rs"), "fn main() { run(); }")?;
+    let service = new_service()?;
+    service.stage_tracked_changes().await?;
+    let rebuilt = service.get_git_info().await?;
+    assert_eq!(rebuilt.branch, first.branch);
+    assert_eq!(fs::read_dir(cache_dir.path())?.count(), 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_prompt_history_respects_the_configured_count() -> Result<()> {
+    let (temp_dir, _git_repo) = setup_test_repo()?;
+    let config = Config {
+        enhanced_history_count: 3,
+        no_cache: true,
+        ..Config::default()
+    };
+    let repo_path = PathBuf::from(temp_dir.path());
+    let service_repo = GitRepo::new(temp_dir.path())?;
+    let service = CommitService::new(config, &repo_path, "test", false, service_repo)?;
+
+    let mut context = service.get_git_info().await?;
+    context.author_history = (1..=8).map(|i| format!("fix: change number {i}")).collect();
+
+    let prompt_context = service.prompt_context(context.clone());
+    assert_eq!(prompt_context.author_history.len(), 3);
+
+    let service = CommitService::new(
+        Config {
+            enhanced_history_count: 0,
+            ..Config::default()
+        },
+        &repo_path,
+        "test",
+        false,
+        GitRepo::new(temp_dir.path())?,
+    )?;
+    assert!(service.prompt_context(context).author_history.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_yes_commits_the_generated_message() -> Result<()> {
+    let (temp_dir, _git_repo) = setup_test_repo()?;
+    let mut config = Config::default();
+    config.no_cache = true;
+    let repo_path = PathBuf::from(temp_dir.path());
+    let service_repo = GitRepo::new(temp_dir.path())?;
+    let service = CommitService::new(config, &repo_path, "test", false, service_repo)?;
+
+    fs::write(temp_dir.path().join("src/main.rs"), "fn main() {}")?;
+    service.stage_tracked_changes().await?;
+    let message = GeneratedMessage {
+        emoji: None,
+        title: "refactor: empty the main function".to_string(),
+        message: "Nothing needs to run on startup any more.".to_string(),
+    };
+
+    let output = commit_generated_message(&service, &message, false, None)?;
+
+    let repo = git2::Repository::open(temp_dir.path())?;
+    let head = repo.head()?.peel_to_commit()?;
+    let committed = head.message().expect("commit message is UTF-8");
+    assert_eq!(
+        committed.trim_end(),
+        format_commit_message(&message).trim_end()
+    );
+    assert!(output.contains(&head.id().to_string()[..7]));
+    assert!(output.ends_with("Nothing needs to run on startup any more."));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_dry_run_explains_a_known_commit() -> Result<()> {
+    let (temp_dir, _git_repo) = setup_test_repo()?;
+    let repo_path = PathBuf::from(temp_dir.path());
+    let service_repo = GitRepo::new(temp_dir.path())?;
+    let service = CommitService::new(Config::default(), &repo_path, "test", false, service_repo)?;
+
+    let explanation = service.dry_run_explanation("HEAD").await?;
+
+    assert!(explanation.what_changed.contains("Add main function"));
+    assert!(explanation.what_changed.contains("src/main.rs"));
+    let rendered = format_commit_explanation(&explanation);
+    for heading in ["## What changed", "## Likely intent", "## Risk"] {
+        assert!(rendered.contains(heading), "missing {heading}");
+    }
+
+    Ok(())
+}
@@ -39,6 +39,14 @@ fn test_concurrent_access_to_same_repository() {
                 filters: vec![format!("src{thread_id}")],
                 commit_hash: None,
                 mtd: None,
+                checksum: None,
+                post_sync: Vec::new(),
+                prune: false,
+                follow_symlinks: false,
+                token_env: None,
+                max_files: None,
+                max_total_bytes: None,
+                ignore: Vec::new(),
             };
 
             // Create a wire operation (unused but simulates the operation creation)
@@ -12,6 +12,14 @@ fn test_repository_deduplication_logic() {
             filters: vec!["src/".to_string(), "lib/".to_string()],
             commit_hash: None,
             mtd: None,
+            checksum: None,
+            post_sync: Vec::new(),
+            prune: false,
+            follow_symlinks: false,
+            token_env: None,
+            max_files: None,
+            max_total_bytes: None,
+            ignore: Vec::new(),
         },
         RepositoryConfiguration {
             url: "https://github.com/example/repo.git".to_string(), // Same repo
@@ -20,6 +28,14 @@ fn test_repository_deduplication_logic() {
             filters: vec!["utils/".to_string()],
             commit_hash: None,
             mtd: None,
+            checksum: None,
+            post_sync: Vec::new(),
+            prune: false,
+            follow_symlinks: false,
+            token_env: None,
+            max_files: None,
+            max_total_bytes: None,
+            ignore: Vec::new(),
         },
         RepositoryConfiguration {
             url: "https://github.com/other/repo.git".to_string(), // Different repo
@@ -28,6 +44,14 @@ fn test_repository_deduplication_logic() {
             filters: vec!["docs/".to_string()],
             commit_hash: None,
             mtd: None,
+            checksum: None,
+            post_sync: Vec::new(),
+            prune: false,
+            follow_symlinks: false,
+            token_env: None,
+            max_files: None,
+            max_total_bytes: None,
+            ignore: Vec::new(),
         },
     ];
 
@@ -81,6 +105,14 @@ fn test_cached_repository_usage() {
         filters: vec!["src/".to_string()],
         commit_hash: None,
         mtd: None,
+        checksum: None,
+        post_sync: Vec::new(),
+        prune: false,
+        follow_symlinks: false,
+        token_env: None,
+        max_files: None,
+        max_total_bytes: None,
+        ignore: Vec::new(),
     };
 
     let config2 = RepositoryConfiguration {
@@ -90,6 +122,14 @@ fn test_cached_repository_usage() {
         filters: vec!["utils/".to_string()],
         commit_hash: None,
         mtd: None,
+        checksum: None,
+        post_sync: Vec::new(),
+        prune: false,
+        follow_symlinks: false,
+        token_env: None,
+        max_files: None,
+        max_total_bytes: None,
+        ignore: Vec::new(),
     };
 
     let op1 = WireOperation::new(config1, cached_repo.local_cache_path.clone());
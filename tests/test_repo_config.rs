@@ -9,6 +9,14 @@ fn test_repository_configuration_creation() {
         filters: vec!["src/".to_string(), "lib/".to_string()],
         commit_hash: None,
         mtd: None,
+        checksum: None,
+        post_sync: Vec::new(),
+        prune: false,
+        follow_symlinks: false,
+        token_env: None,
+        max_files: None,
+        max_total_bytes: None,
+        ignore: Vec::new(),
     };
 
     assert_eq!(config.url, "https://github.com/example/repo.git");
@@ -27,6 +35,14 @@ fn test_repository_configuration_with_commit_hash() {
         filters: vec!["utils/".to_string()],
         commit_hash: Some("abc123def456".to_string()),
         mtd: None,
+        checksum: None,
+        post_sync: Vec::new(),
+        prune: false,
+        follow_symlinks: false,
+        token_env: None,
+        max_files: None,
+        max_total_bytes: None,
+        ignore: Vec::new(),
     };
 
     assert_eq!(config.commit_hash, Some("abc123def456".to_string()));
@@ -41,7 +57,50 @@ fn test_repository_configuration_default_branch() {
         filters: vec![],
         commit_hash: None,
         mtd: None,
+        checksum: None,
+        post_sync: Vec::new(),
+        prune: false,
+        follow_symlinks: false,
+        token_env: None,
+        max_files: None,
+        max_total_bytes: None,
+        ignore: Vec::new(),
     };
 
     assert_eq!(config.branch, "main");
 }
+
+#[test]
+fn test_builder_fills_defaults() {
+    let config = RepositoryConfiguration::builder()
+        .url("https://github.com/example/repo.git")
+        .target_path("./src/module1")
+        .filter("src/")
+        .build()
+        .expect("minimal configuration builds");
+
+    assert_eq!(config.url, "https://github.com/example/repo.git");
+    assert_eq!(config.branch, "main");
+    assert_eq!(config.target_path, "./src/module1");
+    assert_eq!(config.filters, vec!["src/".to_string()]);
+    assert_eq!(config.commit_hash, None);
+    assert!(!config.prune);
+}
+
+#[test]
+fn test_builder_rejects_invalid_configuration() {
+    let both_pins = RepositoryConfiguration::builder()
+        .url("https://github.com/example/repo.git")
+        .target_path("./src/module1")
+        .branch("develop")
+        .commit_hash("abc123def456")
+        .build()
+        .expect_err("branch and commit_hash conflict");
+    assert!(both_pins.to_string().contains("not both"));
+
+    let no_url = RepositoryConfiguration::builder()
+        .target_path("./src/module1")
+        .build()
+        .expect_err("url is required");
+    assert!(no_url.to_string().contains("url"));
+}
@@ -21,6 +21,14 @@ fn test_repository_deduplication_performance() {
             filters: vec![format!("src{i}")],
             commit_hash: None,
             mtd: None,
+            checksum: None,
+            post_sync: Vec::new(),
+            prune: false,
+            follow_symlinks: false,
+            token_env: None,
+            max_files: None,
+            max_total_bytes: None,
+            ignore: Vec::new(),
         });
     }
 
@@ -314,6 +314,7 @@ impl MockDataBuilder {
                 timestamp: "1234567890".to_string(),
             }],
             staged_files: vec![Self::staged_file()],
+            ticket_id: None,
             user_name: "Test User".to_string(),
             user_email: "test@example.com".to_string(),
             author_history: vec!["feat: add user authentication".to_string()],
@@ -354,6 +355,7 @@ impl MockDataBuilder {
                     content_excluded: false,
                 },
             ],
+            ticket_id: None,
 
 
             user_name: "Test User".to_string(),
@@ -0,0 +1,45 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Files whose stdout output is the command's result rather than a diagnostic
+const RESULT_OUTPUT_FILES: [&str; 3] = ["check.rs", "reconcile.rs", "status.rs"];
+
+fn rust_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(dir).expect("Failed to read source directory") {
+        let path = entry.expect("Failed to read directory entry").path();
+        if path.is_dir() {
+            rust_files(&path, files);
+        } else if path.extension().is_some_and(|ext| ext == "rs") {
+            files.push(path);
+        }
+    }
+}
+
+#[test]
+fn test_wire_diagnostics_go_through_log() {
+    let remote = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/remote");
+    let mut files = Vec::new();
+    rust_files(&remote, &mut files);
+    assert!(!files.is_empty());
+
+    let mut stray = Vec::new();
+    for path in files {
+        let name = path.file_name().and_then(|name| name.to_str());
+        if name.is_some_and(|name| RESULT_OUTPUT_FILES.contains(&name)) {
+            continue;
+        }
+        let source = fs::read_to_string(&path).expect("Failed to read source file");
+        // Tests may print freely
+        let code = source.split("#[cfg(test)]").next().unwrap_or_default();
+        for (number, line) in code.lines().enumerate() {
+            if line.contains("println!(") {
+                stray.push(format!("{}:{}", path.display(), number + 1));
+            }
+        }
+    }
+
+    assert!(
+        stray.is_empty(),
+        "use `log` instead of printing in wire code: {stray:?}"
+    );
+}
@@ -1,6 +1,17 @@
 use gait::{
-    RepositoryConfiguration, WireOperation, remote::cache::key_generator::CacheKeyGenerator,
+    CacheManager, RepositoryConfiguration, WireOperation,
+    remote::{
+        cache::{
+            fetcher::RepositoryFetcher, key_generator::CacheKeyGenerator, manager::CacheMetrics,
+        },
+        reconcile::DifferenceKind,
+    },
 };
+use git2::{Repository, RepositoryInitOptions, Signature};
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use tempfile::TempDir;
 
 #[test]
 fn test_wire_operation_creation() {
@@ -11,6 +22,14 @@ fn test_wire_operation_creation() {
         filters: vec!["src/".to_string(), "lib/".to_string()],
         commit_hash: None,
         mtd: None,
+        checksum: None,
+        post_sync: Vec::new(),
+        prune: false,
+        follow_symlinks: false,
+        token_env: None,
+        max_files: None,
+        max_total_bytes: None,
+        ignore: Vec::new(),
     };
 
     let wire_op = WireOperation::new(config, "/tmp/cache/repo1".to_string());
@@ -40,6 +59,14 @@ fn test_wire_operation_with_commit_hash() {
         filters: vec!["utils/".to_string()],
         commit_hash: Some("abc123".to_string()),
         mtd: None,
+        checksum: None,
+        post_sync: Vec::new(),
+        prune: false,
+        follow_symlinks: false,
+        token_env: None,
+        max_files: None,
+        max_total_bytes: None,
+        ignore: Vec::new(),
     };
 
     let wire_op = WireOperation::new(config, "/tmp/cache/repo2".to_string());
@@ -141,3 +168,120 @@ fn test_generate_url_branch_key() {
     // Different branch should produce different key
     assert_ne!(key1, key3);
 }
+
+/// A `main` branch with `proto/api.proto` committed
+fn init_upstream(path: &Path) {
+    let repo = Repository::init_opts(path, RepositoryInitOptions::new().initial_head("main"))
+        .expect("Failed to init repo");
+    fs::create_dir_all(path.join("proto")).expect("Failed to create directory");
+    fs::write(path.join("proto/api.proto"), "syntax = \"proto3\";\n").expect("Failed to write");
+
+    let mut index = repo.index().expect("Failed to open index");
+    index
+        .add_path(Path::new("proto/api.proto"))
+        .expect("Failed to stage file");
+    index.write().expect("Failed to write index");
+    let tree = repo
+        .find_tree(index.write_tree().expect("Failed to write tree"))
+        .expect("Failed to find tree");
+    let signature = Signature::now("Test", "test@example.com").expect("Failed to sign");
+    repo.commit(Some("HEAD"), &signature, &signature, "initial", &tree, &[])
+        .expect("Failed to commit");
+}
+
+#[tokio::test]
+async fn test_sync_then_check_shares_one_clone() {
+    let dir = TempDir::new().expect("Failed to create temp dir");
+    let upstream = dir.path().join("upstream");
+    init_upstream(&upstream);
+    let cache_path = dir.path().join("cache").to_string_lossy().to_string();
+    let root = dir.path().join("project");
+
+    let config = RepositoryConfiguration::builder()
+        .url(upstream.to_string_lossy())
+        .target_path("vendor")
+        .filter("proto")
+        .build()
+        .expect("valid configuration");
+    let reused = RepositoryFetcher
+        .fetch_repository(&config, &cache_path)
+        .await
+        .expect("upstream clones");
+    assert!(!reused);
+
+    // With the upstream gone, a second clone for the check would fail
+    fs::remove_dir_all(&upstream).expect("Failed to remove upstream");
+
+    let wire_op = WireOperation::new(config, cache_path);
+    let outcome = wire_op
+        .sync_then_check(&root)
+        .expect("sync and check succeed");
+    assert_eq!(outcome.files.created, 1);
+    assert!(outcome.is_clean());
+    assert!(root.join("vendor/proto/api.proto").exists());
+
+    fs::write(root.join("vendor/proto/api.proto"), "edited").expect("Failed to edit");
+    let differences = wire_op.check(&root).expect("check succeeds");
+    assert_eq!(differences.len(), 1);
+    assert_eq!(differences[0].kind, DifferenceKind::Changed);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 3)]
+async fn test_entries_sharing_a_url_pull_it_once() {
+    let dir = TempDir::new().expect("Failed to create temp dir");
+    let shared = dir.path().join("shared");
+    let other = dir.path().join("other");
+    init_upstream(&shared);
+    init_upstream(&other);
+    let entry = |upstream: &Path, target_path: &str| {
+        RepositoryConfiguration::builder()
+            .url(upstream.to_string_lossy())
+            .target_path(target_path)
+            .filter("proto")
+            .build()
+            .expect("valid configuration")
+    };
+    let entries = [
+        entry(&shared, "vendor/a"),
+        entry(&shared, "vendor/b"),
+        entry(&other, "vendor/c"),
+    ];
+
+    // Fetch every entry at once on separate threads
+    let cache_root = dir.path().join("cache");
+    let cache_manager = Arc::new(CacheManager::new());
+    let fetches: Vec<_> = entries
+        .into_iter()
+        .map(|config| {
+            let cache_manager = Arc::clone(&cache_manager);
+            let cache_dir = cache_root.join(CacheKeyGenerator::generate_key(&config));
+            tokio::spawn(async move {
+                cache_manager
+                    .fetch(&config, &cache_dir, false)
+                    .await
+                    .map_err(|e| e.to_string())
+            })
+        })
+        .collect();
+    let mut cache_paths = Vec::new();
+    for fetch in fetches {
+        let cache_path = fetch
+            .await
+            .expect("fetch task completes")
+            .expect("entry fetches");
+        cache_paths.push(cache_path);
+    }
+
+    assert_eq!(
+        cache_manager.metrics(),
+        CacheMetrics {
+            unique_pulls: 2,
+            cache_hits: 1,
+        }
+    );
+    assert_eq!(cache_paths[0], cache_paths[1]);
+    assert_ne!(cache_paths[0], cache_paths[2]);
+    for cache_path in &cache_paths {
+        assert!(Path::new(cache_path).join("proto/api.proto").exists());
+    }
+}
@@ -2,7 +2,9 @@ use gait::{
     Config,
     core::{
         context::{ChangeType, CommitContext, RecentCommit, StagedFile},
-        token_optimizer::TokenOptimizer,
+        token_optimizer::{
+            ContextItem, ContextItemType, DefaultScorer, ImportanceScorer, TokenOptimizer,
+        },
     },
 };
 
@@ -274,6 +276,7 @@ fn create_test_context_with_large_data() -> CommitContext {
                 content: Some(large_content),
             },
         ],
+        ticket_id: None,
 
         user_name: "Test User".to_string(),
         user_email: "test@example.com".to_string(),
@@ -337,6 +340,7 @@ async fn test_importance_weighted_token_distribution() {
                 content: Some("large file content\n".repeat(10)), // ~30 tokens
             },
         ],
+        ticket_id: None,
         user_name: "Test User".to_string(),
         user_email: "test@example.com".to_string(),
         author_history: vec![],
@@ -413,6 +417,7 @@ async fn test_importance_order_processing() {
                 content: Some("less important content".to_string()),
             },
         ],
+        ticket_id: None,
         user_name: "Test User".to_string(),
         user_email: "test@example.com".to_string(),
         author_history: vec![],
@@ -452,6 +457,7 @@ async fn test_importance_weighted_empty_context() {
         branch: "main".to_string(),
         recent_commits: vec![],
         staged_files: vec![],
+        ticket_id: None,
         user_name: "Test User".to_string(),
         user_email: "test@example.com".to_string(),
         author_history: vec![],
@@ -493,6 +499,7 @@ async fn test_commit_importance_position_factor() {
             },
         ],
         staged_files: vec![],
+        ticket_id: None,
         user_name: "Test User".to_string(),
         user_email: "test@example.com".to_string(),
         author_history: vec![],
@@ -541,6 +548,7 @@ async fn test_content_relevance_factor() {
                 content: Some("This is unstaged file content".to_string()),
             },
         ],
+        ticket_id: None,
         user_name: "Test User".to_string(),
         user_email: "test@example.com".to_string(),
         author_history: vec![],
@@ -623,6 +631,7 @@ async fn test_extreme_importance_differences() {
             content_excluded: false,
             content: Some("small content".to_string()), // 3 tokens - less important
         }],
+        ticket_id: None,
         user_name: "Test User".to_string(),
         user_email: "test@example.com".to_string(),
         author_history: vec![],
@@ -649,3 +658,141 @@ async fn test_extreme_importance_differences() {
     // Smaller items should be relatively preserved
     assert!(commit_tokens > 0, "Commit should retain some tokens");
 }
+
+// Large commits are counted in parallel; the counts and the budget must hold as before
+#[tokio::test]
+async fn test_many_files_are_counted_like_one_at_a_time() {
+    let mut context = create_test_context();
+    context.staged_files = (0..100)
+        .map(|i| StagedFile {
+            path: format!("src/module_{i}.rs"),
+            change_type: if i % 3 == 0 {
+                ChangeType::Added
+            } else {
+                ChangeType::Modified
+            },
+            diff: format!("+ fn handler_{i}() {{ process({i}); }}\n").repeat(i % 7 + 1),
+            content_excluded: false,
+            content: (i % 2 == 0).then(|| format!("// Module {i}\n").repeat(i % 5 + 1)),
+        })
+        .collect();
+
+    let config = create_test_config();
+    let optimizer = TokenOptimizer::new(500, config).expect("Failed to initialize token optimizer");
+
+    let diffs: Vec<&str> = context
+        .staged_files
+        .iter()
+        .map(|f| f.diff.as_str())
+        .collect();
+    let serial: Vec<usize> = diffs.iter().map(|d| optimizer.count_tokens(d)).collect();
+    assert_eq!(optimizer.count_tokens_batch(&diffs), serial);
+    assert_eq!(optimizer.count_tokens_batch(&diffs[..3]), serial[..3]);
+
+    let _ = optimizer.optimize_context(&mut context).await;
+
+    let total_tokens = count_total_tokens(&context, &optimizer);
+    assert!(
+        total_tokens <= 500,
+        "Total tokens ({total_tokens}) exceeds limit of 500"
+    );
+    assert_eq!(context.staged_files.len(), 100);
+}
+
+// The budget is a hard limit, whatever the shape of the context
+#[tokio::test]
+async fn test_optimizer_never_exceeds_the_limit() {
+    let file = |i: usize, diff_lines: usize, content_lines: usize| StagedFile {
+        path: format!("src/module_{i}.rs"),
+        change_type: if i % 3 == 0 {
+            ChangeType::Added
+        } else {
+            ChangeType::Modified
+        },
+        diff: format!("+ let value_{i} = compute({i});\n").repeat(diff_lines),
+        content_excluded: false,
+        content: (content_lines > 0)
+            .then(|| format!("fn helper_{i}() {{}}\n").repeat(content_lines)),
+    };
+    let fixtures = [
+        (
+            "typical",
+            (0..5).map(|i| file(i, 10, 20)).collect::<Vec<_>>(),
+        ),
+        (
+            "many_small_files",
+            (0..500).map(|i| file(i, 1, 2)).collect(),
+        ),
+        ("single_huge_file", vec![file(0, 5_000, 5_000)]),
+        ("diffs_only", (0..20).map(|i| file(i, 30, 0)).collect()),
+    ];
+
+    for (name, staged_files) in fixtures {
+        for limit in [0, 1, 10, 100, 1_000, 10_000] {
+            let mut context = create_test_context();
+            context.staged_files.clone_from(&staged_files);
+            let optimizer = TokenOptimizer::new(limit, create_test_config())
+                .expect("Failed to initialize token optimizer");
+
+            let _ = optimizer.optimize_context(&mut context).await;
+
+            let total_tokens = count_total_tokens(&context, &optimizer);
+            assert!(
+                total_tokens <= limit,
+                "{name}: total tokens ({total_tokens}) exceed the limit of {limit}"
+            );
+            assert_eq!(context.staged_files.len(), staged_files.len());
+        }
+    }
+}
+
+// A custom scorer decides what is trimmed first
+#[tokio::test]
+async fn test_custom_scorer_trims_zero_scored_files_first() {
+    struct NoTestsScorer;
+
+    impl ImportanceScorer for NoTestsScorer {
+        fn score(&self, item: &ContextItem, context: &CommitContext) -> f32 {
+            let file = match item.item_type {
+                ContextItemType::Diff { file_index } | ContextItemType::Content { file_index } => {
+                    context.staged_files.get(file_index)
+                }
+                ContextItemType::Commit { .. } => None,
+            };
+            if file.is_some_and(|file| file.path.ends_with("_test.rs")) {
+                0.0
+            } else {
+                DefaultScorer.score(item, context)
+            }
+        }
+    }
+
+    let diff = "+ let parsed = parse(input);\n".repeat(5);
+    let mut context = create_test_context();
+    context.staged_files = ["src/parser.rs", "src/parser_test.rs"]
+        .into_iter()
+        .map(|path| StagedFile {
+            path: path.to_string(),
+            change_type: ChangeType::Modified,
+            diff: diff.clone(),
+            content_excluded: false,
+            content: Some("fn parse() {}\n".repeat(10)),
+        })
+        .collect();
+
+    let optimizer = TokenOptimizer::new(100, create_test_config())
+        .expect("Failed to initialize token optimizer")
+        .with_scorer(NoTestsScorer);
+    assert!(count_total_tokens(&context, &optimizer) > 100);
+
+    let _ = optimizer.optimize_context(&mut context).await;
+
+    assert!(count_total_tokens(&context, &optimizer) <= 100);
+    let (source, test) = (&context.staged_files[0], &context.staged_files[1]);
+    assert_eq!(source.diff, diff, "The source diff should be kept whole");
+    assert!(
+        test.diff.is_empty(),
+        "The test diff should be trimmed first"
+    );
+    assert!(test.content.is_none());
+}